@@ -3,70 +3,741 @@
 /// Monitors Numo Engine pools on Celo and executes arbitrage when pool-implied
 /// discount factors diverge from the SOFR curve.
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use dotenv::dotenv;
 use ethers::prelude::*;
 use ethers::providers::{Provider, Ws};
 use ethers::signers::{LocalWallet, Signer};
-use std::str::FromStr;
-use std::sync::Arc;
-use tracing::{info, Level};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use tracing::{info, warn, Level};
 use tracing_subscriber::{filter, prelude::*};
 
 use artemis_core::collectors::block_collector::{BlockCollector, NewBlock};
+use artemis_core::collectors::mempool_collector::MempoolCollector;
 use artemis_core::engine::Engine;
-use artemis_core::executors::mempool_executor::MempoolExecutor;
-use artemis_core::types::{CollectorMap, ExecutorMap};
+use artemis_core::types::{Collector, CollectorMap, ExecutorMap};
 
-use numo_arb::sofr::SofrCurve;
+use numo_arb::approval::{ApprovalPolicy, LineApprover};
+use numo_arb::balance::BalanceVerifier;
+use numo_arb::bench::{fetch_maturities, CurveBench};
+use numo_arb::budget::CapitalBudget;
+use numo_arb::cancel::{CancelExecutor, InflightTrades};
+use numo_arb::carry::CarryPositions;
+use numo_arb::catchup::CatchUpCollector;
+use numo_arb::clock::{Clock, SystemClock};
+use numo_arb::confirmation::ConfirmingExecutor;
+use numo_arb::curve_feed::CurveFileCollector;
+use numo_arb::execution::{DefaultRouterPolicy, ExecutionPolicy};
+use numo_arb::fallback::PollingFallbackCollector;
+use numo_arb::gas::GasBufferTuner;
+use numo_arb::loss_limit::DailyLossLimit;
+use numo_arb::nonce::{NonceGapMonitor, NonceReset};
+use numo_arb::pnl::PnlTracker;
+use numo_arb::recorder::{read_session, replay_session};
+use numo_arb::reload::{apply_reload, ignored_changes, SharedConfig};
+use numo_arb::selftest::run_self_test;
+use numo_arb::snapshot::{simulate_snapshot, StateSnapshot};
+use numo_arb::sofr::{Compounding, RateCurve, RateIndex};
 use numo_arb::strategy::NumoArb;
-use numo_arb::types::{Action, Config, Event, NewBlockEvent};
+use numo_arb::submission::{submission_jitter, JitteredExecutor};
+use numo_arb::tick::TickCollector;
+use numo_arb::tx_manager::RetryPolicy;
+use numo_arb::types::{Action, Config, Event, NewBlockEvent, PendingTxEvent};
+use numo_arb::unsigned::UnsignedTxExecutor;
 
 /// CLI Options for the Numo arbitrage bot
 #[derive(Parser, Debug)]
 #[command(name = "numo")]
 #[command(about = "Numo Engine arbitrage bot for Celo", long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Celo node WebSocket endpoint (e.g., wss://forno.celo.org/ws)
-    #[arg(long, env = "WSS")]
-    pub wss: String,
+    #[arg(long, env = "WSS", required_unless_present = "simulate_from_snapshot")]
+    pub wss: Option<String>,
 
     /// Private key for sending transactions (64 hex chars, no 0x prefix)
-    #[arg(long, env = "PRIVATE_KEY")]
-    pub private_key: String,
+    #[arg(
+        long,
+        env = "PRIVATE_KEY",
+        required_unless_present_any = ["emit_unsigned_to", "simulate_from_snapshot"]
+    )]
+    pub private_key: Option<String>,
+
+    /// Re-run the opportunity search on a snapshot written to --snapshot-path and
+    /// exit, without network access
+    #[arg(long)]
+    pub simulate_from_snapshot: Option<PathBuf>,
+
+    /// Write unsigned transactions into this directory for offline signing
+    /// instead of submitting them; no private key is loaded
+    #[arg(long, env = "EMIT_UNSIGNED_TO", requires = "from_address")]
+    pub emit_unsigned_to: Option<PathBuf>,
+
+    /// Address the unsigned transactions are sent from and pay out to
+    #[arg(long, env = "FROM_ADDRESS")]
+    pub from_address: Option<String>,
+
+    /// Strategy settings, overriding the environment read by [Config::from_env]
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    /// Replay blocks from this height to the current head at startup, logging what
+    /// they would have traded without trading; needs an archive node for past state
+    #[arg(long, env = "START_BLOCK")]
+    pub start_block: Option<u64>,
+
+    /// Maximum number of historical blocks replayed at startup
+    #[arg(long, env = "MAX_CATCHUP_BLOCKS", default_value = "256")]
+    pub max_catchup_blocks: u64,
+
+    /// Delay between historical block fetches in milliseconds
+    #[arg(long, env = "CATCHUP_DELAY_MS", default_value = "100")]
+    pub catchup_delay_ms: u64,
+
+    /// Re-evaluate every N seconds between blocks (disabled when unset)
+    #[arg(long, env = "TICK_INTERVAL_SECS")]
+    pub tick_interval_secs: Option<u64>,
+
+    /// Re-evaluate when a pending transaction touches a monitored pool
+    /// (needs a node serving pending transaction subscriptions)
+    #[arg(long, env = "WATCH_MEMPOOL")]
+    pub watch_mempool: bool,
+
+    /// Fall back to polling for new blocks after this many seconds without one
+    /// from the WS subscription (disabled when unset)
+    #[arg(long, env = "BLOCK_SILENCE_TIMEOUT_SECS")]
+    pub block_silence_timeout_secs: Option<u64>,
+
+    /// Delay between block polls once degraded to polling, in milliseconds
+    #[arg(long, env = "BLOCK_POLL_INTERVAL_MS", default_value_t = 1000)]
+    pub block_poll_interval_ms: u64,
+
+    /// HTTP endpoint polled once degraded to polling (default: the WS endpoint)
+    #[arg(long, env = "BLOCK_POLL_URL")]
+    pub block_poll_url: Option<String>,
+
+    /// Maximum per-instance submission delay in milliseconds, derived
+    /// deterministically from the bot address (disabled at 0)
+    #[arg(long, env = "SUBMISSION_JITTER_MS", default_value = "0")]
+    pub submission_jitter_ms: u64,
 
-    /// Address of the deployed NumoArbRouter contract
-    #[arg(long, env = "ROUTER_ADDRESS")]
-    pub router_address: String,
+    /// Show every trade and submit it only once approved on stdin, when it is next
+    /// evaluated; the bot keeps running while it waits (requires an interactive
+    /// terminal)
+    #[arg(long, env = "CONFIRM_EACH_TRADE")]
+    pub confirm_each_trade: bool,
 
-    /// Comma-separated list of Numo Engine pool addresses to monitor
-    #[arg(long, env = "POOL_ADDRESSES", value_delimiter = ',')]
-    pub pool_addresses: Vec<String>,
+    /// Seconds to wait for trade approval before rejecting it
+    #[arg(long, env = "CONFIRM_TIMEOUT_SECS", default_value = "30")]
+    pub confirm_timeout_secs: u64,
+
+    /// Seconds an answer stands before the trade is asked about again
+    #[arg(long, env = "CONFIRM_DECISION_TTL_SECS", default_value = "60")]
+    pub confirm_decision_ttl_secs: u64,
+
+    /// Overnight rate index used for target prices (SOFR, ESTR or SONIA)
+    #[arg(long, env = "RATE_INDEX", default_value = "SOFR")]
+    pub rate_index: String,
+
+    /// JSON file of the rate curve (knots and day count) used for target prices,
+    /// replacing the rate index's placeholder preset
+    #[arg(long, env = "SOFR_CURVE")]
+    pub sofr_curve: Option<std::path::PathBuf>,
+
+    /// Compounding of the curve's rates into discount factors (Simple, Continuous
+    /// or Daily), replacing the curve's own (Simple unless set in its file)
+    #[arg(long, env = "CURVE_COMPOUNDING")]
+    pub curve_compounding: Option<String>,
+
+    /// Second rate index whose curve must confirm every divergence before trading
+    #[arg(long, env = "REFERENCE_RATE_INDEX")]
+    pub reference_rate_index: Option<String>,
+}
+
+/// Offline modes; the bot runs when no command is given
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Feed a recorded session back through the strategy without network access
+    Replay {
+        /// JSONL file written with --record-events-path
+        file: std::path::PathBuf,
+    },
+
+    /// Print every pool's discount factor and target price under each curve
+    /// interpolation method, reading the pools' maturities once
+    BenchCurve,
+}
+
+/// Command-line overrides for the strategy configuration
+///
+/// Values are kept as raw strings and validated by [Config::from_vars], so flags and
+/// environment variables share one parser.
+#[derive(clap::Args, Debug, Clone)]
+pub struct ConfigArgs {
+    /// Startup preset supplying defaults that explicit flags override:
+    /// conservative, aggressive or monitor_only [env: PROFILE]
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Address of the deployed NumoArbRouter contract [env: ROUTER_ADDRESS]
+    #[arg(long)]
+    pub router_address: Option<String>,
+
+    /// Comma-separated list of Numo Engine pool addresses to monitor [env: POOL_ADDRESSES]
+    #[arg(long)]
+    pub pool_addresses: Option<String>,
 
     /// Minimum edge in basis points before executing arbitrage (default: 10 = 0.10%)
-    #[arg(long, env = "EDGE_BPS", default_value = "10")]
-    pub edge_bps: u32,
+    /// [env: EDGE_BPS]
+    #[arg(long)]
+    pub edge_bps: Option<String>,
 
-    /// Slippage tolerance in basis points (default: 50 = 0.50%)
-    #[arg(long, env = "SLIPPAGE_BPS", default_value = "50")]
-    pub slippage_bps: u32,
+    /// Slippage tolerance in basis points (default: 50 = 0.50%) [env: SLIPPAGE_BPS]
+    #[arg(long)]
+    pub slippage_bps: Option<String>,
 
-    /// Maximum FY token amount per trade (in smallest units, e.g., wei)
-    #[arg(long, env = "MAX_FY_AMOUNT")]
-    pub max_fy_amount: Option<u128>,
+    /// Maximum FY token amount per trade (in smallest units, e.g., wei) [env: MAX_FY_AMOUNT]
+    #[arg(long)]
+    pub max_fy_amount: Option<String>,
 
-    /// Maximum base token amount to risk per trade
-    #[arg(long, env = "MAX_BASE_AMOUNT")]
-    pub max_base_amount: Option<u128>,
+    /// Maximum base token amount to risk per trade [env: MAX_BASE_AMOUNT]
+    #[arg(long)]
+    pub max_base_amount: Option<String>,
 
     /// Percentage of expected profit to bid in gas fees (0-100, default: 80)
-    #[arg(long, env = "BID_PERCENTAGE", default_value = "80")]
-    pub bid_percentage: u64,
+    /// [env: BID_PERCENTAGE]
+    #[arg(long)]
+    pub bid_percentage: Option<String>,
+
+    /// Maximum price impact in basis points per trade leg (e.g., 50 = 0.50%)
+    /// [env: MAX_PRICE_IMPACT_BPS]
+    #[arg(long)]
+    pub max_price_impact_bps: Option<String>,
+
+    /// CSV file to append every evaluated opportunity to (disabled when unset)
+    /// [env: DATA_LOG_PATH]
+    #[arg(long)]
+    pub data_log_path: Option<String>,
+
+    /// CSV ledger of realized PnL, continued across runs (disabled when unset)
+    /// [env: PNL_LEDGER_PATH]
+    #[arg(long)]
+    pub pnl_ledger_path: Option<String>,
+
+    /// Order actions produced in the same block by expected profit [env: ORDER_BY_PROFIT]
+    #[arg(long)]
+    pub order_by_profit: bool,
+
+    /// Pause all trading while this file exists (trading also pauses when
+    /// NUMO_KILL_SWITCH=1 is set) [env: KILL_SWITCH_FILE]
+    #[arg(long)]
+    pub kill_switch_file: Option<String>,
+
+    /// After this many consecutive blocks without an opportunity, skip full
+    /// evaluation until the spread between pools reaches the edge threshold
+    /// [env: BACKOFF_AFTER_BLOCKS]
+    #[arg(long)]
+    pub backoff_after_blocks: Option<String>,
+
+    /// Maximum number of pools accepted, a sanity cap on the pool list (default: 100)
+    /// [env: MAX_POOLS]
+    #[arg(long)]
+    pub max_pools: Option<String>,
+
+    /// Maximum number of pools synced concurrently at startup (default: 8)
+    /// [env: SYNC_CONCURRENCY]
+    #[arg(long)]
+    pub sync_concurrency: Option<String>,
+
+    /// Re-fetch pool reserves every this many blocks (default: 1, every block)
+    /// [env: STATE_REFRESH_BLOCKS]
+    #[arg(long)]
+    pub state_refresh_blocks: Option<String>,
+
+    /// Gas limit buffer on top of the estimate in basis points (default: 2000 = 20%)
+    /// [env: GAS_BUFFER_BPS]
+    #[arg(long)]
+    pub gas_buffer_bps: Option<String>,
+
+    /// Adjust the gas buffer toward the p95 of observed gas used / estimate ratios
+    /// [env: AUTO_ADJUST_GAS_BUFFER]
+    #[arg(long)]
+    pub auto_adjust_gas_buffer: bool,
+
+    /// Only log opportunities, skipping previews, gas estimation and submission
+    /// [env: LOG_OPPORTUNITIES_ONLY]
+    #[arg(long)]
+    pub log_opportunities_only: bool,
+
+    /// Size and preview opportunities as for live trades, logging them instead of
+    /// submitting [env: DRY_RUN]
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Approve the router at startup for each base token whose allowance would not
+    /// cover a maximum-size trade, trading once the approvals confirm (FY tokens
+    /// only need one in carry mode) [env: AUTO_APPROVE]
+    #[arg(long)]
+    pub auto_approve: bool,
+
+    /// Allowance granted by --auto-approve, in smallest units (unlimited when unset)
+    /// [env: APPROVAL_AMOUNT]
+    #[arg(long)]
+    pub approval_amount: Option<String>,
+
+    /// Report an unchanged opportunity at most once per this many blocks
+    /// [env: DEDUP_TTL_BLOCKS]
+    #[arg(long)]
+    pub dedup_ttl_blocks: Option<String>,
+
+    /// Reuse gas estimates per pool pair for this many seconds
+    /// [env: GAS_CACHE_TTL_SECS]
+    #[arg(long)]
+    pub gas_cache_ttl_secs: Option<String>,
+
+    /// Edge of buy-FY-then-sell-FY trades (default: edge) [env: EDGE_BPS_BUY_THEN_SELL]
+    #[arg(long)]
+    pub edge_bps_buy_then_sell: Option<String>,
+
+    /// Edge of sell-base-then-sell-FY trades (default: edge) [env: EDGE_BPS_SELL_BASE]
+    #[arg(long)]
+    pub edge_bps_sell_base: Option<String>,
+
+    /// Divergence in basis points required to enter a pair (default: edge)
+    /// [env: ENTER_BPS]
+    #[arg(long)]
+    pub enter_bps: Option<String>,
+
+    /// Divergence in basis points an entered pair must narrow below before
+    /// re-entering (default: edge) [env: EXIT_BPS]
+    #[arg(long)]
+    pub exit_bps: Option<String>,
+
+    /// Log knot changes and discount factor shifts on curve updates (default: true)
+    /// [env: LOG_CURVE_CHANGES]
+    #[arg(long)]
+    pub log_curve_changes: Option<String>,
+
+    /// Seconds without a curve refresh before reporting unready; needs
+    /// --curve-refresh-secs [env: MAX_CURVE_AGE_SECS]
+    #[arg(long)]
+    pub max_curve_age_secs: Option<String>,
+
+    /// Re-read the --sofr-curve file every this many seconds
+    /// [env: CURVE_REFRESH_SECS]
+    #[arg(long)]
+    pub curve_refresh_secs: Option<String>,
+
+    /// Floor for the buffered gas limit (default: 100000) [env: MIN_GAS_LIMIT]
+    #[arg(long)]
+    pub min_gas_limit: Option<String>,
+
+    /// Priority tip in wei per gas bid over the network gas price, within the bid
+    /// budget (default: 1 gwei) [env: GAS_TIP_WEI]
+    #[arg(long)]
+    pub gas_tip_wei: Option<String>,
+
+    /// Cancel in-flight trades pending longer than this [env: CANCEL_AFTER_SECS]
+    #[arg(long)]
+    pub cancel_after_secs: Option<String>,
+
+    /// Recover the nonce of submissions unconfirmed for this long
+    /// [env: NONCE_STUCK_AFTER_SECS]
+    #[arg(long)]
+    pub nonce_stuck_after_secs: Option<String>,
+
+    /// Stuck nonce recovery: fill_gap or reset (default: fill_gap) [env: NONCE_RECOVERY]
+    #[arg(long)]
+    pub nonce_recovery: Option<String>,
+
+    /// Gas price bump of cancellations in bps (default: 1250) [env: CANCEL_GAS_BUMP_BPS]
+    #[arg(long)]
+    pub cancel_gas_bump_bps: Option<String>,
+
+    /// Resubmit trades not mined within this many blocks with bumped gas
+    /// [env: TX_TIMEOUT_BLOCKS]
+    #[arg(long)]
+    pub tx_timeout_blocks: Option<String>,
+
+    /// Submissions of one trade when resubmitting, the first included (default: 3)
+    /// [env: TX_MAX_ATTEMPTS]
+    #[arg(long)]
+    pub tx_max_attempts: Option<String>,
+
+    /// Cap on the total gas price bump of resubmissions in bps (default: 5000)
+    /// [env: MAX_GAS_BUMP_BPS]
+    #[arg(long)]
+    pub max_gas_bump_bps: Option<String>,
+
+    /// Skip pools whose reserves are more imbalanced than this ratio
+    /// [env: MAX_RESERVE_IMBALANCE_RATIO]
+    #[arg(long)]
+    pub max_reserve_imbalance_ratio: Option<String>,
+
+    /// Skip pools whose quoted price deviates from their reserves' invariant price
+    /// by more than this, in bps [env: MAX_INVARIANT_DEVIATION_BPS]
+    #[arg(long)]
+    pub max_invariant_deviation_bps: Option<String>,
+
+    /// Rank live opportunities across blocks, dropping pairs not refreshed within
+    /// this many blocks [env: OPPORTUNITY_MAX_AGE_BLOCKS]
+    #[arg(long)]
+    pub opportunity_max_age_blocks: Option<String>,
+
+    /// Soft budget of pricing RPC calls per block, beyond which pools are priced
+    /// from cache [env: MAX_RPC_CALLS_PER_BLOCK]
+    #[arg(long)]
+    pub max_rpc_calls_per_block: Option<String>,
+
+    /// Pools priced fresh per block, rotating through the rest
+    /// [env: POOLS_PER_BLOCK]
+    #[arg(long)]
+    pub pools_per_block: Option<String>,
+
+    /// Price pools whose previews fail from their reserves instead of skipping them
+    /// [env: RESERVE_PRICE_FALLBACK]
+    #[arg(long)]
+    pub reserve_price_fallback: bool,
+
+    /// Consecutive blocks an edge must persist before it is acted on
+    /// [env: MIN_EDGE_PERSISTENCE_BLOCKS]
+    #[arg(long)]
+    pub min_edge_persistence_blocks: Option<String>,
+
+    /// Executed trades allowed per sliding minute [env: MAX_TRADES_PER_MINUTE]
+    #[arg(long)]
+    pub max_trades_per_minute: Option<String>,
+
+    /// Maximum total base across in-flight trades [env: MAX_TOTAL_BASE_AT_RISK]
+    #[arg(long)]
+    pub max_total_base_at_risk: Option<String>,
+
+    /// Realized loss within the last 24h that halts trading until it ages out
+    /// [env: MAX_DAILY_LOSS]
+    #[arg(long)]
+    pub max_daily_loss: Option<String>,
+
+    /// Alert when a trade's receiver balance change deviates from its expected
+    /// profit by more than this many bps [env: BALANCE_TOLERANCE_BPS]
+    #[arg(long)]
+    pub balance_tolerance_bps: Option<String>,
+
+    /// Chain ID the node must report at startup [env: CHAIN_ID]
+    #[arg(long)]
+    pub chain_id: Option<String>,
+
+    /// Slippage model: flat, quoter, vol_scaled or vol_scaled:<reference bps>
+    /// [env: SLIPPAGE_MODEL]
+    #[arg(long)]
+    pub slippage_model: Option<String>,
+
+    /// Gas limits: on_chain, fixed:<gas> or historical_p95 (default: on_chain)
+    /// [env: GAS_ESTIMATION]
+    #[arg(long)]
+    pub gas_estimation: Option<String>,
+
+    /// Expected gas cost of one trade in base token units, for break-even edge
+    /// reporting [env: GAS_COST_BASE]
+    #[arg(long)]
+    pub gas_cost_base: Option<String>,
+
+    /// Smallest expected profit after gas a trade must net [env: MIN_NET_PROFIT]
+    #[arg(long)]
+    pub min_net_profit: Option<String>,
+
+    /// Price of the native gas token in base tokens, for pricing gas at the base
+    /// fee [env: NATIVE_PRICE_IN_BASE]
+    #[arg(long)]
+    pub native_price_in_base: Option<String>,
+
+    /// Warn when evaluating a block takes longer than this [env: MAX_EVAL_LATENCY_MS]
+    #[arg(long)]
+    pub max_eval_latency_ms: Option<String>,
+
+    /// Consecutive slow blocks before degraded mode [env: DEGRADE_AFTER_SLOW_BLOCKS]
+    #[arg(long)]
+    pub degrade_after_slow_blocks: Option<String>,
+
+    /// Probe sizes priced per pool, median taken above 1 (default: 1) [env: PRICE_PROBES]
+    #[arg(long)]
+    pub price_probes: Option<String>,
+
+    /// Trade single pools on implied rate gaps to the curve [env: CARRY_MODE]
+    #[arg(long)]
+    pub carry_mode: bool,
+
+    /// Skip trades whose on-chain previews drift from the local quoter
+    /// [env: STRICT_QUOTER]
+    #[arg(long)]
+    pub strict_quoter: bool,
+
+    /// Drift allowed under --strict-quoter (default: 10) [env: QUOTER_TOLERANCE_BPS]
+    #[arg(long)]
+    pub quoter_tolerance_bps: Option<String>,
+
+    /// JSON file receiving the pool-by-pool spread heatmap [env: HEATMAP_PATH]
+    #[arg(long)]
+    pub heatmap_path: Option<String>,
+
+    /// Export the heatmap every N blocks (default: 10) [env: HEATMAP_EVERY_BLOCKS]
+    #[arg(long)]
+    pub heatmap_every_blocks: Option<String>,
+
+    /// JSON file receiving the pool and curve state of every evaluated block
+    /// [env: SNAPSHOT_PATH]
+    #[arg(long)]
+    pub snapshot_path: Option<String>,
+
+    /// Simulate trades expecting at least this profit before submission
+    /// [env: MIN_PROFIT_TO_SIMULATE]
+    #[arg(long)]
+    pub min_profit_to_simulate: Option<String>,
+
+    /// Attach an access list created once per pool pair to every trade
+    /// [env: USE_ACCESS_LISTS]
+    #[arg(long)]
+    pub use_access_lists: bool,
+
+    /// Storage slot of the pools' reserves cache, to simulate on top of in-flight
+    /// trades [env: POOL_RESERVES_SLOT]
+    #[arg(long)]
+    pub pool_reserves_slot: Option<String>,
+
+    /// Multicall3 contract batching pool pricing, e.g.
+    /// 0xcA11bde05977b3631167028862bE2a173976CA11 [env: MULTICALL_ADDRESS]
+    #[arg(long)]
+    pub multicall_address: Option<String>,
+
+    /// Paper trade for this many seconds, then go live only if the paper net
+    /// profit is positive [env: DRY_RUN_DURATION_SECS]
+    #[arg(long)]
+    pub dry_run_duration: Option<String>,
+
+    /// Paper net profit the dry run must exceed (default: 0)
+    /// [env: PROMOTION_MIN_NET_PROFIT]
+    #[arg(long, allow_hyphen_values = true)]
+    pub promotion_min_net_profit: Option<String>,
+
+    /// Comma-separated pools excluded from trading but still monitored
+    /// [env: DISABLED_POOLS]
+    #[arg(long)]
+    pub disabled_pools: Option<String>,
+
+    /// Comma-separated pools priced and reported but never traded
+    /// [env: OBSERVE_ONLY_POOLS]
+    #[arg(long)]
+    pub observe_only_pools: Option<String>,
+
+    /// Per-pool target rate spreads in bps, as `pool=bps,...`
+    /// [env: POOL_CURVE_SPREADS_BPS]
+    #[arg(long, allow_hyphen_values = true)]
+    pub pool_curve_spreads_bps: Option<String>,
+
+    /// Base token decimals, for human-readable amounts (default: 18) [env: BASE_DECIMALS]
+    #[arg(long)]
+    pub base_decimals: Option<String>,
+
+    /// FY token decimals, for human-readable amounts (default: 18) [env: FY_DECIMALS]
+    #[arg(long)]
+    pub fy_decimals: Option<String>,
+
+    /// Record every inbound event for `numo replay` [env: RECORD_EVENTS_PATH]
+    #[arg(long)]
+    pub record_events_path: Option<String>,
+
+    /// Maximum candidate pairs sized concurrently (default: 4) [env: SIZING_CONCURRENCY]
+    #[arg(long)]
+    pub sizing_concurrency: Option<String>,
+
+    /// Token aggregate profit is reported in [env: PROFIT_TOKEN]
+    #[arg(long)]
+    pub profit_token: Option<String>,
+
+    /// Profit token units per base token unit, as `base=rate,...` [env: PROFIT_RATES]
+    #[arg(long)]
+    pub profit_rates: Option<String>,
+}
+
+impl ConfigArgs {
+    /// Flags given on the command line, keyed by their environment variable
+    fn overrides(&self) -> Vec<(&'static str, String)> {
+        let flags = [
+            ("PROFILE", &self.profile),
+            ("ROUTER_ADDRESS", &self.router_address),
+            ("POOL_ADDRESSES", &self.pool_addresses),
+            ("EDGE_BPS", &self.edge_bps),
+            ("SLIPPAGE_BPS", &self.slippage_bps),
+            ("MAX_FY_AMOUNT", &self.max_fy_amount),
+            ("MAX_BASE_AMOUNT", &self.max_base_amount),
+            ("BID_PERCENTAGE", &self.bid_percentage),
+            ("MAX_PRICE_IMPACT_BPS", &self.max_price_impact_bps),
+            ("DATA_LOG_PATH", &self.data_log_path),
+            ("PNL_LEDGER_PATH", &self.pnl_ledger_path),
+            ("KILL_SWITCH_FILE", &self.kill_switch_file),
+            ("BACKOFF_AFTER_BLOCKS", &self.backoff_after_blocks),
+            ("MAX_POOLS", &self.max_pools),
+            ("SYNC_CONCURRENCY", &self.sync_concurrency),
+            ("STATE_REFRESH_BLOCKS", &self.state_refresh_blocks),
+            ("GAS_BUFFER_BPS", &self.gas_buffer_bps),
+            ("DEDUP_TTL_BLOCKS", &self.dedup_ttl_blocks),
+            ("GAS_CACHE_TTL_SECS", &self.gas_cache_ttl_secs),
+            ("EDGE_BPS_BUY_THEN_SELL", &self.edge_bps_buy_then_sell),
+            ("EDGE_BPS_SELL_BASE", &self.edge_bps_sell_base),
+            ("ENTER_BPS", &self.enter_bps),
+            ("EXIT_BPS", &self.exit_bps),
+            ("LOG_CURVE_CHANGES", &self.log_curve_changes),
+            ("MAX_CURVE_AGE_SECS", &self.max_curve_age_secs),
+            ("CURVE_REFRESH_SECS", &self.curve_refresh_secs),
+            ("MIN_GAS_LIMIT", &self.min_gas_limit),
+            ("GAS_TIP_WEI", &self.gas_tip_wei),
+            ("PROFIT_TOKEN", &self.profit_token),
+            ("PROFIT_RATES", &self.profit_rates),
+            ("CANCEL_AFTER_SECS", &self.cancel_after_secs),
+            ("NONCE_STUCK_AFTER_SECS", &self.nonce_stuck_after_secs),
+            ("NONCE_RECOVERY", &self.nonce_recovery),
+            ("SIZING_CONCURRENCY", &self.sizing_concurrency),
+            ("RECORD_EVENTS_PATH", &self.record_events_path),
+            (
+                "MAX_RESERVE_IMBALANCE_RATIO",
+                &self.max_reserve_imbalance_ratio,
+            ),
+            (
+                "MAX_INVARIANT_DEVIATION_BPS",
+                &self.max_invariant_deviation_bps,
+            ),
+            (
+                "OPPORTUNITY_MAX_AGE_BLOCKS",
+                &self.opportunity_max_age_blocks,
+            ),
+            ("MAX_RPC_CALLS_PER_BLOCK", &self.max_rpc_calls_per_block),
+            ("POOLS_PER_BLOCK", &self.pools_per_block),
+            ("CANCEL_GAS_BUMP_BPS", &self.cancel_gas_bump_bps),
+            ("TX_TIMEOUT_BLOCKS", &self.tx_timeout_blocks),
+            ("TX_MAX_ATTEMPTS", &self.tx_max_attempts),
+            ("MAX_GAS_BUMP_BPS", &self.max_gas_bump_bps),
+            (
+                "MIN_EDGE_PERSISTENCE_BLOCKS",
+                &self.min_edge_persistence_blocks,
+            ),
+            ("MAX_TRADES_PER_MINUTE", &self.max_trades_per_minute),
+            ("MAX_TOTAL_BASE_AT_RISK", &self.max_total_base_at_risk),
+            ("MAX_DAILY_LOSS", &self.max_daily_loss),
+            ("BALANCE_TOLERANCE_BPS", &self.balance_tolerance_bps),
+            ("CHAIN_ID", &self.chain_id),
+            ("SLIPPAGE_MODEL", &self.slippage_model),
+            ("GAS_ESTIMATION", &self.gas_estimation),
+            ("GAS_COST_BASE", &self.gas_cost_base),
+            ("MIN_NET_PROFIT", &self.min_net_profit),
+            ("NATIVE_PRICE_IN_BASE", &self.native_price_in_base),
+            ("MAX_EVAL_LATENCY_MS", &self.max_eval_latency_ms),
+            ("DEGRADE_AFTER_SLOW_BLOCKS", &self.degrade_after_slow_blocks),
+            ("PRICE_PROBES", &self.price_probes),
+            ("QUOTER_TOLERANCE_BPS", &self.quoter_tolerance_bps),
+            ("HEATMAP_PATH", &self.heatmap_path),
+            ("HEATMAP_EVERY_BLOCKS", &self.heatmap_every_blocks),
+            ("SNAPSHOT_PATH", &self.snapshot_path),
+            ("MIN_PROFIT_TO_SIMULATE", &self.min_profit_to_simulate),
+            ("POOL_RESERVES_SLOT", &self.pool_reserves_slot),
+            ("MULTICALL_ADDRESS", &self.multicall_address),
+            ("DRY_RUN_DURATION_SECS", &self.dry_run_duration),
+            ("APPROVAL_AMOUNT", &self.approval_amount),
+            ("PROMOTION_MIN_NET_PROFIT", &self.promotion_min_net_profit),
+            ("DISABLED_POOLS", &self.disabled_pools),
+            ("OBSERVE_ONLY_POOLS", &self.observe_only_pools),
+            ("POOL_CURVE_SPREADS_BPS", &self.pool_curve_spreads_bps),
+            ("BASE_DECIMALS", &self.base_decimals),
+            ("FY_DECIMALS", &self.fy_decimals),
+        ];
+        let mut overrides: Vec<(&'static str, String)> = flags
+            .into_iter()
+            .filter_map(|(key, value)| value.clone().map(|value| (key, value)))
+            .collect();
+        if self.order_by_profit {
+            overrides.push(("ORDER_BY_PROFIT", "true".to_string()));
+        }
+        if self.auto_adjust_gas_buffer {
+            overrides.push(("AUTO_ADJUST_GAS_BUFFER", "true".to_string()));
+        }
+        if self.use_access_lists {
+            overrides.push(("USE_ACCESS_LISTS", "true".to_string()));
+        }
+        if self.reserve_price_fallback {
+            overrides.push(("RESERVE_PRICE_FALLBACK", "true".to_string()));
+        }
+        if self.log_opportunities_only {
+            overrides.push(("LOG_OPPORTUNITIES_ONLY", "true".to_string()));
+        }
+        if self.dry_run {
+            overrides.push(("DRY_RUN", "true".to_string()));
+        }
+        if self.auto_approve {
+            overrides.push(("AUTO_APPROVE", "true".to_string()));
+        }
+        if self.carry_mode {
+            overrides.push(("CARRY_MODE", "true".to_string()));
+        }
+        if self.strict_quoter {
+            overrides.push(("STRICT_QUOTER", "true".to_string()));
+        }
+        overrides
+    }
+
+    /// Build the strategy configuration, preferring flags over the environment
+    fn to_config(&self) -> Result<Config> {
+        self.to_config_with(|key| std::env::var(key).ok())
+    }
+
+    /// Build the strategy configuration again from a freshly read `.env` file, whose
+    /// values now take precedence over the environment the process started with
+    ///
+    /// The startup `.env` was loaded into the process environment, so the fallback is
+    /// the environment as it was before; a key deleted from the file reverts to it.
+    fn reload_config(&self) -> Result<Config> {
+        // The iterator reads the file without touching the process environment
+        #[allow(deprecated)]
+        let file_vars: HashMap<String, String> = dotenv::dotenv_iter()
+            .map(|vars| vars.filter_map(Result::ok).collect())
+            .unwrap_or_default();
+        let process_env = PROCESS_ENV.get();
+        self.to_config_with(|key| {
+            file_vars
+                .get(key)
+                .or_else(|| process_env.and_then(|env| env.get(key)))
+                .cloned()
+        })
+    }
+
+    /// Build the strategy configuration, preferring flags over `fallback`
+    fn to_config_with(&self, fallback: impl Fn(&str) -> Option<String>) -> Result<Config> {
+        let overrides = self.overrides();
+        Config::from_vars(|key| {
+            overrides
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.clone())
+                .or_else(|| fallback(key))
+        })
+    }
 }
 
+/// Process environment before `.env` was loaded into it, the fallback of reloads
+static PROCESS_ENV: OnceLock<HashMap<String, String>> = OnceLock::new();
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Load environment variables from .env file if present
+    // Load environment variables from .env file if present, remembering the
+    // environment without them for config reloads
+    PROCESS_ENV.get_or_init(|| {
+        std::env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+            .collect()
+    });
     dotenv().ok();
 
     // Set up tracing/logging
@@ -85,105 +756,459 @@ async fn main() -> Result<()> {
     // Parse command-line arguments (with .env fallback)
     let args = Args::parse();
 
-    // Validate configuration
-    if args.pool_addresses.is_empty() {
-        anyhow::bail!("At least one pool address must be specified");
+    // Build and validate strategy configuration from flags and the environment
+    let config = args.config.to_config()?;
+
+    if let Some(Command::Replay { file }) = &args.command {
+        return replay(file, config, load_curve(&args)?).await;
+    }
+    if let Some(Command::BenchCurve) = &args.command {
+        return bench_curve(&args, &config).await;
+    }
+    if let Some(file) = &args.simulate_from_snapshot {
+        return simulate_from_snapshot(file, config).await;
     }
+    // Required by clap whenever no command is given
+    let wss = args.wss.clone().unwrap_or_default();
+
+    // Interactive approval cannot run headless, so refuse before connecting
+    let approver = if args.confirm_each_trade {
+        let timeout = Duration::from_secs(args.confirm_timeout_secs);
+        Some(Arc::new(LineApprover::stdin(timeout)?))
+    } else {
+        None
+    };
 
     info!(
-        wss = %args.wss,
-        router = %args.router_address,
-        pools = args.pool_addresses.len(),
-        edge_bps = args.edge_bps,
-        slippage_bps = args.slippage_bps,
+        wss = %wss,
+        router = ?config.router_address,
+        pools = config.pool_addresses.len(),
+        edge_bps = config.edge_bps,
+        slippage_bps = config.slippage_bps,
         "Configuration loaded"
     );
 
     // Connect to Celo via WebSocket
     info!("Connecting to Celo...");
-    let ws = Ws::connect(&args.wss).await?;
+    let ws = Ws::connect(&wss).await?;
     let provider = Provider::new(ws);
 
-    // Set up wallet
-    let wallet: LocalWallet = args.private_key.parse()?;
+    if let Some(dir) = &args.emit_unsigned_to {
+        // Required by clap alongside --emit-unsigned-to
+        let address: Address = args.from_address.clone().unwrap_or_default().parse()?;
+        info!(
+            bot_address = ?address,
+            dir = %dir.display(),
+            "Emitting unsigned transactions, no wallet loaded"
+        );
+        let provider = Arc::new(provider.with_sender(address));
+        return run(provider, address, config, &args, approver, None).await;
+    }
+
+    // Set up wallet; required by clap unless emitting unsigned transactions
+    let wallet: LocalWallet = args.private_key.clone().unwrap_or_default().parse()?;
     let address = wallet.address();
     info!(bot_address = ?address, "Wallet loaded");
 
     // Wrap provider with signer and nonce manager
     let provider = Arc::new(provider.nonce_manager(address).with_signer(wallet));
+    let nonce_reset: Arc<dyn NonceReset> = provider.clone();
+    run(
+        provider,
+        address,
+        config,
+        &args,
+        approver,
+        Some(nonce_reset),
+    )
+    .await
+}
 
-    // Parse pool addresses
-    let pool_addresses: Result<Vec<Address>> = args
-        .pool_addresses
-        .iter()
-        .map(|s| {
-            Address::from_str(s).map_err(|e| anyhow::anyhow!("Invalid pool address {}: {}", s, e))
-        })
-        .collect();
-    let pool_addresses = pool_addresses?;
-
-    // Build strategy configuration
-    let config = Config {
-        router_address: Address::from_str(&args.router_address)?,
-        pool_addresses,
-        edge_bps: args.edge_bps,
-        slippage_bps: args.slippage_bps,
-        max_fy_amount: args.max_fy_amount.unwrap_or(100_000u128 * 10u128.pow(18)),
-        max_base_amount: args.max_base_amount.unwrap_or(50_000u128 * 10u128.pow(18)),
-        bid_percentage: args.bid_percentage,
-    };
-
+/// Run the bot against `provider`, trading from `address`
+async fn run<M>(
+    provider: Arc<M>,
+    address: Address,
+    config: Config,
+    args: &Args,
+    approver: Option<Arc<LineApprover>>,
+    nonce_reset: Option<Arc<dyn NonceReset>>,
+) -> Result<()>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+    M::Provider: PubsubClient,
+{
     info!(
         router = ?config.router_address,
         pools = config.pool_addresses.len(),
         "Strategy configuration initialized"
     );
 
-    // Initialize SOFR curve with default USD rates
-    // TODO: Load real SOFR rates from data provider
-    let sofr_curve = SofrCurve::default_usd();
+    let curve = load_curve(args)?;
     info!(
-        "SOFR curve initialized with {} knots",
-        sofr_curve.knots.len()
+        index = curve.index.name(),
+        "Rate curve initialized with {} knots",
+        curve.knots.len()
     );
 
+    // Fail fast on misconfiguration instead of on the first block
+    run_self_test(provider.clone(), &config, &curve, Some(address))
+        .await
+        .into_result()?;
+
     // Set up Artemis Engine
     let mut engine: Engine<Event, Action> = Engine::default();
 
+    // One clock stamps ticks, realized losses and blocks without a header timestamp
+    // so their windows agree
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+
     // Add block collector
     let block_collector = Box::new(BlockCollector::new(provider.clone()));
-    let block_collector = CollectorMap::new(block_collector, |block: NewBlock| {
-        // Get current timestamp from system (blocks don't have timestamps in the event)
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+    let block_clock = clock.clone();
+    let block_collector = CollectorMap::new(block_collector, move |block: NewBlock| {
+        // Time to maturity runs off the block's own time; the clock only stands in
+        // for a header without one
+        let timestamp = match block.timestamp.as_u64() {
+            0 => block_clock.now(),
+            timestamp => timestamp,
+        };
 
         Event::NewBlock(NewBlockEvent {
             block_number: block.number.as_u64(),
             timestamp,
-            base_fee: None, // Not available in NewBlock event
+            base_fee: block.base_fee_per_gas,
         })
     });
-    engine.add_collector(Box::new(block_collector));
+    let block_collector: Box<dyn Collector<Event>> = match args.block_silence_timeout_secs {
+        Some(secs) => {
+            let silence_timeout = Duration::from_secs(secs);
+            let poll_interval = Duration::from_millis(args.block_poll_interval_ms);
+            let block_collector: Box<dyn Collector<Event>> = Box::new(block_collector);
+            info!(
+                silence_timeout_secs = secs,
+                poll_url = args.block_poll_url.as_deref().unwrap_or("ws"),
+                "Block polling fallback enabled"
+            );
+            match &args.block_poll_url {
+                Some(url) => {
+                    let http = Arc::new(Provider::<Http>::try_from(url.as_str())?);
+                    Box::new(
+                        PollingFallbackCollector::new(block_collector, http, silence_timeout)
+                            .with_poll_interval(poll_interval),
+                    )
+                }
+                None => Box::new(
+                    PollingFallbackCollector::new(
+                        block_collector,
+                        provider.clone(),
+                        silence_timeout,
+                    )
+                    .with_poll_interval(poll_interval),
+                ),
+            }
+        }
+        None => Box::new(block_collector),
+    };
+
+    // Replay missed blocks read-only first, then hand over to the live blocks
+    let block_collector: Box<dyn Collector<Event>> = match args.start_block {
+        Some(start_block) => {
+            info!(
+                start_block,
+                max_blocks = args.max_catchup_blocks,
+                "Historical catch-up runs before live blocks"
+            );
+            Box::new(
+                CatchUpCollector::new(provider.clone(), start_block, block_collector)
+                    .with_max_blocks(args.max_catchup_blocks)
+                    .with_delay(Duration::from_millis(args.catchup_delay_ms)),
+            )
+        }
+        None => block_collector,
+    };
+    engine.add_collector(block_collector);
     info!("Block collector added");
 
+    // Add tick collector
+    if let Some(secs) = args.tick_interval_secs {
+        let tick_collector =
+            TickCollector::new(Duration::from_secs(secs)).with_clock(clock.clone());
+        engine.add_collector(Box::new(tick_collector));
+        info!(interval_secs = secs, "Tick collector added");
+    }
+
+    // Add curve refresh collector
+    if let Some(secs) = config.curve_refresh_secs {
+        let path = args
+            .sofr_curve
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("CURVE_REFRESH_SECS needs --sofr-curve to re-read"))?;
+        let compounding = args
+            .curve_compounding
+            .as_deref()
+            .and_then(Compounding::from_name);
+        let curve_collector = CurveFileCollector::new(path, Duration::from_secs(secs))
+            .with_compounding(compounding)
+            .with_clock(clock.clone());
+        engine.add_collector(Box::new(curve_collector));
+        info!(interval_secs = secs, "Curve refresh collector added");
+    }
+
+    // Add mempool collector
+    if args.watch_mempool {
+        let mempool_clock = clock.clone();
+        let mempool_collector = CollectorMap::new(
+            Box::new(MempoolCollector::new(provider.clone())),
+            move |tx: Transaction| {
+                Event::PendingTx(PendingTxEvent::from_transaction(&tx, mempool_clock.now()))
+            },
+        );
+        engine.add_collector(Box::new(mempool_collector));
+        info!("Mempool collector added");
+    }
+
+    // Gas buffer shared between the execution policy and the receipt-driven tuner
+    let gas_tuner = Arc::new(GasBufferTuner::new(
+        config.gas_buffer_bps,
+        config.auto_adjust_gas_buffer,
+    ));
+    let policy = DefaultRouterPolicy::from_config(Arc::new(provider.clone()), &config)
+        .with_gas_buffer(gas_tuner.buffer());
+
+    // Gas used by confirmed trades (when gas limits are taken from their p95), gas
+    // estimates reused per pool pair (dropped when a trade reverts) and reserve
+    // moves of submitted trades (overlaid on simulations until they resolve), fed
+    // by the executor's receipts
+    let gas_history = policy.gas_history();
+    let gas_cache = policy.gas_cache();
+    let pending = policy.pending_state();
+
+    let mut policy: Box<dyn ExecutionPolicy> = Box::new(policy);
+    if let Some(approver) = approver {
+        info!("Every trade needs operator approval");
+        let mut approval = ApprovalPolicy::new(policy, approver)
+            .with_decimals(config.token_decimals)
+            .with_decision_ttl(Duration::from_secs(args.confirm_decision_ttl_secs));
+        if let Some(price) = config.native_price_in_base {
+            approval = approval.with_native_price(price);
+        }
+        policy = Box::new(approval);
+    }
+
+    // Submitted trades, so superseded or stale ones can be cancelled
+    let inflight = Arc::new(InflightTrades::new());
+    let cancel_gas_bump_bps = config.cancel_gas_bump_bps;
+    let (nonce_stuck_after_secs, nonce_recovery) =
+        (config.nonce_stuck_after_secs, config.nonce_recovery);
+    let retries = config.tx_timeout_blocks.map(|timeout_blocks| RetryPolicy {
+        timeout_blocks,
+        max_attempts: config.tx_max_attempts,
+        gas_bump_bps: config.cancel_gas_bump_bps,
+        max_gas_bump_bps: config.max_gas_bump_bps,
+    });
+    let balance_tolerance_bps = config.balance_tolerance_bps;
+
+    // Realized PnL of reconciled trades over the last 24h, halting trading after a
+    // day's losses
+    let loss_limit = config.max_daily_loss.map(|max_loss| {
+        Arc::new(
+            DailyLossLimit::new(max_loss)
+                .with_gas_cost(config.gas_cost_base)
+                .with_clock(clock.clone()),
+        )
+    });
+
+    // Realized PnL of confirmed trades, continuing the ledger of earlier runs and
+    // feeding the loss limit
+    let pnl = if config.pnl_ledger_path.is_some() || loss_limit.is_some() {
+        let mut tracker = PnlTracker::new(provider.clone());
+        if let Some(price) = config.native_price_in_base {
+            tracker = tracker.with_native_price(price, config.token_decimals.base);
+        }
+        if let Some(limit) = &loss_limit {
+            tracker = tracker.with_loss_limit(limit.clone());
+        }
+        if let Some(path) = &config.pnl_ledger_path {
+            tracker = tracker.with_ledger(path)?;
+        }
+        Some(Arc::new(tracker))
+    } else {
+        None
+    };
+
+    // Capital reserved by in-flight trades, released as they resolve
+    let budget = config
+        .max_total_base_at_risk
+        .map(|max_total| Arc::new(CapitalBudget::new(max_total)));
+
     // Create Numo arbitrage strategy
     // Note: sync_state() is called automatically by the Engine
-    let strategy = NumoArb::new(Arc::new(provider.clone()), config, sofr_curve);
+    // Settings adjustable without a restart, re-read from `.env` on SIGHUP
+    let shared_config: SharedConfig = Arc::new(RwLock::new(config.clone()));
+    #[cfg(unix)]
+    {
+        let (shared, config_args) = (shared_config.clone(), args.config.clone());
+        tokio::spawn(async move {
+            if let Err(e) = reload_on_sighup(shared, config_args).await {
+                warn!(error = ?e, "Failed to install the SIGHUP handler, config reload disabled");
+            }
+        });
+    }
+
+    // Carry positions held until their pool matures, freed again by failed trades
+    let carry_positions = Arc::new(CarryPositions::new());
+
+    let compounding = curve.compounding;
+    let mut strategy = NumoArb::new_with_policy(Arc::new(provider.clone()), config, curve, policy)
+        .with_inflight_trades(inflight.clone())
+        .with_carry_positions(carry_positions.clone())
+        .with_shared_config(shared_config);
+    if let Some(name) = &args.reference_rate_index {
+        let index = RateIndex::from_name(name);
+        let reference = RateCurve::preset(&index)
+            .ok_or_else(|| {
+                anyhow::anyhow!("No default curve for reference rate index {}", index.name())
+            })?
+            .with_compounding(compounding);
+        info!(
+            index = index.name(),
+            "Reference curve must confirm divergences"
+        );
+        strategy = strategy.with_reference_curve(reference);
+    }
+    if let Some(budget) = &budget {
+        strategy = strategy.with_capital_budget(budget.clone());
+    }
+    if let Some(limit) = &loss_limit {
+        strategy = strategy.with_loss_limit(limit.clone());
+    }
 
     engine.add_strategy(Box::new(strategy));
     info!("Numo arbitrage strategy added");
 
-    // Add mempool executor
-    let executor = Box::new(MempoolExecutor::new(provider.clone()));
+    // Offline signing: write transactions out, nothing is submitted or cancelled
+    if let Some(dir) = &args.emit_unsigned_to {
+        let executor = UnsignedTxExecutor::new(dir)?;
+        let executor = ExecutorMap::new(Box::new(executor), |action| match action {
+            Action::SubmitTx(tx) | Action::Approve(tx) => Some(tx),
+            _ => None,
+        });
+        engine.add_executor(Box::new(executor));
+        info!(dir = %dir.display(), "Unsigned transaction executor added");
+        return run_engine(engine).await;
+    }
+
+    // Add mempool executor, reporting receipts to the gas tuner
+    let mut executor = ConfirmingExecutor::new(provider.clone())
+        .with_observer(gas_tuner)
+        .with_observer(inflight.clone())
+        .with_observer(carry_positions);
+    if let Some(cache) = gas_cache {
+        executor = executor.with_observer(cache);
+    }
+    if let Some(history) = gas_history {
+        executor = executor.with_observer(history);
+    }
+    if let Some(policy) = retries {
+        executor = executor.with_retries(policy);
+        info!(
+            timeout_blocks = policy.timeout_blocks,
+            max_attempts = policy.max_attempts,
+            max_gas_bump_bps = policy.max_gas_bump_bps,
+            "Resubmission of unmined trades enabled"
+        );
+    }
+    if let Some(budget) = budget {
+        executor = executor.with_observer(budget);
+    }
+    if let Some(pending) = pending {
+        executor = executor.with_observer(pending);
+    }
+    if let Some(tolerance_bps) = balance_tolerance_bps {
+        let verifier = BalanceVerifier::new(provider.clone()).with_tolerance(tolerance_bps);
+        executor = executor.with_observer(Arc::new(verifier));
+        info!(tolerance_bps, "Receiver balance verification enabled");
+    }
+    if let Some(pnl) = &pnl {
+        executor = executor.with_observer(pnl.clone());
+        let summary = pnl.summary();
+        info!(
+            trades = summary.trades,
+            net_profit = summary.net_profit,
+            "Realized PnL tracking enabled"
+        );
+    }
+    if let Some(secs) = nonce_stuck_after_secs {
+        let mut monitor =
+            NonceGapMonitor::new(provider.clone(), address, Duration::from_secs(secs))
+                .with_mode(nonce_recovery)
+                .with_gas_bump(cancel_gas_bump_bps);
+        if let Some(resetter) = nonce_reset {
+            monitor = monitor.with_resetter(resetter);
+        }
+        let monitor = Arc::new(monitor);
+        executor = executor.with_observer(monitor.clone());
+        tokio::spawn(async move { monitor.run().await });
+        info!(
+            stuck_after_secs = secs,
+            mode = ?nonce_recovery,
+            "Nonce gap recovery enabled"
+        );
+    }
+    let executor = Box::new(executor);
     let executor = ExecutorMap::new(executor, |action| match action {
         Action::SubmitTx(tx) => Some(tx),
+        _ => None,
     });
+    let jitter = submission_jitter(address.as_bytes(), args.submission_jitter_ms);
+    let executor = JitteredExecutor::new(Box::new(executor), jitter);
     engine.add_executor(Box::new(executor));
-    info!("Mempool executor added");
+    info!(
+        jitter_ms = jitter.as_millis() as u64,
+        "Mempool executor added"
+    );
+
+    // Cancellations go out without jitter
+    let cancel_executor = CancelExecutor::new(provider.clone())
+        .with_gas_bump(cancel_gas_bump_bps)
+        .with_inflight(inflight);
+    let cancel_executor = ExecutorMap::new(Box::new(cancel_executor), |action| match action {
+        Action::Cancel(tx_hash) => Some(tx_hash),
+        _ => None,
+    });
+    engine.add_executor(Box::new(cancel_executor));
+    info!("Cancel executor added");
+
+    // Approvals go out without jitter, kept away from the trades' receipt observers
+    let approval_executor = ExecutorMap::new(
+        Box::new(ConfirmingExecutor::new(provider.clone())),
+        |action| match action {
+            Action::Approve(tx) => Some(tx),
+            _ => None,
+        },
+    );
+    engine.add_executor(Box::new(approval_executor));
+    info!("Approval executor added");
 
-    // Start the engine
+    let result = run_engine(engine).await;
+    if let Some(pnl) = &pnl {
+        let summary = pnl.summary();
+        info!(
+            trades = summary.trades,
+            expected_profit = summary.expected_profit,
+            gross_profit = summary.gross_profit,
+            gas_spent = summary.gas_spent,
+            net_profit = summary.net_profit,
+            pending = pnl.pending(),
+            "Realized PnL"
+        );
+    }
+    result
+}
+
+/// Run the engine until all of its tasks finish
+async fn run_engine(engine: Engine<Event, Action>) -> Result<()> {
     info!("Starting Artemis engine...");
     info!("Bot is now running. Press Ctrl+C to stop.");
 
@@ -199,3 +1224,120 @@ async fn main() -> Result<()> {
     info!("Shutting down...");
     Ok(())
 }
+
+/// Re-read the configuration on every SIGHUP and apply its reloadable settings
+#[cfg(unix)]
+async fn reload_on_sighup(shared: SharedConfig, config_args: ConfigArgs) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    while hangups.recv().await.is_some() {
+        let reloaded = match config_args.reload_config() {
+            Ok(reloaded) => reloaded,
+            Err(e) => {
+                warn!(error = ?e, "Config reload failed, keeping the current settings");
+                continue;
+            }
+        };
+        let mut current = shared.write().unwrap_or_else(|e| e.into_inner());
+        for field in ignored_changes(&current, &reloaded) {
+            warn!(field, "Config change needs a restart, ignored");
+        }
+        let changes = apply_reload(&mut current, &reloaded);
+        if changes.is_empty() {
+            info!("Config reloaded, no changes");
+        }
+        for change in changes {
+            info!(%change, "Config setting reloaded");
+        }
+    }
+    Ok(())
+}
+
+/// Print the targets of every configured pool under each curve convention
+async fn bench_curve(args: &Args, config: &Config) -> Result<()> {
+    let wss = args
+        .wss
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("bench-curve needs --wss to read pool maturities"))?;
+    let curve = load_curve(args)?;
+    let provider = Arc::new(Provider::new(Ws::connect(&wss).await?));
+
+    let maturities = fetch_maturities(provider, &config.pool_addresses).await?;
+    let bench = CurveBench::new(&curve, SystemClock.now(), &maturities);
+    println!("{} curve, {} pools", curve.index.name(), maturities.len());
+    print!("{bench}");
+    Ok(())
+}
+
+/// Re-evaluate an exported snapshot and log the decision it leads to
+async fn simulate_from_snapshot(file: &std::path::Path, config: Config) -> Result<()> {
+    let snapshot = StateSnapshot::read(file)?;
+    info!(
+        file = %file.display(),
+        block_number = snapshot.block_number,
+        timestamp = snapshot.timestamp,
+        pools = snapshot.pool_states.len(),
+        "Simulating from snapshot"
+    );
+
+    let decision = simulate_snapshot(snapshot, config).await?;
+    let record = &decision.record;
+    match &decision.opportunity {
+        Some(opp) => info!(
+            cheap_pool = ?opp.cheap_pool,
+            rich_pool = ?opp.rich_pool,
+            fy_amount = opp.fy_amount,
+            expected_profit = opp.expected_profit,
+            "Snapshot yields an opportunity"
+        ),
+        None => info!(
+            skip_reason = ?record.skip_reason,
+            cheap_pool = ?record.cheap_pool,
+            rich_pool = ?record.rich_pool,
+            divergence_bps = ?record.divergence_bps,
+            "Snapshot yields no opportunity"
+        ),
+    }
+    Ok(())
+}
+
+/// Rate curve from `--sofr-curve` when given, otherwise the preset of the
+/// configured rate index, compounded as `--curve-compounding` sets
+fn load_curve(args: &Args) -> Result<RateCurve> {
+    let curve = if let Some(path) = &args.sofr_curve {
+        let curve = RateCurve::from_file(path)?;
+        info!(file = %path.display(), "Rate curve loaded from file");
+        curve
+    } else {
+        let index = RateIndex::from_name(&args.rate_index);
+        RateCurve::preset(&index)
+            .ok_or_else(|| anyhow::anyhow!("No default curve for rate index {}", index.name()))?
+    };
+    let Some(name) = &args.curve_compounding else {
+        return Ok(curve);
+    };
+    let compounding = Compounding::from_name(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown curve compounding {name}"))?;
+    info!(?compounding, "Rate curve compounding set");
+    Ok(curve.with_compounding(compounding))
+}
+
+/// Replay a recorded session and log the actions the strategy emits
+async fn replay(file: &std::path::Path, config: Config, curve: RateCurve) -> Result<()> {
+    let session = read_session(file)?;
+    info!(file = %file.display(), events = session.len(), "Replaying recorded session");
+
+    let replayed = replay_session(session, config, curve).await?;
+    for event in &replayed {
+        for action in &event.actions {
+            info!(event = ?event.event, action = ?action, "Replayed action");
+        }
+    }
+    info!(
+        events = replayed.len(),
+        actions = replayed.iter().map(|e| e.actions.len()).sum::<usize>(),
+        "Replay complete"
+    );
+    Ok(())
+}
@@ -4,22 +4,24 @@ use async_trait::async_trait;
 use ethers::{
     prelude::Middleware,
     providers::PubsubClient,
-    types::{H256, U64},
+    types::{H256, U256, U64},
 };
 use std::sync::Arc;
 use tokio_stream::StreamExt;
 
 /// A collector that listens for new blocks, and generates a stream of
-/// [events](NewBlock) which contain the block number and hash.
+/// [events](NewBlock) which contain the block number, hash and header fields.
 pub struct BlockCollector<M> {
     provider: Arc<M>,
 }
 
-/// A new block event, containing the block number and hash.
+/// A new block event, containing the block number, hash, timestamp and base fee.
 #[derive(Debug, Clone)]
 pub struct NewBlock {
     pub hash: H256,
     pub number: U64,
+    pub timestamp: U256,
+    pub base_fee_per_gas: Option<U256>,
 }
 
 impl<M> BlockCollector<M> {
@@ -40,7 +42,12 @@ where
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, NewBlock>> {
         let stream = self.provider.subscribe_blocks().await?;
         let stream = stream.filter_map(|block| match block.hash {
-            Some(hash) => block.number.map(|number| NewBlock { hash, number }),
+            Some(hash) => block.number.map(|number| NewBlock {
+                hash,
+                number,
+                timestamp: block.timestamp,
+                base_fee_per_gas: block.base_fee_per_gas,
+            }),
             None => None,
         });
         Ok(Box::pin(stream))
@@ -0,0 +1,86 @@
+/// Benchmarks for the pricing hot path
+/// Run with `cargo bench -p numo-arb`; everything runs against mocks or local math,
+/// so no network access is needed
+use std::hint::black_box;
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ethers::prelude::*;
+
+use numo_arb::pricing::{marginal_price_base_per_fy, solve_fy_amount_to_target, PoolState};
+use numo_arb::quoter::LocalQuoter;
+use numo_arb::sofr::RateCurve;
+use numo_arb::test_utils::push_pool_prices;
+use numo_bindings::NumoEnginePool;
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+
+fn pool_state(base: u128, fy: u128) -> PoolState {
+    PoolState {
+        address: Address::repeat_byte(0x22),
+        base_reserves: base * ONE,
+        fy_reserves: fy * ONE,
+        fee_bps: 5,
+        maturity: 0,
+    }
+}
+
+fn bench_marginal_price(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let (provider, mock) = Provider::mocked();
+    let pool = NumoEnginePool::new(Address::repeat_byte(0x22), Arc::new(provider));
+
+    // Includes queuing the two mocked preview responses each iteration
+    c.bench_function("marginal_price_base_per_fy (mock)", |b| {
+        b.iter(|| {
+            push_pool_prices(&mock, &[ONE / 2]);
+            runtime
+                .block_on(marginal_price_base_per_fy(black_box(&pool)))
+                .unwrap()
+        })
+    });
+}
+
+fn bench_solver(c: &mut Criterion) {
+    let rich = pool_state(1_000_000, 1_100_000);
+    let quoter = LocalQuoter::new(&rich, 0.5);
+    let target = U256::from((quoter.marginal_price() * 0.995 * 1e18) as u128);
+
+    c.bench_function("solve_fy_amount_to_target", |b| {
+        b.iter(|| solve_fy_amount_to_target(&rich, 0.5, black_box(target), 100_000 * ONE).unwrap())
+    });
+}
+
+fn bench_quoter(c: &mut Criterion) {
+    let quoter = LocalQuoter::new(&pool_state(1_000_000, 1_100_000), 0.5);
+
+    c.bench_function("LocalQuoter::sell_fy_preview", |b| {
+        b.iter(|| quoter.sell_fy_preview(black_box(1_000 * ONE)))
+    });
+    c.bench_function("LocalQuoter::buy_fy_preview", |b| {
+        b.iter(|| quoter.buy_fy_preview(black_box(1_000 * ONE)))
+    });
+    c.bench_function("LocalQuoter::max_fy_in_for_impact", |b| {
+        b.iter(|| quoter.max_fy_in_for_impact(black_box(50), 100_000 * ONE))
+    });
+}
+
+fn bench_curve(c: &mut Criterion) {
+    let curve = RateCurve::default_usd();
+
+    c.bench_function("RateCurve::rate", |b| {
+        b.iter(|| curve.rate(black_box(0.37)))
+    });
+    c.bench_function("RateCurve::discount_factor", |b| {
+        b.iter(|| curve.discount_factor(black_box(0.37)))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_marginal_price,
+    bench_solver,
+    bench_quoter,
+    bench_curve
+);
+criterion_main!(benches);
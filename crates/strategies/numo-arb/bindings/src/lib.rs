@@ -14,6 +14,7 @@ abigen!(
         function buyBasePreview(uint128 baseOut) external view returns (uint128 fyIn)
         function getCache() external view returns (uint128 baseReserves, uint128 fyReserves, uint16 feeBps)
         function maturity() external view returns (uint32)
+        function base() external view returns (address)
     ]"#
 );
 
@@ -23,6 +24,9 @@ abigen!(
     r#"[
         function arbBuyFYThenSellFY(address cheapPool, address richPool, uint128 fyOutTarget, uint128 maxBaseIn, uint128 minBaseOutRich, address receiver) external returns (uint128 baseSpent, uint128 baseReceived)
         function arbSellBaseThenSellFY(address cheapPool, address richPool, uint128 baseIn, uint128 minFYOut, uint128 minBaseOut, address receiver) external returns (uint128 fyAcquired, uint128 baseReceived)
+        function buyFY(address pool, uint128 fyOut, uint128 maxBaseIn, address receiver) external returns (uint128 baseSpent)
+        function sellFY(address pool, uint128 fyIn, uint128 minBaseOut, address receiver) external returns (uint128 baseReceived)
+        function fyToken() external view returns (address)
     ]"#
 );
 
@@ -27,7 +27,8 @@ interface IERC20 {
 /// @notice Minimal router that performs a cheap→rich cycle in one atomic transaction:
 ///   1) Acquire FY on cheap pool (buyFYToken or sellBase flow)
 ///   2) Sell FY into rich pool
-/// @dev Caller must pre-approve base tokens to this router if using buyFY path.
+/// @dev Caller must pre-approve base tokens to this router if using buyFY path, and
+///   FY tokens for single-pool sellFY.
 contract NumoArbRouter {
 
     address public immutable baseToken;  // USDT or other base asset
@@ -153,6 +154,75 @@ contract NumoArbRouter {
         return (fyAcquired, baseReceived);
     }
 
+    /// @notice Buy FY on a single pool, opening a carry position
+    /// @param pool Pool to buy FY on
+    /// @param fyOut Amount of FY tokens to buy
+    /// @param maxBaseIn Maximum base tokens willing to spend
+    /// @param receiver Address to receive the FY tokens
+    /// @return baseSpent Amount of base tokens spent
+    function buyFY(
+        address pool,
+        uint128 fyOut,
+        uint128 maxBaseIn,
+        address receiver
+    ) external returns (uint128 baseSpent) {
+        IERC20 base = IERC20(baseToken);
+
+        // Pull base tokens from caller to pay for FY purchase
+        if (!base.transferFrom(msg.sender, address(this), maxBaseIn)) {
+            revert TransferFailed();
+        }
+
+        // Approve pool to spend base tokens
+        base.approve(pool, type(uint256).max);
+
+        baseSpent = INumoEnginePool(pool).buyFYToken(fyOut, receiver);
+        if (baseSpent > maxBaseIn) {
+            revert SlippageExceeded();
+        }
+
+        // Return any leftover base tokens to caller
+        uint256 leftover = base.balanceOf(address(this));
+        if (leftover > 0) {
+            if (!base.transfer(msg.sender, leftover)) {
+                revert TransferFailed();
+            }
+        }
+
+        return baseSpent;
+    }
+
+    /// @notice Sell FY on a single pool, opening a carry position
+    /// @dev Caller must pre-approve FY tokens to this router
+    /// @param pool Pool to sell FY on
+    /// @param fyIn Amount of FY tokens to sell
+    /// @param minBaseOut Minimum base tokens to receive
+    /// @param receiver Address to receive the base tokens
+    /// @return baseReceived Amount of base tokens received
+    function sellFY(
+        address pool,
+        uint128 fyIn,
+        uint128 minBaseOut,
+        address receiver
+    ) external returns (uint128 baseReceived) {
+        IERC20 fy = IERC20(fyToken);
+
+        // Pull FY tokens from caller
+        if (!fy.transferFrom(msg.sender, address(this), fyIn)) {
+            revert TransferFailed();
+        }
+
+        // Approve pool to spend FY tokens
+        fy.approve(pool, type(uint256).max);
+
+        baseReceived = INumoEnginePool(pool).sellFYToken(receiver, minBaseOut);
+        if (baseReceived < minBaseOut) {
+            revert SlippageExceeded();
+        }
+
+        return baseReceived;
+    }
+
     /// @notice Emergency function to recover stuck tokens
     /// @param token Token address to recover
     /// @param to Recipient address
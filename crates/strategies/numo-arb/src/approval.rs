@@ -0,0 +1,607 @@
+/// Interactive trade approval for the Numo strategy
+/// Wraps an execution policy so every trade is shown to the operator and only
+/// submitted once approved on stdin; no answer before the timeout rejects it
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use ethers::prelude::*;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use futures::lock::Mutex;
+use futures::StreamExt;
+use tracing::{debug, info};
+
+use crate::carry::{CarryDirection, CarryTrade};
+use crate::dedup::{carry_key, opportunity_key};
+use crate::execution::ExecutionPolicy;
+use crate::fmt::TokenDecimals;
+use crate::pricing::{max_gas_price_for_profit, max_profitable_gas_price};
+use crate::types::{Action, ArbDirection, ArbOpportunity};
+
+/// Default time the operator has to approve a trade
+pub const DEFAULT_APPROVAL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time an operator's answer stands, about five blocks
+pub const DEFAULT_DECISION_TTL: Duration = Duration::from_secs(60);
+
+/// Decides whether a trade may be submitted
+#[async_trait]
+pub trait Approver: Send + Sync {
+    async fn approve(&self, report: &str) -> bool;
+}
+
+/// Multi-line report of everything known about an opportunity, with its gas
+/// headroom once the transaction's gas limit is known
+///
+/// Amounts and prices are shown in whole tokens, followed by their raw value. The
+/// headroom is in native wei, converting the profit at `native_price_in_base` base
+/// tokens per native token when it is known.
+pub fn opportunity_report(
+    opp: &ArbOpportunity,
+    gas_limit: Option<U256>,
+    decimals: TokenDecimals,
+    native_price_in_base: Option<f64>,
+) -> String {
+    let native_price = native_price_in_base.map(|price| (price, decimals.base));
+    let report = format!(
+        "Arbitrage opportunity\n  \
+         cheap pool:      {:?}\n  \
+         rich pool:       {:?}\n  \
+         fy amount:       {} ({})\n  \
+         max base in:     {} ({})\n  \
+         min base out:    {} ({})\n  \
+         expected profit: {} ({})\n  \
+         cheap price:     {} ({})\n  \
+         rich price:      {} ({})\n  \
+         target price:    {} ({})",
+        opp.cheap_pool,
+        opp.rich_pool,
+        decimals.fy_amount(opp.fy_amount),
+        opp.fy_amount,
+        decimals.base_amount(opp.max_base_in),
+        opp.max_base_in,
+        decimals.base_amount(opp.min_base_out),
+        opp.min_base_out,
+        decimals.base_amount(opp.expected_profit),
+        opp.expected_profit,
+        decimals.price(opp.cheap_price),
+        opp.cheap_price,
+        decimals.price(opp.rich_price),
+        opp.rich_price,
+        decimals.price(opp.target_price),
+        opp.target_price,
+    );
+    with_gas_headroom(report, gas_limit, |gas_limit| {
+        max_profitable_gas_price(opp, gas_limit, 100, native_price)
+    })
+}
+
+/// [opportunity_report] for a carry trade
+pub fn carry_report(
+    trade: &CarryTrade,
+    gas_limit: Option<U256>,
+    decimals: TokenDecimals,
+    native_price_in_base: Option<f64>,
+) -> String {
+    let native_price = native_price_in_base.map(|price| (price, decimals.base));
+    let position = &trade.position;
+    let base_limit = match position.direction {
+        CarryDirection::BuyFy => "max base in:    ",
+        CarryDirection::SellFy => "min base out:   ",
+    };
+    let report = format!(
+        "Carry position\n  \
+         pool:            {:?}\n  \
+         direction:       {:?}\n  \
+         fy amount:       {} ({})\n  \
+         {base_limit} {} ({})\n  \
+         expected profit: {} ({})\n  \
+         pool price:      {} ({})\n  \
+         pool rate:       {:.4}\n  \
+         curve rate:      {:.4}\n  \
+         rate gap:        {} bps",
+        position.pool,
+        position.direction,
+        decimals.fy_amount(position.fy_amount),
+        position.fy_amount,
+        decimals.base_amount(trade.base_limit),
+        trade.base_limit,
+        decimals.base_amount(trade.expected_profit),
+        trade.expected_profit,
+        decimals.price(trade.price),
+        trade.price,
+        position.pool_rate,
+        position.curve_rate,
+        position.rate_gap_bps,
+    );
+    with_gas_headroom(report, gas_limit, |gas_limit| {
+        max_gas_price_for_profit(trade.expected_profit, gas_limit, 100, native_price)
+    })
+}
+
+/// `report` followed by the gas limit and its break-even gas price, once known
+fn with_gas_headroom(
+    report: String,
+    gas_limit: Option<U256>,
+    max_gas_price: impl FnOnce(U256) -> U256,
+) -> String {
+    match gas_limit {
+        Some(gas_limit) => format!(
+            "{report}\n  \
+             gas limit:       {gas_limit}\n  \
+             max gas price:   {}",
+            max_gas_price(gas_limit)
+        ),
+        None => report,
+    }
+}
+
+/// Operator decision for an input line: only `y` or `yes` approves
+pub fn parse_decision(line: &str) -> bool {
+    matches!(line.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Approver reading operator answers line by line
+///
+/// Lines typed while no trade is waiting are discarded, so a late answer never
+/// approves the next trade.
+pub struct LineApprover {
+    lines: Mutex<UnboundedReceiver<String>>,
+    timeout: Duration,
+}
+
+impl LineApprover {
+    /// Approve from lines arriving on `lines`, rejecting after `timeout`
+    pub fn new(lines: UnboundedReceiver<String>, timeout: Duration) -> Self {
+        Self {
+            lines: Mutex::new(lines),
+            timeout,
+        }
+    }
+
+    /// Approve from the terminal's stdin; fails when stdin is not a terminal
+    pub fn stdin(timeout: Duration) -> Result<Self> {
+        if !std::io::stdin().is_terminal() {
+            bail!("Confirming each trade needs an interactive terminal on stdin");
+        }
+        let (sender, receiver) = unbounded();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if sender.unbounded_send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self::new(receiver, timeout))
+    }
+}
+
+#[async_trait]
+impl Approver for LineApprover {
+    async fn approve(&self, report: &str) -> bool {
+        let mut lines = self.lines.lock().await;
+        // Drop answers given before this prompt
+        while let Ok(Some(_)) = lines.try_next() {}
+
+        print!(
+            "{report}\nSubmit this trade? [y/N] (rejects in {}s): ",
+            self.timeout.as_secs()
+        );
+        let _ = std::io::stdout().flush();
+
+        match tokio::time::timeout(self.timeout, lines.next()).await {
+            Ok(Some(line)) => parse_decision(&line),
+            Ok(None) | Err(_) => {
+                println!();
+                false
+            }
+        }
+    }
+}
+
+/// Execution policy asking an [Approver] before every trade
+///
+/// The operator is asked on a spawned task so evaluation carries on meanwhile, one
+/// trade at a time. An approval is acted on the next time the same opportunity, by
+/// [opportunity_key], is evaluated, and a rejected one is not asked about again,
+/// as long as the answer is younger than the decision TTL. An answer is dropped
+/// once its route's opportunity has materially changed, i.e. its key differs, so
+/// the new one is shown to the operator again. Carry trades are asked about the
+/// same way, keyed by [carry_key] with their pool as the route.
+pub struct ApprovalPolicy {
+    inner: Box<dyn ExecutionPolicy>,
+    approver: Arc<dyn Approver>,
+    decimals: TokenDecimals,
+    native_price_in_base: Option<f64>,
+
+    /// Time an answer stands
+    decision_ttl: Duration,
+
+    /// Operator answers by opportunity key, approvals until they are acted on
+    decisions: Arc<std::sync::Mutex<HashMap<u64, Decision>>>,
+
+    /// Whether a trade is waiting for the operator's answer
+    prompting: Arc<AtomicBool>,
+}
+
+/// Operator answer about one opportunity
+#[derive(Debug, Clone, Copy)]
+struct Decision {
+    approved: bool,
+
+    /// Pools of the opportunity asked about
+    route: (Address, Address),
+
+    answered_at: Instant,
+}
+
+impl ApprovalPolicy {
+    pub fn new(inner: Box<dyn ExecutionPolicy>, approver: Arc<dyn Approver>) -> Self {
+        Self {
+            inner,
+            approver,
+            decimals: TokenDecimals::default(),
+            native_price_in_base: None,
+            decision_ttl: DEFAULT_DECISION_TTL,
+            decisions: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            prompting: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Show amounts in reports with `decimals`
+    pub fn with_decimals(mut self, decimals: TokenDecimals) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Report gas headroom converting profit at `native_price_in_base` base tokens
+    /// per native token
+    pub fn with_native_price(mut self, native_price_in_base: f64) -> Self {
+        self.native_price_in_base = Some(native_price_in_base);
+        self
+    }
+
+    /// Let operator answers stand for `ttl`
+    pub fn with_decision_ttl(mut self, ttl: Duration) -> Self {
+        self.decision_ttl = ttl;
+        self
+    }
+
+    /// Answer standing for the opportunity `key` on `route`, dropping expired
+    /// answers and those its route's opportunity has changed from
+    fn decision(&self, route: (Address, Address), key: u64) -> Option<bool> {
+        let mut decisions = self.decisions.lock().unwrap();
+        decisions.retain(|&decided, decision| {
+            let expired = decision.answered_at.elapsed() >= self.decision_ttl;
+            let superseded = decision.route == route && decided != key;
+            if superseded {
+                debug!(rich_pool = ?route.1, "Opportunity changed, asking the operator again");
+            }
+            !expired && !superseded
+        });
+        decisions.get(&key).map(|decision| decision.approved)
+    }
+
+    /// Act on the standing answer about the opportunity `key` on `route`, or show
+    /// the trade `build` creates to the operator, described by `report` given its
+    /// gas limit
+    async fn gate<B, F>(
+        &self,
+        key: u64,
+        route: (Address, Address),
+        build: B,
+        report: impl FnOnce(Option<U256>) -> String,
+    ) -> Result<Option<Action>>
+    where
+        B: FnOnce() -> F,
+        F: Future<Output = Result<Option<Action>>>,
+    {
+        let rich_pool = route.1;
+        match self.decision(route, key) {
+            Some(true) => {
+                let action = build().await?;
+                if action.is_some() {
+                    self.decisions.lock().unwrap().remove(&key);
+                    info!(rich_pool = ?rich_pool, "Submitting trade approved by operator");
+                }
+                return Ok(action);
+            }
+            Some(false) => {
+                debug!(rich_pool = ?rich_pool, "Trade rejected by operator, skipping");
+                return Ok(None);
+            }
+            None => {}
+        }
+        if self.prompting.load(Ordering::SeqCst) {
+            debug!(rich_pool = ?rich_pool, "Operator busy with another trade, skipping");
+            return Ok(None);
+        }
+
+        let Some(action) = build().await? else {
+            return Ok(None);
+        };
+        let gas_limit = match &action {
+            Action::SubmitTx(submit) => submit.tx.gas().copied(),
+            _ => None,
+        };
+        let report = report(gas_limit);
+        let (approver, decisions, prompting) = (
+            self.approver.clone(),
+            self.decisions.clone(),
+            self.prompting.clone(),
+        );
+        self.prompting.store(true, Ordering::SeqCst);
+        tokio::spawn(async move {
+            let approved = approver.approve(&report).await;
+            if approved {
+                info!(rich_pool = ?rich_pool, "Trade approved by operator");
+            } else {
+                info!(rich_pool = ?rich_pool, "Trade rejected by operator");
+            }
+            let decision = Decision {
+                approved,
+                route,
+                answered_at: Instant::now(),
+            };
+            decisions.lock().unwrap().insert(key, decision);
+            prompting.store(false, Ordering::SeqCst);
+        });
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl ExecutionPolicy for ApprovalPolicy {
+    async fn build_action(&self, opp: &ArbOpportunity) -> Result<Option<Action>> {
+        let route = (opp.cheap_pool, opp.rich_pool);
+        self.gate(
+            opportunity_key(opp),
+            route,
+            || self.inner.build_action(opp),
+            |gas_limit| {
+                opportunity_report(opp, gas_limit, self.decimals, self.native_price_in_base)
+            },
+        )
+        .await
+    }
+
+    async fn build_carry_action(&self, trade: &CarryTrade) -> Result<Option<Action>> {
+        let route = (trade.position.pool, trade.position.pool);
+        self.gate(
+            carry_key(trade),
+            route,
+            || self.inner.build_carry_action(trade),
+            |gas_limit| carry_report(trade, gas_limit, self.decimals, self.native_price_in_base),
+        )
+        .await
+    }
+
+    fn expected_gas(
+        &self,
+        cheap_pool: Address,
+        rich_pool: Address,
+        direction: ArbDirection,
+    ) -> Option<U256> {
+        self.inner.expected_gas(cheap_pool, rich_pool, direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::channel::mpsc::UnboundedSender;
+
+    use crate::carry::CarryPosition;
+    use crate::test_utils::opportunity;
+    use crate::types::SubmitTxToMempool;
+
+    struct FixedPolicy;
+
+    fn fixed_action() -> Action {
+        Action::SubmitTx(SubmitTxToMempool {
+            tx: TransactionRequest::new().into(),
+            gas_bid_info: None,
+        })
+    }
+
+    #[async_trait]
+    impl ExecutionPolicy for FixedPolicy {
+        async fn build_action(&self, _opp: &ArbOpportunity) -> Result<Option<Action>> {
+            Ok(Some(fixed_action()))
+        }
+
+        async fn build_carry_action(&self, _trade: &CarryTrade) -> Result<Option<Action>> {
+            Ok(Some(fixed_action()))
+        }
+    }
+
+    /// 1M FY bought at 0.9 with 1% slippage, 50 bps of price edge
+    fn carry_trade() -> CarryTrade {
+        let position = CarryPosition {
+            pool: Address::repeat_byte(0x11),
+            direction: CarryDirection::BuyFy,
+            fy_amount: 1_000_000,
+            pool_rate: 0.06,
+            curve_rate: 0.05,
+            rate_gap_bps: 100,
+            price_edge_bps: 50.0,
+        };
+        CarryTrade::new(position, U256::exp10(17) * 9, 100)
+    }
+
+    #[test]
+    fn test_parse_decision() {
+        assert!(parse_decision("y"));
+        assert!(parse_decision(" YES \n"));
+        assert!(!parse_decision("n"));
+        assert!(!parse_decision(""));
+        assert!(!parse_decision("maybe"));
+    }
+
+    /// Type `line` shortly after the prompt has been shown
+    fn answer_later(sender: &UnboundedSender<String>, line: &str) {
+        let (sender, line) = (sender.clone(), line.to_string());
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            sender.unbounded_send(line).unwrap();
+        });
+    }
+
+    /// Wait for the operator's answer to the trade being asked about
+    async fn answered(policy: &ApprovalPolicy) {
+        while policy.prompting.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_policy_follows_operator_answers() {
+        let (sender, receiver) = unbounded();
+        let approver = Arc::new(LineApprover::new(receiver, Duration::from_millis(200)));
+        let policy = ApprovalPolicy::new(Box::new(FixedPolicy), approver);
+        let (opp, other) = (
+            opportunity(),
+            ArbOpportunity {
+                fy_amount: 2_000,
+                ..opportunity()
+            },
+        );
+
+        // Asking does not wait for the answer, and other trades are skipped meanwhile
+        answer_later(&sender, "y");
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+        assert!(policy.build_action(&other).await.unwrap().is_none());
+        answered(&policy).await;
+        assert!(policy.build_action(&opp).await.unwrap().is_some());
+
+        // The approval is used up, so the next trade is asked about again
+        answer_later(&sender, "n");
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+        answered(&policy).await;
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+        assert!(!policy.prompting.load(Ordering::SeqCst));
+
+        answer_later(&sender, "whatever");
+        assert!(policy.build_action(&other).await.unwrap().is_none());
+        answered(&policy).await;
+        assert!(policy.build_action(&other).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_answers_expire_and_changed_opportunities_ask_again() {
+        let (sender, receiver) = unbounded();
+        let approver = Arc::new(LineApprover::new(receiver, Duration::from_millis(200)));
+        let policy = ApprovalPolicy::new(Box::new(FixedPolicy), approver)
+            .with_decision_ttl(Duration::from_millis(100));
+        let opp = opportunity();
+
+        // An approval not acted on in time is dropped and the trade asked about again
+        answer_later(&sender, "y");
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+        answered(&policy).await;
+        tokio::time::sleep(Duration::from_millis(110)).await;
+        answer_later(&sender, "n");
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+        answered(&policy).await;
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+        assert_eq!(policy.decisions.lock().unwrap().len(), 1);
+
+        // Once the route's opportunity changes, the rejection is dropped and the new
+        // one shown to the operator
+        let changed = ArbOpportunity {
+            expected_profit: 60,
+            ..opportunity()
+        };
+        answer_later(&sender, "y");
+        assert!(policy.build_action(&changed).await.unwrap().is_none());
+        assert!(policy.decisions.lock().unwrap().is_empty());
+        answered(&policy).await;
+        assert!(policy.build_action(&changed).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_carry_trades_are_asked_about() {
+        let (sender, receiver) = unbounded();
+        let approver = Arc::new(LineApprover::new(receiver, Duration::from_millis(200)));
+        let policy = ApprovalPolicy::new(Box::new(FixedPolicy), approver);
+        let trade = carry_trade();
+
+        answer_later(&sender, "y");
+        assert!(policy.build_carry_action(&trade).await.unwrap().is_none());
+        answered(&policy).await;
+        assert!(policy.build_carry_action(&trade).await.unwrap().is_some());
+
+        // A resized position on the same pool is asked about again
+        let resized = CarryTrade {
+            expected_profit: 9_000,
+            ..carry_trade()
+        };
+        answer_later(&sender, "n");
+        assert!(policy.build_carry_action(&resized).await.unwrap().is_none());
+        answered(&policy).await;
+        assert!(policy.build_carry_action(&resized).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_stale_answers_reject() {
+        let (sender, receiver) = unbounded();
+        let approver = LineApprover::new(receiver, Duration::from_millis(20));
+
+        // Nobody answers
+        assert!(!approver.approve("report").await);
+
+        // An answer typed before the prompt is discarded
+        sender.unbounded_send("y".to_string()).unwrap();
+        assert!(!approver.approve("report").await);
+    }
+
+    #[test]
+    fn test_report_shows_every_field() {
+        let decimals = TokenDecimals { base: 6, fy: 6 };
+        let report = opportunity_report(&opportunity(), None, decimals, None);
+        for field in [
+            "cheap pool",
+            "rich pool",
+            "fy amount:       0.001000 (1000)",
+            "expected profit: 0.000030 (30)",
+            "target price:    0.000000 (960000)",
+        ] {
+            assert!(report.contains(field), "missing {field}");
+        }
+        assert!(!report.contains("max gas price"));
+
+        // 30 of profit over 10 gas breaks even at 3 wei per gas
+        let report = opportunity_report(&opportunity(), Some(U256::from(10)), decimals, None);
+        assert!(report.contains("gas limit:       10"));
+        assert!(report.contains("max gas price:   3"));
+
+        // At 1.5 base per native that profit is 0.00002 native, 2,000 gwei per gas
+        let report = opportunity_report(&opportunity(), Some(U256::from(10)), decimals, Some(1.5));
+        assert!(report.contains("max gas price:   2000000000000"));
+    }
+
+    #[test]
+    fn test_carry_report_shows_the_bound_and_headroom() {
+        let decimals = TokenDecimals { base: 6, fy: 6 };
+        let report = carry_report(&carry_trade(), Some(U256::from(10)), decimals, None);
+        for field in [
+            "Carry position",
+            "direction:       BuyFy",
+            "fy amount:       1.000000 (1000000)",
+            "max base in:     0.909000 (909000)",
+            "expected profit: 0.004500 (4500)",
+            "rate gap:        100 bps",
+            "gas limit:       10",
+            "max gas price:   450",
+        ] {
+            assert!(report.contains(field), "missing {field}");
+        }
+    }
+}
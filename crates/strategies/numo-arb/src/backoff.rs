@@ -0,0 +1,80 @@
+/// Adaptive evaluation backoff for the Numo strategy
+/// After a run of blocks without opportunities, only a cheap spread pre-check runs
+/// until the spread between pools indicates a candidate again
+#[derive(Debug, Clone)]
+pub struct EvaluationBackoff {
+    /// Consecutive empty blocks before backing off
+    threshold: u32,
+
+    /// Consecutive blocks without an opportunity
+    misses: u32,
+}
+
+impl EvaluationBackoff {
+    /// Back off after `threshold` consecutive blocks without an opportunity
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            misses: 0,
+        }
+    }
+
+    /// Whether only the cheap spread pre-check should run
+    pub fn is_backing_off(&self) -> bool {
+        self.threshold > 0 && self.misses >= self.threshold
+    }
+
+    /// Whether the full evaluation should run given the pre-check result
+    pub fn should_evaluate(&self, candidate_spread: bool) -> bool {
+        !self.is_backing_off() || candidate_spread
+    }
+
+    /// Record the outcome of a block
+    pub fn record(&mut self, found_opportunity: bool) {
+        if found_opportunity {
+            self.misses = 0;
+        } else {
+            self.misses = self.misses.saturating_add(1);
+        }
+    }
+
+    /// Consecutive blocks without an opportunity
+    pub fn misses(&self) -> u32 {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backs_off_after_threshold() {
+        let mut backoff = EvaluationBackoff::new(3);
+
+        for _ in 0..2 {
+            backoff.record(false);
+            assert!(backoff.should_evaluate(false));
+        }
+
+        // Third empty block trips the backoff: full evaluation only on a candidate spread
+        backoff.record(false);
+        assert!(backoff.is_backing_off());
+        assert!(!backoff.should_evaluate(false));
+        assert!(backoff.should_evaluate(true));
+
+        // Finding an opportunity resets it
+        backoff.record(true);
+        assert!(!backoff.is_backing_off());
+        assert_eq!(backoff.misses(), 0);
+    }
+
+    #[test]
+    fn test_zero_threshold_never_backs_off() {
+        let mut backoff = EvaluationBackoff::new(0);
+        for _ in 0..10 {
+            backoff.record(false);
+        }
+        assert!(backoff.should_evaluate(false));
+    }
+}
@@ -0,0 +1,355 @@
+/// Receiver balance verification for the Numo strategy
+/// Compares the base token balance change of a confirmed trade's receiver with the
+/// trade's expected profit, so fees or partial fills that leak value are caught
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use tracing::{debug, info, warn};
+
+use crate::confirmation::{ReceiptObserver, SubmittedTx};
+use numo_bindings::{NumoEnginePool, ERC20};
+
+/// Default deviation from the expected profit tolerated before alerting (1%)
+pub const DEFAULT_BALANCE_TOLERANCE_BPS: u32 = 100;
+
+/// Router functions whose calldata is `(cheapPool, richPool, _, _, _, receiver)`
+const ROUTER_SIGNATURES: [&str; 2] = [
+    "arbBuyFYThenSellFY(address,address,uint128,uint128,uint128,address)",
+    "arbSellBaseThenSellFY(address,address,uint128,uint128,uint128,address)",
+];
+
+/// Expected against observed base token balance change of one confirmed trade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceCheck {
+    pub tx_hash: TxHash,
+    pub token: Address,
+    pub receiver: Address,
+    pub expected: i128,
+    pub observed: i128,
+}
+
+impl BalanceCheck {
+    /// Value expected but not received; negative when more was received
+    pub fn shortfall(&self) -> i128 {
+        self.expected.saturating_sub(self.observed)
+    }
+
+    /// Deviation of the observed change from the expected one, in bps of the
+    /// expected profit
+    pub fn deviation_bps(&self) -> u128 {
+        let expected = self.expected.unsigned_abs().max(1);
+        self.shortfall().unsigned_abs().saturating_mul(10_000) / expected
+    }
+
+    /// Whether the observed change deviates from the expected one by more than
+    /// `tolerance_bps`
+    pub fn is_mismatch(&self, tolerance_bps: u32) -> bool {
+        self.deviation_bps() > tolerance_bps as u128
+    }
+}
+
+/// Signed change from `before` to `after`, saturating at the i128 range
+pub fn balance_delta(before: U256, after: U256) -> i128 {
+    let clamp = |value: U256| value.min(U256::from(i128::MAX as u128)).as_u128() as i128;
+    if after >= before {
+        clamp(after - before)
+    } else {
+        -clamp(before - after)
+    }
+}
+
+/// Rich pool and receiver of a router trade, if `calldata` is one
+pub fn trade_parties(calldata: &[u8]) -> Option<(Address, Address)> {
+    let (_, rich_pool, receiver) = trade_route(calldata)?;
+    Some((rich_pool, receiver))
+}
+
+/// Cheap pool, rich pool and receiver of a router trade, if `calldata` is one
+pub fn trade_route(calldata: &[u8]) -> Option<(Address, Address, Address)> {
+    let selector = calldata.get(..4)?;
+    if !ROUTER_SIGNATURES
+        .iter()
+        .any(|signature| ethers::utils::id(signature) == selector)
+    {
+        return None;
+    }
+    let word = |index: usize| {
+        let start = 4 + index * 32;
+        calldata
+            .get(start + 12..start + 32)
+            .map(Address::from_slice)
+    };
+    Some((word(0)?, word(1)?, word(5)?))
+}
+
+/// Verifies the receiver's base token balance after every confirmed trade
+///
+/// Balances are read at the receipt's block and the block before it, so other
+/// transfers to the receiver in the same block show up as mismatches.
+pub struct BalanceVerifier<M> {
+    client: Arc<M>,
+    tolerance_bps: u32,
+    base_tokens: Arc<Mutex<HashMap<Address, Address>>>,
+    checks: Arc<Mutex<Vec<BalanceCheck>>>,
+}
+
+impl<M> Clone for BalanceVerifier<M> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            tolerance_bps: self.tolerance_bps,
+            base_tokens: self.base_tokens.clone(),
+            checks: self.checks.clone(),
+        }
+    }
+}
+
+impl<M> BalanceVerifier<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            tolerance_bps: DEFAULT_BALANCE_TOLERANCE_BPS,
+            base_tokens: Arc::new(Mutex::new(HashMap::new())),
+            checks: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Alert when a trade's balance change deviates by more than `tolerance_bps`
+    pub fn with_tolerance(mut self, tolerance_bps: u32) -> Self {
+        self.tolerance_bps = tolerance_bps;
+        self
+    }
+
+    /// Every verified trade, oldest first
+    pub fn checks(&self) -> Vec<BalanceCheck> {
+        self.checks.lock().unwrap().clone()
+    }
+
+    /// Total observed base token change across verified trades
+    pub fn realized_total(&self) -> i128 {
+        self.checks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|check| check.observed)
+            .fold(0i128, i128::saturating_add)
+    }
+}
+
+impl<M> BalanceVerifier<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    /// Compare the balance change of the trade behind `receipt` with its expected
+    /// profit, recording and returning the check
+    ///
+    /// Reverted transactions, transactions without an expected profit and calldata
+    /// other than a router trade are not checked.
+    pub async fn verify(
+        &self,
+        submitted: &SubmittedTx,
+        receipt: &TransactionReceipt,
+    ) -> Result<Option<BalanceCheck>> {
+        let Some(check) = self.observe(submitted, receipt).await? else {
+            return Ok(None);
+        };
+        if check.is_mismatch(self.tolerance_bps) {
+            warn!(
+                tx_hash = ?check.tx_hash,
+                token = ?check.token,
+                expected = check.expected,
+                observed = check.observed,
+                shortfall = check.shortfall(),
+                deviation_bps = check.deviation_bps(),
+                tolerance_bps = self.tolerance_bps,
+                "Realized balance change differs from expected profit"
+            );
+        } else {
+            debug!(
+                tx_hash = ?check.tx_hash,
+                expected = check.expected,
+                observed = check.observed,
+                "Balance change matches expected profit"
+            );
+        }
+        self.checks.lock().unwrap().push(check.clone());
+        info!(
+            realized_total = self.realized_total(),
+            trades = self.checks.lock().unwrap().len(),
+            "Realized balance change recorded"
+        );
+        Ok(Some(check))
+    }
+
+    /// Read the balance change of the trade behind `receipt` without recording or
+    /// judging it; skips the same transactions as [BalanceVerifier::verify]
+    pub async fn observe(
+        &self,
+        submitted: &SubmittedTx,
+        receipt: &TransactionReceipt,
+    ) -> Result<Option<BalanceCheck>> {
+        if receipt.status != Some(U64::one()) {
+            return Ok(None);
+        }
+        let (Some(expected), Some(calldata)) = (submitted.expected_profit, &submitted.calldata)
+        else {
+            return Ok(None);
+        };
+        let Some((rich_pool, receiver)) = trade_parties(calldata) else {
+            return Ok(None);
+        };
+        let block = receipt
+            .block_number
+            .ok_or_else(|| anyhow!("Receipt has no block number"))?
+            .as_u64();
+
+        let token = self.base_token(rich_pool).await?;
+        let erc20 = ERC20::new(token, self.client.clone());
+        let before = erc20
+            .balance_of(receiver)
+            .block(block.saturating_sub(1))
+            .call()
+            .await?;
+        let after = erc20.balance_of(receiver).block(block).call().await?;
+
+        let check = BalanceCheck {
+            tx_hash: submitted.tx_hash,
+            token,
+            receiver,
+            expected: balance_delta(U256::zero(), expected),
+            observed: balance_delta(before, after),
+        };
+        Ok(Some(check))
+    }
+
+    /// Base token of `pool`, read once
+    async fn base_token(&self, pool: Address) -> Result<Address> {
+        if let Some(token) = self.base_tokens.lock().unwrap().get(&pool) {
+            return Ok(*token);
+        }
+        let token = NumoEnginePool::new(pool, self.client.clone())
+            .base()
+            .call()
+            .await?;
+        self.base_tokens.lock().unwrap().insert(pool, token);
+        Ok(token)
+    }
+}
+
+impl<M> ReceiptObserver for BalanceVerifier<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    fn on_receipt(&self, submitted: &SubmittedTx, receipt: &TransactionReceipt) {
+        let verifier = self.clone();
+        let (submitted, receipt) = (submitted.clone(), receipt.clone());
+        tokio::spawn(async move {
+            if let Err(e) = verifier.verify(&submitted, &receipt).await {
+                warn!(tx_hash = ?submitted.tx_hash, error = ?e, "Balance verification failed");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{encode, Token};
+    use numo_bindings::NumoArbRouter;
+
+    use crate::test_utils::encode_uints;
+
+    fn check(expected: i128, observed: i128) -> BalanceCheck {
+        BalanceCheck {
+            tx_hash: TxHash::zero(),
+            token: Address::zero(),
+            receiver: Address::zero(),
+            expected,
+            observed,
+        }
+    }
+
+    #[test]
+    fn test_balance_check_flags_mismatch() {
+        // Within 1% of the expected 1_000
+        assert!(!check(1_000, 995).is_mismatch(100));
+        assert!(!check(1_000, 1_010).is_mismatch(100));
+        // A fee eating 5% of the profit, or a fill that lost money
+        assert_eq!(check(1_000, 950).deviation_bps(), 500);
+        assert!(check(1_000, 950).is_mismatch(100));
+        assert_eq!(check(1_000, -200).shortfall(), 1_200);
+        assert!(check(1_000, -200).is_mismatch(100));
+
+        assert_eq!(balance_delta(U256::from(10), U256::from(25)), 15);
+        assert_eq!(balance_delta(U256::from(25), U256::from(10)), -15);
+    }
+
+    #[tokio::test]
+    async fn test_verify_compares_observed_delta() {
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        let verifier = BalanceVerifier::new(provider.clone());
+        let (cheap, rich) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let (receiver, token) = (Address::repeat_byte(0xb0), Address::repeat_byte(0xba));
+        let calldata = NumoArbRouter::new(Address::repeat_byte(0x33), provider)
+            .arb_buy_fy_then_sell_fy(cheap, rich, 1_000, 950, 980, receiver)
+            .calldata()
+            .unwrap();
+        assert_eq!(trade_parties(&calldata), Some((rich, receiver)));
+        assert_eq!(trade_parties(&[0xde, 0xad, 0xbe, 0xef]), None);
+
+        let submitted = SubmittedTx {
+            tx_hash: TxHash::repeat_byte(0x01),
+            gas_estimate: U256::from(100_000),
+            expected_profit: Some(U256::from(30)),
+            calldata: Some(calldata),
+        };
+        let receipt = TransactionReceipt {
+            status: Some(U64::one()),
+            block_number: Some(U64::from(100)),
+            ..Default::default()
+        };
+
+        // Responses are served last-in first-out: the pool's base token, then the
+        // balance before and after. Only 18 of the expected 30 arrived.
+        mock.push::<Bytes, _>(encode_uints(&[5_018])).unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[5_000])).unwrap();
+        let base_token: Bytes = encode(&[Token::Address(token)]).into();
+        mock.push::<Bytes, _>(base_token).unwrap();
+        let first = verifier
+            .verify(&submitted, &receipt)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.token, token);
+        assert_eq!((first.expected, first.observed), (30, 18));
+        assert!(first.is_mismatch(DEFAULT_BALANCE_TOLERANCE_BPS));
+
+        // The base token is cached; a matching delta passes
+        mock.push::<Bytes, _>(encode_uints(&[5_048])).unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[5_018])).unwrap();
+        let second = verifier
+            .verify(&submitted, &receipt)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!second.is_mismatch(DEFAULT_BALANCE_TOLERANCE_BPS));
+        assert_eq!(verifier.checks().len(), 2);
+        assert_eq!(verifier.realized_total(), 48);
+
+        // Reverted trades are not checked
+        let reverted = TransactionReceipt {
+            status: Some(U64::zero()),
+            ..receipt
+        };
+        assert!(verifier
+            .verify(&submitted, &reverted)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,106 @@
+/// Fair value band for the Numo strategy
+/// Enters a pair when divergence exceeds `enter_bps`, then does not re-enter it
+/// until the divergence has narrowed below `exit_bps`, to avoid churning around
+/// a single threshold
+use std::collections::HashSet;
+
+use ethers::prelude::*;
+
+/// Per-pair hysteresis between an entry and an exit threshold
+#[derive(Debug, Clone)]
+pub struct FairValueBand {
+    /// Divergence required to enter a pair
+    enter_bps: u32,
+
+    /// Divergence below which an entered pair may be entered again
+    exit_bps: u32,
+
+    /// `(cheap, rich)` pairs entered and waiting for the divergence to narrow
+    entered: HashSet<(Address, Address)>,
+}
+
+impl FairValueBand {
+    pub fn new(enter_bps: u32, exit_bps: u32) -> Self {
+        Self {
+            enter_bps,
+            exit_bps,
+            entered: HashSet::new(),
+        }
+    }
+
+    /// Divergence required to enter a pair
+    pub fn enter_bps(&self) -> u32 {
+        self.enter_bps
+    }
+
+    /// Move the thresholds, keeping the pairs already entered
+    pub fn set_thresholds(&mut self, enter_bps: u32, exit_bps: u32) {
+        self.enter_bps = enter_bps;
+        self.exit_bps = exit_bps;
+    }
+
+    /// Whether a pair is waiting for its divergence to narrow below `exit_bps`
+    pub fn is_entered(&self, pair: (Address, Address)) -> bool {
+        self.entered.contains(&pair)
+    }
+
+    /// Whether the divergence allows entering the pair now
+    pub fn allows_entry(&self, pair: (Address, Address), divergence_bps: u32) -> bool {
+        !self.is_entered(pair) && divergence_bps >= self.enter_bps
+    }
+
+    /// Mark a pair as entered after trading it
+    pub fn enter(&mut self, pair: (Address, Address)) {
+        self.entered.insert(pair);
+    }
+
+    /// Observe the current divergence of a pair, re-arming it once it has narrowed
+    pub fn observe(&mut self, pair: (Address, Address), divergence_bps: u32) {
+        if divergence_bps < self.exit_bps {
+            self.entered.remove(&pair);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_band_hysteresis() {
+        let pair = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let mut band = FairValueBand::new(30, 10);
+
+        // Below the entry threshold
+        band.observe(pair, 20);
+        assert!(!band.allows_entry(pair, 20));
+
+        // Divergence widens past enter_bps: enter once
+        band.observe(pair, 35);
+        assert!(band.allows_entry(pair, 35));
+        band.enter(pair);
+
+        // Still wide, or narrowed but inside the band: no re-entry
+        for divergence in [40, 25, 10] {
+            band.observe(pair, divergence);
+            assert!(!band.allows_entry(pair, divergence));
+        }
+
+        // Narrowed below exit_bps: re-armed, and entered again on the next widening
+        band.observe(pair, 5);
+        assert!(!band.is_entered(pair));
+        band.observe(pair, 31);
+        assert!(band.allows_entry(pair, 31));
+    }
+
+    #[test]
+    fn test_band_state_is_per_pair() {
+        let a = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let b = (Address::repeat_byte(0x22), Address::repeat_byte(0x11));
+        let mut band = FairValueBand::new(30, 10);
+
+        band.enter(a);
+        assert!(!band.allows_entry(a, 50));
+        assert!(band.allows_entry(b, 50));
+    }
+}
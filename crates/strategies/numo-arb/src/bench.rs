@@ -0,0 +1,208 @@
+/// Curve convention benchmark for the Numo strategy
+/// Compares the discount factor and target price each interpolation method gives
+/// every pool's maturity, so operators can see what the convention choice moves
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+
+use crate::fmt::TokenDecimals;
+use crate::sofr::{Interpolation, RateCurve};
+use numo_bindings::NumoEnginePool;
+
+/// Interpolation methods compared, in report order
+pub const BENCH_INTERPOLATIONS: [Interpolation; 3] = [
+    Interpolation::LinearRate,
+    Interpolation::LogLinearDf,
+    Interpolation::MonotoneCubic,
+];
+
+/// Target of one pool's maturity under one convention
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurveBenchRow {
+    pub pool: Address,
+    pub maturity: u32,
+
+    /// Years to maturity under the curve's day count
+    pub ttm: f64,
+
+    pub interpolation: Interpolation,
+    pub discount_factor: f64,
+
+    /// Target price (base per FY, 1e18 scaled)
+    pub target_price: U256,
+}
+
+/// Targets of every pool under every convention, grouped by pool
+#[derive(Debug, Clone, Default)]
+pub struct CurveBench {
+    pub rows: Vec<CurveBenchRow>,
+}
+
+impl CurveBench {
+    /// Evaluate `curve` at `timestamp` for each `(pool, maturity)` under every
+    /// method of [BENCH_INTERPOLATIONS]
+    pub fn new(curve: &RateCurve, timestamp: u64, maturities: &[(Address, u32)]) -> Self {
+        let mut rows = Vec::with_capacity(maturities.len() * BENCH_INTERPOLATIONS.len());
+        for (pool, maturity) in maturities {
+            for interpolation in BENCH_INTERPOLATIONS {
+                let variant = curve.clone().with_interpolation(interpolation);
+                let ttm = variant.time_to_maturity(timestamp, *maturity);
+                let discount_factor = variant.discount_factor(ttm);
+                rows.push(CurveBenchRow {
+                    pool: *pool,
+                    maturity: *maturity,
+                    ttm,
+                    interpolation,
+                    discount_factor,
+                    target_price: U256::from((discount_factor * 1e18) as u128),
+                });
+            }
+        }
+        Self { rows }
+    }
+
+    /// Rows of `pool`, in [BENCH_INTERPOLATIONS] order
+    pub fn pool_rows(&self, pool: Address) -> impl Iterator<Item = &CurveBenchRow> {
+        self.rows.iter().filter(move |row| row.pool == pool)
+    }
+
+    /// Widest gap between the target prices of `pool` across conventions, in bps of
+    /// the lowest: an edge narrower than this depends on the convention chosen
+    pub fn spread_bps(&self, pool: Address) -> f64 {
+        let dfs = self.pool_rows(pool).map(|row| row.discount_factor);
+        let (low, high) = dfs.fold((f64::MAX, f64::MIN), |(low, high), df| {
+            (low.min(df), high.max(df))
+        });
+        if low <= 0.0 || high < low {
+            return 0.0;
+        }
+        (high - low) / low * 10_000.0
+    }
+}
+
+impl fmt::Display for CurveBench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = TokenDecimals::default();
+        let mut last_pool = None;
+        for row in &self.rows {
+            if last_pool != Some(row.pool) {
+                writeln!(
+                    f,
+                    "pool {:?} maturity {} ({:.4}y), spread {:.2} bps",
+                    row.pool,
+                    row.maturity,
+                    row.ttm,
+                    self.spread_bps(row.pool)
+                )?;
+                last_pool = Some(row.pool);
+            }
+            writeln!(
+                f,
+                "  {:<12} df {:.8}  target {}",
+                format!("{:?}", row.interpolation),
+                row.discount_factor,
+                decimals.price(row.target_price)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Maturity of every pool, read once, in pool order
+pub async fn fetch_maturities<M: Middleware + 'static>(
+    client: Arc<M>,
+    pools: &[Address],
+) -> Result<Vec<(Address, u32)>> {
+    let mut maturities = Vec::with_capacity(pools.len());
+    for pool in pools {
+        let maturity = NumoEnginePool::new(*pool, client.clone())
+            .maturity()
+            .call()
+            .await
+            .map_err(|e| anyhow!("Failed to read maturity of pool {pool:?}: {e}"))?;
+        maturities.push((*pool, maturity));
+    }
+    Ok(maturities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::encode_uints;
+
+    const NOW: u64 = 1_700_000_000;
+
+    /// Pools one week, three months, one year and three years out
+    fn maturities() -> Vec<(Address, u32)> {
+        [7u64, 91, 365, 1_095]
+            .iter()
+            .enumerate()
+            .map(|(i, days)| {
+                let pool = Address::repeat_byte(0x11 * (i as u8 + 1));
+                (pool, (NOW + days * 86_400) as u32)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bench_compares_every_method_per_pool() {
+        let curve = RateCurve::default_usd();
+        let maturities = maturities();
+        let bench = CurveBench::new(&curve, NOW, &maturities);
+        assert_eq!(
+            bench.rows.len(),
+            maturities.len() * BENCH_INTERPOLATIONS.len()
+        );
+
+        for (pool, maturity) in &maturities {
+            let rows: Vec<_> = bench.pool_rows(*pool).collect();
+            assert_eq!(rows.len(), BENCH_INTERPOLATIONS.len());
+            for (row, interpolation) in rows.iter().zip(BENCH_INTERPOLATIONS) {
+                assert_eq!(row.maturity, *maturity);
+                assert_eq!(row.interpolation, interpolation);
+                let expected = curve
+                    .clone()
+                    .with_interpolation(interpolation)
+                    .discount_factor(row.ttm);
+                assert_eq!(row.discount_factor, expected);
+                assert!(row.discount_factor > 0.0 && row.discount_factor < 1.0);
+            }
+            assert!(bench.spread_bps(*pool) >= 0.0);
+        }
+
+        // Further maturities discount more under every method
+        for interpolation in BENCH_INTERPOLATIONS {
+            let dfs: Vec<f64> = bench
+                .rows
+                .iter()
+                .filter(|row| row.interpolation == interpolation)
+                .map(|row| row.discount_factor)
+                .collect();
+            assert!(dfs.windows(2).all(|pair| pair[0] > pair[1]));
+        }
+
+        let report = bench.to_string();
+        assert_eq!(report.matches("pool 0x").count(), maturities.len());
+        assert_eq!(report.matches("LogLinearDf").count(), maturities.len());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_maturities_in_pool_order() {
+        let (provider, mock) = Provider::mocked();
+        let pools = [Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+
+        // Responses are served last-in first-out
+        mock.push::<Bytes, _>(encode_uints(&[1_800_000_000]))
+            .unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[1_750_000_000]))
+            .unwrap();
+        let maturities = fetch_maturities(Arc::new(provider), &pools).await.unwrap();
+        assert_eq!(
+            maturities,
+            vec![(pools[0], 1_750_000_000), (pools[1], 1_800_000_000)]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+/// Capital budget across in-flight trades for the Numo strategy
+/// Caps the total base committed to unconfirmed trades, reserving each trade's
+/// `max_base_in` when it is emitted and releasing it once the trade resolves
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use ethers::prelude::*;
+
+use crate::confirmation::{ReceiptObserver, SubmittedTx};
+
+/// Base reserved by unconfirmed trades, keyed by their calldata
+#[derive(Debug)]
+pub struct CapitalBudget {
+    /// Maximum total base across in-flight trades
+    max_total: u128,
+
+    reserved: Mutex<HashMap<Bytes, u128>>,
+}
+
+impl CapitalBudget {
+    pub fn new(max_total: u128) -> Self {
+        Self {
+            max_total,
+            reserved: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Total base reserved by in-flight trades
+    pub fn in_use(&self) -> u128 {
+        self.reserved.lock().unwrap().values().sum()
+    }
+
+    /// Base still available for new trades
+    pub fn available(&self) -> u128 {
+        self.max_total.saturating_sub(self.in_use())
+    }
+
+    /// Reserve `amount` for the trade carrying `calldata`, unless it would exceed
+    /// the budget
+    pub fn try_reserve(&self, calldata: Bytes, amount: u128) -> bool {
+        let mut reserved = self.reserved.lock().unwrap();
+        let in_use: u128 = reserved.values().sum();
+        if in_use.saturating_add(amount) > self.max_total {
+            return false;
+        }
+        *reserved.entry(calldata).or_default() += amount;
+        true
+    }
+
+    /// Release the reservation of the trade carrying `calldata`
+    pub fn release(&self, calldata: &Bytes) {
+        self.reserved.lock().unwrap().remove(calldata);
+    }
+}
+
+impl ReceiptObserver for CapitalBudget {
+    fn on_receipt(&self, submitted: &SubmittedTx, _receipt: &TransactionReceipt) {
+        // Confirmed or reverted, the trade no longer holds capital
+        if let Some(calldata) = &submitted.calldata {
+            self.release(calldata);
+        }
+    }
+
+    fn on_abandoned(&self, calldata: &Bytes) {
+        self.release(calldata);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_gates_and_releases() {
+        let budget = CapitalBudget::new(2_000);
+        let trade = |byte: u8| Bytes::from(vec![byte]);
+
+        assert!(budget.try_reserve(trade(1), 950));
+        assert!(budget.try_reserve(trade(2), 950));
+        assert_eq!(budget.in_use(), 1_900);
+        assert_eq!(budget.available(), 100);
+        assert!(!budget.try_reserve(trade(3), 950));
+
+        let submitted = SubmittedTx {
+            tx_hash: TxHash::repeat_byte(0x01),
+            gas_estimate: U256::from(200_000u64),
+            expected_profit: None,
+            calldata: Some(trade(1)),
+        };
+        budget.on_receipt(&submitted, &TransactionReceipt::default());
+        assert_eq!(budget.in_use(), 950);
+        assert!(budget.try_reserve(trade(3), 950));
+
+        budget.on_abandoned(&trade(2));
+        assert_eq!(budget.in_use(), 950);
+    }
+}
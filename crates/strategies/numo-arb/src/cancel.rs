@@ -0,0 +1,197 @@
+/// In-flight transaction cancellation for the Numo strategy
+/// Tracks submitted trades by calldata and replace-cancels them with a 0-value
+/// self-transfer at the same nonce and a higher gas price
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::prelude::*;
+use tracing::{debug, info};
+
+use artemis_core::types::Executor;
+
+use crate::confirmation::{ReceiptObserver, SubmittedTx};
+
+/// Default gas price bump for a replacement, above the usual 10% node minimum
+pub const DEFAULT_CANCEL_GAS_BUMP_BPS: u32 = 1_250;
+
+/// Gas limit of a plain value transfer
+const TRANSFER_GAS_LIMIT: u64 = 21_000;
+
+/// Hashes of submitted, unconfirmed transactions keyed by their calldata
+///
+/// The executor fills this in as it submits; the strategy looks up the hash of a
+/// trade it emitted when it needs to cancel it.
+#[derive(Debug, Default)]
+pub struct InflightTrades {
+    by_calldata: Mutex<HashMap<Bytes, TxHash>>,
+}
+
+impl InflightTrades {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a submitted transaction
+    pub fn submitted(&self, calldata: Bytes, tx_hash: TxHash) {
+        self.by_calldata.lock().unwrap().insert(calldata, tx_hash);
+    }
+
+    /// Forget a transaction once it is mined or cancelled
+    pub fn resolved(&self, tx_hash: TxHash) {
+        self.by_calldata
+            .lock()
+            .unwrap()
+            .retain(|_, hash| *hash != tx_hash);
+    }
+
+    /// Hash of the in-flight transaction carrying `calldata`, if any
+    pub fn tx_hash(&self, calldata: &Bytes) -> Option<TxHash> {
+        self.by_calldata.lock().unwrap().get(calldata).copied()
+    }
+}
+
+impl ReceiptObserver for InflightTrades {
+    fn on_submitted(&self, submitted: &SubmittedTx) {
+        if let Some(calldata) = &submitted.calldata {
+            self.submitted(calldata.clone(), submitted.tx_hash);
+        }
+    }
+
+    fn on_receipt(&self, submitted: &SubmittedTx, _receipt: &TransactionReceipt) {
+        self.resolved(submitted.tx_hash);
+    }
+}
+
+/// Bump a gas price by `bump_bps`, rounding up so the bump is never lost
+pub fn bumped_gas_price(gas_price: U256, bump_bps: u32) -> U256 {
+    let bumped = gas_price * U256::from(10_000 + bump_bps as u64);
+    (bumped + U256::from(9_999)) / U256::from(10_000)
+}
+
+/// An executor that replace-cancels pending transactions by hash
+pub struct CancelExecutor<M> {
+    client: Arc<M>,
+    gas_bump_bps: u32,
+    inflight: Option<Arc<InflightTrades>>,
+}
+
+impl<M: Middleware> CancelExecutor<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            gas_bump_bps: DEFAULT_CANCEL_GAS_BUMP_BPS,
+            inflight: None,
+        }
+    }
+
+    /// Bump the replaced transaction's gas price by `bump_bps`
+    pub fn with_gas_bump(mut self, bump_bps: u32) -> Self {
+        self.gas_bump_bps = bump_bps;
+        self
+    }
+
+    /// Forget cancelled transactions in `inflight`
+    pub fn with_inflight(mut self, inflight: Arc<InflightTrades>) -> Self {
+        self.inflight = Some(inflight);
+        self
+    }
+
+    /// The self-transfer replacing `pending`, or `None` if it is already mined
+    pub fn replacement(&self, pending: &Transaction) -> Option<TransactionRequest> {
+        if pending.block_number.is_some() {
+            return None;
+        }
+        let gas_price = pending
+            .gas_price
+            .or(pending.max_fee_per_gas)
+            .unwrap_or_default();
+        Some(
+            TransactionRequest::new()
+                .from(pending.from)
+                .to(pending.from)
+                .value(U256::zero())
+                .nonce(pending.nonce)
+                .gas(TRANSFER_GAS_LIMIT)
+                .gas_price(bumped_gas_price(gas_price, self.gas_bump_bps)),
+        )
+    }
+}
+
+#[async_trait]
+impl<M> Executor<TxHash> for CancelExecutor<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    async fn execute(&self, tx_hash: TxHash) -> Result<()> {
+        let pending = self
+            .client
+            .get_transaction(tx_hash)
+            .await
+            .context("Error fetching transaction to cancel")?;
+
+        let replacement = match pending.as_ref().and_then(|tx| self.replacement(tx)) {
+            Some(replacement) => replacement,
+            None => {
+                debug!(tx_hash = ?tx_hash, "Transaction no longer pending, nothing to cancel");
+                if let Some(inflight) = &self.inflight {
+                    inflight.resolved(tx_hash);
+                }
+                return Ok(());
+            }
+        };
+
+        let cancel_hash = *self.client.send_transaction(replacement, None).await?;
+        info!(tx_hash = ?tx_hash, cancel_hash = ?cancel_hash, "Cancellation submitted");
+        if let Some(inflight) = &self.inflight {
+            inflight.resolved(tx_hash);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replacement_reuses_nonce_with_higher_gas_price() {
+        let (provider, _mock) = Provider::mocked();
+        let executor = CancelExecutor::new(Arc::new(provider)).with_gas_bump(1_000);
+        let sender = Address::repeat_byte(0x44);
+        let pending = Transaction {
+            from: sender,
+            nonce: U256::from(7),
+            gas_price: Some(U256::from(1_000_000_001u64)),
+            ..Default::default()
+        };
+
+        let replacement = executor.replacement(&pending).unwrap();
+        assert_eq!(replacement.to, Some(sender.into()));
+        assert_eq!(replacement.value, Some(U256::zero()));
+        assert_eq!(replacement.nonce, Some(U256::from(7)));
+        // 10% above, rounded up
+        assert_eq!(replacement.gas_price, Some(U256::from(1_100_000_002u64)));
+
+        let mined = Transaction {
+            block_number: Some(U64::from(1)),
+            ..pending
+        };
+        assert!(executor.replacement(&mined).is_none());
+    }
+
+    #[test]
+    fn test_inflight_trades_track_by_calldata() {
+        let inflight = InflightTrades::new();
+        let calldata = Bytes::from(vec![1, 2, 3]);
+        let tx_hash = TxHash::repeat_byte(0xaa);
+
+        assert_eq!(inflight.tx_hash(&calldata), None);
+        inflight.submitted(calldata.clone(), tx_hash);
+        assert_eq!(inflight.tx_hash(&calldata), Some(tx_hash));
+        inflight.resolved(tx_hash);
+        assert_eq!(inflight.tx_hash(&calldata), None);
+    }
+}
@@ -0,0 +1,324 @@
+/// Single-pool carry trading for the Numo strategy
+/// Takes a directional position in one pool when its implied rate diverges from
+/// the curve, sized by the price move the rate gap implies until maturity
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use ethers::prelude::*;
+
+use crate::confirmation::{ReceiptObserver, SubmittedTx};
+use crate::pricing::{apply_slippage, base_for_fy_at_price};
+
+/// Side of a carry position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CarryDirection {
+    /// The pool's implied rate is above the curve: FY is cheap, buy it
+    BuyFy,
+    /// The pool's implied rate is below the curve: FY is rich, sell it
+    SellFy,
+}
+
+/// A carry position to open in one pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarryPosition {
+    pub pool: Address,
+    pub direction: CarryDirection,
+    pub fy_amount: u128,
+
+    /// Simple annual rate implied by the pool price
+    pub pool_rate: f64,
+
+    /// Simple annual rate implied by the curve at the pool's maturity
+    pub curve_rate: f64,
+
+    /// Pool rate minus curve rate, in bps
+    pub rate_gap_bps: i64,
+
+    /// Price move when the gap closes, to first order `|rate_gap| * ttm`, in bps
+    pub price_edge_bps: f64,
+}
+
+impl CarryPosition {
+    /// Expected base gained if the gap closes, given the pool price (1e18 scaled)
+    pub fn expected_profit(&self, price: U256) -> u128 {
+        let notional = self.fy_amount as f64 * price.as_u128() as f64 / 1e18;
+        (notional * self.price_edge_bps / 10_000.0) as u128
+    }
+}
+
+/// A carry position bounded for submission through the router
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarryTrade {
+    pub position: CarryPosition,
+
+    /// Pool price the position was sized at (base per FY, 1e18 scaled)
+    pub price: U256,
+
+    /// Most base spent buying FY, or least base received selling it
+    pub base_limit: u128,
+
+    /// Expected base gained if the gap closes
+    pub expected_profit: u128,
+}
+
+impl CarryTrade {
+    /// Bound `position`, opened at `price`, by `slippage_bps` of its base value
+    pub fn new(position: CarryPosition, price: U256, slippage_bps: u32) -> Self {
+        let base = base_for_fy_at_price(position.fy_amount, price);
+        let is_max_in = position.direction == CarryDirection::BuyFy;
+        Self {
+            expected_profit: position.expected_profit(price),
+            base_limit: apply_slippage(base, slippage_bps, is_max_in),
+            position,
+            price,
+        }
+    }
+
+    /// Base the router may pull from the bot: the bound when buying FY, none when
+    /// selling it
+    pub fn base_in(&self) -> u128 {
+        match self.position.direction {
+            CarryDirection::BuyFy => self.base_limit,
+            CarryDirection::SellFy => 0,
+        }
+    }
+}
+
+/// Carry positions held per pool until it matures
+///
+/// A position counts as open from the moment its trade is emitted, so the same gap
+/// is not traded again every block, and is dropped once its trade reverts or is
+/// abandoned unconfirmed, so the pool can be traded again.
+#[derive(Debug, Default)]
+pub struct CarryPositions {
+    /// Side held per pool, with the calldata of the trade opening it
+    open: Mutex<HashMap<Address, (CarryDirection, Bytes)>>,
+}
+
+impl CarryPositions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the position on `pool` opened by the trade carrying `calldata`
+    pub fn open(&self, pool: Address, direction: CarryDirection, calldata: Bytes) {
+        self.open
+            .lock()
+            .unwrap()
+            .insert(pool, (direction, calldata));
+    }
+
+    /// Side of the position held on `pool`, if any
+    pub fn position(&self, pool: Address) -> Option<CarryDirection> {
+        self.open
+            .lock()
+            .unwrap()
+            .get(&pool)
+            .map(|(direction, _)| *direction)
+    }
+
+    /// Stop holding the position on `pool`, e.g., once it matures
+    pub fn close(&self, pool: Address) {
+        self.open.lock().unwrap().remove(&pool);
+    }
+
+    /// Drop the position opened by the trade carrying `calldata`
+    fn release(&self, calldata: &Bytes) {
+        self.open
+            .lock()
+            .unwrap()
+            .retain(|_, (_, opened_by)| opened_by != calldata);
+    }
+}
+
+impl ReceiptObserver for CarryPositions {
+    fn on_receipt(&self, submitted: &SubmittedTx, receipt: &TransactionReceipt) {
+        // A confirmed trade opened the position, a reverted one did not
+        if receipt.status == Some(U64::zero()) {
+            if let Some(calldata) = &submitted.calldata {
+                self.release(calldata);
+            }
+        }
+    }
+
+    fn on_abandoned(&self, calldata: &Bytes) {
+        self.release(calldata);
+    }
+}
+
+/// Simple annual rate implied by a zero-coupon price `ttm` years from maturity,
+/// matching the curve's `1 / (1 + r * t)` discount factors
+pub fn implied_rate(price: f64, ttm: f64) -> Option<f64> {
+    if ttm <= 0.0 || price <= 0.0 {
+        return None;
+    }
+    Some((1.0 / price - 1.0) / ttm)
+}
+
+/// FY amount for a carry position on a rate gap of `rate_gap_bps` with `ttm` years left
+///
+/// The gap is worth about `rate_gap_bps * ttm` bps of price, so the same gap
+/// supports a smaller position as maturity approaches. Positions open at half of
+/// `max_fy_amount` once that price edge reaches `edge_bps` and grow linearly to the
+/// full size at twice the edge.
+pub fn carry_fy_amount(rate_gap_bps: u32, ttm: f64, edge_bps: u32, max_fy_amount: u128) -> u128 {
+    if ttm <= 0.0 {
+        return 0;
+    }
+    let price_edge_bps = rate_gap_bps as f64 * ttm;
+    if price_edge_bps < edge_bps as f64 || price_edge_bps == 0.0 {
+        return 0;
+    }
+    let fraction = if edge_bps == 0 {
+        1.0
+    } else {
+        (price_edge_bps / (2.0 * edge_bps as f64)).min(1.0)
+    };
+    (max_fy_amount as f64 * fraction) as u128
+}
+
+/// Carry position for `pool` priced at `price` (base per FY, 1e18 scaled), against a
+/// curve discount factor `curve_df` at its maturity, or `None` below the edge
+pub fn carry_position(
+    pool: Address,
+    price: U256,
+    ttm: f64,
+    curve_df: f64,
+    edge_bps: u32,
+    max_fy_amount: u128,
+) -> Option<CarryPosition> {
+    let pool_rate = implied_rate(price.as_u128() as f64 / 1e18, ttm)?;
+    let curve_rate = implied_rate(curve_df, ttm)?;
+    let rate_gap_bps = ((pool_rate - curve_rate) * 10_000.0).round() as i64;
+    let gap = rate_gap_bps.unsigned_abs().min(u32::MAX as u64) as u32;
+
+    let fy_amount = carry_fy_amount(gap, ttm, edge_bps, max_fy_amount);
+    if fy_amount == 0 {
+        return None;
+    }
+    let direction = if rate_gap_bps > 0 {
+        CarryDirection::BuyFy
+    } else {
+        CarryDirection::SellFy
+    };
+    Some(CarryPosition {
+        pool,
+        direction,
+        fy_amount,
+        pool_rate,
+        curve_rate,
+        rate_gap_bps,
+        price_edge_bps: gap as f64 * ttm,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: u128 = 1_000_000;
+
+    #[test]
+    fn test_carry_size_scales_with_gap_and_ttm() {
+        // 100 bps over half a year is a 50 bps price edge: full size at edge 10
+        assert_eq!(carry_fy_amount(100, 0.5, 10, MAX), MAX);
+        // 30 bps over half a year is 15 bps, three quarters of twice the edge
+        assert_eq!(carry_fy_amount(30, 0.5, 10, MAX), 750_000);
+        // Exactly at the edge opens half a position
+        assert_eq!(carry_fy_amount(20, 0.5, 10, MAX), 500_000);
+        // The same gap closer to maturity is below the edge
+        assert_eq!(carry_fy_amount(30, 0.25, 10, MAX), 0);
+        assert_eq!(carry_fy_amount(30, 0.0, 10, MAX), 0);
+        assert_eq!(carry_fy_amount(0, 1.0, 0, MAX), 0);
+        assert_eq!(carry_fy_amount(1, 1.0, 0, MAX), MAX);
+    }
+
+    #[test]
+    fn test_carry_position_direction_follows_rate_gap() {
+        let pool = Address::repeat_byte(0x11);
+        let ttm = 0.5;
+        // Curve at 5%: discount factor 1 / (1 + 0.05 * 0.5)
+        let curve_df = 1.0 / 1.025;
+        let price_at = |rate: f64| U256::from((1e18 / (1.0 + rate * ttm)) as u128);
+
+        // Pool implies 6%: FY is cheap, 100 bps * 0.5 = 50 bps of price
+        let cheap = carry_position(pool, price_at(0.06), ttm, curve_df, 10, MAX).unwrap();
+        assert_eq!(cheap.direction, CarryDirection::BuyFy);
+        assert_eq!(cheap.rate_gap_bps, 100);
+        assert_eq!(cheap.fy_amount, MAX);
+        assert!((cheap.price_edge_bps - 50.0).abs() < 1e-9);
+
+        // Pool implies 4.7%: FY is rich, 30 bps * 0.5 = 15 bps of price
+        let rich = carry_position(pool, price_at(0.047), ttm, curve_df, 10, MAX).unwrap();
+        assert_eq!(rich.direction, CarryDirection::SellFy);
+        assert_eq!(rich.rate_gap_bps, -30);
+        assert_eq!(rich.fy_amount, 750_000);
+
+        // A pool on the curve, or a matured one, has no position
+        assert!(carry_position(pool, price_at(0.05), ttm, curve_df, 10, MAX).is_none());
+        assert!(carry_position(pool, price_at(0.06), 0.0, 1.0, 10, MAX).is_none());
+    }
+
+    #[test]
+    fn test_positions_held_until_their_trade_fails() {
+        let positions = CarryPositions::new();
+        let (pool, other) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let trade = |byte: u8| Bytes::from(vec![byte]);
+        positions.open(pool, CarryDirection::BuyFy, trade(1));
+        positions.open(other, CarryDirection::SellFy, trade(2));
+
+        // A confirmed trade keeps its position open
+        let submitted = SubmittedTx {
+            tx_hash: TxHash::repeat_byte(0x01),
+            gas_estimate: U256::from(200_000u64),
+            expected_profit: None,
+            calldata: Some(trade(1)),
+        };
+        let confirmed = TransactionReceipt {
+            status: Some(U64::one()),
+            ..Default::default()
+        };
+        positions.on_receipt(&submitted, &confirmed);
+        assert_eq!(positions.position(pool), Some(CarryDirection::BuyFy));
+
+        // A reverted or abandoned one frees the pool
+        let reverted = TransactionReceipt {
+            status: Some(U64::zero()),
+            ..Default::default()
+        };
+        positions.on_receipt(&submitted, &reverted);
+        assert_eq!(positions.position(pool), None);
+        positions.on_abandoned(&trade(2));
+        assert_eq!(positions.position(other), None);
+
+        positions.open(pool, CarryDirection::BuyFy, trade(3));
+        positions.close(pool);
+        assert_eq!(positions.position(pool), None);
+    }
+
+    #[test]
+    fn test_carry_trade_bounds_base_by_slippage() {
+        let pool = Address::repeat_byte(0x11);
+        let price = U256::exp10(17) * 9;
+        let position = |direction| CarryPosition {
+            pool,
+            direction,
+            fy_amount: 1_000_000,
+            pool_rate: 0.06,
+            curve_rate: 0.05,
+            rate_gap_bps: 100,
+            price_edge_bps: 50.0,
+        };
+
+        // 0.9 base per FY: at most 1% over 900_000 spent buying, drawn from the bot
+        let buy = CarryTrade::new(position(CarryDirection::BuyFy), price, 100);
+        assert_eq!(buy.base_limit, 909_000);
+        assert_eq!(buy.base_in(), 909_000);
+        assert_eq!(buy.expected_profit, 4_500);
+
+        // ...and at least 1% under it received selling, which draws no base
+        let sell = CarryTrade::new(position(CarryDirection::SellFy), price, 100);
+        assert_eq!(sell.base_limit, 891_000);
+        assert_eq!(sell.base_in(), 0);
+    }
+}
@@ -0,0 +1,214 @@
+/// Historical catch-up for the Numo strategy
+/// Replays blocks from a configured start height up to the current head at startup,
+/// so opportunities that appeared while the bot was down are still evaluated, then
+/// hands over to the live block stream
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::prelude::*;
+use futures::StreamExt;
+use tracing::{info, warn};
+
+use artemis_core::types::{Collector, CollectorStream};
+
+use crate::types::{Event, NewBlockEvent};
+
+/// Default maximum number of blocks replayed at startup
+pub const DEFAULT_MAX_CATCHUP_BLOCKS: u64 = 256;
+
+/// Default delay between historical block fetches
+pub const DEFAULT_CATCHUP_DELAY: Duration = Duration::from_millis(100);
+
+/// Compute the block range to replay, bounded to the most recent `max_blocks`
+/// before `head`. Returns `None` when there is nothing to replay.
+pub fn catchup_range(start_block: u64, head: u64, max_blocks: u64) -> Option<RangeInclusive<u64>> {
+    if start_block > head || max_blocks == 0 {
+        return None;
+    }
+    let earliest = head.saturating_sub(max_blocks - 1);
+    Some(start_block.max(earliest)..=head)
+}
+
+/// A collector that emits one [Event::HistoricalBlock] per block between the start
+/// block and the head at startup, then forwards the `live` block collector
+///
+/// The live stream is subscribed before the replay starts, so blocks mined during
+/// catch-up are buffered rather than missed, and are only forwarded once the
+/// replay is done. Historical blocks are fetched one at a time with a delay
+/// between fetches to avoid hammering the node.
+pub struct CatchUpCollector<M> {
+    provider: Arc<M>,
+    live: Box<dyn Collector<Event>>,
+    start_block: u64,
+    max_blocks: u64,
+    delay: Duration,
+}
+
+impl<M> CatchUpCollector<M> {
+    pub fn new(provider: Arc<M>, start_block: u64, live: Box<dyn Collector<Event>>) -> Self {
+        Self {
+            provider,
+            live,
+            start_block,
+            max_blocks: DEFAULT_MAX_CATCHUP_BLOCKS,
+            delay: DEFAULT_CATCHUP_DELAY,
+        }
+    }
+
+    /// Set the maximum number of blocks to replay
+    pub fn with_max_blocks(mut self, max_blocks: u64) -> Self {
+        self.max_blocks = max_blocks;
+        self
+    }
+
+    /// Set the delay between historical block fetches
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+#[async_trait]
+impl<M> Collector<Event> for CatchUpCollector<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Event>> {
+        let live = self.live.get_event_stream().await?;
+        let head = self.provider.get_block_number().await?.as_u64();
+
+        let blocks: Vec<u64> = match catchup_range(self.start_block, head, self.max_blocks) {
+            Some(range) => {
+                if *range.start() > self.start_block {
+                    warn!(
+                        start_block = self.start_block,
+                        replay_from = *range.start(),
+                        max_blocks = self.max_blocks,
+                        "Catch-up range truncated"
+                    );
+                }
+                range.collect()
+            }
+            None => vec![],
+        };
+
+        info!(
+            start_block = self.start_block,
+            head,
+            blocks = blocks.len(),
+            "Starting historical catch-up"
+        );
+
+        let provider = self.provider.clone();
+        let delay = self.delay;
+        let stream = futures::stream::iter(blocks)
+            .then(move |number| {
+                let provider = provider.clone();
+                async move {
+                    tokio::time::sleep(delay).await;
+                    match provider.get_block(BlockNumber::Number(number.into())).await {
+                        Ok(Some(block)) => NewBlockEvent::from_block(&block),
+                        Ok(None) => {
+                            warn!(block_number = number, "Historical block not found");
+                            None
+                        }
+                        Err(e) => {
+                            warn!(
+                                block_number = number,
+                                error = ?e,
+                                "Failed to fetch historical block"
+                            );
+                            None
+                        }
+                    }
+                }
+            })
+            .filter_map(|event| async move { event.map(Event::HistoricalBlock) });
+
+        Ok(Box::pin(stream.chain(live)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A live block stream delivering `blocks` and then ending
+    struct StubLive {
+        blocks: Vec<u64>,
+    }
+
+    #[async_trait]
+    impl Collector<Event> for StubLive {
+        async fn get_event_stream(&self) -> Result<CollectorStream<'_, Event>> {
+            let events = self.blocks.iter().map(|block_number| {
+                Event::NewBlock(NewBlockEvent {
+                    block_number: *block_number,
+                    timestamp: 1_700_000_000 + block_number * 5,
+                    base_fee: None,
+                })
+            });
+            Ok(Box::pin(futures::stream::iter(events.collect::<Vec<_>>())))
+        }
+    }
+
+    #[test]
+    fn test_catchup_range() {
+        // Full range when within the bound
+        assert_eq!(catchup_range(100, 110, 256), Some(100..=110));
+
+        // Truncated to the most recent blocks
+        assert_eq!(catchup_range(100, 1_000, 10), Some(991..=1_000));
+
+        // Nothing to replay
+        assert_eq!(catchup_range(1_001, 1_000, 10), None);
+        assert_eq!(catchup_range(100, 1_000, 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_replays_small_range() {
+        let (provider, mock) = Provider::mocked();
+
+        // Responses are served last-in first-out
+        for number in (10u64..=12).rev() {
+            let block = Block::<TxHash> {
+                number: Some(number.into()),
+                timestamp: U256::from(1_700_000_000 + number * 5),
+                base_fee_per_gas: Some(U256::from(25)),
+                ..Default::default()
+            };
+            mock.push(block).unwrap();
+        }
+        mock.push(U64::from(12)).unwrap();
+
+        let live = Box::new(StubLive {
+            blocks: vec![13, 14],
+        });
+        let collector =
+            CatchUpCollector::new(Arc::new(provider), 10, live).with_delay(Duration::ZERO);
+        let events: Vec<Event> = collector.get_event_stream().await.unwrap().collect().await;
+
+        // The whole replay comes out before the first live block
+        let blocks: Vec<(bool, &NewBlockEvent)> = events
+            .iter()
+            .map(|event| match event {
+                Event::HistoricalBlock(block) => (true, block),
+                Event::NewBlock(block) => (false, block),
+                _ => panic!("expected a block event"),
+            })
+            .collect();
+        assert_eq!(
+            blocks
+                .iter()
+                .map(|(historical, block)| (*historical, block.block_number))
+                .collect::<Vec<_>>(),
+            vec![(true, 10), (true, 11), (true, 12), (false, 13), (false, 14)]
+        );
+        assert_eq!(blocks[0].1.timestamp, 1_700_000_050);
+        assert_eq!(blocks[2].1.base_fee, Some(U256::from(25)));
+    }
+}
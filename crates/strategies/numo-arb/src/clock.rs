@@ -0,0 +1,72 @@
+/// Wall clock for the Numo strategy
+/// Block and tick timestamps and realized-loss windows read the time through a
+/// [Clock], so tests can drive them with a [MockClock] instead of the system time
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current Unix time in seconds
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> u64;
+}
+
+/// The system wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
+
+/// A clock that only moves when told to
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: AtomicU64,
+}
+
+impl MockClock {
+    pub fn new(now: u64) -> Self {
+        Self {
+            now: AtomicU64::new(now),
+        }
+    }
+
+    /// Move the clock forward by `secs`
+    pub fn advance(&self, secs: u64) {
+        self.now.fetch_add(secs, Ordering::Relaxed);
+    }
+
+    /// Set the clock to `now`
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::Relaxed);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_moves_only_when_told() {
+        let clock = MockClock::new(1_700_000_000);
+        assert_eq!(clock.now(), 1_700_000_000);
+        assert_eq!(clock.now(), 1_700_000_000);
+
+        clock.advance(60);
+        assert_eq!(clock.now(), 1_700_000_060);
+        clock.set(5);
+        assert_eq!(clock.now(), 5);
+
+        assert!(SystemClock.now() > 1_700_000_000);
+    }
+}
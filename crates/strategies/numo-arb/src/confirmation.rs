@@ -0,0 +1,214 @@
+/// Transaction confirmation for the Numo strategy
+/// Submits transactions like the mempool executor, then waits for their receipts
+/// in the background and hands them to observers (gas tuning, PnL, ...)
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::prelude::*;
+use tracing::{debug, warn};
+
+use artemis_core::types::Executor;
+
+use crate::tx_manager::{RetryPolicy, TxManager};
+use crate::types::SubmitTxToMempool;
+
+/// A transaction submitted by [ConfirmingExecutor]
+#[derive(Debug, Clone)]
+pub struct SubmittedTx {
+    /// Transaction hash
+    pub tx_hash: TxHash,
+
+    /// Gas estimate taken right before submission
+    pub gas_estimate: U256,
+
+    /// Expected profit carried by the action, if any
+    pub expected_profit: Option<U256>,
+
+    /// Transaction calldata
+    pub calldata: Option<Bytes>,
+}
+
+/// Receives the receipt of every confirmed transaction
+pub trait ReceiptObserver: Send + Sync {
+    /// Called right after a transaction is sent, before its receipt is awaited
+    fn on_submitted(&self, _submitted: &SubmittedTx) {}
+
+    /// Called with the calldata of a transaction that failed to send or was dropped
+    /// before confirming
+    fn on_abandoned(&self, _calldata: &Bytes) {}
+
+    fn on_receipt(&self, submitted: &SubmittedTx, receipt: &TransactionReceipt);
+}
+
+/// An executor that sends transactions to the mempool and reports their receipts
+///
+/// Gas pricing matches the mempool executor. Receipts are awaited on a separate
+/// task so submissions are never blocked by confirmations.
+pub struct ConfirmingExecutor<M> {
+    client: Arc<M>,
+    observers: Vec<Arc<dyn ReceiptObserver>>,
+    retries: Option<RetryPolicy>,
+}
+
+impl<M: Middleware> ConfirmingExecutor<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            observers: vec![],
+            retries: None,
+        }
+    }
+
+    /// Report receipts to `observer`
+    pub fn with_observer(mut self, observer: Arc<dyn ReceiptObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Resubmit transactions that are not mined in time as `policy` allows
+    pub fn with_retries(mut self, policy: RetryPolicy) -> Self {
+        self.retries = Some(policy);
+        self
+    }
+}
+
+impl<M> ConfirmingExecutor<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    /// Tell observers that the transaction carrying `calldata` will not confirm
+    fn abandon(observers: &[Arc<dyn ReceiptObserver>], calldata: Option<&Bytes>) {
+        if let Some(calldata) = calldata {
+            for observer in observers {
+                observer.on_abandoned(calldata);
+            }
+        }
+    }
+
+    async fn submit(&self, mut action: SubmitTxToMempool) -> Result<()> {
+        let gas_estimate = self
+            .client
+            .estimate_gas(&action.tx, None)
+            .await
+            .context("Error estimating gas usage")?;
+
+        let expected_profit = action.gas_bid_info.as_ref().map(|info| info.total_profit);
+        let calldata = action.tx.data().cloned();
+        let bid_gas_price = match (action.tx.gas_price(), &action.gas_bid_info) {
+            // Already capped to the bid by the execution policy
+            (Some(gas_price), _) => gas_price,
+            // Gas price at which we'd break even, scaled to the bid percentage
+            (None, Some(info)) => info.total_profit / gas_estimate * info.bid_percentage / 100,
+            (None, None) => self
+                .client
+                .get_gas_price()
+                .await
+                .context("Error getting gas price")?,
+        };
+        action.tx.set_gas_price(bid_gas_price);
+
+        // Retries replace the transaction at its nonce, so it is assigned up front
+        let retries = match self.retries {
+            Some(policy) => {
+                self.client
+                    .fill_transaction(&mut action.tx, None)
+                    .await
+                    .context("Error filling transaction")?;
+                Some((
+                    TxManager::new(self.client.clone(), policy),
+                    action.tx.clone(),
+                ))
+            }
+            None => None,
+        };
+
+        let tx_hash = *self.client.send_transaction(action.tx, None).await?;
+        debug!(tx_hash = ?tx_hash, "Transaction submitted");
+
+        if self.observers.is_empty() && retries.is_none() {
+            return Ok(());
+        }
+
+        let submitted = SubmittedTx {
+            tx_hash,
+            gas_estimate,
+            expected_profit,
+            calldata,
+        };
+        for observer in &self.observers {
+            observer.on_submitted(&submitted);
+        }
+        let client = self.client.clone();
+        let observers = self.observers.clone();
+        tokio::spawn(async move {
+            let confirmed = match retries {
+                Some((manager, tx)) => {
+                    // Observers track each resubmission like a fresh submission
+                    let resubmitted = |tx_hash| {
+                        let resubmission = SubmittedTx {
+                            tx_hash,
+                            ..submitted.clone()
+                        };
+                        for observer in &observers {
+                            observer.on_submitted(&resubmission);
+                        }
+                    };
+                    manager
+                        .confirm(tx, tx_hash, resubmitted)
+                        .await
+                        .map(|confirmed| {
+                            confirmed.map(|(tx_hash, receipt)| {
+                                let mined = SubmittedTx {
+                                    tx_hash,
+                                    ..submitted.clone()
+                                };
+                                (mined, receipt)
+                            })
+                        })
+                }
+                None => PendingTransaction::new(tx_hash, client.provider())
+                    .await
+                    .map(|receipt| receipt.map(|receipt| (submitted.clone(), receipt)))
+                    .map_err(anyhow::Error::from),
+            };
+            match confirmed {
+                Ok(Some((submitted, receipt))) => {
+                    if receipt.status == Some(U64::zero()) {
+                        warn!(tx_hash = ?submitted.tx_hash, "Transaction reverted on-chain");
+                    }
+                    for observer in &observers {
+                        observer.on_receipt(&submitted, &receipt);
+                    }
+                }
+                Ok(None) => {
+                    warn!(tx_hash = ?tx_hash, "Transaction dropped before confirmation");
+                    Self::abandon(&observers, submitted.calldata.as_ref());
+                }
+                Err(e) => {
+                    warn!(tx_hash = ?tx_hash, error = ?e, "Failed to fetch receipt");
+                    Self::abandon(&observers, submitted.calldata.as_ref());
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M> Executor<SubmitTxToMempool> for ConfirmingExecutor<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    async fn execute(&self, action: SubmitTxToMempool) -> Result<()> {
+        let calldata = action.tx.data().cloned();
+        let result = self.submit(action).await;
+        if result.is_err() {
+            Self::abandon(&self.observers, calldata.as_ref());
+        }
+        result
+    }
+}
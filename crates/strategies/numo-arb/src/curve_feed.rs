@@ -0,0 +1,121 @@
+/// Live rate curve refresh for the Numo strategy
+/// Re-reads the curve file on a fixed interval so an external job can publish new
+/// knots without a restart; every read, good or bad, becomes a [CurveRefreshEvent]
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::warn;
+
+use artemis_core::types::{Collector, CollectorStream};
+
+use crate::clock::{Clock, SystemClock};
+use crate::sofr::{Compounding, RateCurve};
+use crate::types::{CurveRefreshEvent, Event};
+
+/// A collector re-reading a rate curve file every `interval`
+pub struct CurveFileCollector {
+    path: PathBuf,
+    interval: Duration,
+
+    /// Compounding forced onto every read curve, as at startup
+    compounding: Option<Compounding>,
+
+    clock: Arc<dyn Clock>,
+}
+
+impl CurveFileCollector {
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        Self {
+            path,
+            interval,
+            compounding: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Replace each read curve's own compounding with `compounding`
+    pub fn with_compounding(mut self, compounding: Option<Compounding>) -> Self {
+        self.compounding = compounding;
+        self
+    }
+
+    /// Stamp refreshes with `clock`'s time rather than the system time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Read the curve file once, `None` when it is missing or invalid
+    fn read(&self) -> Option<RateCurve> {
+        match RateCurve::from_file(&self.path) {
+            Ok(curve) => Some(match self.compounding {
+                Some(compounding) => curve.with_compounding(compounding),
+                None => curve,
+            }),
+            Err(e) => {
+                warn!(error = ?e, "Rate curve refresh could not read the curve file");
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Collector<Event> for CurveFileCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Event>> {
+        let mut interval = tokio::time::interval(self.interval);
+        // The startup curve was just loaded; the first refresh follows the interval
+        interval.tick().await;
+        let stream = futures::stream::unfold(interval, move |mut interval| async move {
+            interval.tick().await;
+            let refresh = CurveRefreshEvent {
+                timestamp: self.clock.now(),
+                curve: self.read(),
+            };
+            Some((Event::CurveRefresh(refresh), interval))
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_refreshes_read_the_file_and_report_failures() {
+        let path = std::env::temp_dir().join(format!("numo-curve-{}.json", std::process::id()));
+        let curve = RateCurve::default_usd();
+        std::fs::write(&path, serde_json::to_string(&curve).unwrap()).unwrap();
+
+        let clock = Arc::new(MockClock::new(1_700_000_000));
+        let collector = CurveFileCollector::new(path.clone(), Duration::from_millis(10))
+            .with_compounding(Some(Compounding::Continuous))
+            .with_clock(clock.clone());
+        let mut stream = collector.get_event_stream().await.unwrap();
+
+        let Event::CurveRefresh(first) = stream.next().await.unwrap() else {
+            panic!("expected a curve refresh");
+        };
+        assert_eq!(first.timestamp, 1_700_000_000);
+        let read = first.curve.unwrap();
+        assert_eq!(read.knots.len(), curve.knots.len());
+        assert_eq!(read.compounding, Compounding::Continuous);
+
+        // A broken file reports a failed refresh instead of ending the stream
+        std::fs::write(&path, "not a curve").unwrap();
+        clock.advance(60);
+        let Event::CurveRefresh(second) = stream.next().await.unwrap() else {
+            panic!("expected a curve refresh");
+        };
+        assert_eq!(second.timestamp, 1_700_000_060);
+        assert!(second.curve.is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
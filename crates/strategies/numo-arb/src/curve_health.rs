@@ -0,0 +1,145 @@
+/// Rate curve health for the Numo strategy
+/// Tracks when the curve was last refreshed, how many refreshes failed since, and
+/// which curve is in use, so a stalled curve feed shows up as unready instead of
+/// quietly trading against stale rates
+use serde::Serialize;
+
+use crate::recorder::curve_fingerprint;
+use crate::sofr::RateCurve;
+
+/// Point-in-time view of the curve source, for readiness checks and metrics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CurveHealthStatus {
+    /// Time of the last successful curve fetch (Unix seconds), if any
+    pub last_success: Option<u64>,
+
+    /// Fetches failed since the last success
+    pub consecutive_failures: u32,
+
+    /// Fingerprint of the curve in use, see [curve_fingerprint]
+    pub fingerprint: String,
+
+    /// Whether the curve is fresh enough to trade on
+    pub ready: bool,
+}
+
+/// Freshness of the rate curve in use
+#[derive(Debug, Clone)]
+pub struct CurveHealth {
+    /// Age beyond which the curve is stale. Never stale when unset.
+    max_age_secs: Option<u64>,
+
+    last_success: Option<u64>,
+    consecutive_failures: u32,
+    fingerprint: String,
+}
+
+impl CurveHealth {
+    pub fn new(curve: &RateCurve, max_age_secs: Option<u64>) -> Self {
+        Self {
+            max_age_secs,
+            last_success: None,
+            consecutive_failures: 0,
+            fingerprint: curve_fingerprint(curve),
+        }
+    }
+
+    /// Count the startup curve as fetched at `now`, unless a fetch was already
+    /// recorded
+    pub fn seed(&mut self, now: u64) {
+        self.last_success.get_or_insert(now);
+    }
+
+    /// Record `curve` fetched successfully at `now`
+    pub fn record_success(&mut self, now: u64, curve: &RateCurve) {
+        self.last_success = Some(now);
+        self.consecutive_failures = 0;
+        self.fingerprint = curve_fingerprint(curve);
+    }
+
+    /// Record a failed fetch, keeping the current curve
+    pub fn record_failure(&mut self) {
+        self.consecutive_failures += 1;
+    }
+
+    /// Whether the curve at `now` is within `max_age_secs` of its last fetch
+    pub fn is_ready(&self, now: u64) -> bool {
+        match (self.max_age_secs, self.last_success) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(max_age), Some(at)) => now.saturating_sub(at) <= max_age,
+        }
+    }
+
+    pub fn last_success(&self) -> Option<u64> {
+        self.last_success
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    pub fn status(&self, now: u64) -> CurveHealthStatus {
+        CurveHealthStatus {
+            last_success: self.last_success,
+            consecutive_failures: self.consecutive_failures,
+            fingerprint: self.fingerprint.clone(),
+            ready: self.is_ready(now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::clock::{Clock, MockClock};
+
+    #[test]
+    fn test_readiness_lost_on_stale_curve_and_restored_by_refresh() {
+        let clock = MockClock::new(1_700_000_000);
+        let curve = RateCurve::default_usd();
+        let mut health = CurveHealth::new(&curve, Some(300));
+
+        // Unready until the startup curve is seeded
+        assert!(!health.is_ready(clock.now()));
+        health.seed(clock.now());
+        assert!(health.is_ready(clock.now()));
+        assert_eq!(health.fingerprint(), curve_fingerprint(&curve));
+
+        // Failed refreshes keep the curve ready until it ages past the threshold
+        clock.advance(200);
+        health.record_failure();
+        health.record_failure();
+        assert!(health.is_ready(clock.now()));
+        clock.advance(100);
+        assert!(health.is_ready(clock.now()));
+        clock.advance(1);
+        let status = health.status(clock.now());
+        assert!(!status.ready);
+        assert_eq!(status.consecutive_failures, 2);
+        assert_eq!(status.last_success, Some(1_700_000_000));
+
+        // A later seed does not pretend the curve was refreshed
+        health.seed(clock.now());
+        assert!(!health.is_ready(clock.now()));
+
+        // A successful refresh restores readiness and resets the failure count
+        health.record_success(clock.now(), &curve);
+        let status = health.status(clock.now());
+        assert!(status.ready);
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(status.last_success, Some(clock.now()));
+    }
+
+    #[test]
+    fn test_never_stale_without_threshold() {
+        let health = CurveHealth::new(&RateCurve::default_usd(), None);
+        assert!(health.is_ready(0));
+        assert!(health.is_ready(u64::MAX));
+    }
+}
@@ -0,0 +1,179 @@
+/// Opportunity data log for offline analysis
+/// Appends one CSV row per evaluated opportunity per block, including skipped ones
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+
+use crate::types::SkipReason;
+
+/// Column names of the opportunity log, in order
+pub const COLUMNS: [&str; 11] = [
+    "block",
+    "cheap_pool",
+    "rich_pool",
+    "cheap_price",
+    "rich_price",
+    "target_price",
+    "divergence_bps",
+    "fy_amount",
+    "expected_profit",
+    "decision",
+    "skip_reason",
+];
+
+/// A single evaluated opportunity, filled in as the evaluation progresses
+#[derive(Debug, Clone, Default)]
+pub struct OpportunityRecord {
+    pub block: u64,
+    pub cheap_pool: Option<Address>,
+    pub rich_pool: Option<Address>,
+    pub cheap_price: Option<U256>,
+    pub rich_price: Option<U256>,
+    pub target_price: Option<U256>,
+    pub divergence_bps: Option<u32>,
+    pub fy_amount: Option<u128>,
+    pub expected_profit: Option<u128>,
+    pub skip_reason: Option<SkipReason>,
+}
+
+impl OpportunityRecord {
+    /// Create an empty record for a block
+    pub fn new(block: u64) -> Self {
+        Self {
+            block,
+            ..Default::default()
+        }
+    }
+
+    /// Mark the opportunity as skipped
+    pub fn skip(&mut self, reason: SkipReason) {
+        self.skip_reason = Some(reason);
+    }
+
+    /// Final decision for the opportunity, `execute` only once a trade was emitted;
+    /// opportunities found but not traded carry the reason they were not
+    pub fn decision(&self) -> &'static str {
+        match self.skip_reason {
+            Some(_) => "skip",
+            None => "execute",
+        }
+    }
+
+    /// Render the record as a CSV row matching [`COLUMNS`]
+    pub fn to_csv_row(&self) -> String {
+        fn opt<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(ToString::to_string).unwrap_or_default()
+        }
+        fn addr(value: &Option<Address>) -> String {
+            value.map(|a| format!("{a:?}")).unwrap_or_default()
+        }
+
+        [
+            self.block.to_string(),
+            addr(&self.cheap_pool),
+            addr(&self.rich_pool),
+            opt(&self.cheap_price),
+            opt(&self.rich_price),
+            opt(&self.target_price),
+            opt(&self.divergence_bps),
+            opt(&self.fy_amount),
+            opt(&self.expected_profit),
+            self.decision().to_string(),
+            opt(&self.skip_reason),
+        ]
+        .join(",")
+    }
+}
+
+/// Appends opportunity records to a CSV file
+#[derive(Debug, Clone)]
+pub struct DataLogger {
+    path: PathBuf,
+}
+
+impl DataLogger {
+    /// Create a logger writing to `path`; the file is created on first write
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record, writing the header first if the file is new or empty
+    pub fn log(&self, record: &OpportunityRecord) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open data log {}", self.path.display()))?;
+
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", COLUMNS.join(","))?;
+        }
+        writeln!(file, "{}", record.to_csv_row())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("numo-{}-{}.csv", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_rows_written_with_expected_schema() {
+        let path = temp_path("data-log");
+        let logger = DataLogger::new(&path);
+
+        let mut executed = OpportunityRecord::new(100);
+        executed.cheap_pool = Some(Address::repeat_byte(0x11));
+        executed.rich_pool = Some(Address::repeat_byte(0x22));
+        executed.cheap_price = Some(U256::from(950_000u64));
+        executed.rich_price = Some(U256::from(970_000u64));
+        executed.target_price = Some(U256::from(960_000u64));
+        executed.divergence_bps = Some(104);
+        executed.fy_amount = Some(1_000);
+        executed.expected_profit = Some(12);
+
+        let mut skipped = OpportunityRecord::new(101);
+        skipped.skip(SkipReason::BelowEdge);
+
+        logger.log(&executed).unwrap();
+        logger.log(&skipped).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        std::fs::remove_file(&path).unwrap();
+
+        // Header is written once, followed by one row per record
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], COLUMNS.join(","));
+        for line in &lines[1..] {
+            assert_eq!(line.split(',').count(), COLUMNS.len());
+        }
+
+        let row: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(row[0], "100");
+        assert_eq!(row[1], format!("{:?}", Address::repeat_byte(0x11)));
+        assert_eq!(row[6], "104");
+        assert_eq!(row[9], "execute");
+        assert_eq!(row[10], "");
+
+        let row: Vec<&str> = lines[2].split(',').collect();
+        assert_eq!(row[0], "101");
+        assert_eq!(row[1], "");
+        assert_eq!(row[9], "skip");
+        assert_eq!(row[10], "below_edge");
+    }
+}
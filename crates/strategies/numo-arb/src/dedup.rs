@@ -0,0 +1,126 @@
+/// Opportunity deduplication for the Numo strategy
+/// The same economic opportunity can persist for several blocks before a trade
+/// lands; identical opportunities are only reported once per window
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::carry::CarryTrade;
+use crate::types::ArbOpportunity;
+
+/// Significant digits kept when rounding sizes and profits for the content hash
+const DEDUP_SIGNIFICANT_DIGITS: u32 = 3;
+
+/// Round a value to its leading significant decimal digits
+fn round_significant(value: u128, digits: u32) -> u128 {
+    let magnitude = value.checked_ilog10().unwrap_or(0) + 1;
+    if magnitude <= digits {
+        return value;
+    }
+    let scale = 10u128.pow(magnitude - digits);
+    (value + scale / 2) / scale * scale
+}
+
+/// Content hash of an opportunity: pools plus rounded size and profit
+pub fn opportunity_key(opp: &ArbOpportunity) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    opp.cheap_pool.hash(&mut hasher);
+    opp.rich_pool.hash(&mut hasher);
+    round_significant(opp.fy_amount, DEDUP_SIGNIFICANT_DIGITS).hash(&mut hasher);
+    round_significant(opp.expected_profit, DEDUP_SIGNIFICANT_DIGITS).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Content hash of a carry trade: pool and side plus rounded size and profit
+pub fn carry_key(trade: &CarryTrade) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    trade.position.pool.hash(&mut hasher);
+    trade.position.direction.hash(&mut hasher);
+    round_significant(trade.position.fy_amount, DEDUP_SIGNIFICANT_DIGITS).hash(&mut hasher);
+    round_significant(trade.expected_profit, DEDUP_SIGNIFICANT_DIGITS).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Suppresses opportunities already reported within the last `ttl_blocks` blocks
+#[derive(Debug, Clone)]
+pub struct OpportunityDeduper {
+    /// Window length in blocks
+    ttl_blocks: u64,
+
+    /// Block at which each opportunity was last reported
+    seen: HashMap<u64, u64>,
+}
+
+impl OpportunityDeduper {
+    pub fn new(ttl_blocks: u64) -> Self {
+        Self {
+            ttl_blocks,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Whether the opportunity should be reported at `block_number`
+    /// Reported opportunities are remembered until the window expires
+    pub fn check(&mut self, opp: &ArbOpportunity, block_number: u64) -> bool {
+        let ttl = self.ttl_blocks;
+        self.seen
+            .retain(|_, reported| block_number < reported.saturating_add(ttl));
+
+        let key = opportunity_key(opp);
+        if self.seen.contains_key(&key) {
+            return false;
+        }
+        self.seen.insert(key, block_number);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn opportunity(fy_amount: u128, expected_profit: u128) -> ArbOpportunity {
+        ArbOpportunity {
+            fy_amount,
+            expected_profit,
+            ..test_utils::opportunity()
+        }
+    }
+
+    #[test]
+    fn test_round_significant() {
+        assert_eq!(round_significant(123_456, 3), 123_000);
+        assert_eq!(round_significant(123_500, 3), 124_000);
+        assert_eq!(round_significant(999, 3), 999);
+        assert_eq!(round_significant(0, 3), 0);
+    }
+
+    #[test]
+    fn test_unchanged_opportunity_reported_once_per_window() {
+        let mut dedup = OpportunityDeduper::new(3);
+        let opp = opportunity(1_000_000, 5_000);
+
+        assert!(dedup.check(&opp, 100));
+        assert!(!dedup.check(&opp, 101));
+        // Rounding noise in size and profit is the same opportunity
+        assert!(!dedup.check(&opportunity(1_000_400, 5_002), 102));
+
+        // The window has expired
+        assert!(dedup.check(&opp, 103));
+        assert!(!dedup.check(&opp, 104));
+    }
+
+    #[test]
+    fn test_changed_opportunity_is_reported() {
+        let mut dedup = OpportunityDeduper::new(10);
+
+        assert!(dedup.check(&opportunity(1_000_000, 5_000), 100));
+        assert!(dedup.check(&opportunity(1_000_000, 6_000), 101));
+        assert!(dedup.check(&opportunity(2_000_000, 5_000), 102));
+
+        let mut reversed = opportunity(1_000_000, 5_000);
+        std::mem::swap(&mut reversed.cheap_pool, &mut reversed.rich_pool);
+        assert!(dedup.check(&reversed, 103));
+    }
+}
@@ -0,0 +1,1004 @@
+/// Execution policies for the Numo strategy
+/// Decouple the decision to trade an opportunity from how it is turned into an action
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::prelude::*;
+use ethers::providers::{spoof, RawCall};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip2930::AccessList;
+use tracing::{debug, warn};
+
+use crate::carry::{CarryDirection, CarryTrade};
+use crate::gas::{
+    apply_gas_buffer, clamp_gas_limit, is_suspicious_estimate, GasEstimateCache, GasUsageHistory,
+    DEFAULT_GAS_TIP_WEI, DEFAULT_MIN_GAS_LIMIT,
+};
+use crate::pending::PendingPoolState;
+use crate::pricing::{max_gas_price_for_profit, max_profitable_gas_price};
+use crate::types::{
+    Action, ArbDirection, ArbOpportunity, Config, GasBidInfo, GasEstimation, SubmitTxToMempool,
+};
+use numo_bindings::{NumoArbRouter, NumoEnginePool};
+
+/// Gas limit used when estimation fails
+pub(crate) const FALLBACK_GAS_LIMIT: u64 = 500_000;
+
+/// Default gas buffer on top of the estimate (20%)
+pub const DEFAULT_GAS_BUFFER_BPS: u32 = 2_000;
+
+/// Turns an arbitrage opportunity into an action for the executors
+#[async_trait]
+pub trait ExecutionPolicy: Send + Sync {
+    /// Build the action for an opportunity, or `None` to skip it
+    async fn build_action(&self, opp: &ArbOpportunity) -> Result<Option<Action>>;
+
+    /// Build the action opening a carry position, or `None` to skip it; policies
+    /// without carry support skip every position
+    async fn build_carry_action(&self, _trade: &CarryTrade) -> Result<Option<Action>> {
+        Ok(None)
+    }
+
+    /// Gas a trade on the pair is expected to use before any buffer, when known
+    /// without calling the node
+    fn expected_gas(
+        &self,
+        _cheap_pool: Address,
+        _rich_pool: Address,
+        _direction: ArbDirection,
+    ) -> Option<U256> {
+        None
+    }
+}
+
+/// Default policy: a single `arbBuyFYThenSellFY` router call submitted to the
+/// mempool, with a gas buffer (20% by default) and a profit-based gas bid; carry
+/// positions go through the router's bounded `buyFY` and `sellFY`
+pub struct DefaultRouterPolicy<M> {
+    /// Ethereum client
+    client: Arc<M>,
+
+    /// Router contract instance
+    router: NumoArbRouter<M>,
+
+    /// Percentage of expected profit to bid in gas (0-100)
+    bid_percentage: u64,
+
+    /// Gas buffer on top of the estimate in basis points, shared with a tuner
+    gas_buffer_bps: Arc<AtomicU32>,
+
+    /// How the gas limit is chosen before the buffer is applied
+    gas_estimation: GasEstimation,
+
+    /// Gas used by confirmed trades, for [GasEstimation::HistoricalP95]
+    gas_history: Option<Arc<GasUsageHistory>>,
+
+    /// Gas estimates reused across attempts on the same pair, if enabled
+    gas_cache: Option<Arc<GasEstimateCache>>,
+
+    /// Floor for the buffered gas limit
+    min_gas_limit: u64,
+
+    /// Priority tip (wei per gas) bid over the network gas price
+    gas_tip: U256,
+
+    /// Native token price in base tokens and the base token's decimals, converting
+    /// profit into the native wei gas is paid in; profit is taken as native when
+    /// unset
+    native_price: Option<(f64, u8)>,
+
+    /// Expected profit from which trades are simulated before submission, if enabled
+    min_profit_to_simulate: Option<u128>,
+
+    /// Reserve moves of in-flight trades overlaid on simulations, if enabled
+    pending: Option<Arc<PendingPoolState>>,
+
+    /// EIP-2930 access list of each pool pair traded so far, if enabled
+    access_lists: Option<Mutex<HashMap<(Address, Address), AccessList>>>,
+}
+
+impl<M: Middleware + 'static> DefaultRouterPolicy<M> {
+    /// Create the default policy for the router at `router_address`
+    pub fn new(client: Arc<M>, router_address: Address, bid_percentage: u64) -> Self {
+        let router = NumoArbRouter::new(router_address, client.clone());
+        Self {
+            client,
+            router,
+            bid_percentage,
+            gas_buffer_bps: Arc::new(AtomicU32::new(DEFAULT_GAS_BUFFER_BPS)),
+            gas_estimation: GasEstimation::OnChain,
+            gas_history: None,
+            gas_cache: None,
+            min_gas_limit: DEFAULT_MIN_GAS_LIMIT,
+            gas_tip: U256::from(DEFAULT_GAS_TIP_WEI),
+            native_price: None,
+            min_profit_to_simulate: None,
+            pending: None,
+            access_lists: None,
+        }
+    }
+
+    /// Create the policy `config` describes: its router, gas bid, buffer and
+    /// estimation, and the gas history, gas cache, simulation, pending state and
+    /// access lists it enables
+    ///
+    /// The shared components are exposed so receipt observers can feed them.
+    pub fn from_config(client: Arc<M>, config: &Config) -> Self {
+        let mut policy = Self::new(client, config.router_address, config.bid_percentage)
+            .with_gas_buffer(Arc::new(AtomicU32::new(config.gas_buffer_bps)))
+            .with_min_gas_limit(config.min_gas_limit)
+            .with_gas_tip(config.gas_tip_wei)
+            .with_gas_estimation(config.gas_estimation);
+        if let Some(price) = config.native_price_in_base {
+            policy = policy.with_native_price(price, config.token_decimals.base);
+        }
+        if config.gas_estimation == GasEstimation::HistoricalP95 {
+            policy = policy.with_gas_history(Arc::new(GasUsageHistory::new()));
+        }
+        if let Some(ttl) = config.gas_cache_ttl_secs {
+            let cache = GasEstimateCache::new(Duration::from_secs(ttl));
+            policy = policy.with_gas_cache(Arc::new(cache));
+        }
+        if let Some(min_profit) = config.min_profit_to_simulate {
+            policy = policy.with_simulation(min_profit);
+        }
+        if let Some(slot) = config.pool_reserves_slot {
+            policy = policy.with_pending_state(Arc::new(PendingPoolState::new(slot)));
+        }
+        if config.use_access_lists {
+            policy = policy.with_access_lists();
+        }
+        policy
+    }
+
+    /// Gas used by confirmed trades, if gas limits are taken from its p95
+    pub fn gas_history(&self) -> Option<Arc<GasUsageHistory>> {
+        self.gas_history.clone()
+    }
+
+    /// Gas estimates reused per pool pair, if enabled
+    pub fn gas_cache(&self) -> Option<Arc<GasEstimateCache>> {
+        self.gas_cache.clone()
+    }
+
+    /// Reserve moves of in-flight trades, if simulations overlay them
+    pub fn pending_state(&self) -> Option<Arc<PendingPoolState>> {
+        self.pending.clone()
+    }
+
+    /// Read the gas buffer from a shared handle, e.g., a [crate::gas::GasBufferTuner]
+    pub fn with_gas_buffer(mut self, gas_buffer_bps: Arc<AtomicU32>) -> Self {
+        self.gas_buffer_bps = gas_buffer_bps;
+        self
+    }
+
+    /// Never submit with a gas limit below `min_gas_limit`
+    pub fn with_min_gas_limit(mut self, min_gas_limit: u64) -> Self {
+        self.min_gas_limit = min_gas_limit;
+        self
+    }
+
+    /// Bid `gas_tip_wei` per gas over the network gas price
+    pub fn with_gas_tip(mut self, gas_tip_wei: u64) -> Self {
+        self.gas_tip = U256::from(gas_tip_wei);
+        self
+    }
+
+    /// Convert profit into native wei at `native_price_in_base` base tokens per
+    /// native token, for a base token of `base_decimals`
+    pub fn with_native_price(mut self, native_price_in_base: f64, base_decimals: u8) -> Self {
+        self.native_price = Some((native_price_in_base, base_decimals));
+        self
+    }
+
+    /// Gas price spending the bid percentage of `opp`'s profit on `gas_limit` gas
+    pub fn gas_budget_price(&self, opp: &ArbOpportunity, gas_limit: U256) -> U256 {
+        max_profitable_gas_price(opp, gas_limit, self.bid_percentage, self.native_price)
+    }
+
+    /// Choose gas limits with `gas_estimation` instead of estimating on-chain
+    pub fn with_gas_estimation(mut self, gas_estimation: GasEstimation) -> Self {
+        self.gas_estimation = gas_estimation;
+        self
+    }
+
+    /// Read the gas used by confirmed trades from `history`
+    pub fn with_gas_history(mut self, history: Arc<GasUsageHistory>) -> Self {
+        self.gas_history = Some(history);
+        self
+    }
+
+    /// Reuse gas estimates per pool pair from `cache`
+    pub fn with_gas_cache(mut self, cache: Arc<GasEstimateCache>) -> Self {
+        self.gas_cache = Some(cache);
+        self
+    }
+
+    /// Simulate trades expecting at least `min_profit` with `eth_call` and drop the
+    /// ones that revert; smaller trades are submitted without spending the call
+    pub fn with_simulation(mut self, min_profit: u128) -> Self {
+        self.min_profit_to_simulate = Some(min_profit);
+        self
+    }
+
+    /// Attach an access list from `eth_createAccessList` to every trade, created
+    /// once per pool pair
+    pub fn with_access_lists(mut self) -> Self {
+        self.access_lists = Some(Mutex::new(HashMap::new()));
+        self
+    }
+
+    /// Access list of the opportunity's pool pair, created on its first trade;
+    /// `None` when disabled or the node cannot create one
+    async fn access_list(&self, opp: &ArbOpportunity, tx: &TypedTransaction) -> Option<AccessList> {
+        let lists = self.access_lists.as_ref()?;
+        let pair = (opp.cheap_pool, opp.rich_pool);
+        let cached = lists.lock().unwrap().get(&pair).cloned();
+        if cached.is_some() {
+            return cached;
+        }
+        match self.client.create_access_list(tx, None).await {
+            Ok(created) => {
+                debug!(
+                    cheap_pool = ?opp.cheap_pool,
+                    rich_pool = ?opp.rich_pool,
+                    entries = created.access_list.0.len(),
+                    gas_used = %created.gas_used,
+                    "Access list created"
+                );
+                lists
+                    .lock()
+                    .unwrap()
+                    .insert(pair, created.access_list.clone());
+                Some(created.access_list)
+            }
+            Err(e) => {
+                debug!(error = ?e, "Failed to create access list, sending without one");
+                None
+            }
+        }
+    }
+
+    /// Simulate against the pools' reserves moved by the in-flight trades in
+    /// `pending`, and record each built trade there
+    pub fn with_pending_state(mut self, pending: Arc<PendingPoolState>) -> Self {
+        self.pending = Some(pending);
+        self
+    }
+
+    /// State override applying the in-flight trades on the opportunity's pools to
+    /// their current reserves, `None` when no trade on them is in flight
+    pub async fn simulation_override(&self, opp: &ArbOpportunity) -> Result<Option<spoof::State>> {
+        let Some(pending) = &self.pending else {
+            return Ok(None);
+        };
+        let mut reserves = vec![];
+        for pool in [opp.cheap_pool, opp.rich_pool] {
+            if pending.pending_delta(pool).is_none() {
+                continue;
+            }
+            let (base, fy, _) = NumoEnginePool::new(pool, self.client.clone())
+                .get_cache()
+                .call()
+                .await?;
+            reserves.push((pool, base, fy));
+        }
+        if reserves.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(pending.state_override(&reserves)))
+    }
+
+    /// Simulate the call, on top of the in-flight trades when there are any
+    async fn simulate(
+        &self,
+        opp: &ArbOpportunity,
+        call: &ContractCall<M, (u128, u128)>,
+    ) -> Result<()> {
+        match self.simulation_override(opp).await? {
+            Some(state) => {
+                debug!(
+                    cheap_pool = ?opp.cheap_pool,
+                    rich_pool = ?opp.rich_pool,
+                    "Simulating on top of in-flight trades"
+                );
+                self.client
+                    .provider()
+                    .call_raw(&call.tx)
+                    .state(&state)
+                    .await?;
+            }
+            None => {
+                call.call().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the opportunity is worth a pre-submission simulation
+    fn should_simulate(&self, opp: &ArbOpportunity) -> bool {
+        self.min_profit_to_simulate
+            .map(|min_profit| opp.expected_profit >= min_profit)
+            .unwrap_or(false)
+    }
+
+    /// Gas limit for the call before the buffer, per the gas estimation mode
+    async fn estimate_gas(
+        &self,
+        opp: &ArbOpportunity,
+        call: &ContractCall<M, (u128, u128)>,
+    ) -> U256 {
+        match self.gas_estimation {
+            GasEstimation::OnChain => {}
+            GasEstimation::Fixed(gas_limit) => return U256::from(gas_limit),
+            GasEstimation::HistoricalP95 => match self.gas_history.as_ref().and_then(|h| h.p95()) {
+                Some(p95) => return p95,
+                None => debug!("Too few confirmed trades for a gas p95, estimating"),
+            },
+        }
+        self.estimate_gas_on_chain(opp, call).await
+    }
+
+    /// Network gas price plus the tip, capped at `budget_price`, or the network
+    /// price as the error when it asks for more; the budget is bid when the price
+    /// cannot be fetched
+    async fn bid_price(&self, budget_price: U256) -> std::result::Result<U256, U256> {
+        match self.client.get_gas_price().await {
+            Ok(network_price) if network_price > budget_price => Err(network_price),
+            Ok(network_price) => Ok(network_price.saturating_add(self.gas_tip).min(budget_price)),
+            Err(e) => {
+                debug!(error = ?e, "Failed to fetch gas price, bidding the budget");
+                Ok(budget_price)
+            }
+        }
+    }
+
+    /// Last estimate cached for the pair in `direction`, if fresh
+    fn cached_gas(
+        &self,
+        cheap_pool: Address,
+        rich_pool: Address,
+        direction: ArbDirection,
+    ) -> Option<U256> {
+        self.gas_cache
+            .as_ref()
+            .and_then(|cache| cache.get(cheap_pool, rich_pool, direction))
+    }
+
+    /// Gas estimate for the call, from the cache when fresh
+    async fn estimate_gas_on_chain(
+        &self,
+        opp: &ArbOpportunity,
+        call: &ContractCall<M, (u128, u128)>,
+    ) -> U256 {
+        let cache = self.gas_cache.as_ref();
+        if let Some(estimate) = self.cached_gas(opp.cheap_pool, opp.rich_pool, opp.direction) {
+            return estimate;
+        }
+        match call.estimate_gas().await {
+            Ok(estimate) if is_suspicious_estimate(estimate) => {
+                warn!(
+                    estimate = %estimate,
+                    min_gas_limit = self.min_gas_limit,
+                    "Gas estimate is implausibly low, clamping to the minimum gas limit"
+                );
+                estimate
+            }
+            Ok(estimate) => {
+                if let Some(cache) = cache {
+                    cache.insert(opp.cheap_pool, opp.rich_pool, opp.direction, estimate);
+                }
+                estimate
+            }
+            Err(e) => {
+                debug!(error = ?e, "Gas estimation failed, using fallback limit");
+                if let Some(cache) = cache {
+                    cache.invalidate(opp.cheap_pool, opp.rich_pool, opp.direction);
+                }
+                U256::from(FALLBACK_GAS_LIMIT)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> ExecutionPolicy for DefaultRouterPolicy<M> {
+    async fn build_action(&self, opp: &ArbOpportunity) -> Result<Option<Action>> {
+        // Build transaction to call router
+        let receiver = self.client.default_sender().unwrap_or_default();
+        let mut call = match opp.direction {
+            ArbDirection::BuyFyThenSellFy => self.router.arb_buy_fy_then_sell_fy(
+                opp.cheap_pool,
+                opp.rich_pool,
+                opp.fy_amount,
+                opp.max_base_in,
+                opp.min_base_out,
+                receiver,
+            ),
+            ArbDirection::SellBaseThenSellFy => self.router.arb_sell_base_then_sell_fy(
+                opp.cheap_pool,
+                opp.rich_pool,
+                opp.max_base_in,
+                opp.fy_amount,
+                opp.min_base_out,
+                receiver,
+            ),
+        };
+
+        if self.should_simulate(opp) {
+            if let Err(e) = self.simulate(opp, &call).await {
+                warn!(
+                    cheap_pool = ?opp.cheap_pool,
+                    rich_pool = ?opp.rich_pool,
+                    expected_profit = opp.expected_profit,
+                    error = ?e,
+                    "Trade simulation reverted, skipping"
+                );
+                return Ok(None);
+            }
+        }
+
+        // Known slots make the trade cheaper, so attach them before estimating gas
+        if let Some(access_list) = self.access_list(opp, &call.tx).await {
+            call.tx.set_access_list(access_list);
+        }
+
+        // Estimate gas
+        let gas_estimate = self.estimate_gas(opp, &call).await;
+        let gas_with_buffer = clamp_gas_limit(
+            apply_gas_buffer(gas_estimate, self.gas_buffer_bps.load(Ordering::Relaxed)),
+            self.min_gas_limit,
+        );
+
+        // Bid the network price plus the tip, capped at the bid percentage of the
+        // profit in fees, and skip the trade when the network asks for more
+        let total_profit = U256::from(opp.expected_profit);
+        let budget_price = self.gas_budget_price(opp, gas_with_buffer);
+        let bid_price = match self.bid_price(budget_price).await {
+            Ok(bid_price) => bid_price,
+            Err(network_price) => {
+                warn!(
+                    cheap_pool = ?opp.cheap_pool,
+                    rich_pool = ?opp.rich_pool,
+                    expected_profit = opp.expected_profit,
+                    gas_limit = %gas_with_buffer,
+                    network_gas_price = %network_price,
+                    budget_gas_price = %budget_price,
+                    bid_percentage = self.bid_percentage,
+                    "Required gas fees exceed the bid budget, skipping"
+                );
+                return Ok(None);
+            }
+        };
+
+        // Build transaction
+        let mut tx = call.tx;
+        tx.set_gas(gas_with_buffer);
+        tx.set_gas_price(bid_price);
+
+        if let (Some(cache), Some(calldata)) = (&self.gas_cache, tx.data()) {
+            cache.track(
+                calldata.clone(),
+                opp.cheap_pool,
+                opp.rich_pool,
+                opp.direction,
+            );
+        }
+        if let (Some(pending), Some(calldata)) = (&self.pending, tx.data()) {
+            pending.record(calldata.clone(), opp);
+        }
+
+        // Create gas bid info
+        let gas_bid_info = Some(GasBidInfo {
+            total_profit,
+            bid_percentage: self.bid_percentage,
+        });
+
+        Ok(Some(Action::SubmitTx(SubmitTxToMempool {
+            tx,
+            gas_bid_info,
+        })))
+    }
+
+    async fn build_carry_action(&self, trade: &CarryTrade) -> Result<Option<Action>> {
+        // The router pulls the bot's base or FY and trades it on the pool, bounded
+        // by the trade's base limit
+        let position = &trade.position;
+        let receiver = self.client.default_sender().unwrap_or_default();
+        let call = match position.direction {
+            CarryDirection::BuyFy => self.router.buy_fy(
+                position.pool,
+                position.fy_amount,
+                trade.base_limit,
+                receiver,
+            ),
+            CarryDirection::SellFy => self.router.sell_fy(
+                position.pool,
+                position.fy_amount,
+                trade.base_limit,
+                receiver,
+            ),
+        };
+
+        let simulate = self
+            .min_profit_to_simulate
+            .is_some_and(|min_profit| trade.expected_profit >= min_profit);
+        if simulate {
+            if let Err(e) = call.call().await {
+                warn!(
+                    pool = ?position.pool,
+                    expected_profit = trade.expected_profit,
+                    error = ?e,
+                    "Carry simulation reverted, skipping"
+                );
+                return Ok(None);
+            }
+        }
+
+        // Cached and historical gas is that of two-pool trades, so a single pool
+        // trade is estimated unless its limit is fixed
+        let gas_estimate = match self.gas_estimation {
+            GasEstimation::Fixed(gas_limit) => U256::from(gas_limit),
+            GasEstimation::OnChain | GasEstimation::HistoricalP95 => {
+                match call.estimate_gas().await {
+                    Ok(estimate) => estimate,
+                    Err(e) => {
+                        debug!(error = ?e, "Gas estimation failed, using fallback limit");
+                        U256::from(FALLBACK_GAS_LIMIT)
+                    }
+                }
+            }
+        };
+        let gas_with_buffer = clamp_gas_limit(
+            apply_gas_buffer(gas_estimate, self.gas_buffer_bps.load(Ordering::Relaxed)),
+            self.min_gas_limit,
+        );
+
+        let budget_price = max_gas_price_for_profit(
+            trade.expected_profit,
+            gas_with_buffer,
+            self.bid_percentage,
+            self.native_price,
+        );
+        let bid_price = match self.bid_price(budget_price).await {
+            Ok(bid_price) => bid_price,
+            Err(network_price) => {
+                warn!(
+                    pool = ?position.pool,
+                    expected_profit = trade.expected_profit,
+                    gas_limit = %gas_with_buffer,
+                    network_gas_price = %network_price,
+                    budget_gas_price = %budget_price,
+                    bid_percentage = self.bid_percentage,
+                    "Required gas fees exceed the bid budget, skipping"
+                );
+                return Ok(None);
+            }
+        };
+
+        let mut tx = call.tx;
+        tx.set_gas(gas_with_buffer);
+        tx.set_gas_price(bid_price);
+        Ok(Some(Action::SubmitTx(SubmitTxToMempool {
+            tx,
+            gas_bid_info: Some(GasBidInfo {
+                total_profit: U256::from(trade.expected_profit),
+                bid_percentage: self.bid_percentage,
+            }),
+        })))
+    }
+
+    fn expected_gas(
+        &self,
+        cheap_pool: Address,
+        rich_pool: Address,
+        direction: ArbDirection,
+    ) -> Option<U256> {
+        match self.gas_estimation {
+            GasEstimation::Fixed(gas_limit) => Some(U256::from(gas_limit)),
+            GasEstimation::HistoricalP95 => self
+                .gas_history
+                .as_ref()
+                .and_then(|history| history.p95())
+                .or_else(|| self.cached_gas(cheap_pool, rich_pool, direction)),
+            GasEstimation::OnChain => self.cached_gas(cheap_pool, rich_pool, direction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::confirmation::{ReceiptObserver, SubmittedTx};
+    use crate::pending::pack_reserves;
+    use crate::test_utils::{encode_uints, opportunity};
+    use ethers::types::transaction::eip2930::{AccessListItem, AccessListWithGasUsed};
+
+    fn gas_limit(action: Option<Action>) -> U256 {
+        match action {
+            Some(Action::SubmitTx(submit)) => *submit.tx.gas().unwrap(),
+            _ => panic!("expected a SubmitTx action"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_low_gas_estimate_clamped_to_floor() {
+        let (provider, mock) = Provider::mocked();
+        let policy = DefaultRouterPolicy::new(Arc::new(provider), Address::repeat_byte(0x33), 80)
+            .with_min_gas_limit(150_000);
+
+        // A no-op simulation: buffered to 25_200, then raised to the floor
+        mock.push(U256::from(21_000)).unwrap();
+        let action = policy.build_action(&opportunity()).await.unwrap();
+        assert_eq!(gas_limit(action), U256::from(150_000));
+
+        // Plausible estimates keep their buffer
+        mock.push(U256::from(200_000)).unwrap();
+        let action = policy.build_action(&opportunity()).await.unwrap();
+        assert_eq!(gas_limit(action), U256::from(240_000));
+    }
+
+    #[tokio::test]
+    async fn test_only_profitable_trades_are_simulated() {
+        let (provider, mock) = Provider::mocked();
+        let policy = DefaultRouterPolicy::new(Arc::new(provider), Address::repeat_byte(0x33), 80)
+            .with_simulation(30);
+        let mut opp = opportunity();
+
+        // At the threshold: the simulation finds no response and is treated as a revert
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+
+        // ...and succeeds once the call returns, before gas is estimated
+        mock.push(U256::from(200_000)).unwrap();
+        mock.push::<Bytes, _>(crate::test_utils::encode_uints(&[950, 980]))
+            .unwrap();
+        assert_eq!(
+            gas_limit(policy.build_action(&opp).await.unwrap()),
+            U256::from(240_000)
+        );
+
+        // Below the threshold only the gas estimate is requested
+        opp.expected_profit = 29;
+        mock.push(U256::from(200_000)).unwrap();
+        assert_eq!(
+            gas_limit(policy.build_action(&opp).await.unwrap()),
+            U256::from(240_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inflight_trade_reflected_in_simulation() {
+        let (provider, mock) = Provider::mocked();
+        let pending = Arc::new(PendingPoolState::new(8));
+        let policy = DefaultRouterPolicy::new(Arc::new(provider), Address::repeat_byte(0x33), 80)
+            .with_simulation(30)
+            .with_pending_state(pending.clone());
+        let opp = opportunity();
+
+        // Nothing in flight: a plain simulation, no reserves read
+        assert!(policy.simulation_override(&opp).await.unwrap().is_none());
+
+        // An unconfirmed trade on the pair moves both pools' reserves in the override
+        pending.record(Bytes::from(vec![0xaa]), &opp);
+        // Responses are served last-in first-out; the cheap pool's cache is read first
+        mock.push::<Bytes, _>(encode_uints(&[500_000, 400_000, 0]))
+            .unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[100_000, 200_000, 0]))
+            .unwrap();
+        let state = policy.simulation_override(&opp).await.unwrap().unwrap();
+        let slot = H256::from_low_u64_be(8);
+        let mut expected = spoof::state();
+        expected
+            .account(opp.cheap_pool)
+            .store(slot, pack_reserves(100_950, 199_000));
+        expected
+            .account(opp.rich_pool)
+            .store(slot, pack_reserves(499_020, 401_000));
+        assert_eq!(
+            serde_json::to_value(&state).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+
+        // The overridden simulation passes, and the new trade is in flight too
+        mock.push(U256::from(200_000)).unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[950, 980])).unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[500_000, 400_000, 0]))
+            .unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[100_000, 200_000, 0]))
+            .unwrap();
+        let action = policy.build_action(&opp).await.unwrap();
+        assert_eq!(gas_limit(action), U256::from(240_000));
+        assert_eq!(pending.pending_delta(opp.rich_pool).unwrap().fy, 2_000);
+
+        // Reserves that cannot be read fail the simulation, so the trade is skipped
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_from_config_exposes_shared_components() {
+        let (provider, _mock) = Provider::mocked();
+        let client = Arc::new(provider);
+        let policy = DefaultRouterPolicy::from_config(client.clone(), &Config::default());
+        assert!(policy.gas_history().is_none());
+        assert!(policy.gas_cache().is_none());
+        assert!(policy.pending_state().is_none());
+
+        let config = Config {
+            gas_buffer_bps: 3_000,
+            gas_estimation: GasEstimation::HistoricalP95,
+            gas_cache_ttl_secs: Some(60),
+            pool_reserves_slot: Some(8),
+            ..Config::default()
+        };
+        let policy = DefaultRouterPolicy::from_config(client, &config);
+        assert_eq!(policy.gas_buffer_bps.load(Ordering::Relaxed), 3_000);
+        // The handles are the policy's own, so observers feeding them feed it
+        let cache = policy.gas_cache().unwrap();
+        cache.insert(
+            Address::repeat_byte(0x11),
+            Address::repeat_byte(0x22),
+            ArbDirection::BuyFyThenSellFy,
+            U256::from(150_000),
+        );
+        assert_eq!(
+            policy.expected_gas(
+                Address::repeat_byte(0x11),
+                Address::repeat_byte(0x22),
+                ArbDirection::BuyFyThenSellFy
+            ),
+            Some(U256::from(150_000))
+        );
+        assert!(policy.gas_history().is_some());
+        assert!(policy.pending_state().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cached_gas_estimate_reused_until_revert() {
+        let (provider, mock) = Provider::mocked();
+        let cache = Arc::new(GasEstimateCache::new(Duration::from_secs(60)));
+        let policy = DefaultRouterPolicy::new(Arc::new(provider), Address::repeat_byte(0x33), 80)
+            .with_gas_cache(cache.clone());
+        let opp = opportunity();
+        let expected_gas = || policy.expected_gas(opp.cheap_pool, opp.rich_pool, opp.direction);
+        assert!(expected_gas().is_none());
+
+        // First attempt estimates and caches, and profit checks price the estimate
+        mock.push(U256::from(100_000)).unwrap();
+        let first = policy.build_action(&opp).await.unwrap();
+        assert_eq!(gas_limit(first.clone()), U256::from(120_000));
+        assert_eq!(expected_gas(), Some(U256::from(100_000)));
+
+        // The other entrypoint on the same pair has its own estimate
+        let sell_base = ArbDirection::SellBaseThenSellFy;
+        assert!(policy
+            .expected_gas(opp.cheap_pool, opp.rich_pool, sell_base)
+            .is_none());
+        cache.insert(
+            opp.cheap_pool,
+            opp.rich_pool,
+            sell_base,
+            U256::from(150_000),
+        );
+
+        // Second attempt reuses the estimate; an estimate RPC would find no
+        // response and fall back to the default limit
+        let second = policy.build_action(&opp).await.unwrap();
+        assert_eq!(gas_limit(second), U256::from(120_000));
+
+        // A reverted receipt for the transaction drops the cached estimate
+        let calldata = match first {
+            Some(Action::SubmitTx(submit)) => submit.tx.data().cloned(),
+            _ => None,
+        };
+        let submitted = SubmittedTx {
+            tx_hash: TxHash::zero(),
+            gas_estimate: U256::from(100_000),
+            expected_profit: None,
+            calldata,
+        };
+        let receipt = TransactionReceipt {
+            status: Some(U64::zero()),
+            ..Default::default()
+        };
+        cache.on_receipt(&submitted, &receipt);
+        assert!(cache
+            .get(opp.cheap_pool, opp.rich_pool, opp.direction)
+            .is_none());
+        // Only the reverted direction is dropped
+        assert_eq!(
+            cache.get(opp.cheap_pool, opp.rich_pool, sell_base),
+            Some(U256::from(150_000))
+        );
+
+        let third = policy.build_action(&opp).await.unwrap();
+        assert_eq!(gas_limit(third), U256::from(FALLBACK_GAS_LIMIT) * 12 / 10);
+    }
+
+    #[tokio::test]
+    async fn test_access_list_attached_and_cached_per_pair() {
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        let router = Address::repeat_byte(0x33);
+        let opp = opportunity();
+        let access_list = AccessList(vec![AccessListItem {
+            address: opp.rich_pool,
+            storage_keys: vec![H256::repeat_byte(0x01), H256::repeat_byte(0x02)],
+        }]);
+        let attached = |action: Option<Action>| match action {
+            Some(Action::SubmitTx(submit)) => submit.tx.access_list().cloned(),
+            _ => panic!("expected a SubmitTx action"),
+        };
+
+        // Responses are served last-in first-out: the access list, then the estimate
+        let policy = DefaultRouterPolicy::new(provider.clone(), router, 80).with_access_lists();
+        mock.push(U256::from(200_000)).unwrap();
+        mock.push(AccessListWithGasUsed {
+            access_list: access_list.clone(),
+            gas_used: U256::from(180_000),
+        })
+        .unwrap();
+        assert_eq!(
+            attached(policy.build_action(&opp).await.unwrap()),
+            Some(access_list.clone())
+        );
+
+        // The pair's list is reused without another call
+        mock.push(U256::from(200_000)).unwrap();
+        let action = policy.build_action(&opp).await.unwrap();
+        assert_eq!(gas_limit(action.clone()), U256::from(240_000));
+        assert_eq!(attached(action), Some(access_list));
+
+        // A node that cannot create one leaves the trade without an access list
+        let failing = DefaultRouterPolicy::new(provider, router, 80)
+            .with_access_lists()
+            .with_gas_estimation(GasEstimation::Fixed(200_000));
+        let attached_list = attached(failing.build_action(&opp).await.unwrap());
+        assert!(attached_list.is_none_or(|list| list.0.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_direction_selects_router_entrypoint() {
+        let (provider, _mock) = Provider::mocked();
+        let policy = DefaultRouterPolicy::new(Arc::new(provider), Address::repeat_byte(0x33), 80)
+            .with_gas_estimation(GasEstimation::Fixed(200_000));
+        let calldata = |opp: ArbOpportunity| {
+            let policy = &policy;
+            async move {
+                let Some(Action::SubmitTx(submit)) = policy.build_action(&opp).await.unwrap()
+                else {
+                    panic!("expected a SubmitTx action");
+                };
+                submit.tx.data().unwrap().to_vec()
+            }
+        };
+        let word = |data: &[u8], index: usize| U256::from_big_endian(&data[4 + index * 32..][..32]);
+
+        let buy = calldata(opportunity()).await;
+        let selector = ethers::utils::id(
+            "arbBuyFYThenSellFY(address,address,uint128,uint128,uint128,address)",
+        );
+        assert_eq!(buy[..4], selector);
+        assert_eq!(word(&buy, 2), U256::from(opportunity().fy_amount));
+        assert_eq!(word(&buy, 3), U256::from(opportunity().max_base_in));
+
+        // Selling base passes the exact base in, then the minimum FY out
+        let sell_base = calldata(ArbOpportunity {
+            direction: ArbDirection::SellBaseThenSellFy,
+            ..opportunity()
+        })
+        .await;
+        let selector = ethers::utils::id(
+            "arbSellBaseThenSellFY(address,address,uint128,uint128,uint128,address)",
+        );
+        assert_eq!(sell_base[..4], selector);
+        assert_eq!(word(&sell_base, 2), U256::from(opportunity().max_base_in));
+        assert_eq!(word(&sell_base, 3), U256::from(opportunity().fy_amount));
+        assert_eq!(word(&sell_base, 4), U256::from(opportunity().min_base_out));
+    }
+
+    #[tokio::test]
+    async fn test_bid_percentage_caps_fees() {
+        let (provider, mock) = Provider::mocked();
+        let policy = DefaultRouterPolicy::new(Arc::new(provider), Address::repeat_byte(0x33), 80)
+            .with_native_price(0.5, 6);
+        // 0.01 of a 6-decimal base is 0.02 native at 0.5 base per native, 80% of
+        // which may go to fees
+        let opp = ArbOpportunity {
+            expected_profit: 10_000,
+            ..opportunity()
+        };
+        let budget = U256::from(16_000_000_000_000_000u64);
+        let gwei = |gwei: u64| U256::from(gwei * 1_000_000_000);
+
+        // Responses are served last-in first-out: the gas estimate, then the
+        // network gas price of 25 gwei, well under the budget
+        mock.push(gwei(25)).unwrap();
+        mock.push(U256::from(200_000)).unwrap();
+        let Some(Action::SubmitTx(submit)) = policy.build_action(&opp).await.unwrap() else {
+            panic!("expected a SubmitTx action");
+        };
+        let gas_limit = *submit.tx.gas().unwrap();
+        assert_eq!(gas_limit, U256::from(240_000));
+        // The cap is the converted budget over the gas limit, about 66.7 gwei
+        let cap = policy.gas_budget_price(&opp, gas_limit);
+        assert_eq!(cap, budget / gas_limit);
+        // The bid is the network price plus the 1 gwei tip, not the whole budget
+        assert_eq!(submit.tx.gas_price().unwrap(), gwei(26));
+
+        // Near the cap the tip is cut to stay within the budget
+        mock.push(gwei(66)).unwrap();
+        mock.push(U256::from(200_000)).unwrap();
+        let Some(Action::SubmitTx(submit)) = policy.build_action(&opp).await.unwrap() else {
+            panic!("expected a SubmitTx action");
+        };
+        assert_eq!(submit.tx.gas_price().unwrap(), cap);
+        assert!(cap * gas_limit <= budget);
+
+        // At 70 gwei the fees would exceed the budget: the trade is skipped
+        mock.push(gwei(70)).unwrap();
+        mock.push(U256::from(200_000)).unwrap();
+        assert!(policy.build_action(&opp).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_gas_estimation_modes() {
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        let router = Address::repeat_byte(0x33);
+        let opp = opportunity();
+
+        // On-chain: the node's estimate plus the 20% buffer
+        let on_chain = DefaultRouterPolicy::new(provider.clone(), router, 80);
+        mock.push(U256::from(200_000)).unwrap();
+        assert_eq!(
+            gas_limit(on_chain.build_action(&opp).await.unwrap()),
+            U256::from(240_000)
+        );
+
+        // Fixed: the configured limit plus the buffer, without an estimate call
+        let fixed = DefaultRouterPolicy::new(provider.clone(), router, 80)
+            .with_gas_estimation(GasEstimation::Fixed(300_000));
+        assert_eq!(
+            gas_limit(fixed.build_action(&opp).await.unwrap()),
+            U256::from(360_000)
+        );
+
+        // Historical: estimated on-chain until 20 trades have confirmed...
+        let history = Arc::new(GasUsageHistory::new());
+        let historical = DefaultRouterPolicy::new(provider, router, 80)
+            .with_gas_estimation(GasEstimation::HistoricalP95)
+            .with_gas_history(history.clone());
+        mock.push(U256::from(150_000)).unwrap();
+        assert_eq!(
+            gas_limit(historical.build_action(&opp).await.unwrap()),
+            U256::from(180_000)
+        );
+
+        // ...then the p95 of their gas used plus the buffer; reverts are ignored
+        let receipt = |status: u64, gas_used: u64| TransactionReceipt {
+            status: Some(U64::from(status)),
+            gas_used: Some(U256::from(gas_used)),
+            ..Default::default()
+        };
+        let submitted = SubmittedTx {
+            tx_hash: TxHash::zero(),
+            gas_estimate: U256::from(150_000),
+            expected_profit: None,
+            calldata: None,
+        };
+        history.on_receipt(&submitted, &receipt(0, 30_000));
+        for i in 0..20 {
+            history.on_receipt(&submitted, &receipt(1, 100_000 + i * 1_000));
+        }
+        assert_eq!(history.len(), 20);
+        assert_eq!(
+            gas_limit(historical.build_action(&opp).await.unwrap()),
+            U256::from(118_000) * 12 / 10
+        );
+    }
+}
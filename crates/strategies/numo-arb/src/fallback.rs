@@ -0,0 +1,235 @@
+/// Polling fallback for silent block subscriptions
+/// Some endpoints accept a WS connection but stop delivering `newHeads`; after a
+/// stretch of silence the collector degrades to polling the node for the latest block
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::prelude::*;
+use futures::StreamExt;
+use tracing::warn;
+
+use artemis_core::types::{Collector, CollectorStream};
+
+use crate::types::{Event, NewBlockEvent};
+
+/// Default delay between polls once degraded to polling
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where block events currently come from
+enum BlockSource<'a> {
+    Subscription(CollectorStream<'a, Event>),
+    Polling,
+}
+
+struct FallbackState<'a, M> {
+    source: BlockSource<'a>,
+    provider: Arc<M>,
+    last_block: Option<u64>,
+}
+
+/// A collector forwarding a block subscription that falls back to polling
+/// `provider` once the subscription stays silent for `silence_timeout` or ends
+///
+/// Polling only emits blocks newer than the last one seen, so a head that has not
+/// moved is not re-evaluated. The fallback is permanent for the life of the stream.
+pub struct PollingFallbackCollector<M> {
+    subscription: Box<dyn Collector<Event>>,
+    provider: Arc<M>,
+    silence_timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl<M> PollingFallbackCollector<M> {
+    pub fn new(
+        subscription: Box<dyn Collector<Event>>,
+        provider: Arc<M>,
+        silence_timeout: Duration,
+    ) -> Self {
+        Self {
+            subscription,
+            provider,
+            silence_timeout,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Set the delay between polls once degraded to polling
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+#[async_trait]
+impl<M> Collector<Event> for PollingFallbackCollector<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Event>> {
+        let state = FallbackState {
+            source: BlockSource::Subscription(self.subscription.get_event_stream().await?),
+            provider: self.provider.clone(),
+            last_block: None,
+        };
+        let silence_timeout = self.silence_timeout;
+        let poll_interval = self.poll_interval;
+
+        let stream = futures::stream::unfold(state, move |mut state| async move {
+            loop {
+                match &mut state.source {
+                    BlockSource::Subscription(stream) => {
+                        let next = tokio::time::timeout(silence_timeout, stream.next()).await;
+                        match next {
+                            Ok(Some(event)) => {
+                                if let Event::NewBlock(block) = &event {
+                                    state.last_block =
+                                        state.last_block.max(Some(block.block_number));
+                                }
+                                return Some((event, state));
+                            }
+                            Ok(None) => {
+                                warn!("Block subscription ended, falling back to polling");
+                                state.source = BlockSource::Polling;
+                            }
+                            Err(_) => {
+                                warn!(
+                                    silence_secs = silence_timeout.as_secs_f64(),
+                                    last_block = state.last_block,
+                                    "Block subscription silent, falling back to polling"
+                                );
+                                state.source = BlockSource::Polling;
+                            }
+                        }
+                    }
+                    BlockSource::Polling => {
+                        tokio::time::sleep(poll_interval).await;
+                        match state.provider.get_block(BlockNumber::Latest).await {
+                            Ok(Some(block)) => {
+                                let Some(block) = NewBlockEvent::from_block(&block) else {
+                                    continue;
+                                };
+                                if state
+                                    .last_block
+                                    .is_none_or(|last| block.block_number > last)
+                                {
+                                    state.last_block = Some(block.block_number);
+                                    return Some((Event::NewBlock(block), state));
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(e) => warn!(error = ?e, "Failed to poll for new blocks"),
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A subscription that delivers `events` and then goes silent without ending
+    struct StubSubscription {
+        events: Vec<Event>,
+    }
+
+    #[async_trait]
+    impl Collector<Event> for StubSubscription {
+        async fn get_event_stream(&self) -> Result<CollectorStream<'_, Event>> {
+            let events = futures::stream::iter(self.events.clone());
+            Ok(Box::pin(events.chain(futures::stream::pending())))
+        }
+    }
+
+    fn block_event(block_number: u64) -> Event {
+        Event::NewBlock(NewBlockEvent {
+            block_number,
+            timestamp: 1_700_000_000 + block_number,
+            base_fee: None,
+        })
+    }
+
+    fn block_numbers(events: &[Event]) -> Vec<u64> {
+        events
+            .iter()
+            .map(|event| match event {
+                Event::NewBlock(block) => block.block_number,
+                _ => panic!("expected a block event"),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_silent_subscription_falls_back_to_polling() {
+        let (provider, mock) = Provider::mocked();
+        // Responses are served last-in first-out: 11 is polled first, then 11 again, then 12
+        for number in [12u64, 11, 11] {
+            let block = Block::<TxHash> {
+                number: Some(number.into()),
+                timestamp: U256::from(1_700_000_000 + number),
+                ..Default::default()
+            };
+            mock.push(block).unwrap();
+        }
+
+        let subscription = Box::new(StubSubscription {
+            events: vec![block_event(10)],
+        });
+        let collector = PollingFallbackCollector::new(
+            subscription,
+            Arc::new(provider),
+            Duration::from_millis(20),
+        )
+        .with_poll_interval(Duration::ZERO);
+        let events: Vec<Event> = collector
+            .get_event_stream()
+            .await
+            .unwrap()
+            .take(3)
+            .collect()
+            .await;
+
+        // The subscribed block, then polled blocks with the repeated head skipped
+        assert_eq!(block_numbers(&events), vec![10, 11, 12]);
+    }
+
+    #[tokio::test]
+    async fn test_polling_skips_blocks_already_delivered() {
+        let (provider, mock) = Provider::mocked();
+        // Block 8 is polled first, then 9
+        for number in [9u64, 8] {
+            let block = Block::<TxHash> {
+                number: Some(number.into()),
+                ..Default::default()
+            };
+            mock.push(block).unwrap();
+        }
+
+        let subscription = Box::new(StubSubscription {
+            events: vec![block_event(7), block_event(8)],
+        });
+        let collector = PollingFallbackCollector::new(
+            subscription,
+            Arc::new(provider),
+            Duration::from_millis(20),
+        )
+        .with_poll_interval(Duration::ZERO);
+        let events: Vec<Event> = collector
+            .get_event_stream()
+            .await
+            .unwrap()
+            .take(3)
+            .collect()
+            .await;
+
+        // Block 8 arrived over the subscription, so polling resumes after it
+        assert_eq!(block_numbers(&events), vec![7, 8, 9]);
+    }
+}
@@ -0,0 +1,156 @@
+/// Human-readable token amounts and prices for the Numo strategy
+/// Shared by logs, reports and the PnL ledger so raw integer amounts are always
+/// shown with the same decimals and rounding
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Fractional digits shown for amounts and prices
+pub const DISPLAY_DECIMALS: u8 = 6;
+
+/// Decimals of the base and FY tokens of the monitored pools
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenDecimals {
+    pub base: u8,
+    pub fy: u8,
+}
+
+impl Default for TokenDecimals {
+    fn default() -> Self {
+        Self { base: 18, fy: 18 }
+    }
+}
+
+impl TokenDecimals {
+    /// A base token amount
+    pub fn base_amount(&self, raw: u128) -> String {
+        format_amount(raw, self.base)
+    }
+
+    /// An FY token amount
+    pub fn fy_amount(&self, raw: u128) -> String {
+        format_amount(raw, self.fy)
+    }
+
+    /// A signed base token amount, e.g., a net profit
+    pub fn signed_base_amount(&self, raw: i128) -> String {
+        format_signed_amount(raw, self.base)
+    }
+
+    /// A 1e18-scaled price of raw base per raw FY, in whole base per whole FY
+    pub fn price(&self, price: U256) -> String {
+        format_price_1e18(price, self.base, self.fy)
+    }
+}
+
+/// Format `raw` token units of a token with `decimals` decimals, rounded half-up
+/// to at most [DISPLAY_DECIMALS] fractional digits
+pub fn format_amount(raw: u128, decimals: u8) -> String {
+    format_scaled(U256::from(raw), decimals)
+}
+
+/// Format a signed amount, e.g., a net profit, like [format_amount]
+pub fn format_signed_amount(raw: i128, decimals: u8) -> String {
+    let formatted = format_amount(raw.unsigned_abs(), decimals);
+    if raw < 0 && formatted.bytes().any(|digit| matches!(digit, b'1'..=b'9')) {
+        format!("-{formatted}")
+    } else {
+        formatted
+    }
+}
+
+/// Format a 1e18-scaled price of raw base per raw FY as whole base per whole FY,
+/// rounded half-up to [DISPLAY_DECIMALS] fractional digits
+pub fn format_price_1e18(price: U256, base_decimals: u8, fy_decimals: u8) -> String {
+    // One whole FY is 10^fy raw FY, one whole base 10^base raw base
+    let scaled = price.saturating_mul(U256::exp10(fy_decimals as usize))
+        / U256::exp10(base_decimals as usize);
+    format_scaled(scaled, 18)
+}
+
+/// Format `raw / 10^decimals` with at most [DISPLAY_DECIMALS] fractional digits
+fn format_scaled(raw: U256, decimals: u8) -> String {
+    let shown = DISPLAY_DECIMALS.min(decimals);
+    let dropped = U256::exp10((decimals - shown) as usize);
+    let rounded = raw.saturating_add(dropped / 2) / dropped;
+    if shown == 0 {
+        return rounded.to_string();
+    }
+    let unit = U256::exp10(shown as usize);
+    format!(
+        "{}.{:0>width$}",
+        rounded / unit,
+        (rounded % unit).to_string(),
+        width = shown as usize
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_six_decimal_amounts() {
+        assert_eq!(format_amount(1_234_567, 6), "1.234567");
+        assert_eq!(format_amount(5, 6), "0.000005");
+        assert_eq!(format_amount(0, 6), "0.000000");
+        assert_eq!(format_amount(1_000_000_000_000, 6), "1000000.000000");
+    }
+
+    #[test]
+    fn test_format_eighteen_decimal_amounts_rounds_half_up() {
+        let one = 10u128.pow(18);
+        assert_eq!(format_amount(one, 18), "1.000000");
+        assert_eq!(format_amount(1_234_567_499_999_999_999, 18), "1.234567");
+        assert_eq!(format_amount(1_234_567_500_000_000_000, 18), "1.234568");
+        // Rounding carries into the integer part
+        assert_eq!(format_amount(one - 1, 18), "1.000000");
+        assert_eq!(format_amount(400_000_000_000, 18), "0.000000");
+        assert_eq!(format_amount(u128::MAX, 18), "340282366920938463463.374607");
+    }
+
+    #[test]
+    fn test_format_signed_amounts() {
+        assert_eq!(format_signed_amount(-2_500_000, 6), "-2.500000");
+        assert_eq!(format_signed_amount(2_500_000, 6), "2.500000");
+        // A loss that rounds to zero is shown without a sign
+        assert_eq!(format_signed_amount(-400_000_000_000, 18), "0.000000");
+        assert_eq!(
+            format_signed_amount(i128::MIN, 0),
+            "-170141183460469231731687303715884105728"
+        );
+    }
+
+    #[test]
+    fn test_format_tokens_with_few_decimals() {
+        assert_eq!(format_amount(12_345, 2), "123.45");
+        assert_eq!(format_amount(12_345, 0), "12345");
+    }
+
+    #[test]
+    fn test_format_price_1e18() {
+        let one = U256::exp10(18);
+        // Same decimals on both sides: the price is shown as is
+        assert_eq!(format_price_1e18(one * 97 / 100, 18, 18), "0.970000");
+        assert_eq!(format_price_1e18(one * 97 / 100, 6, 6), "0.970000");
+        // 0.97 whole base per whole FY with a 6-decimal base and an 18-decimal FY is
+        // 0.97e-12 raw base per raw FY
+        let raw = one * 97 / 100 / U256::exp10(12);
+        assert_eq!(format_price_1e18(raw, 6, 18), "0.970000");
+        // Rounded half-up
+        assert_eq!(
+            format_price_1e18(U256::from(976_543_500_000_000_000u64), 18, 18),
+            "0.976544"
+        );
+    }
+
+    #[test]
+    fn test_token_decimals() {
+        let decimals = TokenDecimals { base: 6, fy: 6 };
+        assert_eq!(decimals.base_amount(2_500_000), "2.500000");
+        assert_eq!(decimals.fy_amount(1), "0.000001");
+        assert_eq!(
+            TokenDecimals::default().base_amount(10u128.pow(18)),
+            "1.000000"
+        );
+    }
+}
@@ -0,0 +1,452 @@
+/// Gas limit buffering for the Numo strategy
+/// Compares the gas used by confirmed trades to their pre-trade estimate so the
+/// buffer can be tuned, optionally adjusting it automatically
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ethers::prelude::*;
+use tracing::{info, warn};
+
+use crate::confirmation::{ReceiptObserver, SubmittedTx};
+use crate::types::ArbDirection;
+
+/// Number of recent gas ratios kept for the p95
+const GAS_RATIO_WINDOW: usize = 100;
+
+/// Samples required before the buffer is adjusted automatically
+const MIN_SAMPLES_FOR_ADJUST: usize = 20;
+
+/// Headroom kept above the observed p95 ratio
+const GAS_BUFFER_MARGIN_BPS: u32 = 500;
+
+/// Maximum number of submitted transactions tracked for revert invalidation
+const MAX_TRACKED_CALLDATA: usize = 1_024;
+
+/// Raw estimates below this are implausible for a two-pool router trade,
+/// e.g., the node simulated a no-op
+pub const SUSPICIOUS_GAS_ESTIMATE: u64 = 50_000;
+
+/// Default floor for the buffered gas limit
+pub const DEFAULT_MIN_GAS_LIMIT: u64 = 100_000;
+
+/// Default priority tip bid over the network gas price, 1 gwei
+pub const DEFAULT_GAS_TIP_WEI: u64 = 1_000_000_000;
+
+/// Apply a buffer in basis points to a gas estimate
+pub fn apply_gas_buffer(estimate: U256, buffer_bps: u32) -> U256 {
+    estimate * U256::from(10_000 + buffer_bps as u64) / U256::from(10_000)
+}
+
+/// Clamp a buffered gas limit to at least `min_gas_limit`
+pub fn clamp_gas_limit(gas_limit: U256, min_gas_limit: u64) -> U256 {
+    gas_limit.max(U256::from(min_gas_limit))
+}
+
+/// Gas price spending at most `bid_percentage`% of `total_profit` (native wei) on
+/// `gas_limit` gas, rounded down so the fee never exceeds the budget
+pub fn bid_gas_price(total_profit: U256, gas_limit: U256, bid_percentage: u64) -> U256 {
+    if gas_limit.is_zero() {
+        return U256::zero();
+    }
+    total_profit.saturating_mul(U256::from(bid_percentage)) / U256::from(100) / gas_limit
+}
+
+/// Gas priced at `base_fee`, converted from native wei into base token wei at
+/// `native_price_in_base` base tokens per native token
+pub fn gas_cost_in_base(
+    gas: U256,
+    base_fee: U256,
+    native_price_in_base: f64,
+    base_decimals: u8,
+) -> u128 {
+    let price_wad = U256::from((native_price_in_base * 1e18) as u128);
+    let cost = gas
+        .saturating_mul(base_fee)
+        .saturating_mul(price_wad)
+        .saturating_mul(U256::exp10(base_decimals as usize))
+        / U256::exp10(36);
+    cost.min(U256::from(u128::MAX)).as_u128()
+}
+
+/// Profit in base token wei converted into native wei at `native_price_in_base`
+/// base tokens per native token, the inverse of [gas_cost_in_base]
+pub fn profit_in_native(profit: U256, native_price_in_base: f64, base_decimals: u8) -> U256 {
+    let price_wad = U256::from((native_price_in_base * 1e18) as u128);
+    if price_wad.is_zero() {
+        return U256::zero();
+    }
+    profit.saturating_mul(U256::exp10(36)) / U256::exp10(base_decimals as usize) / price_wad
+}
+
+/// Whether a raw gas estimate is implausibly low
+pub fn is_suspicious_estimate(estimate: U256) -> bool {
+    estimate < U256::from(SUSPICIOUS_GAS_ESTIMATE)
+}
+
+/// Gas used as a fraction of the estimate, in basis points (10_000 = exact)
+pub fn gas_used_ratio_bps(estimate: U256, gas_used: U256) -> u32 {
+    if estimate.is_zero() {
+        return 0;
+    }
+    (gas_used * U256::from(10_000) / estimate)
+        .min(U256::from(u32::MAX))
+        .as_u32()
+}
+
+/// Move the buffer halfway toward the p95 overshoot plus a safety margin
+pub fn adjusted_buffer_bps(current_bps: u32, p95_ratio_bps: u32) -> u32 {
+    let target = p95_ratio_bps.saturating_sub(10_000) + GAS_BUFFER_MARGIN_BPS;
+    ((current_bps as u64 + target as u64) / 2) as u32
+}
+
+/// 95th percentile of a set of samples (nearest rank)
+fn p95<T: Copy + Ord>(samples: &VecDeque<T>) -> Option<T> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<T> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    let rank = (sorted.len() * 95).div_ceil(100);
+    Some(sorted[rank.saturating_sub(1)])
+}
+
+/// Tracks gas used vs. estimated for confirmed trades
+///
+/// The buffer is shared with the execution policy, so automatic adjustments apply
+/// to the next trade.
+pub struct GasBufferTuner {
+    /// Current gas buffer in basis points
+    buffer_bps: Arc<AtomicU32>,
+
+    /// Whether to adjust the buffer toward the observed p95 ratio
+    auto_adjust: bool,
+
+    /// Recent gas used / estimate ratios in basis points
+    samples: Mutex<VecDeque<u32>>,
+}
+
+impl GasBufferTuner {
+    pub fn new(buffer_bps: u32, auto_adjust: bool) -> Self {
+        Self {
+            buffer_bps: Arc::new(AtomicU32::new(buffer_bps)),
+            auto_adjust,
+            samples: Mutex::new(VecDeque::with_capacity(GAS_RATIO_WINDOW)),
+        }
+    }
+
+    /// Shared handle to the current buffer, for the execution policy
+    pub fn buffer(&self) -> Arc<AtomicU32> {
+        self.buffer_bps.clone()
+    }
+
+    /// Current gas buffer in basis points
+    pub fn buffer_bps(&self) -> u32 {
+        self.buffer_bps.load(Ordering::Relaxed)
+    }
+
+    /// 95th percentile of recent ratios
+    pub fn p95_ratio_bps(&self) -> Option<u32> {
+        p95(&self.samples.lock().unwrap())
+    }
+
+    /// Record a confirmed trade, returning its gas used / estimate ratio
+    pub fn record(&self, estimate: U256, gas_used: U256) -> u32 {
+        let ratio = gas_used_ratio_bps(estimate, gas_used);
+
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == GAS_RATIO_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(ratio);
+
+        let p95_ratio = p95(&samples).unwrap_or(ratio);
+        let current = self.buffer_bps();
+        info!(
+            gas_estimate = %estimate,
+            gas_used = %gas_used,
+            ratio_bps = ratio,
+            p95_ratio_bps = p95_ratio,
+            gas_buffer_bps = current,
+            "Gas used vs. estimate"
+        );
+
+        if self.auto_adjust && samples.len() >= MIN_SAMPLES_FOR_ADJUST {
+            let adjusted = adjusted_buffer_bps(current, p95_ratio);
+            if adjusted != current {
+                info!(from = current, to = adjusted, "Adjusted gas buffer");
+                self.buffer_bps.store(adjusted, Ordering::Relaxed);
+            }
+        }
+        ratio
+    }
+}
+
+impl ReceiptObserver for GasBufferTuner {
+    fn on_receipt(&self, submitted: &SubmittedTx, receipt: &TransactionReceipt) {
+        if let Some(gas_used) = receipt.gas_used {
+            self.record(submitted.gas_estimate, gas_used);
+        }
+    }
+}
+
+/// Gas used by recent confirmed trades, for gas limits taken from history
+#[derive(Debug, Default)]
+pub struct GasUsageHistory {
+    samples: Mutex<VecDeque<u64>>,
+}
+
+impl GasUsageHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the gas used by a confirmed trade
+    pub fn record(&self, gas_used: u64) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == GAS_RATIO_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(gas_used);
+    }
+
+    /// Number of trades recorded
+    pub fn len(&self) -> usize {
+        self.samples.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 95th percentile of the gas used, once enough trades have been recorded
+    pub fn p95(&self) -> Option<U256> {
+        let samples = self.samples.lock().unwrap();
+        if samples.len() < MIN_SAMPLES_FOR_ADJUST {
+            return None;
+        }
+        p95(&samples).map(U256::from)
+    }
+}
+
+impl ReceiptObserver for GasUsageHistory {
+    fn on_receipt(&self, _submitted: &SubmittedTx, receipt: &TransactionReceipt) {
+        // A revert stops early, so its gas used says little about a full trade
+        if receipt.status != Some(U64::one()) {
+            return;
+        }
+        if let Some(gas_used) = receipt.gas_used {
+            self.record(gas_used.min(U256::from(u64::MAX)).as_u64());
+        }
+    }
+}
+
+/// A `(cheap, rich)` pool pair traded through one router entrypoint
+type GasCacheKey = (Address, Address, ArbDirection);
+
+/// Last successful gas estimate per `(cheap, rich)` pool pair and direction
+///
+/// The router path's gas is fairly stable per pair and entrypoint, so estimates
+/// are reused until they expire, an estimate fails, or a transaction built from
+/// them reverts.
+pub struct GasEstimateCache {
+    /// Maximum age of a cached estimate
+    ttl: Duration,
+
+    /// Cached estimates and when they were taken
+    entries: Mutex<HashMap<GasCacheKey, (U256, Instant)>>,
+
+    /// Calldata of submitted transactions, mapped to the pair and direction they
+    /// trade
+    calldata: Mutex<HashMap<Bytes, GasCacheKey>>,
+}
+
+impl GasEstimateCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            calldata: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached estimate for a pair in a direction, if still fresh
+    pub fn get(
+        &self,
+        cheap_pool: Address,
+        rich_pool: Address,
+        direction: ArbDirection,
+    ) -> Option<U256> {
+        let key = (cheap_pool, rich_pool, direction);
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(&key) {
+            Some((estimate, taken)) if taken.elapsed() < self.ttl => Some(*estimate),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a successful estimate for a pair in a direction
+    pub fn insert(
+        &self,
+        cheap_pool: Address,
+        rich_pool: Address,
+        direction: ArbDirection,
+        estimate: U256,
+    ) {
+        self.entries.lock().unwrap().insert(
+            (cheap_pool, rich_pool, direction),
+            (estimate, Instant::now()),
+        );
+    }
+
+    /// Drop the estimate for a pair in a direction
+    pub fn invalidate(&self, cheap_pool: Address, rich_pool: Address, direction: ArbDirection) {
+        self.entries
+            .lock()
+            .unwrap()
+            .remove(&(cheap_pool, rich_pool, direction));
+    }
+
+    /// Remember which pair and direction a transaction trades, so a revert
+    /// invalidates it
+    pub fn track(
+        &self,
+        calldata: Bytes,
+        cheap_pool: Address,
+        rich_pool: Address,
+        direction: ArbDirection,
+    ) {
+        let mut tracked = self.calldata.lock().unwrap();
+        if tracked.len() >= MAX_TRACKED_CALLDATA {
+            tracked.clear();
+        }
+        tracked.insert(calldata, (cheap_pool, rich_pool, direction));
+    }
+}
+
+impl ReceiptObserver for GasEstimateCache {
+    fn on_receipt(&self, submitted: &SubmittedTx, receipt: &TransactionReceipt) {
+        let Some(calldata) = &submitted.calldata else {
+            return;
+        };
+        let Some((cheap_pool, rich_pool, direction)) =
+            self.calldata.lock().unwrap().remove(calldata)
+        else {
+            return;
+        };
+        if receipt.status == Some(U64::zero()) {
+            warn!(
+                tx_hash = ?submitted.tx_hash,
+                cheap_pool = ?cheap_pool,
+                rich_pool = ?rich_pool,
+                ?direction,
+                "Transaction reverted, dropping cached gas estimate"
+            );
+            self.invalidate(cheap_pool, rich_pool, direction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_used_ratio() {
+        assert_eq!(
+            gas_used_ratio_bps(U256::from(100_000), U256::from(90_000)),
+            9_000
+        );
+        assert_eq!(
+            gas_used_ratio_bps(U256::from(100_000), U256::from(112_500)),
+            11_250
+        );
+        assert_eq!(gas_used_ratio_bps(U256::zero(), U256::from(1)), 0);
+        assert_eq!(
+            apply_gas_buffer(U256::from(100_000), 2_000),
+            U256::from(120_000)
+        );
+    }
+
+    #[test]
+    fn test_clamp_gas_limit() {
+        // Below the floor: raised to it
+        assert_eq!(
+            clamp_gas_limit(U256::from(30_000), 100_000),
+            U256::from(100_000)
+        );
+        // At or above the floor: unchanged
+        assert_eq!(
+            clamp_gas_limit(U256::from(100_000), 100_000),
+            U256::from(100_000)
+        );
+        assert_eq!(
+            clamp_gas_limit(U256::from(240_000), 100_000),
+            U256::from(240_000)
+        );
+
+        assert!(is_suspicious_estimate(U256::from(21_000)));
+        assert!(!is_suspicious_estimate(U256::from(200_000)));
+    }
+
+    #[test]
+    fn test_gas_cost_in_base() {
+        let gas = U256::from(500_000);
+        let base_fee = U256::from(5_000_000_000u64);
+        // 0.0025 native at 0.60 base per native, with an 18-decimal base
+        assert_eq!(
+            gas_cost_in_base(gas, base_fee, 0.6, 18),
+            1_500_000_000_000_000
+        );
+        // Same cost with a 6-decimal base
+        assert_eq!(gas_cost_in_base(gas, base_fee, 0.6, 6), 1_500);
+        // Parity keeps the wei amount
+        assert_eq!(gas_cost_in_base(gas, U256::one(), 1.0, 18), 500_000);
+    }
+
+    #[test]
+    fn test_profit_in_native() {
+        // 0.0015 of a 6-decimal base at 0.60 base per native is 0.0025 native
+        assert_eq!(
+            profit_in_native(U256::from(1_500), 0.6, 6),
+            U256::from(2_500_000_000_000_000u64)
+        );
+        // Round trip through the gas cost at one wei per gas
+        let native = profit_in_native(U256::from(10u64.pow(18)), 2.0, 18);
+        assert_eq!(
+            gas_cost_in_base(native, U256::one(), 2.0, 18),
+            10u128.pow(18)
+        );
+    }
+
+    #[test]
+    fn test_adjusted_buffer_moves_toward_p95() {
+        // Trades use less than estimated: shrink toward the margin
+        assert_eq!(adjusted_buffer_bps(2_000, 9_000), 1_250);
+        // Trades overshoot the estimate by 10%: grow toward 15%
+        assert_eq!(adjusted_buffer_bps(0, 11_000), 750);
+        // Already at the target
+        assert_eq!(adjusted_buffer_bps(1_500, 11_000), 1_500);
+    }
+
+    #[test]
+    fn test_tuner_adjusts_only_when_enabled() {
+        let fixed = GasBufferTuner::new(2_000, false);
+        let auto = GasBufferTuner::new(2_000, true);
+
+        for _ in 0..MIN_SAMPLES_FOR_ADJUST {
+            fixed.record(U256::from(100_000), U256::from(90_000));
+            auto.record(U256::from(100_000), U256::from(90_000));
+        }
+
+        assert_eq!(fixed.p95_ratio_bps(), Some(9_000));
+        assert_eq!(fixed.buffer_bps(), 2_000);
+        assert_eq!(auto.buffer_bps(), 1_250);
+        assert_eq!(auto.buffer().load(Ordering::Relaxed), 1_250);
+    }
+}
@@ -0,0 +1,147 @@
+/// Spread heatmap export for the Numo strategy
+/// Periodically writes the pairwise divergence between every pair of pools as a
+/// JSON matrix, so a dashboard can show who is cheap or rich against whom
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Pairwise divergence between the pools priced in one block
+///
+/// `divergence_bps[i][j]` is how far pool `j` prices FY above pool `i`, in bps of
+/// the pair's mid price: positive when `j` is rich against `i`. Measuring against
+/// the mid makes the matrix antisymmetric, so `[j][i] == -[i][j]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpreadHeatmap {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub pools: Vec<Address>,
+    pub divergence_bps: Vec<Vec<i64>>,
+}
+
+impl SpreadHeatmap {
+    /// Build the matrix from each pool's marginal price (1e18 scaled)
+    pub fn from_prices(block_number: u64, timestamp: u64, prices: &[(Address, U256)]) -> Self {
+        let divergence_bps = prices
+            .iter()
+            .map(|(_, from)| {
+                prices
+                    .iter()
+                    .map(|(_, to)| signed_divergence_bps(*from, *to))
+                    .collect()
+            })
+            .collect();
+        Self {
+            block_number,
+            timestamp,
+            pools: prices.iter().map(|(pool, _)| *pool).collect(),
+            divergence_bps,
+        }
+    }
+}
+
+/// Divergence of `to` above `from` in bps of their mid price, rounded toward zero
+fn signed_divergence_bps(from: U256, to: U256) -> i64 {
+    let (diff, sign) = if to >= from {
+        (to - from, 1)
+    } else {
+        (from - to, -1)
+    };
+    let mid = (from + to) / U256::from(2);
+    if mid.is_zero() {
+        return 0;
+    }
+    let bps = diff * U256::from(10_000u32) / mid;
+    sign * bps.min(U256::from(i64::MAX)).as_u64() as i64
+}
+
+/// Writes the heatmap of every `every_blocks`-th block to a JSON file
+#[derive(Debug, Clone)]
+pub struct HeatmapExporter {
+    path: PathBuf,
+    every_blocks: u64,
+}
+
+impl HeatmapExporter {
+    pub fn new(path: impl Into<PathBuf>, every_blocks: u64) -> Self {
+        Self {
+            path: path.into(),
+            every_blocks: every_blocks.max(1),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the heatmap of `block_number` is due for export
+    pub fn is_due(&self, block_number: u64) -> bool {
+        block_number.is_multiple_of(self.every_blocks)
+    }
+
+    /// Replace the exported heatmap; readers never see a partially written file
+    pub fn export(&self, heatmap: &SpreadHeatmap) -> Result<()> {
+        let partial = self.path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_string(heatmap)?)
+            .with_context(|| format!("Failed to write heatmap {}", partial.display()))?;
+        std::fs::rename(&partial, &self.path)
+            .with_context(|| format!("Failed to write heatmap {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heatmap_is_antisymmetric_and_signed() {
+        let one = 10u128.pow(18);
+        let pools = [
+            Address::repeat_byte(0x11),
+            Address::repeat_byte(0x22),
+            Address::repeat_byte(0x33),
+        ];
+        // 0x22 is rich and 0x33 cheap against 0x11
+        let prices = [
+            (pools[0], U256::from(one * 98 / 100)),
+            (pools[1], U256::from(one * 99 / 100)),
+            (pools[2], U256::from(one * 97 / 100)),
+        ];
+        let heatmap = SpreadHeatmap::from_prices(7, 1_700_000_000, &prices);
+
+        assert_eq!(heatmap.pools, pools.to_vec());
+        let m = &heatmap.divergence_bps;
+        for (i, row) in m.iter().enumerate() {
+            assert_eq!(row[i], 0);
+            for (j, divergence) in row.iter().enumerate() {
+                assert_eq!(*divergence, -m[j][i]);
+            }
+        }
+        assert!(m[0][1] > 0);
+        assert!(m[0][2] < 0);
+        // 1 cent between 0.98 and 0.99 is about 101 bps of their mid
+        assert_eq!(m[0][1], 101);
+        assert_eq!(m[2][1], 204);
+    }
+
+    #[test]
+    fn test_export_due_blocks_as_json() {
+        let path = std::env::temp_dir().join(format!("numo-heatmap-{}.json", std::process::id()));
+        let exporter = HeatmapExporter::new(&path, 5);
+        assert!(exporter.is_due(10));
+        assert!(!exporter.is_due(11));
+
+        let heatmap = SpreadHeatmap::from_prices(
+            10,
+            1_700_000_000,
+            &[(Address::repeat_byte(0x11), U256::exp10(18))],
+        );
+        exporter.export(&heatmap).unwrap();
+        let read: SpreadHeatmap =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(read, heatmap);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+/// Emergency kill switch for the Numo strategy
+/// Pauses all trading while a sentinel file exists or an environment toggle is set
+use std::path::PathBuf;
+
+/// Environment variable that pauses trading when set to `1` or `true`
+pub const KILL_SWITCH_ENV: &str = "NUMO_KILL_SWITCH";
+
+/// Kill switch checked at the start of every block
+#[derive(Debug, Clone)]
+pub struct KillSwitch {
+    /// Sentinel file whose existence pauses trading
+    file: Option<PathBuf>,
+
+    /// Environment variable toggling the switch
+    env_var: String,
+}
+
+impl KillSwitch {
+    /// Create a kill switch watching `file` (if any) and [KILL_SWITCH_ENV]
+    pub fn new(file: Option<PathBuf>) -> Self {
+        Self {
+            file,
+            env_var: KILL_SWITCH_ENV.to_string(),
+        }
+    }
+
+    /// Watch a different environment variable
+    pub fn with_env_var(mut self, env_var: impl Into<String>) -> Self {
+        self.env_var = env_var.into();
+        self
+    }
+
+    /// Whether trading is currently paused
+    pub fn is_active(&self) -> bool {
+        let file_present = self.file.as_ref().map(|f| f.exists()).unwrap_or(false);
+        let env_set = std::env::var(&self.env_var)
+            .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true"))
+            .unwrap_or(false);
+        file_present || env_set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentinel_file_toggles_switch() {
+        let path = std::env::temp_dir().join(format!("numo-kill-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let switch = KillSwitch::new(Some(path.clone())).with_env_var("NUMO_TEST_KILL_FILE");
+
+        assert!(!switch.is_active());
+        std::fs::write(&path, b"").unwrap();
+        assert!(switch.is_active());
+        std::fs::remove_file(&path).unwrap();
+        assert!(!switch.is_active());
+    }
+
+    #[test]
+    fn test_env_toggles_switch() {
+        let switch = KillSwitch::new(None).with_env_var("NUMO_TEST_KILL_ENV");
+
+        assert!(!switch.is_active());
+        std::env::set_var("NUMO_TEST_KILL_ENV", "true");
+        assert!(switch.is_active());
+        std::env::set_var("NUMO_TEST_KILL_ENV", "0");
+        assert!(!switch.is_active());
+        std::env::remove_var("NUMO_TEST_KILL_ENV");
+    }
+}
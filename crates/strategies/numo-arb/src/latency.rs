@@ -0,0 +1,79 @@
+/// Block-processing latency alarm for the Numo strategy
+/// Flags blocks whose evaluation takes longer than the configured limit and enters
+/// a degraded mode after a run of slow blocks, so the bot does less work per block
+use std::time::Duration;
+
+/// Tracks slow blocks against a latency limit
+#[derive(Debug, Clone)]
+pub struct LatencyAlarm {
+    /// Longest acceptable evaluation time
+    max_latency: Duration,
+
+    /// Consecutive slow blocks before entering degraded mode
+    degrade_after: u32,
+
+    /// Consecutive slow blocks
+    consecutive_slow: u32,
+
+    /// Total slow blocks since startup
+    slow_blocks: u64,
+}
+
+impl LatencyAlarm {
+    pub fn new(max_latency: Duration, degrade_after: u32) -> Self {
+        Self {
+            max_latency,
+            degrade_after,
+            consecutive_slow: 0,
+            slow_blocks: 0,
+        }
+    }
+
+    /// Record how long a block took, returning whether it was too slow
+    pub fn observe(&mut self, elapsed: Duration) -> bool {
+        if elapsed <= self.max_latency {
+            self.consecutive_slow = 0;
+            return false;
+        }
+        self.consecutive_slow = self.consecutive_slow.saturating_add(1);
+        self.slow_blocks += 1;
+        true
+    }
+
+    /// Whether evaluation has been persistently slow; clears on the next fast block
+    pub fn is_degraded(&self) -> bool {
+        self.consecutive_slow >= self.degrade_after.max(1)
+    }
+
+    /// Total slow blocks since startup
+    pub fn slow_blocks(&self) -> u64 {
+        self.slow_blocks
+    }
+
+    /// Latency limit
+    pub fn max_latency(&self) -> Duration {
+        self.max_latency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_blocks_trip_degraded_mode() {
+        let mut alarm = LatencyAlarm::new(Duration::from_millis(100), 2);
+
+        assert!(!alarm.observe(Duration::from_millis(100)));
+        assert!(alarm.observe(Duration::from_millis(150)));
+        assert!(!alarm.is_degraded());
+        assert!(alarm.observe(Duration::from_millis(300)));
+        assert!(alarm.is_degraded());
+        assert_eq!(alarm.slow_blocks(), 2);
+
+        // One fast block clears degraded mode but keeps the count
+        assert!(!alarm.observe(Duration::from_millis(20)));
+        assert!(!alarm.is_degraded());
+        assert_eq!(alarm.slow_blocks(), 2);
+    }
+}
@@ -11,15 +11,110 @@
 ///
 /// ## Key Components
 ///
-/// - `sofr`: SOFR curve implementation for discount factor calculations
+/// - `sofr`: Overnight rate curves (SOFR, ESTR, SONIA) for discount factor calculations
 /// - `pricing`: Pool price discovery and trade sizing logic
+/// - `price_source`: Pluggable pool pricing (on-chain previews or local quoter)
+/// - `quoter`: Local quoter reproducing the pool invariant off-chain
+/// - `approval`: Interactive operator approval of each trade
+/// - `backoff`: Evaluation backoff after runs of empty blocks
+/// - `balance`: Receiver balance checks of confirmed trades against expected profit
+/// - `band`: Fair value band with entry/exit hysteresis
+/// - `bench`: Target prices of each pool's maturity under every curve convention
+/// - `budget`: Capital budget across in-flight trades
+/// - `cancel`: Replace-cancellation of superseded or stale in-flight trades
+/// - `carry`: Single-pool carry positions on implied rate gaps to the curve
+/// - `catchup`: Historical block replay at startup
+/// - `clock`: Wall clock abstraction with a controllable mock for tests
+/// - `curve_feed`: Periodic rate curve refresh from its file
+/// - `curve_health`: Rate curve freshness for readiness checks and metrics
+/// - `execution`: Execution policies turning opportunities into actions
+/// - `confirmation`: Receipt tracking for submitted transactions
+/// - `fallback`: Polling fallback when the block subscription goes silent
+/// - `fmt`: Human-readable token amounts and prices with consistent decimals
+/// - `gas`: Gas buffer tuning from observed gas usage
+/// - `heatmap`: Periodic pool-by-pool spread heatmap export for dashboards
+/// - `kill_switch`: Emergency pause via sentinel file or environment toggle
+/// - `latency`: Slow block evaluation alarm and degraded mode
+/// - `loss_limit`: Trading halt once a day's realized loss exceeds a limit
+/// - `multicall`: Batched pool pricing tolerant of reverting pools
+/// - `nonce`: Detection and recovery of nonce gaps left by dropped transactions
+/// - `pending`: In-flight trades overlaid on simulations as state overrides
+/// - `persistence`: Requirement that an edge persists across blocks before acting
+/// - `pnl`: Realized PnL ledger of confirmed trades, carried across runs
+/// - `priority`: Event priority and debouncing of redundant re-evaluations
+/// - `queue`: Live opportunities ranked by net profit across blocks
+/// - `recorder`: Event recording and deterministic session replay
+/// - `reload`: Hot-reload of the safe-to-change config settings
+/// - `rpc`: Per-block RPC call counting and soft budget
+/// - `schedule`: Round-robin subset of pools priced per block at scale
+/// - `selftest`: Startup checks of the node, pools, router, curve and wallet
+/// - `snapshot`: Exported pool and curve snapshots re-evaluated offline
+/// - `submission`: Submission ordering and jitter to reduce self-competition
+/// - `throttle`: Sliding-minute cap on executed trades
+/// - `tick`: Periodic re-evaluation ticks between blocks
+/// - `tx_manager`: Resubmission of unmined trades at the same nonce with bumped gas
+/// - `dedup`: Suppression of opportunities repeated across blocks
+/// - `profit`: Profit conversion into a single reporting token
+/// - `promotion`: Dry run with paper PnL gating promotion to live trading
+/// - `data_log`: CSV log of every evaluated opportunity for offline analysis
 /// - `strategy`: Main arbitrage strategy implementation
 /// - `types`: Type definitions for events, actions, and configuration
+/// - `unsigned`: Unsigned transaction files for offline signing
+/// - `volatility`: Per-pool price volatility for volatility-scaled slippage
 /// - `bindings`: Contract ABI bindings for Numo Engine pools and router (external crate)
+pub mod approval;
+pub mod backoff;
+pub mod balance;
+pub mod band;
+pub mod bench;
+pub mod budget;
+pub mod cancel;
+pub mod carry;
+pub mod catchup;
+pub mod clock;
+pub mod confirmation;
+pub mod curve_feed;
+pub mod curve_health;
+pub mod data_log;
+pub mod dedup;
+pub mod execution;
+pub mod fallback;
+pub mod fmt;
+pub mod gas;
+pub mod heatmap;
+pub mod kill_switch;
+pub mod latency;
+pub mod loss_limit;
+pub mod multicall;
+pub mod nonce;
+pub mod pending;
+pub mod persistence;
+pub mod pnl;
+pub mod price_source;
 pub mod pricing;
+pub mod priority;
+pub mod profit;
+pub mod promotion;
+pub mod queue;
+pub mod quoter;
+pub mod recorder;
+pub mod reload;
+pub mod rpc;
+pub mod schedule;
+pub mod selftest;
+pub mod snapshot;
 pub mod sofr;
 pub mod strategy;
+pub mod submission;
+pub mod throttle;
+pub mod tick;
+pub mod tx_manager;
 pub mod types;
+pub mod unsigned;
+pub mod volatility;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
 // Re-exports for convenience
 pub use strategy::NumoArb;
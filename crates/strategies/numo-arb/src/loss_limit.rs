@@ -0,0 +1,214 @@
+/// Daily loss limit for the Numo strategy
+/// Accumulates the realized net PnL of reconciled trades over a rolling 24h window
+/// and halts trading while its loss exceeds the limit
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::clock::{Clock, SystemClock};
+use crate::pnl::PnlEntry;
+
+/// Length of the rolling loss window
+pub const LOSS_WINDOW_SECS: u64 = 86_400;
+
+/// Realized net PnL (base token wei) of the trades of the last 24h
+///
+/// Trades are fed by the [PnlTracker](crate::pnl::PnlTracker) as they are
+/// reconciled, and age out of the window 24h after they were recorded, so a halt
+/// lasts until enough of the losses behind it are a day old.
+#[derive(Debug)]
+pub struct DailyLossLimit {
+    /// Loss within the window that halts trading
+    max_loss: u128,
+
+    /// Gas cost charged against a trade whose gas could not be priced in base
+    /// tokens
+    gas_cost: u128,
+
+    /// Time at which trades are recorded
+    clock: Arc<dyn Clock>,
+
+    /// Net PnL of every trade in the window, by the time it was recorded
+    trades: Mutex<VecDeque<(u64, i128)>>,
+}
+
+impl DailyLossLimit {
+    pub fn new(max_loss: u128) -> Self {
+        Self {
+            max_loss,
+            gas_cost: 0,
+            clock: Arc::new(SystemClock),
+            trades: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Charge `gas_cost` against trades whose gas was not priced in base tokens
+    pub fn with_gas_cost(mut self, gas_cost: u128) -> Self {
+        self.gas_cost = gas_cost;
+        self
+    }
+
+    /// Record trades at `clock`'s time rather than the system time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record a trade's realized `net_pnl` at `now` (Unix seconds)
+    pub fn record(&self, now: u64, net_pnl: i128) {
+        let mut trades = self.trades.lock().unwrap();
+        while trades
+            .front()
+            .is_some_and(|&(at, _)| at + LOSS_WINDOW_SECS <= now)
+        {
+            trades.pop_front();
+        }
+        trades.push_back((now, net_pnl));
+    }
+
+    /// Record a reconciled trade at the clock's time, net of its gas in base tokens
+    pub fn record_entry(&self, entry: &PnlEntry) {
+        let net_pnl = entry.net_profit().unwrap_or_else(|| {
+            let gas_cost = self.gas_cost.min(i128::MAX as u128) as i128;
+            entry.gross_profit.saturating_sub(gas_cost)
+        });
+        self.record(self.clock.now(), net_pnl);
+    }
+
+    /// Realized net PnL of the 24h before `now`
+    pub fn net_pnl(&self, now: u64) -> i128 {
+        self.window(now)
+            .iter()
+            .fold(0, |total: i128, (_, pnl)| total.saturating_add(*pnl))
+    }
+
+    /// Whether the 24h before `now` lost more than the limit
+    pub fn is_halted(&self, now: u64) -> bool {
+        self.exceeds_limit(self.net_pnl(now))
+    }
+
+    /// When a halt at `now` lifts as its losses age out, unless more trades are
+    /// recorded meanwhile; `None` when not halted
+    pub fn resumes_at(&self, now: u64) -> Option<u64> {
+        let window = self.window(now);
+        let mut net_pnl = self.net_pnl(now);
+        if !self.exceeds_limit(net_pnl) {
+            return None;
+        }
+        window.into_iter().find_map(|(at, pnl)| {
+            net_pnl = net_pnl.saturating_sub(pnl);
+            (!self.exceeds_limit(net_pnl)).then_some(at + LOSS_WINDOW_SECS)
+        })
+    }
+
+    pub fn max_loss(&self) -> u128 {
+        self.max_loss
+    }
+
+    fn exceeds_limit(&self, net_pnl: i128) -> bool {
+        net_pnl < 0 && net_pnl.unsigned_abs() > self.max_loss
+    }
+
+    /// Trades recorded in the 24h before `now`, oldest first
+    fn window(&self, now: u64) -> Vec<(u64, i128)> {
+        let trades = self.trades.lock().unwrap();
+        trades
+            .iter()
+            .filter(|&&(at, _)| now < at + LOSS_WINDOW_SECS)
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn test_losses_past_limit_halt_until_they_age_out() {
+        let limit = DailyLossLimit::new(1_000);
+        let start = 1_700_000_000u64;
+
+        limit.record(start, 300);
+        limit.record(start + 60, -900);
+        assert_eq!(limit.net_pnl(start + 60), -600);
+        assert!(!limit.is_halted(start + 60));
+        assert_eq!(limit.resumes_at(start + 60), None);
+
+        // Exactly at the limit still trades, past it halts
+        limit.record(start + 120, -400);
+        assert!(!limit.is_halted(start + 120));
+        limit.record(start + 180, -1);
+        assert!(limit.is_halted(start + 180));
+
+        // The window rolls: the first trade's profit ages out and deepens the loss,
+        // so trading only resumes once the 900 loss is a day old
+        assert_eq!(
+            limit.resumes_at(start + 180),
+            Some(start + 60 + LOSS_WINDOW_SECS)
+        );
+        assert_eq!(limit.net_pnl(start + LOSS_WINDOW_SECS), -1_301);
+        assert!(limit.is_halted(start + 60 + LOSS_WINDOW_SECS - 1));
+        assert!(!limit.is_halted(start + 60 + LOSS_WINDOW_SECS));
+        assert_eq!(limit.net_pnl(start + 60 + LOSS_WINDOW_SECS), -401);
+        assert_eq!(limit.net_pnl(start + 180 + LOSS_WINDOW_SECS), 0);
+    }
+
+    fn entry(gross_profit: i128, gas_cost_base: Option<u128>) -> PnlEntry {
+        PnlEntry {
+            block: 100,
+            tx_hash: Default::default(),
+            cheap_pool: Default::default(),
+            rich_pool: Default::default(),
+            expected_profit: 250,
+            gross_profit,
+            gas_spent: 2 * 10u128.pow(15),
+            gas_cost_base,
+        }
+    }
+
+    #[test]
+    fn test_entries_realize_profit_net_of_gas() {
+        let clock = Arc::new(MockClock::new(1_700_000_000));
+        let limit = DailyLossLimit::new(1_000)
+            .with_gas_cost(100)
+            .with_clock(clock.clone());
+        let now = clock.now();
+
+        // The realized profit counts, not the expected one, net of the receipt's gas
+        limit.record_entry(&entry(180, Some(30)));
+        assert_eq!(limit.net_pnl(now), 150);
+
+        // Reverted trades lose their gas, the flat cost when it was not priced
+        for _ in 0..12 {
+            limit.record_entry(&entry(0, None));
+        }
+        assert_eq!(limit.net_pnl(now), -1_050);
+        assert!(limit.is_halted(now));
+    }
+
+    #[test]
+    fn test_mock_clock_rolls_loss_window() {
+        let clock = Arc::new(MockClock::new(1_700_000_000));
+        let limit = DailyLossLimit::new(1_000).with_clock(clock.clone());
+        let reverted = entry(0, Some(600));
+
+        // Two reverts an hour apart land in the same window and halt it
+        limit.record_entry(&reverted);
+        clock.advance(3_600);
+        limit.record_entry(&reverted);
+        assert!(limit.is_halted(clock.now()));
+
+        // Still halted a second before the first ages out, trading again after
+        clock.advance(LOSS_WINDOW_SECS - 3_600 - 1);
+        assert!(limit.is_halted(clock.now()));
+        clock.advance(1);
+        assert!(!limit.is_halted(clock.now()));
+        assert_eq!(limit.net_pnl(clock.now()), -600);
+
+        // The second is still in the window, so the next revert halts again
+        limit.record_entry(&reverted);
+        assert!(limit.is_halted(clock.now()));
+        assert_eq!(limit.resumes_at(clock.now()), Some(clock.now() + 3_600));
+    }
+}
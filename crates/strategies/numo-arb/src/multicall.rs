@@ -0,0 +1,138 @@
+/// Batched pool pricing for the Numo strategy
+/// Prices every pool in a single Multicall3 `aggregate3` call with failure allowed
+/// per sub-call, so one reverting pool (e.g., a paused one) only loses its own price
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use ethers::abi::Token;
+use ethers::contract::{Multicall, MulticallVersion};
+use ethers::prelude::*;
+
+use numo_bindings::NumoEnginePool;
+
+use crate::pricing::{probe_mid_price_net, PRICE_PROBE_AMOUNT};
+
+/// Marginal price of every pool (base per FY, 1e18 scaled) from one batched call
+///
+/// Results are returned in pool order. A pool whose probe reverted gets an error
+/// and the rest are still priced; only a failure of the batch itself is returned
+/// as an error.
+pub async fn batch_marginal_prices<M: Middleware + 'static>(
+    client: Arc<M>,
+    multicall_address: Address,
+    pools: &[Address],
+) -> Result<Vec<(Address, Result<U256>)>> {
+    let pools: Vec<(Address, u16)> = pools.iter().map(|pool| (*pool, 0)).collect();
+    batch_marginal_prices_net(client, multicall_address, &pools).await
+}
+
+/// Marginal price of every pool net of its fee, see [probe_mid_price_net], from one
+/// batched call; `pools` pairs each pool with its fee in basis points
+pub async fn batch_marginal_prices_net<M: Middleware + 'static>(
+    client: Arc<M>,
+    multicall_address: Address,
+    pools: &[(Address, u16)],
+) -> Result<Vec<(Address, Result<U256>)>> {
+    if pools.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut multicall = Multicall::new(client.clone(), Some(multicall_address))
+        .await?
+        .version(MulticallVersion::Multicall3);
+    for (pool, _) in pools {
+        let pool = NumoEnginePool::new(*pool, client.clone());
+        multicall.add_call(pool.sell_base_preview(PRICE_PROBE_AMOUNT), true);
+        multicall.add_call(pool.sell_fy_token_preview(PRICE_PROBE_AMOUNT), true);
+    }
+
+    let mut results = multicall.call_raw().await?.into_iter();
+    Ok(pools
+        .iter()
+        .map(|(pool, fee_bps)| {
+            // Both probes are consumed so the next pool reads its own results
+            let fy_out = probe_output(results.next());
+            let base_out = probe_output(results.next());
+            let price = fy_out.and_then(|fy_out| {
+                base_out.map(|base_out| {
+                    probe_mid_price_net(PRICE_PROBE_AMOUNT, fy_out, base_out, *fee_bps)
+                })
+            });
+            (*pool, price)
+        })
+        .collect())
+}
+
+/// Preview amount from one sub-call result
+fn probe_output(result: Option<std::result::Result<Token, Bytes>>) -> Result<u128> {
+    match result {
+        Some(Ok(token)) => token_u128(token).ok_or_else(|| anyhow!("Unexpected preview output")),
+        Some(Err(revert)) => Err(anyhow!("Preview reverted: {revert}")),
+        None => Err(anyhow!("Missing preview result")),
+    }
+}
+
+fn token_u128(token: Token) -> Option<u128> {
+    match token {
+        Token::Uint(value) if value <= U256::from(u128::MAX) => Some(value.as_u128()),
+        Token::Tuple(mut tokens) if tokens.len() == 1 => token_u128(tokens.remove(0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+
+    use crate::test_utils::encode_uints;
+
+    /// `aggregate3` response for sub-calls that either return `Some(value)` or revert
+    fn aggregate3_response(results: &[Option<u128>]) -> Bytes {
+        let results = results
+            .iter()
+            .map(|result| {
+                Token::Tuple(match result {
+                    Some(value) => vec![
+                        Token::Bool(true),
+                        Token::Bytes(encode_uints(&[*value]).to_vec()),
+                    ],
+                    None => vec![Token::Bool(false), Token::Bytes(b"paused".to_vec())],
+                })
+            })
+            .collect();
+        encode(&[Token::Array(results)]).into()
+    }
+
+    #[tokio::test]
+    async fn test_reverting_pool_does_not_blind_the_batch() {
+        let (provider, mock) = Provider::mocked();
+        let pools = [
+            Address::repeat_byte(0x11),
+            Address::repeat_byte(0x22),
+            Address::repeat_byte(0x33),
+        ];
+        // The middle pool is paused and reverts both probes; the others price at par
+        let probe = PRICE_PROBE_AMOUNT;
+        mock.push::<Bytes, _>(aggregate3_response(&[
+            Some(probe),
+            Some(probe),
+            None,
+            None,
+            Some(probe),
+            Some(probe),
+        ]))
+        .unwrap();
+
+        let prices = batch_marginal_prices(Arc::new(provider), Address::repeat_byte(0xca), &pools)
+            .await
+            .unwrap();
+
+        assert_eq!(prices.len(), 3);
+        assert_eq!(prices[0].0, pools[0]);
+        assert_eq!(*prices[0].1.as_ref().unwrap(), U256::exp10(18));
+        assert_eq!(prices[1].0, pools[1]);
+        assert!(prices[1].1.is_err());
+        assert_eq!(prices[2].0, pools[2]);
+        assert_eq!(*prices[2].1.as_ref().unwrap(), U256::exp10(18));
+    }
+}
@@ -0,0 +1,323 @@
+/// Nonce gap recovery for the Numo strategy
+/// A dropped transaction leaves a gap that stalls every later nonce; submissions
+/// that stay unconfirmed too long are treated as stuck and the gap is filled or
+/// the nonce manager is reset to the on-chain count
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use ethers::prelude::*;
+use tracing::{info, warn};
+
+use crate::cancel::{bumped_gas_price, DEFAULT_CANCEL_GAS_BUMP_BPS};
+use crate::confirmation::{ReceiptObserver, SubmittedTx};
+use crate::types::NonceRecoveryMode;
+
+/// Gas limit of a plain value transfer
+const TRANSFER_GAS_LIMIT: u64 = 21_000;
+
+/// How to unstick the account once a submission is stuck
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceRecovery {
+    /// Send a 0-value self-transfer at `nonce` so later nonces can be mined
+    FillGap { nonce: U256 },
+
+    /// Reset the nonce manager so the next submission reuses `nonce`
+    ResetNonce { nonce: U256 },
+}
+
+/// Decide how to recover from a stuck submission
+///
+/// `on_chain_nonce` is the account's mined transaction count and `stuck_nonce` the
+/// nonce of the stuck transaction, `None` when the node has dropped it. A stuck
+/// transaction below the on-chain nonce was mined or replaced, so nothing is done;
+/// otherwise the account is stalled at `on_chain_nonce`.
+pub fn recovery_decision(
+    mode: NonceRecoveryMode,
+    on_chain_nonce: U256,
+    stuck_nonce: Option<U256>,
+) -> Option<NonceRecovery> {
+    if stuck_nonce.is_some_and(|nonce| nonce < on_chain_nonce) {
+        return None;
+    }
+    Some(match mode {
+        NonceRecoveryMode::FillGap => NonceRecovery::FillGap {
+            nonce: on_chain_nonce,
+        },
+        NonceRecoveryMode::Reset => NonceRecovery::ResetNonce {
+            nonce: on_chain_nonce,
+        },
+    })
+}
+
+/// A client whose local nonce counter can be resynchronised with the chain
+#[async_trait]
+pub trait NonceReset: Send + Sync {
+    /// Reset the local nonce to the on-chain transaction count, returning it
+    async fn reset_nonce(&self) -> Result<U256>;
+}
+
+#[async_trait]
+impl<M, S> NonceReset for SignerMiddleware<NonceManagerMiddleware<M>, S>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+    S: Signer + 'static,
+{
+    async fn reset_nonce(&self) -> Result<U256> {
+        self.inner()
+            .initialize_nonce(None)
+            .await
+            .map_err(|e| anyhow!("Error resetting nonce: {e}"))
+    }
+}
+
+/// Tracks unconfirmed submissions and recovers the account when one gets stuck
+pub struct NonceGapMonitor<M> {
+    client: Arc<M>,
+    address: Address,
+    stuck_after: Duration,
+    mode: NonceRecoveryMode,
+    gas_bump_bps: u32,
+    resetter: Option<Arc<dyn NonceReset>>,
+    pending: Mutex<HashMap<TxHash, Instant>>,
+}
+
+impl<M> NonceGapMonitor<M> {
+    pub fn new(client: Arc<M>, address: Address, stuck_after: Duration) -> Self {
+        Self {
+            client,
+            address,
+            stuck_after,
+            mode: NonceRecoveryMode::default(),
+            gas_bump_bps: DEFAULT_CANCEL_GAS_BUMP_BPS,
+            resetter: None,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Recover with `mode`
+    pub fn with_mode(mut self, mode: NonceRecoveryMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Bump the gas price of gap-filling transfers by `bump_bps`
+    pub fn with_gas_bump(mut self, bump_bps: u32) -> Self {
+        self.gas_bump_bps = bump_bps;
+        self
+    }
+
+    /// Reset nonces through `resetter` in [NonceRecoveryMode::Reset]
+    pub fn with_resetter(mut self, resetter: Arc<dyn NonceReset>) -> Self {
+        self.resetter = Some(resetter);
+        self
+    }
+
+    /// The oldest submission unconfirmed for at least `stuck_after` as of `now`
+    pub fn oldest_stuck(&self, now: Instant) -> Option<TxHash> {
+        self.pending
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, submitted_at)| now.duration_since(**submitted_at) >= self.stuck_after)
+            .min_by_key(|(_, submitted_at)| **submitted_at)
+            .map(|(tx_hash, _)| *tx_hash)
+    }
+
+    fn forget(&self, tx_hash: TxHash) {
+        self.pending.lock().unwrap().remove(&tx_hash);
+    }
+}
+
+impl<M> NonceGapMonitor<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    /// Look for a stuck submission as of `now` and decide how to recover from it
+    pub async fn detect(&self, now: Instant) -> Result<Option<NonceRecovery>> {
+        let Some(tx_hash) = self.oldest_stuck(now) else {
+            return Ok(None);
+        };
+        let stuck_nonce = self
+            .client
+            .get_transaction(tx_hash)
+            .await
+            .context("Error fetching stuck transaction")?
+            .map(|tx| tx.nonce);
+        let on_chain_nonce = self
+            .client
+            .get_transaction_count(self.address, Some(BlockNumber::Latest.into()))
+            .await
+            .context("Error fetching on-chain nonce")?;
+
+        let recovery = recovery_decision(self.mode, on_chain_nonce, stuck_nonce);
+        match recovery {
+            Some(recovery) => warn!(
+                tx_hash = ?tx_hash,
+                ?stuck_nonce,
+                on_chain_nonce = %on_chain_nonce,
+                ?recovery,
+                "Submission stuck, recovering nonce"
+            ),
+            None => info!(tx_hash = ?tx_hash, "Stuck submission already mined or replaced"),
+        }
+        self.forget(tx_hash);
+        Ok(recovery)
+    }
+
+    /// Apply `recovery`
+    pub async fn recover(&self, recovery: NonceRecovery) -> Result<()> {
+        match recovery {
+            NonceRecovery::FillGap { nonce } => {
+                let gas_price = self
+                    .client
+                    .get_gas_price()
+                    .await
+                    .context("Error getting gas price")?;
+                let fill = TransactionRequest::new()
+                    .from(self.address)
+                    .to(self.address)
+                    .value(U256::zero())
+                    .nonce(nonce)
+                    .gas(TRANSFER_GAS_LIMIT)
+                    .gas_price(bumped_gas_price(gas_price, self.gas_bump_bps));
+                let fill_hash = *self.client.send_transaction(fill, None).await?;
+                info!(nonce = %nonce, fill_hash = ?fill_hash, "Nonce gap filled");
+            }
+            NonceRecovery::ResetNonce { nonce } => {
+                let resetter = self
+                    .resetter
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("No nonce manager to reset"))?;
+                let reset = resetter.reset_nonce().await?;
+                info!(expected = %nonce, nonce = %reset, "Nonce manager reset");
+            }
+        }
+        Ok(())
+    }
+
+    /// Detect and recover from stuck submissions every `stuck_after`, forever
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.stuck_after);
+        loop {
+            interval.tick().await;
+            let result = match self.detect(Instant::now()).await {
+                Ok(Some(recovery)) => self.recover(recovery).await,
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!(error = ?e, "Nonce gap recovery failed");
+            }
+        }
+    }
+}
+
+impl<M: Send + Sync> ReceiptObserver for NonceGapMonitor<M> {
+    fn on_submitted(&self, submitted: &SubmittedTx) {
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(submitted.tx_hash, Instant::now());
+    }
+
+    fn on_receipt(&self, submitted: &SubmittedTx, _receipt: &TransactionReceipt) {
+        self.forget(submitted.tx_hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submitted(tx_hash: TxHash) -> SubmittedTx {
+        SubmittedTx {
+            tx_hash,
+            gas_estimate: U256::from(100_000),
+            expected_profit: None,
+            calldata: None,
+        }
+    }
+
+    #[test]
+    fn test_recovery_decision() {
+        let on_chain = U256::from(7);
+
+        // A dropped transaction leaves the account stalled at the on-chain nonce
+        assert_eq!(
+            recovery_decision(NonceRecoveryMode::FillGap, on_chain, None),
+            Some(NonceRecovery::FillGap { nonce: on_chain })
+        );
+        assert_eq!(
+            recovery_decision(NonceRecoveryMode::Reset, on_chain, None),
+            Some(NonceRecovery::ResetNonce { nonce: on_chain })
+        );
+        // Pending at or above the on-chain nonce: the gap is at the on-chain nonce
+        assert_eq!(
+            recovery_decision(NonceRecoveryMode::FillGap, on_chain, Some(U256::from(9))),
+            Some(NonceRecovery::FillGap { nonce: on_chain })
+        );
+        assert_eq!(
+            recovery_decision(NonceRecoveryMode::FillGap, on_chain, Some(on_chain)),
+            Some(NonceRecovery::FillGap { nonce: on_chain })
+        );
+        // Below the on-chain nonce it was mined or replaced after all
+        assert_eq!(
+            recovery_decision(NonceRecoveryMode::FillGap, on_chain, Some(U256::from(6))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_submission_is_stuck_until_confirmed() {
+        let (provider, _mock) = Provider::mocked();
+        let monitor = NonceGapMonitor::new(
+            Arc::new(provider),
+            Address::repeat_byte(0xb0),
+            Duration::from_secs(30),
+        );
+        let tx_hash = TxHash::repeat_byte(0x01);
+        monitor.on_submitted(&submitted(tx_hash));
+
+        let now = Instant::now();
+        assert_eq!(monitor.oldest_stuck(now), None);
+        assert_eq!(
+            monitor.oldest_stuck(now + Duration::from_secs(31)),
+            Some(tx_hash)
+        );
+
+        monitor.on_receipt(&submitted(tx_hash), &TransactionReceipt::default());
+        assert_eq!(monitor.oldest_stuck(now + Duration::from_secs(31)), None);
+    }
+
+    #[tokio::test]
+    async fn test_detects_dropped_transaction() {
+        let (provider, mock) = Provider::mocked();
+        let monitor = NonceGapMonitor::new(
+            Arc::new(provider),
+            Address::repeat_byte(0xb0),
+            Duration::from_secs(30),
+        )
+        .with_mode(NonceRecoveryMode::Reset);
+        monitor.on_submitted(&submitted(TxHash::repeat_byte(0x01)));
+
+        // Responses are served last-in first-out: the node no longer knows the
+        // transaction, and the account has mined 4 transactions
+        mock.push(U256::from(4)).unwrap();
+        mock.push(serde_json::Value::Null).unwrap();
+
+        let later = Instant::now() + Duration::from_secs(31);
+        assert_eq!(
+            monitor.detect(later).await.unwrap(),
+            Some(NonceRecovery::ResetNonce {
+                nonce: U256::from(4)
+            })
+        );
+        // The stuck submission is handled once
+        assert_eq!(monitor.detect(later).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,195 @@
+/// Pending pool state for the Numo strategy
+/// In-flight trades move pool reserves before they are mined; simulating a new trade
+/// with those moves applied as an `eth_call` state override keeps an opportunity
+/// overlapping an unconfirmed trade from looking profitable twice
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use ethers::prelude::*;
+use ethers::providers::spoof;
+
+use crate::confirmation::{ReceiptObserver, SubmittedTx};
+use crate::types::ArbOpportunity;
+
+/// Signed change to a pool's cached reserves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReserveDelta {
+    pub base: i128,
+    pub fy: i128,
+}
+
+impl ReserveDelta {
+    fn add(self, other: ReserveDelta) -> Self {
+        Self {
+            base: self.base.saturating_add(other.base),
+            fy: self.fy.saturating_add(other.fy),
+        }
+    }
+
+    /// `(base, fy)` reserves after the change, floored at zero
+    pub fn apply(&self, base: u128, fy: u128) -> (u128, u128) {
+        let shift = |reserve: u128, delta: i128| {
+            if delta >= 0 {
+                reserve.saturating_add(delta as u128)
+            } else {
+                reserve.saturating_sub(delta.unsigned_abs())
+            }
+        };
+        (shift(base, self.base), shift(fy, self.fy))
+    }
+}
+
+/// Reserve changes of an opportunity's two pools, at its slippage bounds
+///
+/// The cheap pool receives up to `max_base_in` and pays out the FY, the rich pool
+/// takes the FY and pays out at least `min_base_out`. Both bounds are the moves
+/// least favourable to a following trade on the same pools.
+pub fn trade_deltas(opp: &ArbOpportunity) -> [(Address, ReserveDelta); 2] {
+    let fy = opp.fy_amount.min(i128::MAX as u128) as i128;
+    [
+        (
+            opp.cheap_pool,
+            ReserveDelta {
+                base: opp.max_base_in.min(i128::MAX as u128) as i128,
+                fy: -fy,
+            },
+        ),
+        (
+            opp.rich_pool,
+            ReserveDelta {
+                base: -(opp.min_base_out.min(i128::MAX as u128) as i128),
+                fy,
+            },
+        ),
+    ]
+}
+
+/// Storage word of a pool's reserves cache: base in the low 128 bits, FY in the high
+pub fn pack_reserves(base: u128, fy: u128) -> H256 {
+    let word = (U256::from(fy) << 128) | U256::from(base);
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    H256(bytes)
+}
+
+/// Reserve changes of unconfirmed trades, keyed by their calldata
+///
+/// Trades are recorded when their action is built and dropped once they resolve, so
+/// the pending moves of every in-flight trade can be overlaid on the pools' state.
+#[derive(Debug)]
+pub struct PendingPoolState {
+    /// Storage slot holding a pool's packed reserves cache
+    reserves_slot: H256,
+
+    trades: Mutex<HashMap<Bytes, [(Address, ReserveDelta); 2]>>,
+}
+
+impl PendingPoolState {
+    pub fn new(reserves_slot: u64) -> Self {
+        Self {
+            reserves_slot: H256::from_low_u64_be(reserves_slot),
+            trades: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record the trade carrying `calldata` as in flight
+    pub fn record(&self, calldata: Bytes, opp: &ArbOpportunity) {
+        self.trades
+            .lock()
+            .unwrap()
+            .insert(calldata, trade_deltas(opp));
+    }
+
+    /// Forget the trade carrying `calldata`
+    pub fn release(&self, calldata: &Bytes) {
+        self.trades.lock().unwrap().remove(calldata);
+    }
+
+    /// Combined reserve change of the in-flight trades on `pool`, if any
+    pub fn pending_delta(&self, pool: Address) -> Option<ReserveDelta> {
+        self.trades
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .filter(|(address, _)| *address == pool)
+            .map(|(_, delta)| *delta)
+            .reduce(ReserveDelta::add)
+    }
+
+    /// State override setting each pool's reserves cache to its `(pool, base, fy)`
+    /// reserves with the pending changes applied
+    pub fn state_override(&self, reserves: &[(Address, u128, u128)]) -> spoof::State {
+        let mut state = spoof::state();
+        for &(pool, base, fy) in reserves {
+            let delta = self.pending_delta(pool).unwrap_or_default();
+            let (base, fy) = delta.apply(base, fy);
+            state
+                .account(pool)
+                .store(self.reserves_slot, pack_reserves(base, fy));
+        }
+        state
+    }
+}
+
+impl ReceiptObserver for PendingPoolState {
+    fn on_receipt(&self, submitted: &SubmittedTx, _receipt: &TransactionReceipt) {
+        // Mined or reverted, the pools' own state reflects the trade from now on
+        if let Some(calldata) = &submitted.calldata {
+            self.release(calldata);
+        }
+    }
+
+    fn on_abandoned(&self, calldata: &Bytes) {
+        self.release(calldata);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::opportunity;
+
+    #[test]
+    fn test_pack_reserves() {
+        let word = U256::from_big_endian(pack_reserves(5, 7).as_bytes());
+        assert_eq!(word, (U256::from(7) << 128) + 5);
+        let word = U256::from_big_endian(pack_reserves(u128::MAX, 0).as_bytes());
+        assert_eq!(word, U256::from(u128::MAX));
+    }
+
+    #[test]
+    fn test_pending_trades_overlay_reserves() {
+        let pending = PendingPoolState::new(8);
+        let opp = opportunity();
+        let cheap = opp.cheap_pool;
+        pending.record(Bytes::from(vec![1]), &opp);
+        pending.record(Bytes::from(vec![2]), &opp);
+
+        // Two trades on the pair stack up
+        assert_eq!(
+            pending.pending_delta(cheap),
+            Some(ReserveDelta {
+                base: 1_900,
+                fy: -2_000
+            })
+        );
+        assert_eq!(pending.pending_delta(Address::repeat_byte(0x33)), None);
+
+        let state = pending.state_override(&[(cheap, 100_000, 200_000)]);
+        let mut expected = spoof::state();
+        expected
+            .account(cheap)
+            .store(H256::from_low_u64_be(8), pack_reserves(101_900, 198_000));
+        assert_eq!(
+            serde_json::to_value(&state).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+
+        // Resolved trades no longer move the pools
+        pending.release(&Bytes::from(vec![1]));
+        pending.on_abandoned(&Bytes::from(vec![2]));
+        assert_eq!(pending.pending_delta(cheap), None);
+    }
+}
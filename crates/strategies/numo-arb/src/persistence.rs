@@ -0,0 +1,92 @@
+/// Edge persistence guard for the Numo strategy
+/// Only acts on an opportunity once its pair has shown an edge for several
+/// consecutive blocks, so a single manipulated block cannot trigger a trade
+use ethers::prelude::*;
+
+/// Counts consecutive blocks in which the same pair had an opportunity
+#[derive(Debug, Clone)]
+pub struct EdgePersistence {
+    /// Consecutive blocks required before acting
+    min_blocks: u64,
+
+    /// Pair of the current streak, its length and its last block
+    streak: Option<((Address, Address), u64, u64)>,
+}
+
+impl EdgePersistence {
+    pub fn new(min_blocks: u64) -> Self {
+        Self {
+            min_blocks,
+            streak: None,
+        }
+    }
+
+    /// Record the pair with an opportunity at `block` (or `None`) and return whether
+    /// its edge has now persisted for at least `min_blocks` consecutive blocks
+    ///
+    /// A block without an opportunity, a different pair or a skipped block number
+    /// restarts the streak.
+    pub fn observe(&mut self, pair: Option<(Address, Address)>, block: u64) -> bool {
+        let Some(pair) = pair else {
+            self.streak = None;
+            return false;
+        };
+        let length = match self.streak {
+            Some((streak_pair, length, last_block))
+                if streak_pair == pair && block == last_block + 1 =>
+            {
+                length + 1
+            }
+            // The same block observed again does not extend the streak
+            Some((streak_pair, length, last_block))
+                if streak_pair == pair && block == last_block =>
+            {
+                length
+            }
+            _ => 1,
+        };
+        self.streak = Some((pair, length, block));
+        length >= self.min_blocks
+    }
+
+    /// Length of the current streak
+    pub fn streak(&self) -> u64 {
+        self.streak.map(|(_, length, _)| length).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_must_persist() {
+        let pair = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let other = (Address::repeat_byte(0x22), Address::repeat_byte(0x11));
+        let mut persistence = EdgePersistence::new(3);
+
+        // A one-block spike is ignored
+        assert!(!persistence.observe(Some(pair), 10));
+        assert!(!persistence.observe(None, 11));
+
+        // A persistent edge is taken on its third block, and stays taken
+        assert!(!persistence.observe(Some(pair), 12));
+        assert!(!persistence.observe(Some(pair), 13));
+        assert!(persistence.observe(Some(pair), 14));
+        assert!(persistence.observe(Some(pair), 15));
+
+        // Switching pair or skipping a block restarts the streak
+        assert!(!persistence.observe(Some(other), 16));
+        assert_eq!(persistence.streak(), 1);
+        assert!(!persistence.observe(Some(other), 18));
+        assert_eq!(persistence.streak(), 1);
+    }
+
+    #[test]
+    fn test_single_block_requirement_always_passes() {
+        let pair = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let mut persistence = EdgePersistence::new(1);
+        assert!(persistence.observe(Some(pair), 1));
+        assert!(!persistence.observe(None, 2));
+    }
+}
@@ -0,0 +1,512 @@
+/// Realized PnL ledger for the Numo strategy
+/// Reconciles the base token balance change and gas of every confirmed trade and
+/// appends it to a CSV ledger, whose totals carry over across restarts
+///
+/// Gas is paid in native wei while profit is in base token wei, so the net profit
+/// is only known once gas is priced in base tokens at a native price.
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use ethers::prelude::*;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::balance::{trade_route, BalanceVerifier};
+use crate::confirmation::{ReceiptObserver, SubmittedTx};
+use crate::gas::gas_cost_in_base;
+use crate::loss_limit::DailyLossLimit;
+
+/// Column names of the PnL ledger, in order
+pub const COLUMNS: [&str; 9] = [
+    "block",
+    "tx_hash",
+    "cheap_pool",
+    "rich_pool",
+    "expected_profit",
+    "gross_profit",
+    "gas_spent",
+    "gas_cost_base",
+    "net_profit",
+];
+
+/// Realized outcome of one confirmed trade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PnlEntry {
+    /// Block the trade confirmed in
+    pub block: u64,
+    pub tx_hash: TxHash,
+    pub cheap_pool: Address,
+    pub rich_pool: Address,
+    pub expected_profit: u128,
+
+    /// Observed change of the receiver's base token balance
+    pub gross_profit: i128,
+
+    /// Gas used times the effective gas price, in wei of the fee currency
+    pub gas_spent: u128,
+
+    /// `gas_spent` in base token wei, unknown without a native price
+    pub gas_cost_base: Option<u128>,
+}
+
+impl PnlEntry {
+    /// Gross profit net of the gas cost in base tokens, unknown when the gas was not
+    /// priced in base tokens
+    pub fn net_profit(&self) -> Option<i128> {
+        let gas_cost = self.gas_cost_base?.min(i128::MAX as u128) as i128;
+        Some(self.gross_profit.saturating_sub(gas_cost))
+    }
+
+    /// Render the entry as a CSV row matching [`COLUMNS`]
+    pub fn to_csv_row(&self) -> String {
+        [
+            self.block.to_string(),
+            format!("{:?}", self.tx_hash),
+            format!("{:?}", self.cheap_pool),
+            format!("{:?}", self.rich_pool),
+            self.expected_profit.to_string(),
+            self.gross_profit.to_string(),
+            self.gas_spent.to_string(),
+            optional(self.gas_cost_base),
+            optional(self.net_profit()),
+        ]
+        .join(",")
+    }
+
+    /// Parse a row written by [PnlEntry::to_csv_row]
+    ///
+    /// Rows of earlier ledgers, without the `gas_cost_base` column, are read with an
+    /// unknown gas cost; their net profit mixed units and is dropped.
+    pub fn from_csv_row(row: &str) -> Result<Self> {
+        let fields: Vec<&str> = row.split(',').collect();
+        if fields.len() == COLUMNS.len() - 1 {
+            return Self::from_fields(&fields, None);
+        }
+        if fields.len() != COLUMNS.len() {
+            return Err(anyhow!(
+                "Expected {} ledger columns, found {}",
+                COLUMNS.len(),
+                fields.len()
+            ));
+        }
+        let gas_cost_base = match fields[7] {
+            "" => None,
+            cost => Some(cost.parse()?),
+        };
+        Self::from_fields(&fields, gas_cost_base)
+    }
+
+    fn from_fields(fields: &[&str], gas_cost_base: Option<u128>) -> Result<Self> {
+        Ok(Self {
+            block: fields[0].parse()?,
+            tx_hash: fields[1].parse()?,
+            cheap_pool: fields[2].parse()?,
+            rich_pool: fields[3].parse()?,
+            expected_profit: fields[4].parse()?,
+            gross_profit: fields[5].parse()?,
+            gas_spent: fields[6].parse()?,
+            gas_cost_base,
+        })
+    }
+}
+
+/// Value of an optional ledger column, empty when unknown
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Running totals of the realized PnL
+///
+/// The net profit is only totalled while every trade's gas was priced in base
+/// tokens, and unknown from the first that was not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PnlSummary {
+    pub trades: u64,
+    pub expected_profit: u128,
+    pub gross_profit: i128,
+    pub gas_spent: u128,
+    pub gas_cost_base: u128,
+    pub net_profit: Option<i128>,
+}
+
+impl Default for PnlSummary {
+    fn default() -> Self {
+        Self {
+            trades: 0,
+            expected_profit: 0,
+            gross_profit: 0,
+            gas_spent: 0,
+            gas_cost_base: 0,
+            net_profit: Some(0),
+        }
+    }
+}
+
+impl PnlSummary {
+    fn add(&mut self, entry: &PnlEntry) {
+        self.trades += 1;
+        self.expected_profit = self.expected_profit.saturating_add(entry.expected_profit);
+        self.gross_profit = self.gross_profit.saturating_add(entry.gross_profit);
+        self.gas_spent = self.gas_spent.saturating_add(entry.gas_spent);
+        self.gas_cost_base = self
+            .gas_cost_base
+            .saturating_add(entry.gas_cost_base.unwrap_or_default());
+        self.net_profit = self
+            .net_profit
+            .zip(entry.net_profit())
+            .map(|(total, net)| total.saturating_add(net));
+    }
+}
+
+/// Tracks submitted trades and reconciles them into the ledger as they confirm
+///
+/// Balance changes are read as by [BalanceVerifier], at the receipt's block and the
+/// block before it.
+pub struct PnlTracker<M> {
+    balances: BalanceVerifier<M>,
+    ledger: Option<PathBuf>,
+
+    /// Native token price in base tokens and the base token's decimals, pricing gas
+    /// in base tokens
+    native_price: Option<(f64, u8)>,
+
+    /// Loss limit fed every reconciled trade
+    loss_limit: Option<Arc<DailyLossLimit>>,
+    submitted: Arc<Mutex<HashMap<TxHash, SubmittedTx>>>,
+    summary: Arc<Mutex<PnlSummary>>,
+}
+
+impl<M> Clone for PnlTracker<M> {
+    fn clone(&self) -> Self {
+        Self {
+            balances: self.balances.clone(),
+            ledger: self.ledger.clone(),
+            native_price: self.native_price,
+            loss_limit: self.loss_limit.clone(),
+            submitted: self.submitted.clone(),
+            summary: self.summary.clone(),
+        }
+    }
+}
+
+impl<M> PnlTracker<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            balances: BalanceVerifier::new(client),
+            ledger: None,
+            native_price: None,
+            loss_limit: None,
+            submitted: Arc::new(Mutex::new(HashMap::new())),
+            summary: Arc::new(Mutex::new(PnlSummary::default())),
+        }
+    }
+
+    /// Append every reconciled trade to the CSV file at `path`, starting from the
+    /// totals of the trades already in it
+    pub fn with_ledger(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut summary = PnlSummary::default();
+        for entry in read_ledger(&path)? {
+            summary.add(&entry);
+        }
+        self.summary = Arc::new(Mutex::new(summary));
+        self.ledger = Some(path);
+        Ok(self)
+    }
+
+    /// Price gas in base tokens at `native_price_in_base` base tokens per native
+    /// token, for a base token of `base_decimals`
+    pub fn with_native_price(mut self, native_price_in_base: f64, base_decimals: u8) -> Self {
+        self.native_price = Some((native_price_in_base, base_decimals));
+        self
+    }
+
+    /// Record every reconciled trade's realized PnL against `limit`
+    pub fn with_loss_limit(mut self, limit: Arc<DailyLossLimit>) -> Self {
+        self.loss_limit = Some(limit);
+        self
+    }
+
+    /// Totals across every reconciled trade, including earlier runs' ledger
+    pub fn summary(&self) -> PnlSummary {
+        self.summary.lock().unwrap().clone()
+    }
+
+    /// Trades submitted and not yet reconciled or abandoned
+    pub fn pending(&self) -> usize {
+        self.submitted.lock().unwrap().len()
+    }
+
+    fn append(&self, entry: &PnlEntry) -> Result<()> {
+        let Some(path) = &self.ledger else {
+            return Ok(());
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open PnL ledger {}", path.display()))?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", COLUMNS.join(","))?;
+        }
+        writeln!(file, "{}", entry.to_csv_row())?;
+        Ok(())
+    }
+}
+
+impl<M> PnlTracker<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    /// Reconcile the trade behind `receipt` into the totals and the ledger
+    ///
+    /// Reverted transactions spend gas without a balance change; transactions other
+    /// than router trades are not tracked.
+    pub async fn reconcile(
+        &self,
+        submitted: &SubmittedTx,
+        receipt: &TransactionReceipt,
+    ) -> Result<Option<PnlEntry>> {
+        self.submitted.lock().unwrap().remove(&submitted.tx_hash);
+        let Some((cheap_pool, rich_pool, _)) = submitted.calldata.as_deref().and_then(trade_route)
+        else {
+            return Ok(None);
+        };
+        let block = receipt
+            .block_number
+            .ok_or_else(|| anyhow!("Receipt has no block number"))?
+            .as_u64();
+        let gross_profit = match self.balances.observe(submitted, receipt).await? {
+            Some(check) => check.observed,
+            None => 0,
+        };
+        let (gas_used, gas_price) = (
+            receipt.gas_used.unwrap_or_default(),
+            receipt.effective_gas_price.unwrap_or_default(),
+        );
+        let gas_spent = gas_used.saturating_mul(gas_price);
+        let gas_cost_base = self
+            .native_price
+            .map(|(price, decimals)| gas_cost_in_base(gas_used, gas_price, price, decimals));
+
+        let entry = PnlEntry {
+            block,
+            tx_hash: submitted.tx_hash,
+            cheap_pool,
+            rich_pool,
+            expected_profit: submitted
+                .expected_profit
+                .map_or(0, |profit| profit.min(U256::from(u128::MAX)).as_u128()),
+            gross_profit,
+            gas_spent: gas_spent.min(U256::from(u128::MAX)).as_u128(),
+            gas_cost_base,
+        };
+        if let Some(limit) = &self.loss_limit {
+            limit.record_entry(&entry);
+        }
+        self.append(&entry)?;
+        let summary = {
+            let mut summary = self.summary.lock().unwrap();
+            summary.add(&entry);
+            summary.clone()
+        };
+        info!(
+            tx_hash = ?entry.tx_hash,
+            gross_profit = entry.gross_profit,
+            gas_spent = entry.gas_spent,
+            gas_cost_base = ?entry.gas_cost_base,
+            net_profit = ?entry.net_profit(),
+            total_net_profit = ?summary.net_profit,
+            trades = summary.trades,
+            "Realized PnL recorded"
+        );
+        Ok(Some(entry))
+    }
+}
+
+impl<M> ReceiptObserver for PnlTracker<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    fn on_submitted(&self, submitted: &SubmittedTx) {
+        self.submitted
+            .lock()
+            .unwrap()
+            .insert(submitted.tx_hash, submitted.clone());
+    }
+
+    fn on_abandoned(&self, calldata: &Bytes) {
+        self.submitted
+            .lock()
+            .unwrap()
+            .retain(|_, submitted| submitted.calldata.as_ref() != Some(calldata));
+    }
+
+    fn on_receipt(&self, submitted: &SubmittedTx, receipt: &TransactionReceipt) {
+        let tracker = self.clone();
+        let (submitted, receipt) = (submitted.clone(), receipt.clone());
+        tokio::spawn(async move {
+            if let Err(e) = tracker.reconcile(&submitted, &receipt).await {
+                warn!(tx_hash = ?submitted.tx_hash, error = ?e, "PnL reconciliation failed");
+            }
+        });
+    }
+}
+
+/// Entries of the ledger at `path`; a missing file is an empty ledger
+pub fn read_ledger(path: &Path) -> Result<Vec<PnlEntry>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read PnL ledger {}", path.display()))
+        }
+    };
+    contents
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            PnlEntry::from_csv_row(line)
+                .with_context(|| format!("Invalid row on line {} of {}", index + 1, path.display()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{encode, Token};
+    use numo_bindings::NumoArbRouter;
+
+    use crate::test_utils::encode_uints;
+
+    #[tokio::test]
+    async fn test_confirmed_trades_reconciled_into_ledger() {
+        let path = std::env::temp_dir().join(format!("numo-pnl-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        // Gas is priced at 1,500 base tokens of 6 decimals per native token
+        let tracker = PnlTracker::new(provider.clone())
+            .with_native_price(1_500.0, 6)
+            .with_ledger(&path)
+            .unwrap();
+        let (cheap, rich) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let receiver = Address::repeat_byte(0xb0);
+        let calldata = NumoArbRouter::new(Address::repeat_byte(0x33), provider)
+            .arb_buy_fy_then_sell_fy(cheap, rich, 1_000, 950, 980, receiver)
+            .calldata()
+            .unwrap();
+        let submitted = SubmittedTx {
+            tx_hash: TxHash::repeat_byte(0x01),
+            gas_estimate: U256::from(100_000),
+            expected_profit: Some(U256::from(30)),
+            calldata: Some(calldata),
+        };
+        let receipt = TransactionReceipt {
+            status: Some(U64::one()),
+            block_number: Some(U64::from(100)),
+            gas_used: Some(U256::from(100_000)),
+            effective_gas_price: Some(U256::from(20_000_000_000u64)),
+            ..Default::default()
+        };
+        tracker.on_submitted(&submitted);
+        assert_eq!(tracker.pending(), 1);
+
+        // Responses are served last-in first-out: the pool's base token, then the
+        // balance before and after
+        mock.push::<Bytes, _>(encode_uints(&[10_000_000])).unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[5_000_000])).unwrap();
+        let base_token: Bytes = encode(&[Token::Address(Address::repeat_byte(0xba))]).into();
+        mock.push::<Bytes, _>(base_token).unwrap();
+        let entry = tracker
+            .reconcile(&submitted, &receipt)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(tracker.pending(), 0);
+        assert_eq!((entry.cheap_pool, entry.rich_pool), (cheap, rich));
+        // 0.002 native of gas is 3 base tokens, netted from the 5 made
+        assert_eq!(
+            (entry.gross_profit, entry.gas_spent),
+            (5_000_000, 2 * 10u128.pow(15))
+        );
+        assert_eq!(entry.gas_cost_base, Some(3_000_000));
+        assert_eq!(entry.net_profit(), Some(2_000_000));
+
+        // A reverted trade only spends gas
+        let reverted = TransactionReceipt {
+            status: Some(U64::zero()),
+            ..receipt
+        };
+        let entry = tracker
+            .reconcile(&submitted, &reverted)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.net_profit(), Some(-3_000_000));
+
+        let expected = PnlSummary {
+            trades: 2,
+            expected_profit: 60,
+            gross_profit: 5_000_000,
+            gas_spent: 4 * 10u128.pow(15),
+            gas_cost_base: 6_000_000,
+            net_profit: Some(-1_000_000),
+        };
+        assert_eq!(tracker.summary(), expected);
+
+        // The next run starts from the ledger's totals
+        let ledger = read_ledger(&path).unwrap();
+        let (provider, _mock) = Provider::mocked();
+        let restarted = PnlTracker::new(Arc::new(provider))
+            .with_ledger(&path)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(ledger.len(), 2);
+        assert_eq!(ledger[0].block, 100);
+        assert_eq!(restarted.summary(), expected);
+    }
+
+    #[test]
+    fn test_unpriced_gas_leaves_the_net_profit_unknown() {
+        let entry = PnlEntry {
+            block: 100,
+            tx_hash: TxHash::repeat_byte(0x01),
+            cheap_pool: Address::repeat_byte(0x11),
+            rich_pool: Address::repeat_byte(0x22),
+            expected_profit: 30,
+            gross_profit: 25,
+            gas_spent: 8,
+            gas_cost_base: None,
+        };
+        assert_eq!(entry.net_profit(), None);
+        let row = entry.to_csv_row();
+        assert!(row.ends_with(",25,8,,"));
+        assert_eq!(PnlEntry::from_csv_row(&row).unwrap(), entry);
+
+        // Rows of ledgers without the gas cost column are read unpriced
+        let legacy = row.rsplit_once(',').unwrap().0.to_string();
+        assert_eq!(PnlEntry::from_csv_row(&legacy).unwrap(), entry);
+
+        // The total net profit stays unknown once a trade is unpriced
+        let mut summary = PnlSummary::default();
+        summary.add(&PnlEntry {
+            gas_cost_base: Some(5),
+            ..entry.clone()
+        });
+        assert_eq!(summary.net_profit, Some(20));
+        summary.add(&entry);
+        assert_eq!(summary.net_profit, None);
+        assert_eq!(summary.gas_cost_base, 5);
+    }
+}
@@ -0,0 +1,503 @@
+/// Price sources for the Numo strategy
+/// Abstract pool pricing so the strategy can run against the chain, the local
+/// quoter, a cache or a mock interchangeably
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethers::abi::Detokenize;
+use ethers::contract::ContractCall;
+use ethers::prelude::*;
+use tracing::warn;
+
+use numo_bindings::NumoEnginePool;
+
+use crate::multicall::{batch_marginal_prices, batch_marginal_prices_net};
+use crate::pricing::{
+    median_price, probe_mid_price, probe_mid_price_net, probe_sizes, PRICE_PROBE_AMOUNT,
+};
+use crate::quoter::LocalQuoter;
+use crate::rpc::{RpcCallCounter, PRICE_CALLS_PER_POOL};
+
+/// Prices and trade previews for Numo Engine pools
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Marginal price of a pool (base per FY, 1e18 scaled)
+    async fn marginal_price(&self, pool: Address) -> Result<U256>;
+
+    /// Marginal prices of several pools in pool order, failures included
+    async fn marginal_prices(&self, pools: &[Address]) -> Vec<(Address, Result<U256>)> {
+        let mut prices = Vec::with_capacity(pools.len());
+        for pool in pools {
+            prices.push((*pool, self.marginal_price(*pool).await));
+        }
+        prices
+    }
+
+    /// Marginal price of a pool net of its `fee_bps` fee, for comparing pools
+    ///
+    /// Sources pricing from previews take the fee out of each probe; the default
+    /// suits sources whose marginal price carries no fee, like the local quoter.
+    async fn marginal_price_net(&self, pool: Address, _fee_bps: u16) -> Result<U256> {
+        self.marginal_price(pool).await
+    }
+
+    /// Net marginal prices of several pools, each paired with its fee, in pool order
+    async fn marginal_prices_net(&self, pools: &[(Address, u16)]) -> Vec<(Address, Result<U256>)> {
+        let mut prices = Vec::with_capacity(pools.len());
+        for (pool, fee_bps) in pools {
+            prices.push((*pool, self.marginal_price_net(*pool, *fee_bps).await));
+        }
+        prices
+    }
+
+    /// Base required to buy `fy_out` FY from a pool
+    async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128>;
+
+    /// Base received for selling `fy_in` FY into a pool
+    async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128>;
+
+    /// FY received for selling `base_in` base into a pool
+    async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128>;
+}
+
+/// Prices from the pools' on-chain preview functions
+pub struct OnChainPriceSource<M> {
+    client: Arc<M>,
+
+    /// Multicall3 contract batching the price probes of all pools, if enabled
+    multicall: Option<Address>,
+
+    /// Counter of the calls made, if calls are counted
+    calls: Option<Arc<RpcCallCounter>>,
+
+    /// Block every call is made at, the latest when unset
+    block: Option<BlockId>,
+}
+
+impl<M: Middleware + 'static> OnChainPriceSource<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            multicall: None,
+            calls: None,
+            block: None,
+        }
+    }
+
+    /// Count every call made into `counter`
+    pub fn with_call_counter(mut self, counter: Arc<RpcCallCounter>) -> Self {
+        self.calls = Some(counter);
+        self
+    }
+
+    fn count(&self, calls: u64) {
+        if let Some(counter) = &self.calls {
+            counter.record(calls);
+        }
+    }
+
+    /// Price all pools in one call to the Multicall3 contract at `address`
+    pub fn with_multicall(mut self, address: Address) -> Self {
+        self.multicall = Some(address);
+        self
+    }
+
+    /// Price and preview as of `block`, one call at a time, e.g., to replay a past
+    /// block against its own state
+    pub fn at_block(mut self, block: BlockId) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    fn pool(&self, pool: Address) -> NumoEnginePool<M> {
+        NumoEnginePool::new(pool, self.client.clone())
+    }
+
+    /// Multicall3 contract to batch through, unless calls are pinned to a block
+    fn batched(&self) -> Option<Address> {
+        self.multicall.filter(|_| self.block.is_none())
+    }
+
+    /// Make `call` at the configured block
+    async fn call<D: Detokenize + Send>(&self, call: ContractCall<M, D>) -> Result<D> {
+        let call = match self.block {
+            Some(block) => call.block(block),
+            None => call,
+        };
+        Ok(call.call().await?)
+    }
+
+    /// FY received selling the probe amount of base into `pool`, and base received
+    /// selling the probe amount of FY
+    async fn probe(&self, pool: Address) -> Result<(u128, u128)> {
+        let pool = self.pool(pool);
+        let fy_out = self
+            .call(pool.sell_base_preview(PRICE_PROBE_AMOUNT))
+            .await?;
+        let base_out = self
+            .call(pool.sell_fy_token_preview(PRICE_PROBE_AMOUNT))
+            .await?;
+        Ok((fy_out, base_out))
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> PriceSource for OnChainPriceSource<M> {
+    async fn marginal_price(&self, pool: Address) -> Result<U256> {
+        self.count(PRICE_CALLS_PER_POOL);
+        let (fy_out, base_out) = self.probe(pool).await?;
+        Ok(probe_mid_price(PRICE_PROBE_AMOUNT, fy_out, base_out))
+    }
+
+    async fn marginal_prices(&self, pools: &[Address]) -> Vec<(Address, Result<U256>)> {
+        if let Some(multicall) = self.batched() {
+            self.count(1);
+            match batch_marginal_prices(self.client.clone(), multicall, pools).await {
+                Ok(prices) => return prices,
+                Err(e) => warn!(error = ?e, "Batched pricing failed, pricing pools one by one"),
+            }
+        }
+        let mut prices = Vec::with_capacity(pools.len());
+        for pool in pools {
+            prices.push((*pool, self.marginal_price(*pool).await));
+        }
+        prices
+    }
+
+    async fn marginal_price_net(&self, pool: Address, fee_bps: u16) -> Result<U256> {
+        self.count(PRICE_CALLS_PER_POOL);
+        let (fy_out, base_out) = self.probe(pool).await?;
+        Ok(probe_mid_price_net(
+            PRICE_PROBE_AMOUNT,
+            fy_out,
+            base_out,
+            fee_bps,
+        ))
+    }
+
+    async fn marginal_prices_net(&self, pools: &[(Address, u16)]) -> Vec<(Address, Result<U256>)> {
+        if let Some(multicall) = self.batched() {
+            self.count(1);
+            match batch_marginal_prices_net(self.client.clone(), multicall, pools).await {
+                Ok(prices) => return prices,
+                Err(e) => warn!(error = ?e, "Batched pricing failed, pricing pools one by one"),
+            }
+        }
+        let mut prices = Vec::with_capacity(pools.len());
+        for (pool, fee_bps) in pools {
+            prices.push((*pool, self.marginal_price_net(*pool, *fee_bps).await));
+        }
+        prices
+    }
+
+    async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+        self.count(1);
+        self.call(self.pool(pool).buy_fy_token_preview(fy_out))
+            .await
+    }
+
+    async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+        self.count(1);
+        self.call(self.pool(pool).sell_fy_token_preview(fy_in))
+            .await
+    }
+
+    async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+        self.count(1);
+        self.call(self.pool(pool).sell_base_preview(base_in)).await
+    }
+}
+
+/// Prices from local quoters over reserves snapshots, without RPC calls
+#[derive(Debug, Clone, Default)]
+pub struct QuoterPriceSource {
+    quoters: HashMap<Address, LocalQuoter>,
+}
+
+impl QuoterPriceSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quote `pool` with `quoter`
+    pub fn with_pool(mut self, pool: Address, quoter: LocalQuoter) -> Self {
+        self.quoters.insert(pool, quoter);
+        self
+    }
+
+    /// Replace the quoter for `pool`, e.g., after a state refresh
+    pub fn insert(&mut self, pool: Address, quoter: LocalQuoter) {
+        self.quoters.insert(pool, quoter);
+    }
+
+    fn quoter(&self, pool: Address) -> Result<&LocalQuoter> {
+        self.quoters
+            .get(&pool)
+            .ok_or_else(|| anyhow!("No quoter for pool {pool:?}"))
+    }
+}
+
+#[async_trait]
+impl PriceSource for QuoterPriceSource {
+    async fn marginal_price(&self, pool: Address) -> Result<U256> {
+        let price = self.quoter(pool)?.marginal_price();
+        Ok(U256::from((price * 1e18) as u128))
+    }
+
+    async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+        self.quoter(pool)?
+            .buy_fy_preview(fy_out)
+            .ok_or_else(|| anyhow!("Pool {pool:?} cannot fill a buy of {fy_out} FY"))
+    }
+
+    async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+        self.quoter(pool)?
+            .sell_fy_preview(fy_in)
+            .ok_or_else(|| anyhow!("Pool {pool:?} cannot fill a sale of {fy_in} FY"))
+    }
+
+    async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+        self.quoter(pool)?
+            .sell_base_preview(base_in)
+            .ok_or_else(|| anyhow!("Pool {pool:?} cannot fill a sale of {base_in} base"))
+    }
+}
+
+/// Preview functions memoized by [MedianProbeSource]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Preview {
+    BuyFy,
+    SellFy,
+    SellBase,
+}
+
+/// Prices each pool as the median of several probe mids, so one anomalous
+/// preview cannot skew the marginal price
+///
+/// Previews are cached per pool until the pool is priced again, so probes and
+/// later sizing previews of the same amount cost a single RPC call.
+pub struct MedianProbeSource {
+    inner: Box<dyn PriceSource>,
+    probe_sizes: Vec<u128>,
+    previews: Mutex<HashMap<(Address, Preview, u128), u128>>,
+}
+
+impl MedianProbeSource {
+    /// Price with `probes` probe sizes (see [probe_sizes]) through `inner`
+    pub fn new(inner: Box<dyn PriceSource>, probes: usize) -> Self {
+        Self {
+            inner,
+            probe_sizes: probe_sizes(probes.max(1)),
+            previews: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn cached(&self, pool: Address, preview: Preview, amount: u128) -> Result<u128> {
+        let key = (pool, preview, amount);
+        let cached = self.previews.lock().unwrap().get(&key).copied();
+        if let Some(value) = cached {
+            return Ok(value);
+        }
+        let value = match preview {
+            Preview::BuyFy => self.inner.preview_buy_fy(pool, amount).await?,
+            Preview::SellFy => self.inner.preview_sell_fy(pool, amount).await?,
+            Preview::SellBase => self.inner.preview_sell_base(pool, amount).await?,
+        };
+        self.previews.lock().unwrap().insert(key, value);
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl PriceSource for MedianProbeSource {
+    async fn marginal_price(&self, pool: Address) -> Result<U256> {
+        self.marginal_price_net(pool, 0).await
+    }
+
+    async fn marginal_price_net(&self, pool: Address, fee_bps: u16) -> Result<U256> {
+        // A new pricing pass: previous previews of this pool are stale
+        self.previews
+            .lock()
+            .unwrap()
+            .retain(|(cached_pool, _, _), _| *cached_pool != pool);
+
+        let mut mids = Vec::with_capacity(self.probe_sizes.len());
+        let mut last_error = None;
+        for amount in &self.probe_sizes {
+            let probe = async {
+                let fy_out = self.cached(pool, Preview::SellBase, *amount).await?;
+                let base_out = self.cached(pool, Preview::SellFy, *amount).await?;
+                anyhow::Ok(probe_mid_price_net(*amount, fy_out, base_out, fee_bps))
+            };
+            match probe.await {
+                Ok(mid) => mids.push(mid),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        median_price(&mut mids).ok_or_else(|| {
+            last_error.unwrap_or_else(|| anyhow!("No price probes for pool {pool:?}"))
+        })
+    }
+
+    async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+        self.cached(pool, Preview::BuyFy, fy_out).await
+    }
+
+    async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+        self.cached(pool, Preview::SellFy, fy_in).await
+    }
+
+    async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+        self.cached(pool, Preview::SellBase, base_in).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::pricing::PoolState;
+    use crate::test_utils::{encode_uints, push_pool_prices};
+
+    fn state(address: Address, base_reserves: u128, fy_reserves: u128) -> PoolState {
+        PoolState {
+            address,
+            base_reserves,
+            fy_reserves,
+            fee_bps: 5,
+            maturity: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_on_chain_source_reads_previews() {
+        let (provider, mock) = Provider::mocked();
+        let source = OnChainPriceSource::new(Arc::new(provider));
+        let pool = Address::repeat_byte(0x11);
+
+        push_pool_prices(&mock, &[500_000_000_000_000_000]);
+        let price = source.marginal_price(pool).await.unwrap();
+        assert_eq!(price, U256::from(500_000_000_000_000_000u128));
+
+        mock.push::<Bytes, _>(encode_uints(&[1_234])).unwrap();
+        assert_eq!(source.preview_buy_fy(pool, 1_000).await.unwrap(), 1_234);
+    }
+
+    #[tokio::test]
+    async fn test_on_chain_source_counts_calls() {
+        let (provider, mock) = Provider::mocked();
+        let counter = Arc::new(RpcCallCounter::new());
+        let source = OnChainPriceSource::new(Arc::new(provider)).with_call_counter(counter.clone());
+        let pool = Address::repeat_byte(0x11);
+
+        // Pricing takes two previews
+        push_pool_prices(&mock, &[500_000_000_000_000_000]);
+        source.marginal_price(pool).await.unwrap();
+        assert_eq!(counter.calls(), 2);
+
+        mock.push::<Bytes, _>(encode_uints(&[1_234])).unwrap();
+        source.preview_sell_fy(pool, 1_000).await.unwrap();
+        assert_eq!(counter.calls(), 3);
+
+        // A failed call still cost a request
+        assert!(source.preview_sell_base(pool, 1_000).await.is_err());
+        assert_eq!(counter.calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_quoter_source_matches_quoter() {
+        let pool = Address::repeat_byte(0x11);
+        let one = 10u128.pow(18);
+        let pool_state = state(pool, 1_000_000 * one, 1_050_000 * one);
+        let quoter = LocalQuoter::new(&pool_state, 1.0);
+        let source: Box<dyn PriceSource> =
+            Box::new(QuoterPriceSource::new().with_pool(pool, quoter));
+
+        let price = source.marginal_price(pool).await.unwrap();
+        assert_eq!(price, U256::from((quoter.marginal_price() * 1e18) as u128));
+
+        let amount = 1_000 * one;
+        assert_eq!(
+            source.preview_sell_fy(pool, amount).await.unwrap(),
+            quoter.sell_fy_preview(amount).unwrap()
+        );
+        assert_eq!(
+            source.preview_buy_fy(pool, amount).await.unwrap(),
+            quoter.buy_fy_preview(amount).unwrap()
+        );
+
+        // Unknown pools are an error, not a zero price
+        assert!(source
+            .marginal_price(Address::repeat_byte(0x22))
+            .await
+            .is_err());
+    }
+
+    /// Pool at a constant price of 0.5 whose FY sale preview is off at one size
+    struct OutlierSource {
+        outlier_at: u128,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl PriceSource for OutlierSource {
+        async fn marginal_price(&self, _pool: Address) -> Result<U256> {
+            Err(anyhow!("priced from previews"))
+        }
+
+        async fn preview_buy_fy(&self, _pool: Address, fy_out: u128) -> Result<u128> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(fy_out / 2)
+        }
+
+        async fn preview_sell_fy(&self, _pool: Address, fy_in: u128) -> Result<u128> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(if fy_in == self.outlier_at {
+                fy_in * 5
+            } else {
+                fy_in / 2
+            })
+        }
+
+        async fn preview_sell_base(&self, _pool: Address, base_in: u128) -> Result<u128> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(base_in * 2)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_median_of_probes_ignores_outlier() {
+        let pool = Address::repeat_byte(0x11);
+        let half = U256::from(500_000_000_000_000_000u128);
+        let sizes = probe_sizes(5);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let outlier = || {
+            Box::new(OutlierSource {
+                outlier_at: sizes[0],
+                calls: calls.clone(),
+            })
+        };
+
+        // A single probe is skewed by the bad read
+        let single = MedianProbeSource::new(outlier(), 1);
+        assert!(single.marginal_price(pool).await.unwrap() > half * 2);
+
+        calls.store(0, Ordering::SeqCst);
+        let median = MedianProbeSource::new(outlier(), 5);
+        assert_eq!(median.marginal_price(pool).await.unwrap(), half);
+        assert_eq!(calls.load(Ordering::SeqCst), 10);
+
+        // Previews of a probed amount come from the cache until the pool is repriced
+        let amount = sizes[1];
+        assert_eq!(
+            median.preview_sell_fy(pool, amount).await.unwrap(),
+            amount / 2
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 10);
+        median.marginal_price(pool).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 20);
+    }
+}
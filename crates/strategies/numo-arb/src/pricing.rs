@@ -1,18 +1,28 @@
 /// Pricing module for Numo Engine pools
 /// Calculates marginal prices and solves for optimal trade sizes
+use std::fmt;
+use std::future::Future;
+
 use anyhow::Result;
 use ethers::prelude::*;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
 
 use numo_bindings::NumoEnginePool;
 
+use crate::gas::{bid_gas_price, profit_in_native};
+use crate::quoter::{LocalQuoter, DEFAULT_TIME_STRETCH_YEARS};
+use crate::types::{ArbOpportunity, SlippageModel};
+
 /// Small amount for price discovery (1e15 = 0.001 base tokens with 18 decimals)
-const PRICE_PROBE_AMOUNT: u128 = 1_000_000_000_000_000;
+pub(crate) const PRICE_PROBE_AMOUNT: u128 = 1_000_000_000_000_000;
 
-/// Maximum iterations for bisection solver
-const MAX_BISECTION_ITERATIONS: usize = 25;
+/// Maximum iterations for the bisection solver, which makes no RPC calls and can
+/// afford to resolve the whole u128 range
+const MAX_BISECTION_ITERATIONS: usize = 128;
 
 /// Pool state snapshot
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoolState {
     pub address: Address,
     pub base_reserves: u128,
@@ -27,34 +37,120 @@ pub async fn marginal_price_base_per_fy<M: Middleware + 'static>(
     pool: &NumoEnginePool<M>,
 ) -> Result<U256> {
     // Probe 1: Sell small amount of base, see how much FY we get
-    // Price1 = base_in / fy_out (base per FY)
     let fy_out = pool.sell_base_preview(PRICE_PROBE_AMOUNT).call().await?;
-    let fy_out = fy_out.max(1); // Avoid division by zero
-
-    let one_e18 = U256::exp10(18);
-    let price1 = U256::from(PRICE_PROBE_AMOUNT) * one_e18 / U256::from(fy_out);
 
     // Probe 2: Sell small amount of FY, see how much base we get
-    // Price2 = base_out / fy_in (base per FY)
     let base_out = pool
         .sell_fy_token_preview(PRICE_PROBE_AMOUNT)
         .call()
         .await?;
-    let base_out = base_out.max(1);
 
-    let price2 = U256::from(base_out) * one_e18 / U256::from(PRICE_PROBE_AMOUNT);
+    Ok(probe_mid_price(PRICE_PROBE_AMOUNT, fy_out, base_out))
+}
+
+/// Calculate the marginal price (base per FY) of a pool net of its `fee_bps` fee
+/// Both probes pay the fee, so the raw mid of pools with different fees is
+/// skewed; this removes it for comparing pools. [marginal_price_base_per_fy]
+/// stays the price a trade actually gets.
+pub async fn marginal_price_base_per_fy_net<M: Middleware + 'static>(
+    pool: &NumoEnginePool<M>,
+    fee_bps: u16,
+) -> Result<U256> {
+    let fy_out = pool.sell_base_preview(PRICE_PROBE_AMOUNT).call().await?;
+    let base_out = pool
+        .sell_fy_token_preview(PRICE_PROBE_AMOUNT)
+        .call()
+        .await?;
+
+    Ok(probe_mid_price_net(
+        PRICE_PROBE_AMOUNT,
+        fy_out,
+        base_out,
+        fee_bps,
+    ))
+}
+
+/// Mid price (base per FY, 1e18 scaled) implied by one probe of `amount`
+///
+/// `fy_out` is the FY received for selling `amount` base and `base_out` the base
+/// received for selling `amount` FY; the mid is the average of the two prices.
+pub fn probe_mid_price(amount: u128, fy_out: u128, base_out: u128) -> U256 {
+    let one_e18 = U256::exp10(18);
+    let amount = U256::from(amount.max(1));
+
+    // Price1 = base_in / fy_out (base per FY), avoiding division by zero
+    let price1 = amount * one_e18 / U256::from(fy_out.max(1));
+    // Price2 = base_out / fy_in (base per FY)
+    let price2 = U256::from(base_out.max(1)) * one_e18 / amount;
 
-    // Return average of bid and ask
-    Ok((price1 + price2) / U256::from(2))
+    (price1 + price2) / U256::from(2)
+}
+
+/// Mid price of one probe with the fee taken out of both legs
+///
+/// The fee is charged on the amount sold, so it raises the price of selling base
+/// by `1 / (1 - fee)` and lowers the price of selling FY by `(1 - fee)`. A zero
+/// fee gives [probe_mid_price].
+pub fn probe_mid_price_net(amount: u128, fy_out: u128, base_out: u128, fee_bps: u16) -> U256 {
+    let one_e18 = U256::exp10(18);
+    let amount = U256::from(amount.max(1));
+    let bps = U256::from(10_000u64);
+    let kept = U256::from(10_000u64.saturating_sub(fee_bps as u64).max(1));
+
+    let price1 = amount * one_e18 * kept / (U256::from(fy_out.max(1)) * bps);
+    let price2 = U256::from(base_out.max(1)) * one_e18 * bps / (amount * kept);
+
+    (price1 + price2) / U256::from(2)
+}
+
+/// Probe sizes for median-of-probes pricing: the standard probe, doubled each step
+pub fn probe_sizes(count: usize) -> Vec<u128> {
+    (0..count.min(64) as u32)
+        .map(|step| PRICE_PROBE_AMOUNT.saturating_mul(1u128 << step))
+        .collect()
+}
+
+/// Median of `prices`, averaging the middle two for an even count
+pub fn median_price(prices: &mut [U256]) -> Option<U256> {
+    if prices.is_empty() {
+        return None;
+    }
+    prices.sort();
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 1 {
+        Some(prices[mid])
+    } else {
+        Some((prices[mid - 1] + prices[mid]) / U256::from(2))
+    }
 }
 
 /// Get pool state (reserves, fees, maturity)
+/// Maturity never changes for a pool, so a previously read `maturity` skips the RPC
 pub async fn get_pool_state<M: Middleware + 'static>(
     pool: &NumoEnginePool<M>,
     address: Address,
+    maturity: Option<u32>,
 ) -> Result<PoolState> {
-    let (base_reserves, fy_reserves, fee_bps) = pool.get_cache().call().await?;
-    let maturity = pool.maturity().call().await?;
+    get_pool_state_at(pool, address, maturity, None).await
+}
+
+/// Get pool state as of `block`, or the latest block when `None`
+/// Reading past blocks needs a node that keeps their state, e.g., an archive node
+pub async fn get_pool_state_at<M: Middleware + 'static>(
+    pool: &NumoEnginePool<M>,
+    address: Address,
+    maturity: Option<u32>,
+    block: Option<BlockId>,
+) -> Result<PoolState> {
+    let mut cache = pool.get_cache();
+    if let Some(block) = block {
+        cache = cache.block(block);
+    }
+    let (base_reserves, fy_reserves, fee_bps) = cache.call().await?;
+    let maturity = match maturity {
+        Some(maturity) => maturity,
+        None => pool.maturity().call().await?,
+    };
 
     Ok(PoolState {
         address,
@@ -65,14 +161,144 @@ pub async fn get_pool_state<M: Middleware + 'static>(
     })
 }
 
-/// Solve for the amount of FY tokens to trade such that the post-trade
-/// marginal price of the rich pool equals the target price
+/// Fetch the state of every pool with at most `concurrency` fetches in flight
+/// Results are returned in pool order, failures included
+pub async fn fetch_pool_states<F, Fut>(
+    pools: &[Address],
+    concurrency: usize,
+    fetch: F,
+) -> Vec<(Address, Result<PoolState>)>
+where
+    F: Fn(Address) -> Fut,
+    Fut: Future<Output = Result<PoolState>>,
+{
+    futures::stream::iter(pools.iter().copied())
+        .map(|address| {
+            let state = fetch(address);
+            async move { (address, state.await) }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Size candidate pairs with at most `concurrency` in flight
+/// Results are returned in candidate order, whatever order they complete in
+pub async fn size_candidates<C, T, F, Fut>(
+    candidates: impl IntoIterator<Item = C>,
+    concurrency: usize,
+    size: F,
+) -> Vec<T>
+where
+    F: Fn(C) -> Fut,
+    Fut: Future<Output = T>,
+{
+    futures::stream::iter(candidates)
+        .map(size)
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Index of the most profitable sized opportunity; ties go to the earliest candidate
+pub fn best_opportunity_index(sized: &[Option<ArbOpportunity>]) -> Option<usize> {
+    let mut best: Option<(usize, u128)> = None;
+    for (index, opportunity) in sized.iter().enumerate() {
+        match (opportunity, best) {
+            (Some(opportunity), Some((_, profit))) if opportunity.expected_profit <= profit => {}
+            (Some(opportunity), _) => best = Some((index, opportunity.expected_profit)),
+            (None, _) => {}
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+/// Errors from the trade size solver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverError {
+    /// An intermediate amount does not fit in a u128
+    Overflow(U256),
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::Overflow(value) => write!(f, "Solver amount {value} overflows u128"),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+/// Narrow a solver amount to u128, failing instead of truncating
+pub fn checked_u128(value: U256) -> std::result::Result<u128, SolverError> {
+    u128::try_from(value).map_err(|_| SolverError::Overflow(value))
+}
+
+/// Profit-maximising FY amount to buy from `cheap` and sell into `rich`, in closed
+/// form from the invariant, `None` when the closed form does not apply
+///
+/// It only covers fee-free pools of the same maturity, and it sizes for the two
+/// pools' prices to meet, regardless of any target price; callers bound it by
+/// [solve_fy_amount_to_target] so a trade never pushes the rich pool past its
+/// target.
+///
+/// On `x^a + y^a = k` a pool's marginal price is `(k / y^a - 1)^(t / a)`, so two
+/// pools sharing `t` trade at the same price exactly when `k / y^a` matches. Without
+/// fees the optimum is the amount equalising their prices:
+/// `(y_cheap - rho * y_rich) / (1 + rho)` with `rho = (k_cheap / k_rich)^(1 / a)`.
+/// Fees make the first-order condition non-linear, and different maturities give
+/// the pools different exponents; either case falls back to a numeric solver.
+pub fn optimal_fy_amount_closed_form(
+    cheap: &PoolState,
+    rich: &PoolState,
+    ttm: f64,
+) -> Option<u128> {
+    if cheap.maturity != rich.maturity || cheap.fee_bps != 0 || rich.fee_bps != 0 {
+        return None;
+    }
+    let t = (ttm / DEFAULT_TIME_STRETCH_YEARS).clamp(0.0, 0.99);
+    // Matured pools trade at par, there is nothing to arbitrage
+    if t <= 0.0 {
+        return None;
+    }
+    let (cheap_quoter, rich_quoter) = (LocalQuoter::new(cheap, ttm), LocalQuoter::new(rich, ttm));
+    if cheap_quoter.marginal_price() >= rich_quoter.marginal_price() {
+        return None;
+    }
+
+    let a = 1.0 - t;
+    let (x_cheap, y_cheap) = (cheap.base_reserves as f64, cheap.fy_reserves as f64);
+    let (x_rich, y_rich) = (rich.base_reserves as f64, rich.fy_reserves as f64);
+    let k_cheap = x_cheap.powf(a) + y_cheap.powf(a);
+    let k_rich = x_rich.powf(a) + y_rich.powf(a);
+    let rho = (k_cheap / k_rich).powf(1.0 / a);
+    let fy_amount = (y_cheap - rho * y_rich) / (1.0 + rho);
+    if !fy_amount.is_finite() || fy_amount < 1.0 || fy_amount >= u128::MAX as f64 {
+        return None;
+    }
+    Some(fy_amount as u128)
+}
+
+/// Marginal price (base per FY, 1e18 scaled) of `state` after `fy_sold` FY is sold
+/// into it, from its reserves and fee under the constant-power invariant
 ///
-/// This uses a simple bisection search. For production, consider implementing
-/// a local Numo Engine quoter that computes exact post-trade prices from the
-/// constant-product formula.
-pub async fn solve_fy_amount_to_target<M: Middleware + Clone + 'static>(
-    rich_pool: &NumoEnginePool<M>,
+/// A sale the reserves cannot absorb quotes a zero price.
+pub fn quote_post_trade_price(state: &PoolState, fy_sold: u128, ttm: f64) -> U256 {
+    LocalQuoter::new(state, ttm)
+        .price_after_sell_fy(fy_sold)
+        .filter(|price| price.is_finite() && *price > 0.0)
+        .map_or(U256::zero(), |price| U256::from((price * 1e18) as u128))
+}
+
+/// Solve for the amount of FY tokens to sell into the rich pool such that its
+/// post-trade marginal price, quoted from the `rich` snapshot by
+/// [quote_post_trade_price], falls to the target price
+///
+/// Fails with a [SolverError] rather than truncating an out-of-range amount.
+pub fn solve_fy_amount_to_target(
+    rich: &PoolState,
+    ttm: f64,
     target_price_1e18: U256,
     max_fy_amount: u128,
 ) -> Result<Option<u128>> {
@@ -85,36 +311,27 @@ pub async fn solve_fy_amount_to_target<M: Middleware + Clone + 'static>(
             break;
         }
 
-        let mid = ((U256::from(lo) + U256::from(hi)) / U256::from(2)).as_u128();
+        let mid = checked_u128((U256::from(lo) + U256::from(hi)) / U256::from(2))?;
         if mid == 0 {
             break;
         }
 
-        // Get current marginal price as proxy for post-trade price
-        // In production, compute exact post-trade price from updated reserves
-        let current_price = marginal_price_base_per_fy(rich_pool).await?;
-
-        tracing::debug!(
+        let post_trade_price = quote_post_trade_price(rich, mid, ttm);
+        tracing::trace!(
             iteration,
             mid,
-            current_price = %current_price,
+            post_trade_price = %post_trade_price,
             target_price = %target_price_1e18,
             "Bisection iteration"
         );
 
-        // If current price is higher than target, we need to sell more FY (push price down)
-        if current_price > target_price_1e18 {
+        // Still above target after selling `mid`, so selling more is still rich
+        if post_trade_price > target_price_1e18 {
             best = mid;
             lo = mid.saturating_add(1);
         } else {
-            // Price is already at or below target
             hi = mid.saturating_sub(1);
         }
-
-        // Check convergence
-        if hi - lo < 1000 {
-            break;
-        }
     }
 
     if best == 0 {
@@ -136,10 +353,36 @@ pub fn calculate_profit(
     (gross_profit, net_profit)
 }
 
+/// Rounding direction for integer basis-point math
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// `amount * bps / 10_000`, rounded in the given direction
+/// Computed in U256 so large amounts cannot overflow; saturates at `u128::MAX`
+pub fn bps_of(amount: u128, bps: u32, rounding: Rounding) -> u128 {
+    let numerator = U256::from(amount) * U256::from(bps);
+    let denominator = U256::from(10_000u32);
+    let mut result = numerator / denominator;
+    if rounding == Rounding::Up && !(numerator % denominator).is_zero() {
+        result += U256::one();
+    }
+    if result > U256::from(u128::MAX) {
+        u128::MAX
+    } else {
+        result.as_u128()
+    }
+}
+
 /// Calculate slippage-adjusted amounts
 /// Adds slippage_bps to maxIn, subtracts from minOut
+///
+/// The buffer rounds up, so `max_in` rounds up and `min_out` rounds down: both
+/// err on the conservative side.
 pub fn apply_slippage(amount: u128, slippage_bps: u32, is_max_in: bool) -> u128 {
-    let adjustment = (amount * slippage_bps as u128) / 10_000;
+    let adjustment = bps_of(amount, slippage_bps, Rounding::Up);
 
     if is_max_in {
         // For max_in, add slippage buffer
@@ -150,8 +393,205 @@ pub fn apply_slippage(amount: u128, slippage_bps: u32, is_max_in: bool) -> u128
     }
 }
 
+/// What the slippage models know about a trade besides its base amounts
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SlippageInputs {
+    /// Flat slippage tolerance in basis points
+    pub slippage_bps: u32,
+
+    /// Price impact of the buy leg on the cheap pool in basis points, if quotable
+    pub buy_impact_bps: Option<f64>,
+
+    /// Price impact of the sell leg on the rich pool in basis points, if quotable
+    pub sell_impact_bps: Option<f64>,
+
+    /// Recent per-block price volatility of the traded pools in basis points
+    pub volatility_bps: Option<f64>,
+}
+
+/// Slippage-protected `(max_base_in, min_base_out)` for a trade under `model`
+///
+/// Missing inputs fall back to the flat tolerance, so every model is at least as
+/// conservative as [SlippageModel::Flat].
+pub fn slippage_bounds(
+    model: SlippageModel,
+    max_base_in: u128,
+    min_base_out: u128,
+    inputs: &SlippageInputs,
+) -> (u128, u128) {
+    let flat = inputs.slippage_bps;
+    let with_impact = |impact: Option<f64>| flat.saturating_add(ceil_bps(impact.unwrap_or(0.0)));
+    let (in_bps, out_bps) = match model {
+        SlippageModel::Flat => (flat, flat),
+        SlippageModel::Quoter => (
+            with_impact(inputs.buy_impact_bps),
+            with_impact(inputs.sell_impact_bps),
+        ),
+        SlippageModel::VolScaled { reference_vol_bps } => {
+            let scale = match inputs.volatility_bps {
+                Some(vol) if vol > reference_vol_bps as f64 => vol / reference_vol_bps as f64,
+                _ => 1.0,
+            };
+            let scaled = ceil_bps(flat as f64 * scale);
+            (scaled, scaled)
+        }
+    };
+    (
+        apply_slippage(max_base_in, in_bps.min(10_000), true),
+        apply_slippage(min_base_out, out_bps.min(10_000), false),
+    )
+}
+
+/// Round a basis point amount up to a whole, saturating on non-finite values
+fn ceil_bps(bps: f64) -> u32 {
+    if bps.is_nan() || bps <= 0.0 {
+        return 0;
+    }
+    bps.ceil().min(u32::MAX as f64) as u32
+}
+
+/// Bound `fy_amount` so that buying it on the cheap pool and selling it into
+/// the rich pool moves neither pool's marginal price by more than `max_impact_bps`
+pub fn cap_fy_amount_by_price_impact(
+    fy_amount: u128,
+    cheap: &LocalQuoter,
+    rich: &LocalQuoter,
+    max_impact_bps: u32,
+) -> u128 {
+    fy_amount
+        .min(cheap.max_fy_out_for_impact(max_impact_bps, fy_amount))
+        .min(rich.max_fy_in_for_impact(max_impact_bps, fy_amount))
+}
+
+/// Base amount worth `fy_amount` FY at a 1e18-scaled marginal price, saturating
+pub fn base_for_fy_at_price(fy_amount: u128, price_1e18: U256) -> u128 {
+    let base = U256::from(fy_amount) * price_1e18 / U256::exp10(18);
+    if base > U256::from(u128::MAX) {
+        u128::MAX
+    } else {
+        base.as_u128()
+    }
+}
+
+/// How far a pool's reserves are from balanced: the larger of
+/// `base_reserves / fy_reserves` and its inverse, infinite if either side is empty
+pub fn reserve_imbalance_ratio(state: &PoolState) -> f64 {
+    if state.base_reserves == 0 || state.fy_reserves == 0 {
+        return f64::INFINITY;
+    }
+    let ratio = state.base_reserves as f64 / state.fy_reserves as f64;
+    ratio.max(1.0 / ratio)
+}
+
+/// Deviation in bps between a pool's quoted marginal price and the price its
+/// `getCache` reserves imply under the Numo invariant at `ttm` years to maturity
+///
+/// Reserves that are consistent with the pool's curve reproduce its quoted price;
+/// a large deviation means the cache is stale or the pool is not the curve we model.
+pub fn invariant_deviation_bps(state: &PoolState, ttm: f64, quoted_price: U256) -> u32 {
+    let implied = LocalQuoter::new(state, ttm).marginal_price();
+    if !implied.is_finite() || implied <= 0.0 {
+        return u32::MAX;
+    }
+    price_divergence_bps(quoted_price, U256::from((implied * 1e18) as u128))
+}
+
+/// Whether a pool's reserves satisfy the Numo invariant within `tolerance_bps`
+/// of its quoted marginal price
+pub fn satisfies_invariant(
+    state: &PoolState,
+    ttm: f64,
+    quoted_price: U256,
+    tolerance_bps: u32,
+) -> bool {
+    invariant_deviation_bps(state, ttm, quoted_price) <= tolerance_bps
+}
+
+/// Indices of the cheapest and richest prices, `None` when there are none
+///
+/// Ties resolve to the earliest index on both sides, so identical prices always
+/// select the same pool as cheap and rich.
+pub fn cheap_and_rich_indices(prices: &[U256]) -> Option<(usize, usize)> {
+    let first = *prices.first()?;
+    let (mut cheap, mut rich) = ((0, first), (0, first));
+    for (idx, price) in prices.iter().copied().enumerate().skip(1) {
+        if price < cheap.1 {
+            cheap = (idx, price);
+        }
+        if price > rich.1 {
+            rich = (idx, price);
+        }
+    }
+    Some((cheap.0, rich.0))
+}
+
+/// Minimum edge in basis points at which trading `notional` base between the two
+/// pools breaks even: both pools' fees plus the gas cost amortized over the notional
+///
+/// The gas share rounds up; an empty notional can never break even.
+pub fn break_even_edge_bps(
+    cheap: &PoolState,
+    rich: &PoolState,
+    gas_cost_base: u128,
+    notional: u128,
+) -> u32 {
+    if notional == 0 {
+        return u32::MAX;
+    }
+    let fees_bps = cheap.fee_bps as u32 + rich.fee_bps as u32;
+    let gas_bps = U256::from(gas_cost_base) * U256::from(10_000u64);
+    let gas_bps = (gas_bps + U256::from(notional - 1)) / U256::from(notional);
+    let gas_bps = gas_bps.min(U256::from(u32::MAX)).as_u32();
+    fees_bps.saturating_add(gas_bps)
+}
+
+/// Part of `base_in` a pool keeps as its fee; fees are charged on the input side,
+/// so `fee_bps` of the base paid in never reaches the reserves
+pub fn input_fee(base_in: u128, fee_bps: u16) -> u128 {
+    (U256::from(base_in) * U256::from(fee_bps) / U256::from(10_000u64)).as_u128()
+}
+
+/// Highest gas price (wei per gas) bid for the opportunity with `gas_limit` gas,
+/// spending `bid_percentage`% of its profit on fees; at 100 this is the price at
+/// which it still breaks even, i.e., its gas headroom. Unlimited for a zero gas limit
+///
+/// The profit is converted into native wei at `native_price`, base tokens per
+/// native token and the base token's decimals, and taken as native when unset.
+pub fn max_profitable_gas_price(
+    opp: &ArbOpportunity,
+    gas_limit: U256,
+    bid_percentage: u64,
+    native_price: Option<(f64, u8)>,
+) -> U256 {
+    max_gas_price_for_profit(opp.expected_profit, gas_limit, bid_percentage, native_price)
+}
+
+/// [max_profitable_gas_price] for a trade expecting `expected_profit` base, e.g.,
+/// a carry position
+pub fn max_gas_price_for_profit(
+    expected_profit: u128,
+    gas_limit: U256,
+    bid_percentage: u64,
+    native_price: Option<(f64, u8)>,
+) -> U256 {
+    if gas_limit.is_zero() {
+        return U256::MAX;
+    }
+    let profit = U256::from(expected_profit);
+    let profit = match native_price {
+        Some((price, decimals)) => profit_in_native(profit, price, decimals),
+        None => profit,
+    };
+    bid_gas_price(profit, gas_limit, bid_percentage)
+}
+
+/// Largest divergence [price_divergence_bps] reports (10,000%); anything wider is
+/// clamped to it
+pub const MAX_DIVERGENCE_BPS: u32 = 1_000_000;
+
 /// Calculate price divergence in basis points
-/// Returns how many basis points the pool price differs from target
+/// Returns how many basis points the pool price differs from target, clamped to
+/// [MAX_DIVERGENCE_BPS]
 pub fn price_divergence_bps(pool_price: U256, target_price: U256) -> u32 {
     if target_price.is_zero() {
         return 0;
@@ -163,8 +603,9 @@ pub fn price_divergence_bps(pool_price: U256, target_price: U256) -> u32 {
         target_price - pool_price
     };
 
-    let divergence = (diff * U256::from(10_000)) / target_price;
-    divergence.as_u32()
+    // Widened to 512 bits so `diff * 10_000` cannot overflow
+    let divergence = diff.full_mul(U256::from(10_000)) / U512::from(target_price);
+    divergence.min(U512::from(MAX_DIVERGENCE_BPS)).as_u32()
 }
 
 /// Check if arbitrage opportunity meets minimum edge threshold
@@ -173,9 +614,570 @@ pub fn meets_edge_threshold(pool_price: U256, target_price: U256, edge_bps: u32)
     divergence >= edge_bps
 }
 
+/// Check the edge threshold net of the pool fee
+/// The realized price of a trade includes the fee, so the divergence must exceed
+/// `edge_bps + fee_bps` for the trade to clear it
+pub fn meets_edge_threshold_after_fee(
+    pool_price: U256,
+    target_price: U256,
+    edge_bps: u32,
+    fee_bps: u16,
+) -> bool {
+    meets_edge_threshold(pool_price, target_price, edge_bps + fee_bps as u32)
+}
+
+/// Whether a reference target confirms the divergence from the primary target:
+/// the pool must clear the edge against both, on the same side of each
+pub fn curves_agree(
+    pool_price: U256,
+    target_price: U256,
+    reference_price: U256,
+    edge_bps: u32,
+) -> bool {
+    (pool_price > target_price) == (pool_price > reference_price)
+        && meets_edge_threshold(pool_price, target_price, edge_bps)
+        && meets_edge_threshold(pool_price, reference_price, edge_bps)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::encode_uints;
+    use crate::types::ArbDirection;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_net_mid_price_removes_fee_wedge() {
+        let one = 10u128.pow(18);
+        let mid = |fee_bps: u16| {
+            let state = PoolState {
+                address: Address::zero(),
+                base_reserves: 900_000 * one,
+                fy_reserves: 1_000_000 * one,
+                fee_bps,
+                maturity: 0,
+            };
+            let quoter = LocalQuoter::new(&state, 0.5);
+            let fy_out = quoter.sell_base_preview(PRICE_PROBE_AMOUNT).unwrap();
+            let base_out = quoter.sell_fy_preview(PRICE_PROBE_AMOUNT).unwrap();
+            let raw = probe_mid_price(PRICE_PROBE_AMOUNT, fy_out, base_out);
+            let net = probe_mid_price_net(PRICE_PROBE_AMOUNT, fy_out, base_out, fee_bps);
+            (
+                raw,
+                net,
+                U256::from((quoter.marginal_price() * 1e18) as u128),
+            )
+        };
+
+        // Without a fee both mids agree
+        let (raw, net, _) = mid(0);
+        assert_eq!(raw, net);
+
+        // Same reserves, different fees: the net mids match the fee-free price
+        // while the raw mids drift apart
+        let (raw_low, net_low, marginal) = mid(5);
+        let (raw_high, net_high, _) = mid(300);
+        assert_eq!(price_divergence_bps(net_low, marginal), 0);
+        assert_eq!(price_divergence_bps(net_high, marginal), 0);
+        assert_eq!(price_divergence_bps(net_high, net_low), 0);
+        assert!(price_divergence_bps(raw_high, raw_low) > 0);
+    }
+
+    #[test]
+    fn test_checked_u128_rejects_overflow() {
+        let max = U256::from(u128::MAX);
+        assert_eq!(checked_u128(max), Ok(u128::MAX));
+        assert_eq!(checked_u128(max - 1), Ok(u128::MAX - 1));
+
+        // One past u128::MAX is an error, not a truncation to 0
+        let error = checked_u128(max + 1).unwrap_err();
+        assert_eq!(error, SolverError::Overflow(max + 1));
+        assert!(error.to_string().contains("overflows u128"));
+    }
+
+    #[test]
+    fn test_post_trade_price_falls_with_fy_sold() {
+        let one = 10u128.pow(18);
+        let pool = PoolState {
+            address: Address::repeat_byte(0x22),
+            base_reserves: 818_000 * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 5,
+            maturity: 0,
+        };
+        let marginal = LocalQuoter::new(&pool, 0.5).marginal_price();
+        let unchanged = quote_post_trade_price(&pool, 0, 0.5).as_u128() as f64 / 1e18;
+        assert!((unchanged - marginal).abs() < 1e-12);
+
+        let prices: Vec<U256> = [1_000u128, 10_000, 100_000]
+            .iter()
+            .map(|fy| quote_post_trade_price(&pool, fy * one, 0.5))
+            .collect();
+        assert!(prices.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn test_quoted_solver_sells_more_as_target_drops() {
+        let one = 10u128.pow(18);
+        let ttm = 0.5;
+        let rich = PoolState {
+            address: Address::repeat_byte(0x22),
+            base_reserves: 818_000 * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity: 0,
+        };
+        let marginal = quote_post_trade_price(&rich, 0, ttm);
+
+        // Targets from 5 to 50 bps under the pool's price
+        let amounts: Vec<u128> = [5u64, 10, 20, 50]
+            .iter()
+            .map(|bps| {
+                let target = marginal * U256::from(10_000 - bps) / U256::from(10_000);
+                solve_fy_amount_to_target(&rich, ttm, target, 500_000 * one)
+                    .unwrap()
+                    .unwrap()
+            })
+            .collect();
+        assert!(amounts.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Each solved size lands the post-trade price on its target
+        let target = marginal * U256::from(9_990) / U256::from(10_000);
+        let amount = amounts[1];
+        assert!(quote_post_trade_price(&rich, amount, ttm) > target);
+        assert!(quote_post_trade_price(&rich, amount + one, ttm) <= target);
+
+        // A pool already at or below target needs no trade
+        assert_eq!(
+            solve_fy_amount_to_target(&rich, ttm, marginal, 500_000 * one).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_solver_bounds_near_u128_max() {
+        let one = 10u128.pow(18);
+        let pool = PoolState {
+            address: Address::repeat_byte(0x22),
+            base_reserves: 818_000 * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity: 0,
+        };
+
+        // Against a zero target the solver searches the whole u128 range without
+        // the midpoint sum of two near-max amounts wrapping, settling on the
+        // largest sale the reserves still quote
+        let best = solve_fy_amount_to_target(&pool, 0.5, U256::zero(), u128::MAX)
+            .unwrap()
+            .unwrap();
+        assert!(quote_post_trade_price(&pool, best, 0.5) > U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_maturity_fetched_once() {
+        let (provider, mock) = Provider::mocked();
+        let address = Address::repeat_byte(0x11);
+        let pool = NumoEnginePool::new(address, Arc::new(provider));
+
+        // Responses are served last-in first-out, so they are pushed in reverse:
+        // getCache, maturity, then getCache again
+        mock.push::<Bytes, _>(encode_uints(&[1_100, 2_100, 5]))
+            .unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[1_800_000_000]))
+            .unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[1_000, 2_000, 5]))
+            .unwrap();
+
+        let first = get_pool_state(&pool, address, None).await.unwrap();
+        assert_eq!(first.base_reserves, 1_000);
+        assert_eq!(first.maturity, 1_800_000_000);
+
+        // The cached maturity is served without another call; a second maturity()
+        // call would find no response left and fail
+        let second = get_pool_state(&pool, address, Some(first.maturity))
+            .await
+            .unwrap();
+        assert_eq!(second.base_reserves, 1_100);
+        assert_eq!(second.fy_reserves, 2_100);
+        assert_eq!(second.maturity, 1_800_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pool_states_respects_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let pools: Vec<Address> = (0..20u64).map(Address::from_low_u64_be).collect();
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        let results = fetch_pool_states(&pools, 4, |address| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(2)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(PoolState {
+                    address,
+                    base_reserves: 1_000,
+                    fy_reserves: 1_000,
+                    fee_bps: 5,
+                    maturity: 0,
+                })
+            }
+        })
+        .await;
+
+        // Every pool is fetched, in order, and never more than 4 at once
+        assert_eq!(peak.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            results
+                .iter()
+                .map(|(address, _)| *address)
+                .collect::<Vec<_>>(),
+            pools
+        );
+        assert!(results.iter().all(|(address, state)| state
+            .as_ref()
+            .map(|state| state.address == *address)
+            .unwrap_or(false)));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_sizing_selects_best_deterministically() {
+        use std::time::Duration;
+
+        let opportunity = |profit: u128| ArbOpportunity {
+            cheap_pool: Address::from_low_u64_be(profit as u64),
+            rich_pool: Address::repeat_byte(0xff),
+            fy_amount: 1_000,
+            max_base_in: 1_000,
+            min_base_out: 1_000 + profit,
+            expected_profit: profit,
+            target_price: U256::zero(),
+            cheap_price: U256::zero(),
+            rich_price: U256::zero(),
+            direction: ArbDirection::BuyFyThenSellFy,
+        };
+        // (profit, sizing delay); None profit means the pair could not be sized.
+        // Later candidates finish first, and two pairs tie for the best profit.
+        let candidates = [
+            (Some(30u128), 12u64),
+            (None, 10),
+            (Some(70), 8),
+            (Some(50), 6),
+            (Some(70), 4),
+            (Some(10), 2),
+        ];
+
+        for concurrency in [1, 3, 6] {
+            let sized = size_candidates(candidates, concurrency, |(profit, delay_ms)| async move {
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                profit.map(opportunity)
+            })
+            .await;
+
+            // Results stay in candidate order; the earliest of the tied pairs wins
+            assert_eq!(
+                sized
+                    .iter()
+                    .map(|opp| opp.as_ref().map(|opp| opp.expected_profit))
+                    .collect::<Vec<_>>(),
+                candidates
+                    .iter()
+                    .map(|(profit, _)| *profit)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(best_opportunity_index(&sized), Some(2));
+        }
+        assert_eq!(best_opportunity_index(&[None, None]), None);
+    }
+
+    #[test]
+    fn test_reserve_imbalance_ratio() {
+        let state = |base_reserves: u128, fy_reserves: u128| PoolState {
+            address: Address::zero(),
+            base_reserves,
+            fy_reserves,
+            fee_bps: 5,
+            maturity: 0,
+        };
+
+        // Balanced and mildly skewed pools
+        assert_eq!(reserve_imbalance_ratio(&state(1_000, 1_000)), 1.0);
+        assert_eq!(reserve_imbalance_ratio(&state(1_000, 1_250)), 1.25);
+        // Skew is symmetric in either direction
+        assert_eq!(reserve_imbalance_ratio(&state(50_000, 1_000)), 50.0);
+        assert_eq!(reserve_imbalance_ratio(&state(1_000, 50_000)), 50.0);
+        // A drained side is infinitely imbalanced
+        assert!(reserve_imbalance_ratio(&state(0, 1_000)).is_infinite());
+    }
+
+    /// Profit-maximising FY amount by ternary search over quoted trades
+    fn numeric_optimum(cheap: &LocalQuoter, rich: &LocalQuoter, upper: u128) -> u128 {
+        let profit = |amount: u128| -> f64 {
+            match (rich.sell_fy_preview(amount), cheap.buy_fy_preview(amount)) {
+                (Some(base_out), Some(base_in)) => base_out as f64 - base_in as f64,
+                _ => f64::MIN,
+            }
+        };
+        let (mut lo, mut hi) = (0u128, upper);
+        while hi - lo > 2 {
+            let third = (hi - lo) / 3;
+            let (m1, m2) = (lo + third, hi - third);
+            if profit(m1) < profit(m2) {
+                lo = m1;
+            } else {
+                hi = m2;
+            }
+        }
+        (lo + hi) / 2
+    }
+
+    #[test]
+    fn test_closed_form_size_matches_numeric_optimum() {
+        let one = 10u128.pow(18);
+        let state = |address: u8, base: u128, fy: u128| PoolState {
+            address: Address::repeat_byte(address),
+            base_reserves: base * one,
+            fy_reserves: fy * one,
+            fee_bps: 0,
+            maturity: 1_000,
+        };
+
+        for (cheap, rich, ttm) in [
+            (
+                state(0x11, 900_000, 1_000_000),
+                state(0x22, 960_000, 1_000_000),
+                0.5,
+            ),
+            (
+                state(0x11, 442_000, 1_000_000),
+                state(0x22, 818_000, 1_000_000),
+                0.5,
+            ),
+            // Pools of very different depth
+            (
+                state(0x11, 90_000, 100_000),
+                state(0x22, 4_800_000, 5_000_000),
+                1.0,
+            ),
+        ] {
+            let closed_form = optimal_fy_amount_closed_form(&cheap, &rich, ttm).unwrap();
+            let numeric = numeric_optimum(
+                &LocalQuoter::new(&cheap, ttm),
+                &LocalQuoter::new(&rich, ttm),
+                cheap.fy_reserves - 1,
+            );
+            let error = (closed_form as f64 - numeric as f64).abs() / numeric as f64;
+            assert!(
+                error < 1e-4,
+                "closed form {closed_form} vs numeric {numeric}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_closed_form_size_ignores_the_target() {
+        let one = 10u128.pow(18);
+        let state = |base: u128| PoolState {
+            address: Address::zero(),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity: 1_000,
+        };
+        let (cheap, rich, ttm) = (state(900_000), state(960_000), 0.5);
+
+        // A target just under the rich price is reached well before the pools meet,
+        // so the solver, not the closed form, bounds the trade
+        let rich_price = quote_post_trade_price(&rich, 0, ttm);
+        let target = rich_price * 999 / 1_000;
+        let convergence = optimal_fy_amount_closed_form(&cheap, &rich, ttm).unwrap();
+        let to_target = solve_fy_amount_to_target(&rich, ttm, target, u128::MAX)
+            .unwrap()
+            .unwrap();
+        assert!(to_target < convergence);
+        assert!(quote_post_trade_price(&rich, convergence, ttm) < target);
+    }
+
+    #[test]
+    fn test_closed_form_size_not_applicable() {
+        let state = |base: u128, fee_bps: u16, maturity: u32| PoolState {
+            address: Address::zero(),
+            base_reserves: base,
+            fy_reserves: 1_000_000,
+            fee_bps,
+            maturity,
+        };
+        let (cheap, rich) = (state(900_000, 0, 1_000), state(960_000, 0, 1_000));
+        assert!(optimal_fy_amount_closed_form(&cheap, &rich, 0.5).is_some());
+
+        // Fees, different maturities, matured pools or no divergence
+        assert_eq!(
+            optimal_fy_amount_closed_form(&state(900_000, 5, 1_000), &rich, 0.5),
+            None
+        );
+        assert_eq!(
+            optimal_fy_amount_closed_form(&cheap, &state(960_000, 0, 2_000), 0.5),
+            None
+        );
+        assert_eq!(optimal_fy_amount_closed_form(&cheap, &rich, 0.0), None);
+        assert_eq!(optimal_fy_amount_closed_form(&rich, &cheap, 0.5), None);
+    }
+
+    #[test]
+    fn test_invariant_check() {
+        let state = |base_reserves: u128, fy_reserves: u128| PoolState {
+            address: Address::zero(),
+            base_reserves,
+            fy_reserves,
+            fee_bps: 5,
+            maturity: 0,
+        };
+        let pool = state(900_000, 1_000_000);
+        let ttm = 0.5;
+        let consistent = U256::from((LocalQuoter::new(&pool, ttm).marginal_price() * 1e18) as u128);
+
+        // A quote the reserves reproduce passes a tight tolerance
+        assert!(invariant_deviation_bps(&pool, ttm, consistent) <= 1);
+        assert!(satisfies_invariant(&pool, ttm, consistent, 5));
+
+        // Swapped reserves imply a price above par, far from the quoted one
+        assert!(!satisfies_invariant(
+            &state(1_000_000, 900_000),
+            ttm,
+            consistent,
+            50
+        ));
+        // A quote 5% off the implied price fails too
+        let shifted = consistent * 105 / 100;
+        assert!(!satisfies_invariant(&pool, ttm, shifted, 50));
+        // Empty reserves never satisfy the invariant
+        assert!(!satisfies_invariant(
+            &state(0, 1_000_000),
+            ttm,
+            consistent,
+            10_000
+        ));
+    }
+
+    #[test]
+    fn test_cheap_and_rich_indices() {
+        let prices = |values: &[u64]| values.iter().map(|v| U256::from(*v)).collect::<Vec<_>>();
+
+        assert_eq!(cheap_and_rich_indices(&[]), None);
+        assert_eq!(cheap_and_rich_indices(&prices(&[7])), Some((0, 0)));
+        assert_eq!(cheap_and_rich_indices(&prices(&[5, 3, 9, 4])), Some((1, 2)));
+        // All equal: the same pool on both sides
+        assert_eq!(cheap_and_rich_indices(&prices(&[5, 5, 5])), Some((0, 0)));
+        // Ties resolve to the earliest pool
+        assert_eq!(
+            cheap_and_rich_indices(&prices(&[5, 3, 9, 3, 9])),
+            Some((1, 2))
+        );
+    }
+
+    #[test]
+    fn test_median_price() {
+        let prices = |values: &[u64]| {
+            let mut values: Vec<U256> = values.iter().map(|v| U256::from(*v)).collect();
+            median_price(&mut values)
+        };
+
+        assert_eq!(prices(&[]), None);
+        assert_eq!(prices(&[7, 1_000, 5]), Some(U256::from(7)));
+        assert_eq!(prices(&[4, 8, 6, 1_000]), Some(U256::from(7)));
+
+        assert_eq!(
+            probe_sizes(3),
+            vec![
+                PRICE_PROBE_AMOUNT,
+                2 * PRICE_PROBE_AMOUNT,
+                4 * PRICE_PROBE_AMOUNT
+            ]
+        );
+    }
+
+    #[test]
+    fn test_break_even_edge_bps() {
+        let pool = |fee_bps| PoolState {
+            address: Address::zero(),
+            base_reserves: 1_000_000,
+            fy_reserves: 1_000_000,
+            fee_bps,
+            maturity: 0,
+        };
+
+        // Fees only: 5 + 10 bps
+        assert_eq!(break_even_edge_bps(&pool(5), &pool(10), 0, 1_000_000), 15);
+        // 100 of gas over a 1M notional is 1 bp more
+        assert_eq!(break_even_edge_bps(&pool(5), &pool(10), 100, 1_000_000), 16);
+        // The same gas over a 10k notional costs 100 bps
+        assert_eq!(break_even_edge_bps(&pool(5), &pool(10), 100, 10_000), 115);
+        // Partial basis points of gas round up
+        assert_eq!(break_even_edge_bps(&pool(0), &pool(0), 101, 1_000_000), 2);
+        assert_eq!(break_even_edge_bps(&pool(5), &pool(5), 1, 0), u32::MAX);
+    }
+
+    #[test]
+    fn test_input_fee() {
+        assert_eq!(input_fee(1_000_000, 30), 3_000);
+        assert_eq!(input_fee(1_000_000, 0), 0);
+        // Rounded down, without overflowing near the u128 range
+        assert_eq!(input_fee(999, 10), 0);
+        assert_eq!(input_fee(u128::MAX, 10_000), u128::MAX);
+    }
+
+    #[test]
+    fn test_max_profitable_gas_price() {
+        let opp = |expected_profit| ArbOpportunity {
+            cheap_pool: Address::zero(),
+            rich_pool: Address::zero(),
+            fy_amount: 0,
+            max_base_in: 0,
+            min_base_out: 0,
+            expected_profit,
+            target_price: U256::zero(),
+            cheap_price: U256::zero(),
+            rich_price: U256::zero(),
+            direction: ArbDirection::BuyFyThenSellFy,
+        };
+        let gwei = 10u128.pow(9);
+
+        // 0.005 profit over 250k gas breaks even at 20 gwei
+        let gas_limit = U256::from(250_000);
+        let profit = 5 * 10u128.pow(15);
+        assert_eq!(
+            max_profitable_gas_price(&opp(profit), gas_limit, 100, None),
+            U256::from(20 * gwei)
+        );
+        // Bidding 80% of it caps the price at 16 gwei
+        assert_eq!(
+            max_profitable_gas_price(&opp(profit), gas_limit, 80, None),
+            U256::from(16 * gwei)
+        );
+        // 0.005 of an 18-decimal base at 2 base per native is 0.0025 native
+        assert_eq!(
+            max_profitable_gas_price(&opp(profit), gas_limit, 100, Some((2.0, 18))),
+            U256::from(10 * gwei)
+        );
+        // Rounds down so the break-even price never loses money
+        assert_eq!(
+            max_profitable_gas_price(&opp(499_999), gas_limit, 100, None),
+            U256::from(1)
+        );
+        assert_eq!(
+            max_profitable_gas_price(&opp(249_999), gas_limit, 100, None),
+            U256::zero()
+        );
+        assert_eq!(
+            max_profitable_gas_price(&opp(1), U256::zero(), 100, None),
+            U256::MAX
+        );
+    }
 
     #[test]
     fn test_calculate_profit() {
@@ -204,6 +1206,96 @@ mod tests {
         assert_eq!(min_out, 9_900);
     }
 
+    #[test]
+    fn test_slippage_models_give_distinct_bounds() {
+        let inputs = SlippageInputs {
+            slippage_bps: 50,
+            buy_impact_bps: Some(20.0),
+            sell_impact_bps: Some(35.5),
+            volatility_bps: Some(30.0),
+        };
+        let bounds = |model| slippage_bounds(model, 10_000, 11_000, &inputs);
+
+        // Flat: 50 bps on both legs, as apply_slippage
+        assert_eq!(bounds(SlippageModel::Flat), (10_050, 10_945));
+        // Quoter: 50 + 20 bps in, 50 + 36 bps out
+        assert_eq!(bounds(SlippageModel::Quoter), (10_070, 10_905));
+        // Vol-scaled: 30 bps volatility is 3x the 10 bps reference, so 150 bps
+        let vol_scaled = SlippageModel::VolScaled {
+            reference_vol_bps: 10,
+        };
+        assert_eq!(bounds(vol_scaled), (10_150, 10_835));
+
+        // Calm markets and missing quotes fall back to the flat tolerance
+        let calm = SlippageInputs {
+            slippage_bps: 50,
+            volatility_bps: Some(5.0),
+            ..SlippageInputs::default()
+        };
+        let flat = slippage_bounds(SlippageModel::Flat, 10_000, 11_000, &calm);
+        assert_eq!(slippage_bounds(vol_scaled, 10_000, 11_000, &calm), flat);
+        assert_eq!(
+            slippage_bounds(SlippageModel::Quoter, 10_000, 11_000, &calm),
+            flat
+        );
+    }
+
+    #[test]
+    fn test_apply_slippage_rounds_conservatively() {
+        // 9_999 * 50 / 10_000 = 49.995, which truncation would turn into 49
+        assert_eq!(apply_slippage(9_999, 50, true), 10_049);
+        assert_eq!(apply_slippage(9_999, 50, false), 9_949);
+
+        // Any nonzero slippage buffers small amounts by at least one unit
+        assert_eq!(apply_slippage(199, 50, true), 200);
+        assert_eq!(apply_slippage(199, 50, false), 198);
+
+        // Exact multiples are unaffected
+        assert_eq!(apply_slippage(10_000, 50, true), 10_050);
+        assert_eq!(apply_slippage(10_000, 0, false), 10_000);
+    }
+
+    #[test]
+    fn test_bps_of_large_amounts() {
+        // amount * bps would overflow u128
+        let amount = u128::MAX / 2;
+        assert_eq!(bps_of(amount, 10_000, Rounding::Down), amount);
+        assert_eq!(bps_of(amount, 5_000, Rounding::Down), amount / 2);
+        assert_eq!(bps_of(amount, 5_000, Rounding::Up), amount / 2 + 1);
+        assert_eq!(apply_slippage(u128::MAX, 100, true), u128::MAX);
+    }
+
+    #[test]
+    fn test_cap_fy_amount_by_price_impact() {
+        let one = 10u128.pow(18);
+        let state = |base: u128, fy: u128| PoolState {
+            address: Address::zero(),
+            base_reserves: base * one,
+            fy_reserves: fy * one,
+            fee_bps: 5,
+            maturity: 0,
+        };
+        let deep = LocalQuoter::new(&state(10_000_000, 11_000_000), 1.0);
+        let shallow = LocalQuoter::new(&state(1_000, 1_100), 1.0);
+        let fy_amount = 10_000 * one;
+
+        // Deep pools on both legs leave the size untouched
+        assert_eq!(
+            cap_fy_amount_by_price_impact(fy_amount, &deep, &deep, 50),
+            fy_amount
+        );
+
+        // A shallow rich pool binds the size on the sell leg
+        let capped = cap_fy_amount_by_price_impact(fy_amount, &deep, &shallow, 50);
+        assert!(capped < fy_amount);
+        assert_eq!(capped, shallow.max_fy_in_for_impact(50, fy_amount));
+
+        // A shallow cheap pool binds the size on the buy leg
+        let capped = cap_fy_amount_by_price_impact(fy_amount, &shallow, &deep, 50);
+        assert!(capped < fy_amount);
+        assert_eq!(capped, shallow.max_fy_out_for_impact(50, fy_amount));
+    }
+
     #[test]
     fn test_price_divergence_bps() {
         let target = U256::from(1_000_000);
@@ -217,6 +1309,26 @@ mod tests {
         assert_eq!(price_divergence_bps(pool_low, target), 50); // 50 bps
     }
 
+    #[test]
+    fn test_price_divergence_bps_clamps_extremes() {
+        let one = U256::exp10(18);
+
+        // 1000x the target is 99,900,000 bps, past the clamp
+        assert_eq!(price_divergence_bps(one * 1_000, one), MAX_DIVERGENCE_BPS);
+        // Exactly at the clamp, and just inside it
+        assert_eq!(price_divergence_bps(one * 101, one), MAX_DIVERGENCE_BPS);
+        assert_eq!(price_divergence_bps(one * 100, one), 990_000);
+
+        // diff * 10_000 beyond U256 neither overflows nor truncates
+        assert_eq!(
+            price_divergence_bps(U256::MAX, U256::one()),
+            MAX_DIVERGENCE_BPS
+        );
+        assert_eq!(price_divergence_bps(U256::MAX, U256::MAX), 0);
+        // A zero price is 100% below any target
+        assert_eq!(price_divergence_bps(U256::zero(), U256::MAX), 10_000);
+    }
+
     #[test]
     fn test_meets_edge_threshold() {
         let target = U256::from(1_000_000);
@@ -232,4 +1344,47 @@ mod tests {
         let pool2 = U256::from(1_002_000);
         assert!(meets_edge_threshold(pool2, target, edge_bps));
     }
+
+    #[test]
+    fn test_meets_edge_threshold_after_fee() {
+        let target = U256::from(1_000_000);
+
+        // 20 bps divergence clears a 15 bps edge, but not 15 bps plus a 10 bps fee
+        let pool = U256::from(1_002_000);
+        assert!(meets_edge_threshold_after_fee(pool, target, 15, 0));
+        assert!(!meets_edge_threshold_after_fee(pool, target, 15, 10));
+
+        // 25 bps clears both
+        let pool = U256::from(1_002_500);
+        assert!(meets_edge_threshold_after_fee(pool, target, 15, 10));
+    }
+
+    #[test]
+    fn test_curves_agree() {
+        let pool = U256::from(1_003_000);
+
+        // Both targets 20+ bps below the pool
+        assert!(curves_agree(
+            pool,
+            U256::from(1_000_000),
+            U256::from(1_001_000),
+            15
+        ));
+
+        // The reference sees only 10 bps
+        assert!(!curves_agree(
+            pool,
+            U256::from(1_000_000),
+            U256::from(1_002_000),
+            15
+        ));
+
+        // The reference sees the pool 20 bps cheap rather than rich
+        assert!(!curves_agree(
+            pool,
+            U256::from(1_000_000),
+            U256::from(1_005_000),
+            15
+        ));
+    }
 }
@@ -0,0 +1,81 @@
+/// Event priority for the Numo strategy
+/// Several collectors feed the engine; a block re-evaluates everything, so a
+/// lower-priority re-evaluation right after it is redundant and is debounced
+use crate::types::Event;
+
+/// Relative priority of an event, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EventPriority {
+    /// Periodic re-evaluation or curve refresh without new chain state
+    Tick,
+    /// A pending swap through a monitored pool, ahead of the block mining it
+    PendingTx,
+    /// A new block with fresh chain state
+    Block,
+}
+
+/// Priority of an event
+pub fn event_priority(event: &Event) -> EventPriority {
+    match event {
+        Event::NewBlock(_) | Event::HistoricalBlock(_) => EventPriority::Block,
+        Event::Tick(_) | Event::CurveRefresh(_) => EventPriority::Tick,
+        Event::PendingTx(_) => EventPriority::PendingTx,
+    }
+}
+
+/// Suppresses lower-priority events within `window_secs` of a higher-priority one
+#[derive(Debug, Clone)]
+pub struct EventDebouncer {
+    window_secs: u64,
+    last: Option<(EventPriority, u64)>,
+}
+
+impl EventDebouncer {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window_secs,
+            last: None,
+        }
+    }
+
+    /// Whether an event should be processed, remembering it if so
+    pub fn should_process(&mut self, priority: EventPriority, timestamp: u64) -> bool {
+        if let Some((last_priority, last_timestamp)) = self.last {
+            let within_window = timestamp.saturating_sub(last_timestamp) < self.window_secs;
+            if priority < last_priority && within_window {
+                return false;
+            }
+        }
+        self.last = Some((priority, timestamp));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_suppresses_ticks_within_window() {
+        let mut debouncer = EventDebouncer::new(5);
+
+        assert!(debouncer.should_process(EventPriority::Block, 100));
+        assert!(!debouncer.should_process(EventPriority::Tick, 100));
+        assert!(!debouncer.should_process(EventPriority::Tick, 104));
+
+        // Outside the window the tick runs, and further ticks are not debounced
+        assert!(debouncer.should_process(EventPriority::Tick, 105));
+        assert!(debouncer.should_process(EventPriority::Tick, 106));
+
+        // Higher or equal priority events always run
+        assert!(debouncer.should_process(EventPriority::Block, 106));
+        assert!(debouncer.should_process(EventPriority::Block, 107));
+    }
+
+    #[test]
+    fn test_zero_window_never_debounces() {
+        let mut debouncer = EventDebouncer::new(0);
+        assert!(debouncer.should_process(EventPriority::Block, 100));
+        assert!(debouncer.should_process(EventPriority::Tick, 100));
+    }
+}
@@ -0,0 +1,175 @@
+/// Profit denomination for the Numo strategy
+/// Converts each trade's profit from its pool's base token into a single reporting token,
+/// so deployments spanning several base tokens can aggregate PnL
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use tracing::warn;
+
+/// Exchange rates from base tokens into the profit token
+pub trait ProfitRateOracle: Send + Sync {
+    /// Profit token units per base token unit, scaled by 1e18, if known
+    fn rate(&self, base_token: Address) -> Option<U256>;
+}
+
+/// Oracle using fixed, configured rates
+#[derive(Debug, Clone)]
+pub struct FixedRateOracle {
+    profit_token: Address,
+    rates: HashMap<Address, U256>,
+}
+
+impl FixedRateOracle {
+    /// Create an oracle for `profit_token`, which always converts at 1:1
+    pub fn new(profit_token: Address) -> Self {
+        Self {
+            profit_token,
+            rates: HashMap::new(),
+        }
+    }
+
+    /// Convert `base_token` at `rate` profit token units per base unit
+    ///
+    /// The rate is per smallest unit, so it also absorbs decimal differences.
+    pub fn with_rate(mut self, base_token: Address, rate: f64) -> Self {
+        self.rates
+            .insert(base_token, U256::from((rate * 1e18) as u128));
+        self
+    }
+}
+
+impl ProfitRateOracle for FixedRateOracle {
+    fn rate(&self, base_token: Address) -> Option<U256> {
+        if base_token == self.profit_token {
+            return Some(U256::exp10(18));
+        }
+        self.rates.get(&base_token).copied()
+    }
+}
+
+/// Running profit totals, per base token and in the profit token
+pub struct ProfitLedger {
+    profit_token: Address,
+    oracle: Box<dyn ProfitRateOracle>,
+    native_totals: HashMap<Address, u128>,
+    total: U256,
+    trades: u64,
+    unconverted_trades: u64,
+}
+
+impl ProfitLedger {
+    /// Create a ledger reporting in `profit_token`
+    pub fn new(profit_token: Address, oracle: Box<dyn ProfitRateOracle>) -> Self {
+        Self {
+            profit_token,
+            oracle,
+            native_totals: HashMap::new(),
+            total: U256::zero(),
+            trades: 0,
+            unconverted_trades: 0,
+        }
+    }
+
+    /// Token all aggregate profit is denominated in
+    pub fn profit_token(&self) -> Address {
+        self.profit_token
+    }
+
+    /// Convert a profit in `base_token` units into the profit token, if a rate is known
+    pub fn convert(&self, base_token: Address, profit: u128) -> Option<U256> {
+        self.oracle
+            .rate(base_token)
+            .map(|rate| U256::from(profit) * rate / U256::exp10(18))
+    }
+
+    /// Record a trade's profit in its native base token and return it in the profit token
+    ///
+    /// The native amount is always kept; trades without a rate stay out of the
+    /// converted total.
+    pub fn record(&mut self, base_token: Address, profit: u128) -> Option<U256> {
+        *self.native_totals.entry(base_token).or_default() += profit;
+        self.trades += 1;
+
+        let converted = self.convert(base_token, profit);
+        match converted {
+            Some(amount) => self.total += amount,
+            None => {
+                warn!(base_token = ?base_token, "No profit token rate for base token");
+                self.unconverted_trades += 1;
+            }
+        }
+        converted
+    }
+
+    /// Total profit in the profit token
+    pub fn total(&self) -> U256 {
+        self.total
+    }
+
+    /// Total profit in `base_token` units
+    pub fn native_total(&self, base_token: Address) -> u128 {
+        self.native_totals.get(&base_token).copied().unwrap_or(0)
+    }
+
+    /// Number of recorded trades
+    pub fn trades(&self) -> u64 {
+        self.trades
+    }
+
+    /// Number of recorded trades missing from the converted total
+    pub fn unconverted_trades(&self) -> u64 {
+        self.unconverted_trades
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rates served from a map, standing in for a price feed
+    struct MockOracle(HashMap<Address, U256>);
+
+    impl ProfitRateOracle for MockOracle {
+        fn rate(&self, base_token: Address) -> Option<U256> {
+            self.0.get(&base_token).copied()
+        }
+    }
+
+    #[test]
+    fn test_two_base_profits_in_common_denomination() {
+        let cusd = Address::repeat_byte(0xcc);
+        let usdc = Address::repeat_byte(0x11);
+        let dai = Address::repeat_byte(0x22);
+        let one = 10u128.pow(18);
+
+        let rates = HashMap::from([
+            (usdc, U256::from(one)),
+            // Half a profit token per DAI unit
+            (dai, U256::from(one / 2)),
+        ]);
+        let mut ledger = ProfitLedger::new(cusd, Box::new(MockOracle(rates)));
+
+        assert_eq!(ledger.record(usdc, 1_000), Some(U256::from(1_000)));
+        assert_eq!(ledger.record(dai, 600), Some(U256::from(300)));
+
+        assert_eq!(ledger.total(), U256::from(1_300));
+        assert_eq!(ledger.native_total(usdc), 1_000);
+        assert_eq!(ledger.native_total(dai), 600);
+        assert_eq!(ledger.trades(), 2);
+    }
+
+    #[test]
+    fn test_missing_rate_keeps_native_profit() {
+        let cusd = Address::repeat_byte(0xcc);
+        let unknown = Address::repeat_byte(0x33);
+        let oracle = FixedRateOracle::new(cusd).with_rate(Address::repeat_byte(0x11), 1.0);
+        let mut ledger = ProfitLedger::new(cusd, Box::new(oracle));
+
+        assert_eq!(ledger.record(unknown, 500), None);
+        assert_eq!(ledger.record(cusd, 200), Some(U256::from(200)));
+
+        assert_eq!(ledger.total(), U256::from(200));
+        assert_eq!(ledger.native_total(unknown), 500);
+        assert_eq!(ledger.unconverted_trades(), 1);
+    }
+}
@@ -0,0 +1,170 @@
+/// Supervised rollout for the Numo strategy
+/// Runs in dry run for a configured window, accumulating paper PnL, and promotes
+/// to live trading only if the paper results meet the success criterion
+/// Outcome of a dry run window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromotionDecision {
+    /// Paper results met the criterion: trade live from now on
+    Promote,
+    /// Paper results fell short: keep paper trading and alert the operator
+    StayInDryRun,
+}
+
+/// Paper trades accumulated during the dry run (base token wei)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PaperResults {
+    pub trades: u64,
+    pub gross_profit: u128,
+    pub gas_cost: u128,
+}
+
+impl PaperResults {
+    /// Count a trade that would have been submitted
+    pub fn record(&mut self, expected_profit: u128, gas_cost: u128) {
+        self.trades += 1;
+        self.gross_profit = self.gross_profit.saturating_add(expected_profit);
+        self.gas_cost = self.gas_cost.saturating_add(gas_cost);
+    }
+
+    /// Gross profit net of gas, negative when gas ate the profit
+    pub fn net_profit(&self) -> i128 {
+        let gross = self.gross_profit.min(i128::MAX as u128) as i128;
+        let gas = self.gas_cost.min(i128::MAX as u128) as i128;
+        gross - gas
+    }
+}
+
+/// Promote only when the paper net profit exceeds `min_net_profit`
+pub fn promotion_decision(results: &PaperResults, min_net_profit: i128) -> PromotionDecision {
+    if results.net_profit() > min_net_profit {
+        PromotionDecision::Promote
+    } else {
+        PromotionDecision::StayInDryRun
+    }
+}
+
+/// Dry run window measured in block timestamps, decided once when it elapses
+#[derive(Debug, Clone)]
+pub struct DryRunPromotion {
+    duration_secs: u64,
+    min_net_profit: i128,
+    started_at: Option<u64>,
+    results: PaperResults,
+    decision: Option<PromotionDecision>,
+}
+
+impl DryRunPromotion {
+    pub fn new(duration_secs: u64, min_net_profit: i128) -> Self {
+        Self {
+            duration_secs,
+            min_net_profit,
+            started_at: None,
+            results: PaperResults::default(),
+            decision: None,
+        }
+    }
+
+    /// Whether the dry run passed and trades go live
+    pub fn is_live(&self) -> bool {
+        self.decision == Some(PromotionDecision::Promote)
+    }
+
+    pub fn results(&self) -> &PaperResults {
+        &self.results
+    }
+
+    pub fn decision(&self) -> Option<PromotionDecision> {
+        self.decision
+    }
+
+    /// Count a paper trade while the window is open
+    pub fn record_paper_trade(&mut self, expected_profit: u128, gas_cost: u128) {
+        if self.decision.is_none() {
+            self.results.record(expected_profit, gas_cost);
+        }
+    }
+
+    /// Observe a block; returns the decision on the block that closes the window
+    pub fn observe(&mut self, timestamp: u64) -> Option<PromotionDecision> {
+        if self.decision.is_some() {
+            return None;
+        }
+        let started_at = *self.started_at.get_or_insert(timestamp);
+        if timestamp.saturating_sub(started_at) < self.duration_secs {
+            return None;
+        }
+        let decision = promotion_decision(&self.results, self.min_net_profit);
+        self.decision = Some(decision);
+        Some(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(trades: &[(u128, u128)]) -> PaperResults {
+        let mut results = PaperResults::default();
+        for (profit, gas) in trades {
+            results.record(*profit, *gas);
+        }
+        results
+    }
+
+    #[test]
+    fn test_promotion_decision_from_paper_results() {
+        // Positive net over the window promotes
+        let winning = results(&[(100, 10), (50, 10)]);
+        assert_eq!(winning.net_profit(), 130);
+        assert_eq!(promotion_decision(&winning, 0), PromotionDecision::Promote);
+
+        // Gas eating the profit, or no trades at all, stays in dry run
+        let losing = results(&[(10, 30), (5, 30)]);
+        assert_eq!(losing.net_profit(), -45);
+        assert_eq!(
+            promotion_decision(&losing, 0),
+            PromotionDecision::StayInDryRun
+        );
+        assert_eq!(
+            promotion_decision(&PaperResults::default(), 0),
+            PromotionDecision::StayInDryRun
+        );
+
+        // A stricter criterion needs more than break-even
+        assert_eq!(
+            promotion_decision(&winning, 130),
+            PromotionDecision::StayInDryRun
+        );
+        assert_eq!(
+            promotion_decision(&winning, 129),
+            PromotionDecision::Promote
+        );
+    }
+
+    #[test]
+    fn test_decided_once_when_window_elapses() {
+        let mut promotion = DryRunPromotion::new(3_600, 0);
+        assert_eq!(promotion.observe(1_000), None);
+        promotion.record_paper_trade(100, 10);
+        assert_eq!(promotion.observe(4_599), None);
+        assert!(!promotion.is_live());
+
+        assert_eq!(promotion.observe(4_600), Some(PromotionDecision::Promote));
+        assert!(promotion.is_live());
+        assert_eq!(promotion.observe(9_000), None);
+
+        // Trades after the decision are live, not paper
+        promotion.record_paper_trade(100, 10);
+        assert_eq!(promotion.results().trades, 1);
+    }
+
+    #[test]
+    fn test_failed_window_stays_in_dry_run() {
+        let mut promotion = DryRunPromotion::new(60, 0);
+        promotion.observe(0);
+        promotion.record_paper_trade(10, 20);
+        assert_eq!(promotion.observe(60), Some(PromotionDecision::StayInDryRun));
+        assert!(!promotion.is_live());
+        assert_eq!(promotion.decision(), Some(PromotionDecision::StayInDryRun));
+    }
+}
@@ -0,0 +1,196 @@
+/// Live opportunity queue for the Numo strategy
+/// Keeps each pair's latest opportunity ranked by net profit across blocks, so the
+/// best one is at hand and pairs that stop being refreshed age out
+use std::collections::{BTreeSet, HashMap};
+
+use ethers::prelude::*;
+
+use crate::types::ArbOpportunity;
+
+/// Default maximum number of live opportunities kept
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+/// A (cheap pool, rich pool) pair
+pub type PoolPair = (Address, Address);
+
+/// An opportunity with the net profit it is ranked by and the block it was last seen
+#[derive(Debug, Clone)]
+pub struct LiveOpportunity {
+    pub opportunity: ArbOpportunity,
+    pub net_profit: i128,
+    pub updated_block: u64,
+}
+
+/// Opportunities keyed by pair and ordered by net profit
+///
+/// Updates replace a pair's entry in place. Entries not refreshed within
+/// `max_age_blocks` are evicted, and the least profitable entry makes room once
+/// the queue is full.
+#[derive(Debug, Clone)]
+pub struct OpportunityQueue {
+    entries: HashMap<PoolPair, LiveOpportunity>,
+    ranked: BTreeSet<(i128, PoolPair)>,
+    max_age_blocks: u64,
+    capacity: usize,
+}
+
+impl OpportunityQueue {
+    pub fn new(max_age_blocks: u64) -> Self {
+        Self {
+            entries: HashMap::new(),
+            ranked: BTreeSet::new(),
+            max_age_blocks,
+            capacity: DEFAULT_QUEUE_CAPACITY,
+        }
+    }
+
+    /// Keep at most `capacity` opportunities
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Insert or update the opportunity of its pair, seen at `block`
+    ///
+    /// An opportunity that no longer nets a profit removes its pair instead.
+    pub fn upsert(&mut self, opportunity: ArbOpportunity, net_profit: i128, block: u64) {
+        let pair = (opportunity.cheap_pool, opportunity.rich_pool);
+        self.remove(pair);
+        if net_profit <= 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            match self.ranked.first() {
+                Some(&(lowest, _)) if lowest >= net_profit => return,
+                Some(&(_, lowest_pair)) => {
+                    self.remove(lowest_pair);
+                }
+                None => {}
+            }
+        }
+        self.ranked.insert((net_profit, pair));
+        self.entries.insert(
+            pair,
+            LiveOpportunity {
+                opportunity,
+                net_profit,
+                updated_block: block,
+            },
+        );
+    }
+
+    /// Drop the opportunity of `pair`, if any
+    pub fn remove(&mut self, pair: PoolPair) -> Option<LiveOpportunity> {
+        let entry = self.entries.remove(&pair)?;
+        self.ranked.remove(&(entry.net_profit, pair));
+        Some(entry)
+    }
+
+    /// Drop the opportunities not refreshed within `max_age_blocks` of `block`,
+    /// returning how many were dropped
+    pub fn evict_stale(&mut self, block: u64) -> usize {
+        let stale: Vec<PoolPair> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| block.saturating_sub(entry.updated_block) > self.max_age_blocks)
+            .map(|(pair, _)| *pair)
+            .collect();
+        for pair in &stale {
+            self.remove(*pair);
+        }
+        stale.len()
+    }
+
+    /// The most profitable live opportunity; ties go to the highest pair
+    pub fn best(&self) -> Option<&LiveOpportunity> {
+        let (_, pair) = self.ranked.last()?;
+        self.entries.get(pair)
+    }
+
+    /// Live opportunities, most profitable first
+    pub fn ranked(&self) -> impl Iterator<Item = &LiveOpportunity> {
+        self.ranked
+            .iter()
+            .rev()
+            .filter_map(|(_, pair)| self.entries.get(pair))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils;
+
+    fn opportunity(cheap: u8, rich: u8) -> ArbOpportunity {
+        ArbOpportunity {
+            cheap_pool: Address::repeat_byte(cheap),
+            rich_pool: Address::repeat_byte(rich),
+            ..test_utils::opportunity()
+        }
+    }
+
+    fn ranked_profits(queue: &OpportunityQueue) -> Vec<i128> {
+        queue.ranked().map(|entry| entry.net_profit).collect()
+    }
+
+    #[test]
+    fn test_insertion_orders_by_net_profit() {
+        let mut queue = OpportunityQueue::new(10);
+        queue.upsert(opportunity(0x11, 0x22), 50, 1);
+        queue.upsert(opportunity(0x11, 0x33), 200, 1);
+        queue.upsert(opportunity(0x22, 0x33), 120, 1);
+
+        assert_eq!(ranked_profits(&queue), vec![200, 120, 50]);
+        let best = queue.best().unwrap();
+        assert_eq!(best.opportunity.rich_pool, Address::repeat_byte(0x33));
+        assert_eq!(best.opportunity.cheap_pool, Address::repeat_byte(0x11));
+    }
+
+    #[test]
+    fn test_update_replaces_pair_in_place() {
+        let mut queue = OpportunityQueue::new(10);
+        queue.upsert(opportunity(0x11, 0x22), 50, 1);
+        queue.upsert(opportunity(0x11, 0x33), 200, 1);
+
+        // The pair's new profit moves it to the top, without a second entry
+        queue.upsert(opportunity(0x11, 0x22), 300, 2);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(ranked_profits(&queue), vec![300, 200]);
+        assert_eq!(queue.best().unwrap().updated_block, 2);
+
+        // An update that no longer nets a profit drops the pair
+        queue.upsert(opportunity(0x11, 0x22), -10, 3);
+        assert_eq!(ranked_profits(&queue), vec![200]);
+    }
+
+    #[test]
+    fn test_eviction_by_age_and_capacity() {
+        let mut queue = OpportunityQueue::new(2).with_capacity(2);
+        queue.upsert(opportunity(0x11, 0x22), 50, 1);
+        queue.upsert(opportunity(0x11, 0x33), 200, 3);
+
+        // Full: a less profitable entry is refused, a more profitable one evicts
+        // the lowest
+        queue.upsert(opportunity(0x22, 0x33), 10, 3);
+        assert_eq!(ranked_profits(&queue), vec![200, 50]);
+        queue.upsert(opportunity(0x22, 0x33), 100, 3);
+        assert_eq!(ranked_profits(&queue), vec![200, 100]);
+
+        // Entries not refreshed within two blocks age out
+        queue.upsert(opportunity(0x11, 0x33), 200, 4);
+        assert_eq!(queue.evict_stale(6), 1);
+        assert_eq!(ranked_profits(&queue), vec![200]);
+        assert_eq!(queue.evict_stale(7), 1);
+        assert!(queue.is_empty());
+        assert!(queue.best().is_none());
+    }
+}
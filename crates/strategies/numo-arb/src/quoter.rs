@@ -0,0 +1,415 @@
+/// Local quoter for Numo Engine pools
+/// Reproduces the pool's constant-power invariant off-chain so that post-trade
+/// prices can be computed from a reserves snapshot without extra RPC calls
+use anyhow::{anyhow, Result};
+use ethers::types::{Address, U256};
+
+use crate::price_source::PriceSource;
+use crate::pricing::PoolState;
+
+/// Time stretch of the invariant in years: `t = time_to_maturity / TIME_STRETCH_YEARS`
+pub const DEFAULT_TIME_STRETCH_YEARS: f64 = 10.0;
+
+/// Iterations used when searching for the largest trade within a price impact limit
+const MAX_IMPACT_SEARCH_ITERATIONS: usize = 64;
+
+/// Off-chain quoter over a pool's reserves
+///
+/// The pool holds base reserves `x` and FY reserves `y` on the curve
+/// `x^(1-t) + y^(1-t) = k`, which gives a marginal price of `(x / y)^t`
+/// base per FY. Fees are charged on the input side of every trade.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalQuoter {
+    base_reserves: f64,
+    fy_reserves: f64,
+    fee: f64,
+    t: f64,
+}
+
+impl LocalQuoter {
+    /// Create a quoter from a pool snapshot and the pool's time to maturity in years
+    pub fn new(state: &PoolState, ttm: f64) -> Self {
+        Self::with_time_stretch(state, ttm, DEFAULT_TIME_STRETCH_YEARS)
+    }
+
+    /// Create a quoter with a custom time stretch (in years)
+    pub fn with_time_stretch(state: &PoolState, ttm: f64, time_stretch_years: f64) -> Self {
+        Self {
+            base_reserves: state.base_reserves as f64,
+            fy_reserves: state.fy_reserves as f64,
+            fee: state.fee_bps as f64 / 10_000.0,
+            t: (ttm / time_stretch_years).clamp(0.0, 0.99),
+        }
+    }
+
+    /// Current marginal price (base per FY) as a float
+    pub fn marginal_price(&self) -> f64 {
+        Self::price_at(self.base_reserves, self.fy_reserves, self.t)
+    }
+
+    /// Base received for selling `fy_in` FY into the pool
+    pub fn sell_fy_preview(&self, fy_in: u128) -> Option<u128> {
+        self.after_sell_fy(fy_in)
+            .map(|(x, _)| (self.base_reserves - x).max(0.0) as u128)
+    }
+
+    /// Base required to buy `fy_out` FY from the pool
+    pub fn buy_fy_preview(&self, fy_out: u128) -> Option<u128> {
+        self.after_buy_fy(fy_out)
+            .map(|(x, _)| ((x - self.base_reserves) / (1.0 - self.fee)).ceil() as u128)
+    }
+
+    /// FY received for selling `base_in` base into the pool
+    pub fn sell_base_preview(&self, base_in: u128) -> Option<u128> {
+        let a = 1.0 - self.t;
+        let x = self.base_reserves + base_in as f64 * (1.0 - self.fee);
+        let y = Self::solve_other(self.invariant(), x, a)?;
+        Some((self.fy_reserves - y).max(0.0) as u128)
+    }
+
+    /// Marginal price after selling `fy_in` FY into the pool
+    pub fn price_after_sell_fy(&self, fy_in: u128) -> Option<f64> {
+        self.after_sell_fy(fy_in)
+            .map(|(x, y)| Self::price_at(x, y, self.t))
+    }
+
+    /// Marginal price after buying `fy_out` FY from the pool
+    pub fn price_after_buy_fy(&self, fy_out: u128) -> Option<f64> {
+        self.after_buy_fy(fy_out)
+            .map(|(x, y)| Self::price_at(x, y, self.t))
+    }
+
+    /// Marginal price move in basis points from selling `fy_in` FY into the pool
+    pub fn sell_fy_impact_bps(&self, fy_in: u128) -> Option<f64> {
+        self.price_after_sell_fy(fy_in)
+            .map(|p1| impact_bps(self.marginal_price(), p1))
+    }
+
+    /// Marginal price move in basis points from buying `fy_out` FY from the pool
+    pub fn buy_fy_impact_bps(&self, fy_out: u128) -> Option<f64> {
+        self.price_after_buy_fy(fy_out)
+            .map(|p1| impact_bps(self.marginal_price(), p1))
+    }
+
+    /// Largest FY amount (up to `upper`) that can be sold into the pool while
+    /// moving the marginal price down by at most `max_impact_bps`
+    pub fn max_fy_in_for_impact(&self, max_impact_bps: u32, upper: u128) -> u128 {
+        let p0 = self.marginal_price();
+        self.search_max(upper, |amount| {
+            self.price_after_sell_fy(amount)
+                .map(|p1| impact_bps(p0, p1) <= max_impact_bps as f64)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Largest FY amount (up to `upper`) that can be bought from the pool while
+    /// moving the marginal price up by at most `max_impact_bps`
+    pub fn max_fy_out_for_impact(&self, max_impact_bps: u32, upper: u128) -> u128 {
+        let p0 = self.marginal_price();
+        self.search_max(upper, |amount| {
+            self.price_after_buy_fy(amount)
+                .map(|p1| impact_bps(p0, p1) <= max_impact_bps as f64)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Bisection for the largest amount in `[0, upper]` satisfying `within_limit`,
+    /// assuming the predicate is monotone (true for small trades, false for large)
+    fn search_max(&self, upper: u128, within_limit: impl Fn(u128) -> bool) -> u128 {
+        if within_limit(upper) {
+            return upper;
+        }
+
+        let mut lo: u128 = 0;
+        let mut hi: u128 = upper;
+        for _ in 0..MAX_IMPACT_SEARCH_ITERATIONS {
+            if hi - lo <= 1 {
+                break;
+            }
+            let mid = lo + (hi - lo) / 2;
+            if within_limit(mid) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// Reserves after selling `fy_in` FY into the pool
+    fn after_sell_fy(&self, fy_in: u128) -> Option<(f64, f64)> {
+        let a = 1.0 - self.t;
+        let y = self.fy_reserves + fy_in as f64 * (1.0 - self.fee);
+        let x = Self::solve_other(self.invariant(), y, a)?;
+        Some((x, y))
+    }
+
+    /// Reserves after buying `fy_out` FY from the pool
+    fn after_buy_fy(&self, fy_out: u128) -> Option<(f64, f64)> {
+        let a = 1.0 - self.t;
+        let y = self.fy_reserves - fy_out as f64;
+        if y <= 0.0 {
+            return None;
+        }
+        let x = Self::solve_other(self.invariant(), y, a)?;
+        Some((x, y))
+    }
+
+    fn invariant(&self) -> f64 {
+        let a = 1.0 - self.t;
+        self.base_reserves.powf(a) + self.fy_reserves.powf(a)
+    }
+
+    /// Solve `k = other^a + known^a` for `other`
+    fn solve_other(k: f64, known: f64, a: f64) -> Option<f64> {
+        let remaining = k - known.powf(a);
+        if remaining <= 0.0 {
+            return None;
+        }
+        Some(remaining.powf(1.0 / a))
+    }
+
+    fn price_at(base_reserves: f64, fy_reserves: f64, t: f64) -> f64 {
+        if fy_reserves <= 0.0 {
+            return 0.0;
+        }
+        (base_reserves / fy_reserves).powf(t)
+    }
+}
+
+/// Absolute price move from `p0` to `p1` in basis points
+fn impact_bps(p0: f64, p1: f64) -> f64 {
+    if p0 <= 0.0 {
+        return f64::INFINITY;
+    }
+    ((p1 - p0) / p0).abs() * 10_000.0
+}
+
+/// Difference between a local and an on-chain preview in bps of the on-chain
+/// preview, rounded up so that any drift reads as at least 1 bps
+pub fn preview_drift_bps(local: u128, on_chain: u128) -> u32 {
+    if local == on_chain {
+        return 0;
+    }
+    if on_chain == 0 {
+        return u32::MAX;
+    }
+    let diff = U256::from(local.abs_diff(on_chain)) * U256::from(10_000u32);
+    let on_chain = U256::from(on_chain);
+    let bps = (diff + on_chain - U256::one()) / on_chain;
+    bps.min(U256::from(u32::MAX)).as_u32()
+}
+
+/// Drift of a trade's on-chain previews from the local quoters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewDrift {
+    /// Drift of the base paid for the FY bought on the cheap pool
+    pub buy_fy_bps: u32,
+    /// Drift of the base received for the FY sold on the rich pool
+    pub sell_fy_bps: u32,
+}
+
+impl PreviewDrift {
+    /// Drift of the two legs, local quoters against on-chain `base_in` and `base_out`;
+    /// `None` if a quoter cannot fill `fy_amount`
+    pub fn of_trade(
+        cheap: &LocalQuoter,
+        rich: &LocalQuoter,
+        fy_amount: u128,
+        base_in: u128,
+        base_out: u128,
+    ) -> Option<Self> {
+        Some(Self {
+            buy_fy_bps: preview_drift_bps(cheap.buy_fy_preview(fy_amount)?, base_in),
+            sell_fy_bps: preview_drift_bps(rich.sell_fy_preview(fy_amount)?, base_out),
+        })
+    }
+
+    /// Drift of the worse of the two legs
+    pub fn max_bps(&self) -> u32 {
+        self.buy_fy_bps.max(self.sell_fy_bps)
+    }
+
+    /// Whether neither leg drifts by more than `tolerance_bps`
+    pub fn within(&self, tolerance_bps: u32) -> bool {
+        self.max_bps() <= tolerance_bps
+    }
+}
+
+/// Largest drift in bps between `quoter` and `source`'s previews of `pool` over
+/// `amounts`, for validating the local math against a node or fork in CI
+pub async fn measure_quoter_drift(
+    quoter: &LocalQuoter,
+    source: &dyn PriceSource,
+    pool: Address,
+    amounts: &[u128],
+) -> Result<u32> {
+    let unfilled = |amount| anyhow!("Local quoter cannot fill {amount} for pool {pool:?}");
+    let mut drift = 0;
+    for &amount in amounts {
+        let local = quoter
+            .buy_fy_preview(amount)
+            .ok_or_else(|| unfilled(amount))?;
+        drift = drift.max(preview_drift_bps(
+            local,
+            source.preview_buy_fy(pool, amount).await?,
+        ));
+
+        let local = quoter
+            .sell_fy_preview(amount)
+            .ok_or_else(|| unfilled(amount))?;
+        drift = drift.max(preview_drift_bps(
+            local,
+            source.preview_sell_fy(pool, amount).await?,
+        ));
+
+        let local = quoter
+            .sell_base_preview(amount)
+            .ok_or_else(|| unfilled(amount))?;
+        let on_chain = source.preview_sell_base(pool, amount).await?;
+        drift = drift.max(preview_drift_bps(local, on_chain));
+    }
+    Ok(drift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    const ONE: u128 = 1_000_000_000_000_000_000;
+
+    fn pool(base: u128, fy: u128, fee_bps: u16) -> PoolState {
+        PoolState {
+            address: Address::zero(),
+            base_reserves: base * ONE,
+            fy_reserves: fy * ONE,
+            fee_bps,
+            maturity: 0,
+        }
+    }
+
+    #[test]
+    fn test_marginal_price_below_par() {
+        let quoter = LocalQuoter::new(&pool(1_000_000, 1_100_000, 0), 1.0);
+        let price = quoter.marginal_price();
+        assert!(price < 1.0);
+        assert!(price > 0.95);
+    }
+
+    #[test]
+    fn test_trades_move_price() {
+        let quoter = LocalQuoter::new(&pool(1_000_000, 1_100_000, 5), 1.0);
+        let p0 = quoter.marginal_price();
+
+        // Selling FY pushes the price down, buying FY pushes it up
+        assert!(quoter.price_after_sell_fy(10_000 * ONE).unwrap() < p0);
+        assert!(quoter.price_after_buy_fy(10_000 * ONE).unwrap() > p0);
+
+        // Round trip loses to the fee
+        let base_in = quoter.buy_fy_preview(1_000 * ONE).unwrap();
+        let base_out = quoter.sell_fy_preview(1_000 * ONE).unwrap();
+        assert!(base_in > base_out);
+    }
+
+    #[test]
+    fn test_impact_cap_binds_on_shallow_pool() {
+        let shallow = LocalQuoter::new(&pool(1_000, 1_100, 5), 1.0);
+        let upper = 100_000 * ONE;
+
+        let max_in = shallow.max_fy_in_for_impact(50, upper);
+        let max_out = shallow.max_fy_out_for_impact(50, upper);
+        assert!(max_in > 0 && max_in < upper);
+        assert!(max_out > 0 && max_out < upper);
+
+        // The capped size stays within the limit, and a slightly larger one does not
+        let p0 = shallow.marginal_price();
+        let p1 = shallow.price_after_sell_fy(max_in).unwrap();
+        assert!(impact_bps(p0, p1) <= 50.0);
+        let p2 = shallow.price_after_sell_fy(max_in + max_in / 100).unwrap();
+        assert!(impact_bps(p0, p2) > 50.0);
+    }
+
+    #[test]
+    fn test_impact_cap_does_not_bind_on_deep_pool() {
+        let deep = LocalQuoter::new(&pool(1_000_000_000, 1_100_000_000, 5), 1.0);
+        let upper = 1_000 * ONE;
+        assert_eq!(deep.max_fy_in_for_impact(50, upper), upper);
+        assert_eq!(deep.max_fy_out_for_impact(50, upper), upper);
+    }
+
+    /// On-chain previews that drift from the local math by `drift_bps`
+    struct DriftedSource {
+        quoter: LocalQuoter,
+        drift_bps: u128,
+    }
+
+    impl DriftedSource {
+        fn drift(&self, preview: Option<u128>) -> Result<u128> {
+            let preview = preview.ok_or_else(|| anyhow!("unfilled"))?;
+            Ok(preview * (10_000 + self.drift_bps) / 10_000)
+        }
+    }
+
+    #[async_trait]
+    impl PriceSource for DriftedSource {
+        async fn marginal_price(&self, _pool: Address) -> Result<U256> {
+            Ok(U256::from((self.quoter.marginal_price() * 1e18) as u128))
+        }
+
+        async fn preview_buy_fy(&self, _pool: Address, fy_out: u128) -> Result<u128> {
+            self.drift(self.quoter.buy_fy_preview(fy_out))
+        }
+
+        async fn preview_sell_fy(&self, _pool: Address, fy_in: u128) -> Result<u128> {
+            self.drift(self.quoter.sell_fy_preview(fy_in))
+        }
+
+        async fn preview_sell_base(&self, _pool: Address, base_in: u128) -> Result<u128> {
+            self.drift(self.quoter.sell_base_preview(base_in))
+        }
+    }
+
+    #[test]
+    fn test_preview_drift_bps() {
+        assert_eq!(preview_drift_bps(1_000_000, 1_000_000), 0);
+        assert_eq!(preview_drift_bps(1_001_000, 1_000_000), 10);
+        assert_eq!(preview_drift_bps(999_000, 1_000_000), 10);
+        // Any drift at all reads as at least 1 bps
+        assert_eq!(preview_drift_bps(1_000_001, 1_000_000), 1);
+        assert_eq!(preview_drift_bps(1, 0), u32::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_quoter_drift_at_and_below_tolerance() {
+        let quoter = LocalQuoter::new(&pool(1_000_000, 1_100_000, 5), 1.0);
+        let amounts = [ONE, 1_000 * ONE, 10_000 * ONE];
+        let tolerance_bps = 10;
+        let drift = |drift_bps| {
+            let source = DriftedSource { quoter, drift_bps };
+            async move {
+                measure_quoter_drift(&quoter, &source, Address::zero(), &amounts)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert_eq!(drift(0).await, 0);
+        assert!(drift(5).await <= 5);
+        assert!(drift(tolerance_bps as u128).await <= tolerance_bps);
+        assert!(drift(tolerance_bps as u128 + 2).await > tolerance_bps);
+
+        // Trade-level check on both legs
+        let amount = 1_000 * ONE;
+        let base_in = quoter.buy_fy_preview(amount).unwrap();
+        let base_out = quoter.sell_fy_preview(amount).unwrap();
+        let exact = PreviewDrift::of_trade(&quoter, &quoter, amount, base_in, base_out).unwrap();
+        assert!(exact.within(0));
+        let drifted =
+            PreviewDrift::of_trade(&quoter, &quoter, amount, base_in, base_out * 9_980 / 10_000)
+                .unwrap();
+        assert_eq!(drifted.buy_fy_bps, 0);
+        assert!(drifted.sell_fy_bps >= 20);
+        assert!(!drifted.within(tolerance_bps));
+    }
+}
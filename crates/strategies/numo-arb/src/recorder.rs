@@ -0,0 +1,372 @@
+/// Replayable event recording for the Numo strategy
+/// Records every inbound event with the pool states, curve fingerprint and price
+/// source calls it was evaluated against, and replays sessions deterministically
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use artemis_core::types::Strategy;
+
+use crate::price_source::PriceSource;
+use crate::pricing::PoolState;
+use crate::sofr::RateCurve;
+use crate::strategy::NumoArb;
+use crate::types::{Action, Config, Event};
+
+/// A price source request, with its arguments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceCall {
+    MarginalPrice { pool: Address },
+    PreviewBuyFy { pool: Address, fy_out: u128 },
+    PreviewSellFy { pool: Address, fy_in: u128 },
+    PreviewSellBase { pool: Address, base_in: u128 },
+}
+
+/// A price source request and its outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub call: PriceCall,
+    pub result: Result<U256, String>,
+}
+
+/// One inbound event and everything the strategy read while processing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub event: Event,
+
+    /// Pool states when the event arrived, in pool list order
+    pub pool_states: Vec<PoolState>,
+
+    /// Fingerprint of the rate curve in use, see [curve_fingerprint]
+    pub curve_fingerprint: String,
+
+    /// Price source calls made while processing the event, in call order
+    pub calls: Vec<RecordedCall>,
+}
+
+/// FNV-1a hash of the curve's serialized form, as hex
+pub fn curve_fingerprint(curve: &RateCurve) -> String {
+    let bytes = serde_json::to_vec(curve).unwrap_or_default();
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Appends recorded events to a JSONL file
+#[derive(Debug, Clone)]
+pub struct EventRecorder {
+    path: PathBuf,
+}
+
+impl EventRecorder {
+    /// Create a recorder writing to `path`; the file is created on first write
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the session file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one event as a JSON line
+    pub fn record(&self, event: &RecordedEvent) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open event recording {}", self.path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(event)?)?;
+        Ok(())
+    }
+}
+
+/// Read a recorded session
+pub fn read_session(path: impl AsRef<Path>) -> Result<Vec<RecordedEvent>> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open event recording {}", path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(index, line)| {
+            serde_json::from_str(&line?).with_context(|| {
+                format!("Invalid event on line {} of {}", index + 1, path.display())
+            })
+        })
+        .collect()
+}
+
+/// Price source wrapper logging every call and its result
+pub struct RecordingPriceSource {
+    inner: Box<dyn PriceSource>,
+    calls: Arc<Mutex<Vec<RecordedCall>>>,
+}
+
+impl RecordingPriceSource {
+    /// Wrap `inner`, appending calls to `calls`
+    pub fn new(inner: Box<dyn PriceSource>, calls: Arc<Mutex<Vec<RecordedCall>>>) -> Self {
+        Self { inner, calls }
+    }
+
+    fn log<T: Into<U256> + Copy>(&self, call: PriceCall, result: &Result<T>) {
+        let result = match result {
+            Ok(value) => Ok((*value).into()),
+            Err(e) => Err(format!("{e:#}")),
+        };
+        self.calls
+            .lock()
+            .unwrap()
+            .push(RecordedCall { call, result });
+    }
+}
+
+#[async_trait]
+impl PriceSource for RecordingPriceSource {
+    async fn marginal_price(&self, pool: Address) -> Result<U256> {
+        let result = self.inner.marginal_price(pool).await;
+        self.log(PriceCall::MarginalPrice { pool }, &result);
+        result
+    }
+
+    // Logged as a plain marginal price: playback serves the recorded net price as is
+    async fn marginal_price_net(&self, pool: Address, fee_bps: u16) -> Result<U256> {
+        let result = self.inner.marginal_price_net(pool, fee_bps).await;
+        self.log(PriceCall::MarginalPrice { pool }, &result);
+        result
+    }
+
+    async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+        let result = self.inner.preview_buy_fy(pool, fy_out).await;
+        self.log(PriceCall::PreviewBuyFy { pool, fy_out }, &result);
+        result
+    }
+
+    async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+        let result = self.inner.preview_sell_fy(pool, fy_in).await;
+        self.log(PriceCall::PreviewSellFy { pool, fy_in }, &result);
+        result
+    }
+
+    async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+        let result = self.inner.preview_sell_base(pool, base_in).await;
+        self.log(PriceCall::PreviewSellBase { pool, base_in }, &result);
+        result
+    }
+}
+
+/// Price source serving recorded calls instead of making RPC calls
+///
+/// Calls are matched by request rather than position, so concurrent sizing may
+/// interleave them differently than during recording.
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackPriceSource {
+    calls: Arc<Mutex<VecDeque<RecordedCall>>>,
+}
+
+impl PlaybackPriceSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue the calls recorded for the next event, dropping any left over
+    pub fn load(&self, calls: Vec<RecordedCall>) {
+        *self.calls.lock().unwrap() = calls.into();
+    }
+
+    /// Number of recorded calls not yet served
+    pub fn remaining(&self) -> usize {
+        self.calls.lock().unwrap().len()
+    }
+
+    fn serve(&self, call: PriceCall) -> Result<U256> {
+        let mut calls = self.calls.lock().unwrap();
+        let position = calls
+            .iter()
+            .position(|recorded| recorded.call == call)
+            .ok_or_else(|| anyhow!("Replay diverged: no recorded result for {call:?}"))?;
+        let recorded = calls.remove(position).expect("position is in range");
+        recorded.result.map_err(|e| anyhow!(e))
+    }
+}
+
+#[async_trait]
+impl PriceSource for PlaybackPriceSource {
+    async fn marginal_price(&self, pool: Address) -> Result<U256> {
+        self.serve(PriceCall::MarginalPrice { pool })
+    }
+
+    async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+        Ok(self
+            .serve(PriceCall::PreviewBuyFy { pool, fy_out })?
+            .as_u128())
+    }
+
+    async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+        Ok(self
+            .serve(PriceCall::PreviewSellFy { pool, fy_in })?
+            .as_u128())
+    }
+
+    async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+        Ok(self
+            .serve(PriceCall::PreviewSellBase { pool, base_in })?
+            .as_u128())
+    }
+}
+
+/// Actions the strategy emitted for a replayed event
+#[derive(Debug, Clone)]
+pub struct ReplayedEvent {
+    pub event: Event,
+    pub actions: Vec<Action>,
+}
+
+/// Feed a recorded session back through a fresh strategy
+///
+/// The strategy reads prices from the recording and gets a mock provider, so
+/// nothing touches the network; gas estimates fall back to the default limit.
+pub async fn replay_session(
+    events: Vec<RecordedEvent>,
+    mut config: Config,
+    curve: RateCurve,
+) -> Result<Vec<ReplayedEvent>> {
+    config.record_events_path = None;
+    // Reserves come from the recording, not the mock provider
+    config.state_refresh_blocks = None;
+    let fingerprint = curve_fingerprint(&curve);
+    let (provider, _mock) = Provider::mocked();
+    let playback = PlaybackPriceSource::new();
+    let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+        .with_price_source(Box::new(playback.clone()));
+
+    let mut replayed = Vec::with_capacity(events.len());
+    for recorded in events {
+        if recorded.curve_fingerprint != fingerprint {
+            warn!(
+                recorded = %recorded.curve_fingerprint,
+                replay = %fingerprint,
+                "Replay curve differs from the recorded one"
+            );
+        }
+        strategy.restore_pool_states(recorded.pool_states);
+        playback.load(recorded.calls);
+
+        let actions = strategy.process_event(recorded.event.clone()).await;
+        if playback.remaining() > 0 {
+            warn!(
+                unused_calls = playback.remaining(),
+                "Replay diverged: strategy made fewer price calls than recorded"
+            );
+        }
+        replayed.push(ReplayedEvent {
+            event: recorded.event,
+            actions,
+        });
+    }
+    Ok(replayed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_source::QuoterPriceSource;
+    use crate::quoter::LocalQuoter;
+    use crate::types::NewBlockEvent;
+
+    const NOW: u64 = 1_700_000_000;
+    const HALF_YEAR_SECS: u64 = 182 * 24 * 3600;
+
+    fn pool(address: Address, base: u128, fy: u128) -> PoolState {
+        PoolState {
+            address,
+            base_reserves: base * 10u128.pow(18),
+            fy_reserves: fy * 10u128.pow(18),
+            fee_bps: 0,
+            maturity: (NOW + HALF_YEAR_SECS) as u32,
+        }
+    }
+
+    fn calldata(actions: &[Action]) -> Vec<Option<Bytes>> {
+        actions
+            .iter()
+            .map(|action| match action {
+                Action::SubmitTx(submit) | Action::Approve(submit) => submit.tx.data().cloned(),
+                Action::Cancel(_) => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_recorded_session_replays_identically() {
+        let path = std::env::temp_dir().join(format!("numo-record-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // Cheap pool prices FY near 0.96, rich near 0.99, against a target near 0.977
+        let cheap = pool(Address::repeat_byte(0x11), 442_000, 1_000_000);
+        let rich = pool(Address::repeat_byte(0x22), 818_000, 1_000_000);
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * 10u128.pow(18),
+            record_events_path: Some(path.clone()),
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(NOW, cheap.maturity);
+        let source = QuoterPriceSource::new()
+            .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config.clone(), curve.clone())
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+
+        let mut recorded_actions = vec![];
+        for block_number in 1..=3 {
+            let event = Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp: NOW + block_number * 5,
+                base_fee: None,
+            });
+            recorded_actions.push(calldata(&strategy.process_event(event).await));
+        }
+        assert!(recorded_actions.iter().all(|actions| actions.len() == 1));
+
+        let session = read_session(&path).unwrap();
+        assert_eq!(session.len(), 3);
+        assert_eq!(session[0].pool_states.len(), 2);
+        assert_eq!(session[0].curve_fingerprint, curve_fingerprint(&curve));
+        assert!(!session[0].calls.is_empty());
+
+        let replayed = replay_session(session, config, curve).await.unwrap();
+        let replayed_actions: Vec<_> = replayed.iter().map(|r| calldata(&r.actions)).collect();
+        assert_eq!(replayed_actions, recorded_actions);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_playback_detects_divergence() {
+        let pool = Address::repeat_byte(0x11);
+        let playback = PlaybackPriceSource::new();
+        playback.load(vec![RecordedCall {
+            call: PriceCall::PreviewSellFy { pool, fy_in: 10 },
+            result: Ok(U256::from(9)),
+        }]);
+
+        assert!(playback.preview_sell_fy(pool, 11).await.is_err());
+        assert_eq!(playback.preview_sell_fy(pool, 10).await.unwrap(), 9);
+        assert_eq!(playback.remaining(), 0);
+    }
+}
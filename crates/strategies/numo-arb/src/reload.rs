@@ -0,0 +1,163 @@
+/// Config hot-reload for the Numo strategy
+/// Applies the settings that are safe to change mid-flight from a freshly read
+/// config, leaving the pool list, router and startup-built components untouched
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::types::Config;
+
+/// Config shared between the strategy and the reload handler
+pub type SharedConfig = Arc<RwLock<Config>>;
+
+/// A setting changed by a reload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+    }
+}
+
+/// Copy the reloadable settings of `reloaded` into `current`, returning what changed
+///
+/// Only the edges (`edge_bps`, its per-direction overrides and the fair value band's
+/// `enter_bps` and `exit_bps`), `slippage_bps`, `max_fy_amount`, `max_base_amount`,
+/// `max_price_impact_bps` and `disabled_pools` are reloaded; everything else keeps
+/// its startup value.
+pub fn apply_reload(current: &mut Config, reloaded: &Config) -> Vec<ConfigChange> {
+    let mut changes = vec![];
+    macro_rules! reload {
+        ($($field:ident),*) => {
+            $(
+                if current.$field != reloaded.$field {
+                    changes.push(ConfigChange {
+                        field: stringify!($field),
+                        old: format!("{:?}", current.$field),
+                        new: format!("{:?}", reloaded.$field),
+                    });
+                    current.$field = reloaded.$field;
+                }
+            )*
+        };
+    }
+    reload!(
+        edge_bps,
+        edge_bps_buy_then_sell,
+        edge_bps_sell_base,
+        enter_bps,
+        exit_bps,
+        slippage_bps,
+        max_fy_amount,
+        max_base_amount,
+        max_price_impact_bps
+    );
+    if current.disabled_pools != reloaded.disabled_pools {
+        changes.push(ConfigChange {
+            field: "disabled_pools",
+            old: format!("{:?}", current.disabled_pools),
+            new: format!("{:?}", reloaded.disabled_pools),
+        });
+        current.disabled_pools = reloaded.disabled_pools.clone();
+    }
+    changes
+}
+
+/// Settings that differ in `reloaded` but need a restart to take effect
+pub fn ignored_changes(current: &Config, reloaded: &Config) -> Vec<&'static str> {
+    let mut ignored = vec![];
+    if current.router_address != reloaded.router_address {
+        ignored.push("router_address");
+    }
+    if current.pool_addresses != reloaded.pool_addresses {
+        ignored.push("pool_addresses");
+    }
+    ignored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::prelude::*;
+
+    #[test]
+    fn test_apply_reload_updates_only_safe_fields() {
+        let mut current = Config::default();
+        let reloaded = Config {
+            edge_bps: current.edge_bps + 5,
+            max_fy_amount: 42,
+            pool_addresses: vec![Address::repeat_byte(0x11)],
+            bid_percentage: current.bid_percentage + 1,
+            ..Config::default()
+        };
+        let old_edge = current.edge_bps;
+
+        let changes = apply_reload(&mut current, &reloaded);
+        let fields: Vec<_> = changes.iter().map(|change| change.field).collect();
+        assert_eq!(fields, vec!["edge_bps", "max_fy_amount"]);
+        assert_eq!(
+            changes[0].to_string(),
+            format!("edge_bps: {old_edge} -> {}", old_edge + 5)
+        );
+
+        assert_eq!(current.edge_bps, reloaded.edge_bps);
+        assert_eq!(current.max_fy_amount, 42);
+        assert!(current.pool_addresses.is_empty());
+        assert_eq!(current.bid_percentage, Config::default().bid_percentage);
+        assert_eq!(ignored_changes(&current, &reloaded), vec!["pool_addresses"]);
+
+        // Reloading the same values again changes nothing
+        assert!(apply_reload(&mut current, &reloaded).is_empty());
+    }
+
+    #[test]
+    fn test_apply_reload_updates_every_edge() {
+        let mut current = Config::default();
+        let reloaded = Config {
+            edge_bps_buy_then_sell: Some(40),
+            edge_bps_sell_base: Some(15),
+            enter_bps: Some(30),
+            exit_bps: Some(10),
+            ..Config::default()
+        };
+
+        let changes = apply_reload(&mut current, &reloaded);
+        let fields: Vec<_> = changes.iter().map(|change| change.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "edge_bps_buy_then_sell",
+                "edge_bps_sell_base",
+                "enter_bps",
+                "exit_bps"
+            ]
+        );
+        assert_eq!(current.band_thresholds(), Some((30, 10)));
+
+        // Unsetting them falls back to edge_bps and disables the band
+        apply_reload(&mut current, &Config::default());
+        assert_eq!(current.edge_bps_buy_then_sell, None);
+        assert_eq!(current.band_thresholds(), None);
+    }
+
+    #[test]
+    fn test_apply_reload_toggles_disabled_pools() {
+        let pool = Address::repeat_byte(0x11);
+        let mut current = Config::default();
+        let disabled = Config {
+            disabled_pools: vec![pool],
+            ..Config::default()
+        };
+
+        let changes = apply_reload(&mut current, &disabled);
+        assert_eq!(changes[0].field, "disabled_pools");
+        assert_eq!(current.disabled_pools, vec![pool]);
+
+        apply_reload(&mut current, &Config::default());
+        assert!(current.disabled_pools.is_empty());
+    }
+}
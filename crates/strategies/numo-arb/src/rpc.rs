@@ -0,0 +1,75 @@
+/// RPC call accounting for the Numo strategy
+/// Counts the pricing and preview calls made against the node each block, so the
+/// per-block cost is observable and can be held to a soft budget
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// `eth_call`s needed to price one pool: a base and an FY probe preview
+pub const PRICE_CALLS_PER_POOL: u64 = 2;
+
+/// Calls made since the counter was last taken
+#[derive(Debug, Default)]
+pub struct RpcCallCounter {
+    calls: AtomicU64,
+}
+
+impl RpcCallCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count `calls` calls
+    pub fn record(&self, calls: u64) {
+        self.calls.fetch_add(calls, Ordering::Relaxed);
+    }
+
+    /// Calls counted so far
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Calls counted so far, restarting the count
+    pub fn take(&self) -> u64 {
+        self.calls.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// How many of `pools` can be priced fresh with `max_calls` per block once `used`
+/// calls have been made
+///
+/// A batched (multicall) pricing pass costs a single call whatever the pool count.
+pub fn pools_within_budget(pools: usize, used: u64, max_calls: u64, batched: bool) -> usize {
+    let remaining = max_calls.saturating_sub(used);
+    if batched {
+        return if remaining > 0 { pools } else { 0 };
+    }
+    pools.min((remaining / PRICE_CALLS_PER_POOL) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_counts_until_taken() {
+        let counter = RpcCallCounter::new();
+        counter.record(PRICE_CALLS_PER_POOL);
+        counter.record(1);
+        assert_eq!(counter.calls(), 3);
+
+        assert_eq!(counter.take(), 3);
+        assert_eq!(counter.calls(), 0);
+    }
+
+    #[test]
+    fn test_pools_within_budget() {
+        // Two calls per pool
+        assert_eq!(pools_within_budget(5, 0, 100, false), 5);
+        assert_eq!(pools_within_budget(5, 0, 5, false), 2);
+        assert_eq!(pools_within_budget(5, 4, 5, false), 0);
+        assert_eq!(pools_within_budget(5, 10, 5, false), 0);
+
+        // One call for the whole batch
+        assert_eq!(pools_within_budget(5, 4, 5, true), 5);
+        assert_eq!(pools_within_budget(5, 5, 5, true), 0);
+    }
+}
@@ -0,0 +1,152 @@
+/// Pool pricing schedule for the Numo strategy
+/// Bounds the pools priced fresh each block for large deployments, rotating through
+/// the full set over several blocks while keeping recently divergent pools in view
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+
+/// Blocks a pool stays prioritized after it last diverged from its target
+pub const DEFAULT_HOT_POOL_BLOCKS: u64 = 10;
+
+/// Round-robin choice of at most `pools_per_block` pools to price each block
+///
+/// Pools seen diverging from their target by at least the edge are "hot" and
+/// priced every block until they calm down for `hot_blocks` blocks. One slot is
+/// always left to the rotation, so every pool is eventually priced even while hot
+/// pools fill the rest.
+#[derive(Debug, Clone)]
+pub struct PoolScheduler {
+    pools_per_block: usize,
+
+    /// Blocks a pool stays hot after its last large spread
+    hot_blocks: u64,
+
+    /// Position of the next pool in the rotation
+    cursor: usize,
+
+    /// Last block each hot pool showed a large spread in
+    hot: HashMap<Address, u64>,
+}
+
+impl PoolScheduler {
+    pub fn new(pools_per_block: usize) -> Self {
+        Self {
+            pools_per_block: pools_per_block.max(1),
+            hot_blocks: DEFAULT_HOT_POOL_BLOCKS,
+            cursor: 0,
+            hot: HashMap::new(),
+        }
+    }
+
+    /// Keep pools prioritized for `hot_blocks` blocks after a large spread
+    pub fn with_hot_blocks(mut self, hot_blocks: u64) -> Self {
+        self.hot_blocks = hot_blocks;
+        self
+    }
+
+    /// Record that `pool` showed a large spread in `block`
+    pub fn mark_hot(&mut self, pool: Address, block: u64) {
+        self.hot.insert(pool, block);
+    }
+
+    /// Whether `pool` showed a large spread within the last `hot_blocks` blocks
+    pub fn is_hot(&self, pool: Address, block: u64) -> bool {
+        self.hot
+            .get(&pool)
+            .is_some_and(|seen| block.saturating_sub(*seen) < self.hot_blocks)
+    }
+
+    /// Pools of `eligible` to price in `block`, in `eligible` order: hot pools
+    /// first, the rest of the slots filled by the rotation
+    pub fn select(&mut self, eligible: &[Address], block: u64) -> Vec<Address> {
+        if eligible.len() <= self.pools_per_block {
+            return eligible.to_vec();
+        }
+        self.hot
+            .retain(|_, seen| block.saturating_sub(*seen) < self.hot_blocks);
+
+        let hot_slots = self.pools_per_block - 1;
+        let mut selected: Vec<Address> = eligible
+            .iter()
+            .filter(|pool| self.is_hot(**pool, block))
+            .take(hot_slots)
+            .copied()
+            .collect();
+
+        let start = self.cursor % eligible.len();
+        let mut rotated = 0;
+        for pool in eligible.iter().cycle().skip(start).take(eligible.len()) {
+            if selected.len() >= self.pools_per_block {
+                break;
+            }
+            rotated += 1;
+            if !selected.contains(pool) {
+                selected.push(*pool);
+            }
+        }
+        self.cursor = (start + rotated) % eligible.len();
+
+        eligible
+            .iter()
+            .filter(|pool| selected.contains(pool))
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn pools(n: u8) -> Vec<Address> {
+        (1..=n).map(Address::repeat_byte).collect()
+    }
+
+    #[test]
+    fn test_rotation_prices_every_pool() {
+        let all = pools(7);
+        let mut scheduler = PoolScheduler::new(3);
+
+        // Seven pools, three a block: all are priced within three blocks
+        let mut seen = HashSet::new();
+        for block in 1..=3 {
+            let selected = scheduler.select(&all, block);
+            assert_eq!(selected.len(), 3);
+            seen.extend(selected);
+        }
+        assert_eq!(seen.len(), 7);
+
+        // Few enough pools are all priced every block
+        assert_eq!(PoolScheduler::new(8).select(&all, 1), all);
+    }
+
+    #[test]
+    fn test_hot_pools_priced_every_block() {
+        let all = pools(6);
+        let hot = all[1];
+        let mut scheduler = PoolScheduler::new(2).with_hot_blocks(3);
+        scheduler.mark_hot(hot, 1);
+
+        // The hot pool takes a slot every block while the rotation still moves on
+        let mut seen = HashSet::new();
+        for block in 1..=3 {
+            let selected = scheduler.select(&all, block);
+            assert!(selected.contains(&hot));
+            seen.extend(selected);
+        }
+        assert_eq!(seen.len(), 4);
+
+        // Once calm for three blocks it only comes around with the rotation
+        assert!(!scheduler.is_hot(hot, 4));
+        let selected = scheduler.select(&all, 4);
+        assert!(!selected.contains(&hot));
+        assert_eq!(selected.len(), 2);
+
+        // Over enough blocks every pool is priced anyway
+        for block in 5..=7 {
+            seen.extend(scheduler.select(&all, block));
+        }
+        assert_eq!(seen.len(), 6);
+    }
+}
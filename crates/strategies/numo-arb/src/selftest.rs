@@ -0,0 +1,309 @@
+/// Startup self-test for the Numo strategy
+/// Checks the node, pools, router, rate curve and wallet before the engine starts,
+/// so misconfiguration fails fast with a report of everything that is wrong
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use ethers::prelude::*;
+use tracing::{info, warn};
+
+use crate::sofr::RateCurve;
+use crate::types::Config;
+use numo_bindings::NumoEnginePool;
+
+/// Outcome of a single self-test check
+#[derive(Debug, Clone)]
+pub struct CheckOutcome {
+    /// What was checked, e.g., `pool 0x…`
+    pub name: String,
+
+    /// Why the check failed, if it did
+    pub error: Option<String>,
+}
+
+/// Outcomes of every self-test check, in the order they ran
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    pub checks: Vec<CheckOutcome>,
+}
+
+impl SelfTestReport {
+    fn push(&mut self, name: impl Into<String>, outcome: Result<()>) {
+        self.checks.push(CheckOutcome {
+            name: name.into(),
+            error: outcome.err().map(|e| format!("{e:#}")),
+        });
+    }
+
+    /// Checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &CheckOutcome> {
+        self.checks.iter().filter(|check| check.error.is_some())
+    }
+
+    /// Whether every check passed
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Fail with the full report unless every check passed
+    pub fn into_result(self) -> Result<()> {
+        if self.passed() {
+            return Ok(());
+        }
+        bail!("Startup self-test failed:\n{self}")
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.error {
+                Some(error) => writeln!(f, "  FAIL {}: {}", check.name, error)?,
+                None => writeln!(f, "  ok   {}", check.name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that the node answers and, if `expected` is set, is on that chain
+pub async fn check_chain<M: Middleware>(client: &M, expected: Option<u64>) -> Result<()> {
+    let chain_id = client
+        .get_chainid()
+        .await
+        .map_err(|e| anyhow!("Node unreachable: {e}"))?;
+    match expected {
+        Some(expected) if chain_id != U256::from(expected) => {
+            bail!("Connected to chain {chain_id}, expected {expected}")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that `address` has contract code deployed
+pub async fn check_code<M: Middleware>(client: &M, address: Address) -> Result<()> {
+    let code = client
+        .get_code(address, None)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch code: {e}"))?;
+    if code.is_empty() {
+        bail!("No contract code at {address:?}");
+    }
+    Ok(())
+}
+
+/// Check that a pool answers `getCache` and `maturity`
+pub async fn check_pool<M: Middleware + 'static>(client: Arc<M>, address: Address) -> Result<()> {
+    let pool = NumoEnginePool::new(address, client);
+    pool.get_cache()
+        .call()
+        .await
+        .map_err(|e| anyhow!("getCache failed: {e}"))?;
+    pool.maturity()
+        .call()
+        .await
+        .map_err(|e| anyhow!("maturity failed: {e}"))?;
+    Ok(())
+}
+
+/// Check that `account` holds native currency to pay for gas
+pub async fn check_balance<M: Middleware>(client: &M, account: Address) -> Result<()> {
+    let balance = client
+        .get_balance(account, None)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch balance: {e}"))?;
+    if balance.is_zero() {
+        bail!("Account {account:?} has no native balance for gas");
+    }
+    Ok(())
+}
+
+/// Check that the curve has finite, strictly increasing knots with positive
+/// discount factors
+pub fn check_curve(curve: &RateCurve) -> Result<()> {
+    if curve.knots.is_empty() {
+        bail!("Curve has no knots");
+    }
+    let mut previous_t = f64::NEG_INFINITY;
+    for knot in &curve.knots {
+        if !knot.t.is_finite() || !knot.rate.is_finite() {
+            bail!("Knot at t={} has a non-finite value", knot.t);
+        }
+        if knot.t < 0.0 || knot.t <= previous_t {
+            bail!(
+                "Knot tenors must be non-negative and increasing, got t={}",
+                knot.t
+            );
+        }
+        let df = curve.discount_factor(knot.t);
+        if !df.is_finite() || df <= 0.0 {
+            bail!("Discount factor at t={} is {df}", knot.t);
+        }
+        previous_t = knot.t;
+    }
+    Ok(())
+}
+
+/// Run every startup check, collecting all failures rather than stopping at the first
+///
+/// The balance check only runs when a `signer` is given.
+pub async fn run_self_test<M: Middleware + 'static>(
+    client: Arc<M>,
+    config: &Config,
+    curve: &RateCurve,
+    signer: Option<Address>,
+) -> SelfTestReport {
+    let mut report = SelfTestReport::default();
+
+    let chain = check_chain(client.as_ref(), config.chain_id).await;
+    let reachable = chain.is_ok();
+    report.push("chain", chain);
+    report.push("curve", check_curve(curve));
+    if !reachable {
+        // Every remaining check needs the node
+        return report;
+    }
+
+    report.push(
+        format!("router {:?}", config.router_address),
+        check_code(client.as_ref(), config.router_address).await,
+    );
+    for pool in &config.pool_addresses {
+        report.push(
+            format!("pool {pool:?}"),
+            check_pool(client.clone(), *pool).await,
+        );
+    }
+    if let Some(signer) = signer {
+        report.push(
+            format!("balance {signer:?}"),
+            check_balance(client.as_ref(), signer).await,
+        );
+    }
+
+    for check in report.failures() {
+        warn!(check = %check.name, error = ?check.error, "Self-test check failed");
+    }
+    if report.passed() {
+        info!(checks = report.checks.len(), "Self-test passed");
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sofr::{CurveKnot, DayCount};
+    use crate::test_utils::encode_uints;
+
+    #[tokio::test]
+    async fn test_check_code_present_and_absent() {
+        let (provider, mock) = Provider::mocked();
+        let router = Address::repeat_byte(0x33);
+
+        mock.push::<Bytes, _>(Bytes::from(vec![0x60, 0x80]))
+            .unwrap();
+        assert!(check_code(&provider, router).await.is_ok());
+
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        let error = check_code(&provider, router).await.unwrap_err();
+        assert!(error.to_string().contains("No contract code"));
+    }
+
+    #[tokio::test]
+    async fn test_check_chain() {
+        let (provider, mock) = Provider::mocked();
+
+        mock.push(U256::from(42_220u64)).unwrap();
+        assert!(check_chain(&provider, Some(42_220)).await.is_ok());
+        mock.push(U256::from(1u64)).unwrap();
+        assert!(check_chain(&provider, Some(42_220)).await.is_err());
+        mock.push(U256::from(1u64)).unwrap();
+        assert!(check_chain(&provider, None).await.is_ok());
+
+        // No response: the node is unreachable
+        let error = check_chain(&provider, None).await.unwrap_err();
+        assert!(error.to_string().contains("unreachable"));
+    }
+
+    #[tokio::test]
+    async fn test_check_pool() {
+        let (provider, mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        let pool = Address::repeat_byte(0x11);
+
+        // Responses are served last-in first-out: getCache, then maturity
+        mock.push::<Bytes, _>(encode_uints(&[1_700_000_000]))
+            .unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[1_000, 1_000, 30]))
+            .unwrap();
+        assert!(check_pool(provider.clone(), pool).await.is_ok());
+
+        let error = check_pool(provider, pool).await.unwrap_err();
+        assert!(error.to_string().contains("getCache"));
+    }
+
+    #[tokio::test]
+    async fn test_check_balance() {
+        let (provider, mock) = Provider::mocked();
+        let bot = Address::repeat_byte(0x44);
+
+        mock.push(U256::exp10(18)).unwrap();
+        assert!(check_balance(&provider, bot).await.is_ok());
+        mock.push(U256::zero()).unwrap();
+        assert!(check_balance(&provider, bot).await.is_err());
+    }
+
+    #[test]
+    fn test_check_curve() {
+        assert!(check_curve(&RateCurve::default_usd()).is_ok());
+
+        let empty = RateCurve::new(vec![], DayCount::Act360);
+        assert!(check_curve(&empty).is_err());
+
+        let unordered = RateCurve::new(
+            vec![
+                CurveKnot { t: 0.5, rate: 0.05 },
+                CurveKnot {
+                    t: 0.25,
+                    rate: 0.05,
+                },
+            ],
+            DayCount::Act360,
+        );
+        assert!(check_curve(&unordered).is_err());
+
+        let nan = RateCurve::new(
+            vec![CurveKnot {
+                t: 0.5,
+                rate: f64::NAN,
+            }],
+            DayCount::Act360,
+        );
+        assert!(check_curve(&nan).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_report_collects_every_failure() {
+        let (provider, mock) = Provider::mocked();
+        let config = Config {
+            pool_addresses: vec![Address::repeat_byte(0x11)],
+            ..Config::default()
+        };
+
+        // Served in order: chainId, router code, then the pool's getCache fails
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        mock.push(U256::from(42_220u64)).unwrap();
+        let report =
+            run_self_test(Arc::new(provider), &config, &RateCurve::default_usd(), None).await;
+
+        let failed: Vec<&str> = report.failures().map(|c| c.name.as_str()).collect();
+        assert_eq!(failed.len(), 2);
+        assert!(failed[0].starts_with("router"));
+        assert!(failed[1].starts_with("pool"));
+        let error = report.into_result().unwrap_err().to_string();
+        assert!(error.contains("ok   chain"));
+        assert!(error.contains("FAIL router"));
+    }
+}
@@ -0,0 +1,186 @@
+/// Pool and curve snapshots for the Numo strategy
+/// Exports the state a block was evaluated against, and re-runs the opportunity
+/// search on an exported snapshot offline to reproduce and debug that decision
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::data_log::OpportunityRecord;
+use crate::price_source::QuoterPriceSource;
+use crate::pricing::PoolState;
+use crate::quoter::LocalQuoter;
+use crate::sofr::RateCurve;
+use crate::strategy::NumoArb;
+use crate::types::{ArbOpportunity, Config};
+
+/// Everything one block's opportunity search read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub block_number: u64,
+    pub timestamp: u64,
+
+    /// Curve target prices were taken from
+    pub curve: RateCurve,
+
+    /// State of every monitored pool, in pool list order
+    pub pool_states: Vec<PoolState>,
+}
+
+impl StateSnapshot {
+    /// Read a snapshot written by [StateSnapshot::write]
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read snapshot {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid snapshot {}", path.display()))
+    }
+
+    /// Write the snapshot as JSON, replacing `path` atomically
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write snapshot {}", partial.display()))?;
+        std::fs::rename(&partial, path)
+            .with_context(|| format!("Failed to write snapshot {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Outcome of the opportunity search on a snapshot
+#[derive(Debug, Clone)]
+pub struct SimulatedDecision {
+    /// Pools, prices and skip reason of the search
+    pub record: OpportunityRecord,
+
+    /// Opportunity found, if any
+    pub opportunity: Option<ArbOpportunity>,
+}
+
+/// Search `snapshot` for an opportunity under `config`, without network access
+///
+/// Pools are priced by local quoters over the snapshot's reserves, so the result
+/// matches the live decision as long as the pools priced at their invariant.
+pub async fn simulate_snapshot(
+    snapshot: StateSnapshot,
+    mut config: Config,
+) -> Result<SimulatedDecision> {
+    config.record_events_path = None;
+    config.snapshot_path = None;
+    let source = snapshot
+        .pool_states
+        .iter()
+        .fold(QuoterPriceSource::new(), |source, state| {
+            let ttm = snapshot
+                .curve
+                .time_to_maturity(snapshot.timestamp, state.maturity);
+            source.with_pool(state.address, LocalQuoter::new(state, ttm))
+        });
+    let (provider, _mock) = Provider::mocked();
+    let mut strategy = NumoArb::new(Arc::new(provider), config, snapshot.curve)
+        .with_price_source(Box::new(source));
+    strategy.restore_pool_states(snapshot.pool_states);
+
+    let (opportunity, record) = strategy
+        .evaluate_offline(snapshot.block_number, snapshot.timestamp)
+        .await?;
+    Ok(SimulatedDecision {
+        record,
+        opportunity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SkipReason;
+
+    /// Two pools half a year from maturity, near 0.96 and 0.99, at block 1234
+    const FIXTURE: &str = r#"{
+        "block_number": 1234,
+        "timestamp": 1700000000,
+        "curve": {
+            "knots": [
+                { "t": 0.002777777777777778, "rate": 0.052 },
+                { "t": 1.0, "rate": 0.052 }
+            ],
+            "day_count": "Act360"
+        },
+        "pool_states": [
+            {
+                "address": "0x1111111111111111111111111111111111111111",
+                "base_reserves": 442000000000000000000000,
+                "fy_reserves": 1000000000000000000000000,
+                "fee_bps": 0,
+                "maturity": 1715724800
+            },
+            {
+                "address": "0x2222222222222222222222222222222222222222",
+                "base_reserves": 818000000000000000000000,
+                "fy_reserves": 1000000000000000000000000,
+                "fee_bps": 0,
+                "maturity": 1715724800
+            }
+        ]
+    }"#;
+
+    fn fixture_path(name: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("numo-snapshot-{name}-{}.json", std::process::id()));
+        std::fs::write(&path, FIXTURE).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_simulate_fixture_snapshot() {
+        let path = fixture_path("simulate");
+        let snapshot = StateSnapshot::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(snapshot.pool_states.len(), 2);
+        let (cheap, rich) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let one = 10u128.pow(18);
+        let config = Config {
+            pool_addresses: vec![cheap, rich],
+            max_fy_amount: 1_000 * one,
+            ..Config::default()
+        };
+
+        let decision = simulate_snapshot(snapshot.clone(), config.clone())
+            .await
+            .unwrap();
+        let opp = decision.opportunity.unwrap();
+        assert_eq!((opp.cheap_pool, opp.rich_pool), (cheap, rich));
+        assert!(opp.expected_profit > 0);
+        assert_eq!(decision.record.block, 1234);
+        assert_eq!(decision.record.skip_reason, None);
+
+        // The same snapshot under a wider edge reproduces a skip
+        let strict = Config {
+            edge_bps: 5_000,
+            ..config
+        };
+        let decision = simulate_snapshot(snapshot, strict).await.unwrap();
+        assert!(decision.opportunity.is_none());
+        assert_eq!(decision.record.skip_reason, Some(SkipReason::NoSpread));
+    }
+
+    #[test]
+    fn test_snapshot_round_trips() {
+        let path = fixture_path("round-trip");
+        let snapshot = StateSnapshot::read(&path).unwrap();
+        snapshot.write(&path).unwrap();
+        let reread = StateSnapshot::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(reread.block_number, snapshot.block_number);
+        assert_eq!(reread.curve.knots.len(), 2);
+        assert_eq!(
+            reread.pool_states[1].base_reserves,
+            818_000 * 10u128.pow(18)
+        );
+    }
+}
@@ -1,5 +1,11 @@
-/// SOFR (Secured Overnight Financing Rate) curve module
-/// Implements discount factor calculations using ACT/360 day count convention
+/// Overnight rate curve module
+/// Implements discount factor calculations for SOFR and other overnight indices
+/// (ESTR, SONIA) using their market day count conventions
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 
 /// Day count convention for fixed income calculations
@@ -28,9 +34,83 @@ impl DayCount {
             DayCount::Act365 => days / 365.0,
         }
     }
+
+    /// Days in a year of the convention
+    pub fn days_per_year(&self) -> f64 {
+        match self {
+            DayCount::Act360 => 360.0,
+            DayCount::Act365 => 365.0,
+        }
+    }
+
+    /// Year fraction from `current` to `maturity` (Unix seconds), zero once matured
+    ///
+    /// Works on u64 timestamps throughout, so maturities past the u32 range (2106)
+    /// and arbitrarily large current timestamps neither wrap nor overflow.
+    pub fn year_fraction_from_timestamps(&self, current: u64, maturity: u64) -> f64 {
+        let days = maturity.saturating_sub(current) as f64 / 86400.0;
+        match self {
+            DayCount::Act360 => days / 360.0,
+            DayCount::Act365 => days / 365.0,
+        }
+    }
 }
 
-/// A knot point on the SOFR curve
+/// Overnight rate index a curve is built from
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateIndex {
+    /// Secured Overnight Financing Rate (USD)
+    #[default]
+    Sofr,
+    /// Euro Short-Term Rate (EUR)
+    Estr,
+    /// Sterling Overnight Index Average (GBP)
+    Sonia,
+    /// Any other index, identified by name
+    Custom(String),
+}
+
+impl RateIndex {
+    /// Parse an index from its name (case-insensitive); unknown names become `Custom`
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_uppercase().as_str() {
+            "SOFR" => RateIndex::Sofr,
+            "ESTR" | "€STR" => RateIndex::Estr,
+            "SONIA" => RateIndex::Sonia,
+            _ => RateIndex::Custom(name.trim().to_string()),
+        }
+    }
+
+    /// Display name of the index
+    pub fn name(&self) -> &str {
+        match self {
+            RateIndex::Sofr => "SOFR",
+            RateIndex::Estr => "ESTR",
+            RateIndex::Sonia => "SONIA",
+            RateIndex::Custom(name) => name.as_str(),
+        }
+    }
+
+    /// Currency the index is published for, if known
+    pub fn currency(&self) -> Option<&'static str> {
+        match self {
+            RateIndex::Sofr => Some("USD"),
+            RateIndex::Estr => Some("EUR"),
+            RateIndex::Sonia => Some("GBP"),
+            RateIndex::Custom(_) => None,
+        }
+    }
+
+    /// Market day count convention for the index
+    pub fn day_count(&self) -> DayCount {
+        match self {
+            RateIndex::Sonia => DayCount::Act365,
+            _ => DayCount::Act360,
+        }
+    }
+}
+
+/// A knot point on the rate curve
 /// Represents (time_to_maturity_years, simple_rate)
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct CurveKnot {
@@ -40,79 +120,435 @@ pub struct CurveKnot {
     pub rate: f64,
 }
 
-/// SOFR discount factor curve
-/// Uses piecewise-linear interpolation in simple rate space
+impl CurveKnot {
+    /// Knot at a tenor in days, with `t` computed under `day_count`
+    pub fn from_days(days: u32, rate: f64, day_count: DayCount) -> Self {
+        Self {
+            t: day_count.year_fraction(days as i64),
+            rate,
+        }
+    }
+
+    /// Knot at a tenor in months, using an average month of 365/12 days rounded
+    /// to whole days (1M = 30 days, 3M = 91 days, 12M = 365 days)
+    pub fn from_months(months: u32, rate: f64, day_count: DayCount) -> Self {
+        let days = (months as f64 * 365.0 / 12.0).round() as u32;
+        Self::from_days(days, rate, day_count)
+    }
+}
+
+/// Seconds in a calendar day
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Knots within this distance (in years) are treated as the same tenor
+const KNOT_TENOR_TOLERANCE: f64 = 1e-9;
+
+/// A knot whose rate changed between two curves, matched by tenor
+/// A missing rate means the knot only exists on the other curve
+#[derive(Clone, Debug, PartialEq)]
+pub struct KnotChange {
+    pub t: f64,
+    pub old_rate: Option<f64>,
+    pub new_rate: Option<f64>,
+}
+
+/// Discount factors of two curves at the same maturity
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DiscountFactorShift {
+    pub old: f64,
+    pub new: f64,
+}
+
+impl DiscountFactorShift {
+    /// Relative change in basis points (positive when the new target is higher)
+    pub fn bps(&self) -> f64 {
+        if self.old == 0.0 {
+            return 0.0;
+        }
+        (self.new - self.old) / self.old * 10_000.0
+    }
+}
+
+/// How a curve interpolates between knots
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Piecewise-linear in simple rate space
+    #[default]
+    LinearRate,
+    /// Piecewise-linear in log discount factor space (piecewise-flat forwards)
+    LogLinearDf,
+    /// Monotone cubic (Fritsch-Carlson) in simple rate space: smooth forwards
+    /// without overshooting the knots' rates
+    MonotoneCubic,
+}
+
+/// How a curve continues past its last knot
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Extrapolation {
+    /// Hold the last knot's rate
+    #[default]
+    Flat,
+    /// Continue at the slope of the last segment between knots
+    LastSlope,
+    /// Continue at a fixed slope, in rate per year of tenor
+    Custom(f64),
+}
+
+/// How a rate compounds into a discount factor over a tenor
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Compounding {
+    /// DF = 1 / (1 + r * t)
+    #[default]
+    Simple,
+    /// DF = exp(-r * t)
+    Continuous,
+    /// DF = (1 + r / n)^(-n * t), compounded over each of the n days in a year of
+    /// the day count, as in OIS discounting
+    Daily,
+}
+
+impl Compounding {
+    /// Parse a compounding from its name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "simple" => Some(Compounding::Simple),
+            "continuous" => Some(Compounding::Continuous),
+            "daily" => Some(Compounding::Daily),
+            _ => None,
+        }
+    }
+
+    /// Discount factor of `rate` compounded over `t` years of `day_count`
+    pub fn discount_factor(&self, rate: f64, t: f64, day_count: DayCount) -> f64 {
+        match self {
+            Compounding::Simple => 1.0 / (1.0 + rate * t),
+            Compounding::Continuous => (-rate * t).exp(),
+            Compounding::Daily => {
+                let n = day_count.days_per_year();
+                (1.0 + rate / n).powf(-n * t)
+            }
+        }
+    }
+
+    /// Rate compounding into discount factor `df` over `t` years of `day_count`,
+    /// the inverse of [Compounding::discount_factor]
+    pub fn rate(&self, df: f64, t: f64, day_count: DayCount) -> f64 {
+        match self {
+            Compounding::Simple => (1.0 / df - 1.0) / t,
+            Compounding::Continuous => -df.ln() / t,
+            Compounding::Daily => {
+                let n = day_count.days_per_year();
+                n * (df.powf(-1.0 / (n * t)) - 1.0)
+            }
+        }
+    }
+}
+
+/// Overnight rate discount factor curve
+/// Interpolates piecewise-linearly in simple rate space by default
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct SofrCurve {
+pub struct RateCurve {
+    /// Index the curve is built from
+    #[serde(default)]
+    pub index: RateIndex,
     /// Curve knot points, must be sorted by time
     pub knots: Vec<CurveKnot>,
     /// Day count convention
     pub day_count: DayCount,
+    /// Interpolation method between knots
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    /// How the curve's rates compound into discount factors
+    #[serde(default)]
+    pub compounding: Compounding,
+    /// How the curve continues past its last knot
+    #[serde(default)]
+    pub extrapolation: Extrapolation,
+    /// Calendar days from the trade date to the spot (settlement) date that
+    /// times to maturity are measured from, e.g., 2 for T+2
+    #[serde(default)]
+    pub settlement_lag_days: u32,
 }
 
-impl SofrCurve {
+/// SOFR curves are rate curves on the SOFR index; kept for backward compatibility
+pub type SofrCurve = RateCurve;
+
+impl RateCurve {
     /// Create a new SOFR curve with given knots
     /// Knots should be sorted by time
     pub fn new(knots: Vec<CurveKnot>, day_count: DayCount) -> Self {
-        Self { knots, day_count }
+        Self {
+            index: RateIndex::Sofr,
+            knots,
+            day_count,
+            interpolation: Interpolation::LinearRate,
+            compounding: Compounding::Simple,
+            extrapolation: Extrapolation::Flat,
+            settlement_lag_days: 0,
+        }
+    }
+
+    /// Set how the curve continues past its last knot
+    pub fn with_extrapolation(mut self, extrapolation: Extrapolation) -> Self {
+        self.extrapolation = extrapolation;
+        self
+    }
+
+    /// Value from the spot date `settlement_lag_days` after the trade date
+    pub fn with_spot_date(mut self, settlement_lag_days: u32) -> Self {
+        self.settlement_lag_days = settlement_lag_days;
+        self
+    }
+
+    /// Set the index the curve is built from
+    pub fn with_index(mut self, index: RateIndex) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Set the interpolation method between knots
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Set how the curve's rates compound into discount factors
+    pub fn with_compounding(mut self, compounding: Compounding) -> Self {
+        self.compounding = compounding;
+        self
+    }
+
+    /// Default curve for an index, if a preset exists
+    pub fn preset(index: &RateIndex) -> Option<Self> {
+        match index {
+            RateIndex::Sofr => Some(Self::default_usd()),
+            RateIndex::Estr => Some(Self::default_eur()),
+            RateIndex::Sonia => Some(Self::default_gbp()),
+            RateIndex::Custom(_) => None,
+        }
+    }
+
+    /// Default curve for a currency code (e.g., "EUR"), if a preset exists
+    pub fn for_currency(currency: &str) -> Option<Self> {
+        match currency.trim().to_uppercase().as_str() {
+            "USD" => Some(Self::default_usd()),
+            "EUR" => Some(Self::default_eur()),
+            "GBP" => Some(Self::default_gbp()),
+            _ => None,
+        }
+    }
+
+    /// Load a curve from a JSON file in the curve's serde representation
+    /// (at least `knots` and `day_count`), validated with [RateCurve::validate]
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open rate curve file {}", path.display()))?;
+        Self::from_reader(BufReader::new(file))
+            .with_context(|| format!("Invalid rate curve file {}", path.display()))
+    }
+
+    /// Read a curve from JSON in the curve's serde representation, validated with
+    /// [RateCurve::validate]
+    pub fn from_reader(reader: impl Read) -> Result<Self> {
+        let curve: Self = serde_json::from_reader(reader).context("Failed to parse rate curve")?;
+        curve.validate()?;
+        Ok(curve)
+    }
+
+    /// Check the knots are non-empty, strictly ascending in `t` and carry finite,
+    /// non-negative rates
+    pub fn validate(&self) -> Result<()> {
+        if self.knots.is_empty() {
+            bail!("Rate curve has no knots");
+        }
+        for (i, knot) in self.knots.iter().enumerate() {
+            if !knot.t.is_finite() || knot.t < 0.0 {
+                bail!("Knot {i} has invalid time {}", knot.t);
+            }
+            if !knot.rate.is_finite() || knot.rate < 0.0 {
+                bail!(
+                    "Knot {i} at t = {} has negative or invalid rate {}",
+                    knot.t,
+                    knot.rate
+                );
+            }
+        }
+        if let Some((i, pair)) = self
+            .knots
+            .windows(2)
+            .enumerate()
+            .find(|(_, pair)| pair[1].t <= pair[0].t)
+        {
+            bail!(
+                "Knots must be sorted ascending by t: knot {} at t = {} follows t = {}",
+                i + 1,
+                pair[1].t,
+                pair[0].t
+            );
+        }
+        Ok(())
     }
 
     /// Create a default curve with sample SOFR rates
     /// These are placeholder values - replace with real market data
     pub fn default_usd() -> Self {
+        let day_count = DayCount::Act360;
         Self {
+            index: RateIndex::Sofr,
             knots: vec![
-                CurveKnot {
-                    t: 0.0028,
-                    rate: 0.0520,
-                }, // ~1 day
-                CurveKnot {
-                    t: 0.0833,
-                    rate: 0.0515,
-                }, // 1 month
-                CurveKnot {
-                    t: 0.25,
-                    rate: 0.0500,
-                }, // 3 months
-                CurveKnot {
-                    t: 0.50,
-                    rate: 0.0475,
-                }, // 6 months
-                CurveKnot {
-                    t: 1.00,
-                    rate: 0.0450,
-                }, // 1 year
-                CurveKnot {
-                    t: 2.00,
-                    rate: 0.0425,
-                }, // 2 years
+                CurveKnot::from_days(1, 0.0520, day_count),
+                CurveKnot::from_months(1, 0.0515, day_count),
+                CurveKnot::from_months(3, 0.0500, day_count),
+                CurveKnot::from_months(6, 0.0475, day_count),
+                CurveKnot::from_months(12, 0.0450, day_count),
+                CurveKnot::from_months(24, 0.0425, day_count),
             ],
-            day_count: DayCount::Act360,
+            day_count,
+            interpolation: Interpolation::LinearRate,
+            compounding: Compounding::Simple,
+            extrapolation: Extrapolation::Flat,
+            settlement_lag_days: 0,
+        }
+    }
+
+    /// Create a default curve with sample ESTR rates
+    /// These are placeholder values - replace with real market data
+    pub fn default_eur() -> Self {
+        Self::from_rates(
+            RateIndex::Estr,
+            &[
+                (0.0028, 0.0390),
+                (0.0833, 0.0385),
+                (0.25, 0.0370),
+                (0.50, 0.0350),
+                (1.00, 0.0320),
+                (2.00, 0.0290),
+            ],
+        )
+    }
+
+    /// Create a default curve with sample SONIA rates
+    /// These are placeholder values - replace with real market data
+    pub fn default_gbp() -> Self {
+        Self::from_rates(
+            RateIndex::Sonia,
+            &[
+                (0.0027, 0.0520),
+                (0.0833, 0.0518),
+                (0.25, 0.0510),
+                (0.50, 0.0495),
+                (1.00, 0.0470),
+                (2.00, 0.0440),
+            ],
+        )
+    }
+
+    /// Build a curve from `(t, rate)` pairs using the index's day count
+    fn from_rates(index: RateIndex, rates: &[(f64, f64)]) -> Self {
+        Self {
+            day_count: index.day_count(),
+            index,
+            knots: rates
+                .iter()
+                .map(|&(t, rate)| CurveKnot { t, rate })
+                .collect(),
+            interpolation: Interpolation::LinearRate,
+            compounding: Compounding::Simple,
+            extrapolation: Extrapolation::Flat,
+            settlement_lag_days: 0,
         }
     }
 
     /// Calculate discount factor for a given time to maturity
-    /// DF(t) = 1 / (1 + r(t) * t) using simple compounding
+    /// DF(t) = 1 / (1 + r(t) * t) under the default simple compounding
     pub fn discount_factor(&self, t: f64) -> f64 {
         if t <= 0.0 {
             return 1.0;
         }
-        let rate = self.interpolate_rate(t);
-        1.0 / (1.0 + rate * t)
+        match self.interpolation {
+            Interpolation::LinearRate | Interpolation::MonotoneCubic => {
+                let rate = self.interpolate_rate(t);
+                self.compounding.discount_factor(rate, t, self.day_count)
+            }
+            Interpolation::LogLinearDf => self.interpolate_df(t),
+        }
+    }
+
+    /// Discount factor at `t` interpolated log-linearly between the knots'
+    /// discount factors, whatever the curve's interpolation method
+    ///
+    /// Before the first knot the curve starts from DF(0) = 1; past the last knot
+    /// the last knot's continuously compounded zero rate is continued at the
+    /// extrapolation slope, flat by default.
+    pub fn interpolate_df(&self, t: f64) -> f64 {
+        if t <= 0.0 || self.knots.is_empty() {
+            return 1.0;
+        }
+        let knot_df = |knot: &CurveKnot| {
+            self.compounding
+                .discount_factor(knot.rate, knot.t, self.day_count)
+        };
+
+        let mut prev = (0.0, 0.0); // (t, ln DF)
+        for knot in &self.knots {
+            let ln_df = knot_df(knot).ln();
+            if t <= knot.t {
+                let (t0, ln_df0) = prev;
+                if knot.t - t0 <= KNOT_TENOR_TOLERANCE {
+                    return ln_df.exp();
+                }
+                let alpha = (t - t0) / (knot.t - t0);
+                return (ln_df0 + alpha * (ln_df - ln_df0)).exp();
+            }
+            prev = (knot.t, ln_df);
+        }
+
+        let (t_last, ln_df_last) = prev;
+        let zero_rate = -ln_df_last / t_last + self.extrapolation_slope() * (t - t_last);
+        (-zero_rate * t).exp()
+    }
+
+    /// Slope in rate per year at which the curve continues past its last knot
+    pub fn extrapolation_slope(&self) -> f64 {
+        match self.extrapolation {
+            Extrapolation::Flat => 0.0,
+            Extrapolation::Custom(slope) => slope,
+            Extrapolation::LastSlope => match self.knots.as_slice() {
+                [.., before, last] if last.t - before.t > KNOT_TENOR_TOLERANCE => {
+                    (last.rate - before.rate) / (last.t - before.t)
+                }
+                _ => 0.0,
+            },
+        }
     }
 
-    /// Calculate implied forward rate between two times
-    /// F(t1, t2) = [DF(t1) / DF(t2) - 1] / (t2 - t1)
+    /// Discount factor at `t` with the rate the curve implies, in its compounding,
+    /// shifted by `spread_bps`, e.g., for a pool's structural spread to the curve
+    pub fn discount_factor_with_spread(&self, t: f64, spread_bps: i32) -> f64 {
+        let df = self.discount_factor(t);
+        if t <= 0.0 || spread_bps == 0 {
+            return df;
+        }
+        let rate = self.compounding.rate(df, t, self.day_count) + spread_bps as f64 / 10_000.0;
+        self.compounding.discount_factor(rate, t, self.day_count)
+    }
+
+    /// Calculate implied forward rate between two times, in the curve's compounding
+    /// F(t1, t2) = [DF(t1) / DF(t2) - 1] / (t2 - t1) under simple compounding
     pub fn forward_rate(&self, t1: f64, t2: f64) -> f64 {
         if t2 <= t1 {
             return 0.0;
         }
         let df1 = self.discount_factor(t1);
         let df2 = self.discount_factor(t2);
-        (df1 / df2 - 1.0) / (t2 - t1)
+        match self.compounding {
+            Compounding::Simple => (df1 / df2 - 1.0) / (t2 - t1),
+            compounding => compounding.rate(df2 / df1, t2 - t1, self.day_count),
+        }
     }
 
-    /// Interpolate simple rate for a given time using piecewise-linear method
+    /// Interpolate simple rate for a given time, monotone cubic under
+    /// [Interpolation::MonotoneCubic] and piecewise-linear otherwise
     fn interpolate_rate(&self, t: f64) -> f64 {
         let n = self.knots.len();
         if n == 0 {
@@ -124,20 +560,33 @@ impl SofrCurve {
             return self.knots[0].rate;
         }
 
-        // After last knot - use last rate (flat extrapolation)
+        // After last knot - continue at the extrapolation slope (flat by default)
         if t >= self.knots[n - 1].t {
-            return self.knots[n - 1].rate;
+            let last = self.knots[n - 1];
+            return last.rate + self.extrapolation_slope() * (t - last.t);
         }
 
-        // Linear interpolation between knots
+        let tangents = match self.interpolation {
+            Interpolation::MonotoneCubic => Some(self.monotone_tangents()),
+            _ => None,
+        };
         for i in 1..n {
             let (t0, r0) = (self.knots[i - 1].t, self.knots[i - 1].rate);
             let (t1, r1) = (self.knots[i].t, self.knots[i].rate);
 
             if t <= t1 {
-                // Linear interpolation: r = r0 + (r1 - r0) * (t - t0) / (t1 - t0)
-                let alpha = (t - t0) / (t1 - t0);
-                return r0 + alpha * (r1 - r0);
+                let h = t1 - t0;
+                let alpha = (t - t0) / h;
+                let Some(m) = &tangents else {
+                    // Linear interpolation: r = r0 + (r1 - r0) * (t - t0) / (t1 - t0)
+                    return r0 + alpha * (r1 - r0);
+                };
+                // Cubic Hermite segment through both knots with their tangents
+                let (a2, a3) = (alpha * alpha, alpha * alpha * alpha);
+                return (2.0 * a3 - 3.0 * a2 + 1.0) * r0
+                    + (a3 - 2.0 * a2 + alpha) * h * m[i - 1]
+                    + (-2.0 * a3 + 3.0 * a2) * r1
+                    + (a3 - a2) * h * m[i];
             }
         }
 
@@ -145,16 +594,145 @@ impl SofrCurve {
         self.knots[n - 1].rate
     }
 
+    /// Fritsch-Carlson tangents of the rate at each knot
+    ///
+    /// Tangents are zero at local extrema of the knots' rates and scaled down where
+    /// they would make a segment overshoot, so the interpolated rate is monotone
+    /// wherever the knots are.
+    fn monotone_tangents(&self) -> Vec<f64> {
+        let n = self.knots.len();
+        if n < 2 {
+            return vec![0.0; n];
+        }
+        let slopes: Vec<f64> = self
+            .knots
+            .windows(2)
+            .map(|pair| (pair[1].rate - pair[0].rate) / (pair[1].t - pair[0].t))
+            .collect();
+
+        let mut tangents = Vec::with_capacity(n);
+        tangents.push(slopes[0]);
+        for pair in slopes.windows(2) {
+            tangents.push(if pair[0] * pair[1] <= 0.0 {
+                0.0
+            } else {
+                (pair[0] + pair[1]) / 2.0
+            });
+        }
+        tangents.push(slopes[n - 2]);
+
+        for (i, slope) in slopes.iter().enumerate() {
+            if *slope == 0.0 {
+                tangents[i] = 0.0;
+                tangents[i + 1] = 0.0;
+                continue;
+            }
+            let (a, b) = (tangents[i] / slope, tangents[i + 1] / slope);
+            let norm = a * a + b * b;
+            if norm > 9.0 {
+                let tau = 3.0 / norm.sqrt();
+                tangents[i] = tau * a * slope;
+                tangents[i + 1] = tau * b * slope;
+            }
+        }
+        tangents
+    }
+
     /// Get rate for a given time (alias for interpolate_rate)
     pub fn rate(&self, t: f64) -> f64 {
         self.interpolate_rate(t)
     }
 
+    /// Spot date for a trade at `current_ts`, `settlement_lag_days` later
+    pub fn spot_timestamp(&self, current_ts: u64) -> u64 {
+        current_ts.saturating_add(self.settlement_lag_days as u64 * SECONDS_PER_DAY)
+    }
+
     /// Calculate time to maturity from current timestamp and maturity timestamp
+    /// Measured from the spot date, so a maturity within the settlement lag is matured
     pub fn time_to_maturity(&self, current_ts: u64, maturity_ts: u32) -> f64 {
-        let seconds_to_maturity = (maturity_ts as i64 - current_ts as i64).max(0);
         self.day_count
-            .year_fraction_from_seconds(seconds_to_maturity)
+            .year_fraction_from_timestamps(self.spot_timestamp(current_ts), maturity_ts as u64)
+    }
+
+    /// Knots that differ between this curve (before) and `other` (after)
+    pub fn knot_changes(&self, other: &RateCurve) -> Vec<KnotChange> {
+        let rate_at = |curve: &RateCurve, t: f64| {
+            curve
+                .knots
+                .iter()
+                .find(|knot| (knot.t - t).abs() < KNOT_TENOR_TOLERANCE)
+                .map(|knot| knot.rate)
+        };
+
+        let mut tenors: Vec<f64> = self.knots.iter().chain(&other.knots).map(|k| k.t).collect();
+        tenors.sort_by(|a, b| a.total_cmp(b));
+        tenors.dedup_by(|a, b| (*a - *b).abs() < KNOT_TENOR_TOLERANCE);
+
+        tenors
+            .into_iter()
+            .filter_map(|t| {
+                let old_rate = rate_at(self, t);
+                let new_rate = rate_at(other, t);
+                let changed = match (old_rate, new_rate) {
+                    (Some(old), Some(new)) => (old - new).abs() > f64::EPSILON,
+                    _ => true,
+                };
+                changed.then_some(KnotChange {
+                    t,
+                    old_rate,
+                    new_rate,
+                })
+            })
+            .collect()
+    }
+
+    /// Weighted blend of this curve with `other`, e.g., 70% primary and 30% reference
+    /// as `primary.blend(&reference, 0.7)`
+    ///
+    /// Both curves are sampled on the union of their knot tenors and their rates
+    /// weighted, `weight` (clamped to [0, 1]) going to this curve. The blend keeps
+    /// this curve's index, day count and conventions; `other`'s tenors are
+    /// converted from its own day count.
+    pub fn blend(&self, other: &RateCurve, weight: f64) -> RateCurve {
+        let weight = weight.clamp(0.0, 1.0);
+        // Years of `other`'s day count in one year of this curve's
+        let scale = other.day_count.year_fraction(1) / self.day_count.year_fraction(1);
+
+        let mut tenors: Vec<f64> = self
+            .knots
+            .iter()
+            .map(|k| k.t)
+            .chain(other.knots.iter().map(|k| k.t / scale))
+            .collect();
+        tenors.sort_by(|a, b| a.total_cmp(b));
+        tenors.dedup_by(|a, b| (*a - *b).abs() < KNOT_TENOR_TOLERANCE);
+
+        let knots = tenors
+            .into_iter()
+            .map(|t| CurveKnot {
+                t,
+                rate: weight * self.rate(t) + (1.0 - weight) * other.rate(t * scale),
+            })
+            .collect();
+        RateCurve {
+            knots,
+            ..self.clone()
+        }
+    }
+
+    /// Discount factors of this curve (before) and `other` (after) at a maturity,
+    /// each using its own day count
+    pub fn discount_factor_shift(
+        &self,
+        other: &RateCurve,
+        current_ts: u64,
+        maturity_ts: u32,
+    ) -> DiscountFactorShift {
+        DiscountFactorShift {
+            old: self.discount_factor(self.time_to_maturity(current_ts, maturity_ts)),
+            new: other.discount_factor(other.time_to_maturity(current_ts, maturity_ts)),
+        }
     }
 }
 
@@ -162,6 +740,49 @@ impl SofrCurve {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_curve_from_reader() {
+        let json = r#"{
+            "knots": [{"t": 0.25, "rate": 0.05}, {"t": 1.0, "rate": 0.045}],
+            "day_count": "Act360"
+        }"#;
+        let curve = SofrCurve::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(curve.index, RateIndex::Sofr);
+        assert_eq!(curve.knots.len(), 2);
+        assert!((curve.rate(1.0) - 0.045).abs() < 1e-12);
+
+        // Every preset round-trips through its serde representation
+        let usd = SofrCurve::default_usd();
+        let loaded = SofrCurve::from_reader(&serde_json::to_vec(&usd).unwrap()[..]).unwrap();
+        assert_eq!(loaded.discount_factor(2.0), usd.discount_factor(2.0));
+    }
+
+    #[test]
+    fn test_invalid_curves_rejected() {
+        let error = |knots: &str| {
+            let json = format!(r#"{{"knots": {knots}, "day_count": "Act360"}}"#);
+            SofrCurve::from_reader(json.as_bytes())
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error("[]").contains("no knots"));
+        assert!(
+            error(r#"[{"t": 1.0, "rate": 0.05}, {"t": 0.5, "rate": 0.05}]"#)
+                .contains("sorted ascending")
+        );
+        assert!(
+            error(r#"[{"t": 0.5, "rate": 0.05}, {"t": 0.5, "rate": 0.04}]"#)
+                .contains("sorted ascending")
+        );
+        assert!(error(r#"[{"t": 0.5, "rate": -0.01}]"#).contains("negative"));
+        assert!(SofrCurve::from_reader("not json".as_bytes()).is_err());
+
+        let missing = SofrCurve::from_file(Path::new("/nonexistent/curve.json")).unwrap_err();
+        assert!(missing
+            .to_string()
+            .contains("Failed to open rate curve file"));
+    }
+
     #[test]
     fn test_discount_factor() {
         let curve = SofrCurve::default_usd();
@@ -179,6 +800,201 @@ mod tests {
         assert!(df2 < 1.0);
     }
 
+    #[test]
+    fn test_interpolate_df() {
+        let curve = SofrCurve::default_usd();
+
+        // Downward sloping rates still give strictly decreasing discount factors
+        let mut previous = curve.interpolate_df(0.0);
+        assert_eq!(previous, 1.0);
+        for step in 1..=300 {
+            let df = curve.interpolate_df(step as f64 * 0.01);
+            assert!(
+                df < previous,
+                "DF not decreasing at t = {}",
+                step as f64 * 0.01
+            );
+            previous = df;
+        }
+
+        // Knot discount factors are reproduced exactly by both methods
+        for knot in &curve.knots {
+            assert!((curve.interpolate_df(knot.t) - curve.discount_factor(knot.t)).abs() < 1e-12);
+        }
+
+        // Under LogLinearDf, discount_factor is DF interpolation everywhere
+        let log_linear = curve.clone().with_interpolation(Interpolation::LogLinearDf);
+        for t in [0.01, 0.2, 0.75, 1.5, 3.0] {
+            assert_eq!(log_linear.discount_factor(t), curve.interpolate_df(t));
+        }
+        // and within a few bps of the rate-space curve between knots
+        assert!((log_linear.discount_factor(0.75) - curve.discount_factor(0.75)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_monotone_cubic_smooths_forwards_at_knots() {
+        // A steep rise into a flat stretch, then a gentle fall
+        let knots = vec![
+            CurveKnot {
+                t: 0.25,
+                rate: 0.03,
+            },
+            CurveKnot { t: 0.5, rate: 0.05 },
+            CurveKnot { t: 1.0, rate: 0.05 },
+            CurveKnot { t: 2.0, rate: 0.04 },
+        ];
+        let linear = SofrCurve::new(knots, DayCount::Act360);
+        let cubic = linear
+            .clone()
+            .with_interpolation(Interpolation::MonotoneCubic);
+        assert_eq!(linear.interpolation, Interpolation::LinearRate);
+
+        // Both methods pass through the knots
+        for knot in &linear.knots {
+            assert!((cubic.rate(knot.t) - knot.rate).abs() < 1e-12);
+            assert_eq!(
+                cubic.discount_factor(knot.t),
+                linear.discount_factor(knot.t)
+            );
+        }
+
+        // Forwards just before and after each interior knot: linear rates kink
+        // there, monotone cubic rates are continuous with a continuous slope
+        let eps = 1e-6;
+        let jump = |curve: &SofrCurve, t: f64| {
+            (curve.forward_rate(t - eps, t) - curve.forward_rate(t, t + eps)).abs()
+        };
+        for t in [0.5, 1.0] {
+            assert!(jump(&linear, t) > 1e-3, "linear forward continuous at {t}");
+            assert!(jump(&cubic, t) < 1e-5, "cubic forward jumps at {t}");
+        }
+        // and the rate itself is continuous across every knot
+        for knot in &cubic.knots {
+            assert!((cubic.rate(knot.t - eps) - cubic.rate(knot.t + eps)).abs() < 1e-6);
+        }
+
+        // Monotone between the knots: no overshoot of the flat stretch, no dip
+        let mut previous = cubic.rate(0.25);
+        for step in 1..=25 {
+            let rate = cubic.rate(0.25 + step as f64 * 0.01);
+            assert!(rate >= previous && rate <= 0.05 + 1e-15);
+            previous = rate;
+        }
+        for step in 1..50 {
+            assert!((cubic.rate(0.5 + step as f64 * 0.01) - 0.05).abs() < 1e-15);
+        }
+
+        // Forwards of the downward sloping default curve stay positive
+        let default = SofrCurve::default_usd().with_interpolation(Interpolation::MonotoneCubic);
+        for step in 0..300 {
+            let t = step as f64 * 0.01;
+            assert!(default.forward_rate(t, t + 0.01) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_compounding_conventions() {
+        let rate = 0.05;
+        let df = |compounding: Compounding, t: f64| {
+            compounding.discount_factor(rate, t, DayCount::Act360)
+        };
+        let all = [
+            Compounding::Simple,
+            Compounding::Continuous,
+            Compounding::Daily,
+        ];
+
+        // The three conventions converge as the tenor shrinks
+        let spread = |t: f64| {
+            let dfs = all.map(|compounding| df(compounding, t));
+            dfs.iter().cloned().fold(f64::MIN, f64::max)
+                - dfs.iter().cloned().fold(f64::MAX, f64::min)
+        };
+        let tenors = [1.0, 0.1, 0.01, 0.001, 1e-5];
+        assert!(tenors
+            .windows(2)
+            .all(|pair| spread(pair[1]) < spread(pair[0])));
+        assert!(spread(1e-5) < 1e-10);
+
+        // Continuous discounts more than simple, daily in between
+        for t in [30.0 / 360.0, 0.25, 1.0, 5.0, 30.0] {
+            let (simple, continuous) = (df(Compounding::Simple, t), df(Compounding::Continuous, t));
+            let daily = df(Compounding::Daily, t);
+            assert!(
+                continuous < simple,
+                "continuous not below simple at t = {t}"
+            );
+            assert!(continuous < daily && daily < simple);
+        }
+
+        // Rates round-trip through discount factors
+        for compounding in all {
+            let back = compounding.rate(df(compounding, 0.75), 0.75, DayCount::Act360);
+            assert!((back - rate).abs() < 1e-12);
+        }
+        assert_eq!(Compounding::from_name(" Daily"), Some(Compounding::Daily));
+        assert_eq!(Compounding::from_name("annual"), None);
+
+        // Simple stays the default, and forwards follow the compounding chosen
+        let simple = SofrCurve::default_usd();
+        assert_eq!(simple.compounding, Compounding::Simple);
+        let (t1, t2) = (0.5, 1.5);
+        for compounding in all {
+            let curve = simple.clone().with_compounding(compounding);
+            let forward = curve.forward_rate(t1, t2);
+            let rolled = curve.discount_factor(t1)
+                * compounding.discount_factor(forward, t2 - t1, curve.day_count);
+            assert!((rolled - curve.discount_factor(t2)).abs() < 1e-12);
+            assert_eq!(
+                curve.discount_factor(t1),
+                compounding.discount_factor(curve.rate(t1), t1, curve.day_count)
+            );
+        }
+        let continuous = simple.clone().with_compounding(Compounding::Continuous);
+        assert!(continuous.discount_factor(1.0) < simple.discount_factor(1.0));
+    }
+
+    #[test]
+    fn test_extrapolation_past_last_knot() {
+        // The default curve ends at two years (730 days, Act/360)
+        let flat = SofrCurve::default_usd();
+        let last = *flat.knots.last().unwrap();
+        let before = flat.knots[flat.knots.len() - 2];
+        let last_slope = flat.clone().with_extrapolation(Extrapolation::LastSlope);
+        let custom = flat
+            .clone()
+            .with_extrapolation(Extrapolation::Custom(0.001));
+        assert_eq!(flat.extrapolation, Extrapolation::Flat);
+
+        // At three years: flat holds 4.25%, the last segment keeps falling
+        let t = 3.0;
+        let slope = (last.rate - before.rate) / (last.t - before.t);
+        assert!(slope < 0.0);
+        assert!((flat.rate(t) - last.rate).abs() < 1e-12);
+        assert!((last_slope.rate(t) - (last.rate + slope * (t - last.t))).abs() < 1e-12);
+        assert!((custom.rate(t) - (last.rate + 0.001 * (t - last.t))).abs() < 1e-12);
+
+        let df = |curve: &SofrCurve| curve.discount_factor(t);
+        assert!((df(&flat) - 1.0 / (1.0 + 0.0425 * t)).abs() < 1e-12);
+        assert!((df(&last_slope) - 0.892610).abs() < 1e-5);
+        assert!((df(&custom) - 0.884629).abs() < 1e-5);
+        assert!(df(&last_slope) > df(&flat) && df(&flat) > df(&custom));
+
+        // Log-linear DF interpolation continues its zero rate at the same slope
+        let log_flat = flat.clone().with_interpolation(Interpolation::LogLinearDf);
+        let log_custom = custom
+            .clone()
+            .with_interpolation(Interpolation::LogLinearDf);
+        let z = |curve: &SofrCurve| -curve.discount_factor(t).ln() / t;
+        assert!((z(&log_custom) - z(&log_flat) - 0.001 * (t - last.t)).abs() < 1e-12);
+
+        // Within the curve the modes agree
+        for curve in [&last_slope, &custom] {
+            assert_eq!(curve.discount_factor(1.0), flat.discount_factor(1.0));
+            assert_eq!(curve.discount_factor(last.t), flat.discount_factor(last.t));
+        }
+    }
+
     #[test]
     fn test_rate_interpolation() {
         let curve = SofrCurve::new(
@@ -198,6 +1014,68 @@ mod tests {
         assert!((mid_rate - 0.045).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_knot_tenor_helpers() {
+        let year = CurveKnot::from_days(365, 0.05, DayCount::Act360);
+        assert!((year.t - 1.0139).abs() < 0.0001);
+        assert_eq!(year.rate, 0.05);
+        assert!((CurveKnot::from_days(365, 0.05, DayCount::Act365).t - 1.0).abs() < 1e-12);
+
+        // Months resolve to whole days under the curve's day count
+        assert!((CurveKnot::from_months(1, 0.05, DayCount::Act360).t - 30.0 / 360.0).abs() < 1e-12);
+        assert!((CurveKnot::from_months(3, 0.05, DayCount::Act365).t - 91.0 / 365.0).abs() < 1e-12);
+        assert!((CurveKnot::from_months(12, 0.05, DayCount::Act360).t - year.t).abs() < 1e-12);
+
+        // The default curve's short knot is one day
+        let curve = SofrCurve::default_usd();
+        assert!((curve.knots[0].t - 1.0 / 360.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_curve_diff() {
+        let before = SofrCurve::new(
+            vec![
+                CurveKnot { t: 0.5, rate: 0.05 },
+                CurveKnot { t: 1.0, rate: 0.05 },
+            ],
+            DayCount::Act360,
+        );
+        let after = SofrCurve::new(
+            vec![
+                CurveKnot { t: 0.5, rate: 0.05 },
+                CurveKnot { t: 1.0, rate: 0.04 },
+                CurveKnot { t: 2.0, rate: 0.04 },
+            ],
+            DayCount::Act360,
+        );
+
+        let changes = before.knot_changes(&after);
+        assert_eq!(
+            changes,
+            vec![
+                KnotChange {
+                    t: 1.0,
+                    old_rate: Some(0.05),
+                    new_rate: Some(0.04)
+                },
+                KnotChange {
+                    t: 2.0,
+                    old_rate: None,
+                    new_rate: Some(0.04)
+                },
+            ]
+        );
+        assert!(before.knot_changes(&before).is_empty());
+
+        // One year (Act/360) out: DF moves from 1/1.05 to 1/1.04
+        let now = 1_700_000_000u64;
+        let maturity = (now + 360 * 86_400) as u32;
+        let shift = before.discount_factor_shift(&after, now, maturity);
+        assert!((shift.old - 1.0 / 1.05).abs() < 1e-12);
+        assert!((shift.new - 1.0 / 1.04).abs() < 1e-12);
+        assert!((shift.bps() - (1.05 / 1.04 - 1.0) * 10_000.0).abs() < 1e-6);
+    }
+
     #[test]
     fn test_time_to_maturity() {
         let curve = SofrCurve::default_usd();
@@ -211,6 +1089,157 @@ mod tests {
         assert!((ttm - 1.0139).abs() < 0.001);
     }
 
+    #[test]
+    fn test_settlement_lag_shifts_short_tenors() {
+        let day = 86_400u64;
+        let now = 1_700_000_000u64;
+        let same_day = SofrCurve::default_usd();
+        assert_eq!(same_day.settlement_lag_days, 0);
+        let t_plus_2 = SofrCurve::default_usd().with_spot_date(2);
+        assert_eq!(t_plus_2.spot_timestamp(now), now + 2 * day);
+
+        // A week out, T+2 values five days of discounting instead of seven
+        let maturity = (now + 7 * day) as u32;
+        let ttm = t_plus_2.time_to_maturity(now, maturity);
+        assert!((ttm - 5.0 / 360.0).abs() < 1e-12);
+        let df_spot = same_day.discount_factor(same_day.time_to_maturity(now, maturity));
+        let df_lagged = t_plus_2.discount_factor(ttm);
+        assert!(df_lagged > df_spot);
+        // Two days at about 5.2% is roughly 2.9 bps of discount factor
+        let shift_bps = (df_lagged / df_spot - 1.0) * 10_000.0;
+        assert!((shift_bps - 2.9).abs() < 0.1, "shift {shift_bps} bps");
+
+        // The same two days matter relatively less at longer tenors
+        let year = (now + 360 * day) as u32;
+        let long_shift = t_plus_2.discount_factor(t_plus_2.time_to_maturity(now, year))
+            / same_day.discount_factor(same_day.time_to_maturity(now, year));
+        let long_shift_bps = (long_shift - 1.0) * 10_000.0;
+        assert!(long_shift_bps > 0.0 && long_shift_bps < shift_bps);
+
+        // A maturity inside the settlement lag is already matured
+        let tomorrow = (now + day) as u32;
+        assert_eq!(t_plus_2.time_to_maturity(now, tomorrow), 0.0);
+        assert_eq!(t_plus_2.discount_factor(0.0), 1.0);
+    }
+
+    #[test]
+    fn test_year_fraction_from_timestamps_near_u32_boundary() {
+        let day = 86_400u64;
+        let boundary = u32::MAX as u64;
+        let one_day = DayCount::Act360.year_fraction(1);
+
+        // A day before the u32 limit, and a maturity past it
+        let ttm = DayCount::Act360.year_fraction_from_timestamps(boundary - day, boundary);
+        assert!((ttm - one_day).abs() < 1e-12);
+        let ttm = DayCount::Act360.year_fraction_from_timestamps(boundary, boundary + day);
+        assert!((ttm - one_day).abs() < 1e-12);
+
+        // Matured, including current timestamps beyond any maturity
+        assert_eq!(
+            DayCount::Act365.year_fraction_from_timestamps(boundary + 1, boundary),
+            0.0
+        );
+        assert_eq!(
+            DayCount::Act365.year_fraction_from_timestamps(u64::MAX, 0),
+            0.0
+        );
+
+        // A current timestamp past the i64 range no longer wraps to a huge maturity
+        let curve = SofrCurve::default_usd();
+        assert_eq!(curve.time_to_maturity(u64::MAX, u32::MAX), 0.0);
+        assert_eq!(curve.time_to_maturity(boundary + day, u32::MAX), 0.0);
+        let ttm = curve.time_to_maturity(boundary - day, u32::MAX);
+        assert!((ttm - one_day).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_index_presets() {
+        let estr = RateCurve::default_eur();
+        assert_eq!(estr.index, RateIndex::Estr);
+        assert_eq!(estr.index.name(), "ESTR");
+        assert_eq!(estr.index.currency(), Some("EUR"));
+        assert!(matches!(estr.day_count, DayCount::Act360));
+
+        let sonia = RateCurve::default_gbp();
+        assert_eq!(sonia.index, RateIndex::Sonia);
+        assert_eq!(sonia.index.currency(), Some("GBP"));
+        assert!(matches!(sonia.day_count, DayCount::Act365));
+
+        // Presets are valid discount curves
+        for curve in [estr, sonia] {
+            let df1 = curve.discount_factor(0.5);
+            let df2 = curve.discount_factor(1.0);
+            assert!(df1 < 1.0 && df2 < df1);
+        }
+
+        // Lookup by index and currency
+        assert_eq!(
+            RateCurve::preset(&RateIndex::from_name("sonia"))
+                .unwrap()
+                .index,
+            RateIndex::Sonia
+        );
+        assert_eq!(
+            RateCurve::for_currency("eur").unwrap().index,
+            RateIndex::Estr
+        );
+        assert!(RateCurve::preset(&RateIndex::from_name("TONA")).is_none());
+        assert_eq!(RateIndex::from_name("TONA").name(), "TONA");
+
+        // The SOFR alias still builds SOFR curves
+        let sofr: SofrCurve = SofrCurve::default_usd();
+        assert_eq!(sofr.index, RateIndex::Sofr);
+    }
+
+    #[test]
+    fn test_blend_curves_on_merged_knots() {
+        let primary = SofrCurve::new(
+            vec![
+                CurveKnot {
+                    t: 0.25,
+                    rate: 0.05,
+                },
+                CurveKnot { t: 1.0, rate: 0.04 },
+            ],
+            DayCount::Act360,
+        );
+        let reference = SofrCurve::new(
+            vec![
+                CurveKnot { t: 0.5, rate: 0.03 },
+                CurveKnot { t: 1.0, rate: 0.02 },
+                CurveKnot { t: 2.0, rate: 0.02 },
+            ],
+            DayCount::Act360,
+        );
+        let tenors = [0.1, 0.25, 0.4, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0];
+
+        // Blends sit on the union of both grids
+        let mid = primary.blend(&reference, 0.5);
+        let knots: Vec<f64> = mid.knots.iter().map(|k| k.t).collect();
+        assert_eq!(knots, vec![0.25, 0.5, 1.0, 2.0]);
+
+        // Weight 1 and 0 reproduce the inputs, 0.5 sits halfway
+        for t in tenors {
+            let (p, r) = (primary.rate(t), reference.rate(t));
+            assert!((primary.blend(&reference, 1.0).rate(t) - p).abs() < 1e-12);
+            assert!((primary.blend(&reference, 0.0).rate(t) - r).abs() < 1e-12);
+            assert!((mid.rate(t) - (p + r) / 2.0).abs() < 1e-12);
+        }
+        // At 3M the primary's 5% meets the reference held flat at 3%
+        assert!((mid.knots[0].rate - 0.04).abs() < 1e-12);
+
+        // Weights outside [0, 1] are clamped
+        assert!((primary.blend(&reference, 1.5).rate(0.5) - primary.rate(0.5)).abs() < 1e-12);
+
+        // Tenors of a curve on another day count are converted, 365 days either way
+        let act365 = SofrCurve::new(vec![CurveKnot { t: 1.0, rate: 0.02 }], DayCount::Act365);
+        let blended = primary.blend(&act365, 0.5);
+        assert!(blended
+            .knots
+            .iter()
+            .any(|k| (k.t - 365.0 / 360.0).abs() < 1e-12));
+    }
+
     #[test]
     fn test_forward_rate() {
         let curve = SofrCurve::default_usd();
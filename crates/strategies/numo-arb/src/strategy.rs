@@ -1,23 +1,60 @@
 /// Main strategy module for Numo Engine arbitrage
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use ethers::prelude::*;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 use artemis_core::types::Strategy;
 
+use crate::backoff::EvaluationBackoff;
+use crate::band::FairValueBand;
+use crate::budget::CapitalBudget;
+use crate::cancel::InflightTrades;
+use crate::carry::{carry_position, CarryPositions, CarryTrade};
+use crate::curve_health::CurveHealth;
+use crate::data_log::{DataLogger, OpportunityRecord};
+use crate::dedup::OpportunityDeduper;
+use crate::execution::{DefaultRouterPolicy, ExecutionPolicy, FALLBACK_GAS_LIMIT};
+use crate::gas::gas_cost_in_base;
+use crate::heatmap::{HeatmapExporter, SpreadHeatmap};
+use crate::kill_switch::KillSwitch;
+use crate::latency::LatencyAlarm;
+use crate::loss_limit::DailyLossLimit;
+use crate::persistence::EdgePersistence;
+use crate::price_source::{MedianProbeSource, OnChainPriceSource, PriceSource};
 use crate::pricing::{
-    apply_slippage, get_pool_state, marginal_price_base_per_fy, meets_edge_threshold,
-    solve_fy_amount_to_target, PoolState,
+    base_for_fy_at_price, best_opportunity_index, break_even_edge_bps,
+    cap_fy_amount_by_price_impact, cheap_and_rich_indices, curves_agree, fetch_pool_states,
+    get_pool_state, get_pool_state_at, input_fee, invariant_deviation_bps,
+    max_profitable_gas_price, meets_edge_threshold, meets_edge_threshold_after_fee,
+    optimal_fy_amount_closed_form, price_divergence_bps, reserve_imbalance_ratio, size_candidates,
+    slippage_bounds, solve_fy_amount_to_target, PoolState, SlippageInputs,
 };
-use crate::sofr::SofrCurve;
+use crate::priority::{event_priority, EventDebouncer};
+use crate::profit::{FixedRateOracle, ProfitLedger};
+use crate::promotion::{DryRunPromotion, PromotionDecision};
+use crate::queue::OpportunityQueue;
+use crate::quoter::{LocalQuoter, PreviewDrift};
+use crate::recorder::{
+    curve_fingerprint, EventRecorder, RecordedCall, RecordedEvent, RecordingPriceSource,
+};
+use crate::reload::{apply_reload, SharedConfig};
+use crate::rpc::{pools_within_budget, RpcCallCounter};
+use crate::schedule::PoolScheduler;
+use crate::snapshot::StateSnapshot;
+use crate::sofr::RateCurve;
+use crate::submission::order_actions_by_profit;
+use crate::throttle::TradeThrottle;
 use crate::types::{
-    Action, ArbOpportunity, Config, Event, GasBidInfo, NewBlockEvent, SubmitTxToMempool,
+    Action, ArbDirection, ArbOpportunity, Config, CurveRefreshEvent, Event, NewBlockEvent,
+    OpportunityDecision, PendingTxEvent, SkipReason, SlippageModel, SubmitTxToMempool, TickEvent,
 };
-use numo_bindings::{NumoArbRouter, NumoEnginePool};
+use crate::volatility::PriceVolatility;
+use numo_bindings::{NumoArbRouter, NumoEnginePool, ERC20};
 
 /// Numo arbitrage strategy
 /// Monitors Numo Engine pools and executes arbitrage when prices diverge from SOFR curve
@@ -28,51 +65,668 @@ pub struct NumoArb<M: Middleware> {
     /// Strategy configuration
     config: Config,
 
-    /// SOFR curve for discount factor calculations
-    sofr_curve: SofrCurve,
+    /// Rate curve for discount factor calculations
+    curve: RateCurve,
+
+    /// Second curve that must confirm every divergence before trading, if configured
+    reference_curve: Option<RateCurve>,
 
-    /// Router contract instance
-    router: NumoArbRouter<M>,
+    /// Policy turning opportunities into actions
+    execution_policy: Box<dyn ExecutionPolicy>,
+
+    /// Pool prices and trade previews
+    price_source: Box<dyn PriceSource>,
 
     /// Pool state cache
     pool_states: HashMap<Address, PoolState>,
 
+    /// Pool maturities, read once since they never change
+    maturities: HashMap<Address, u32>,
+
     /// Last processed block
     last_block: u64,
+
+    /// Base fee of the last processed block, if the node reported one
+    base_fee: Option<U256>,
+
+    /// Router approvals found missing at startup, emitted on the first block
+    token_approvals: Vec<Action>,
+
+    /// Tokens approved at startup whose allowance does not cover a trade yet;
+    /// trading waits until it does
+    unconfirmed_approvals: Vec<Address>,
+
+    /// Opportunity data log, if enabled
+    data_logger: Option<DataLogger>,
+
+    /// Periodic spread heatmap export, if enabled
+    heatmap: Option<HeatmapExporter>,
+
+    /// Emergency kill switch checked every block
+    kill_switch: KillSwitch,
+
+    /// Whether trading is paused by the kill switch
+    paused: bool,
+
+    /// Skips full evaluation after a run of empty blocks
+    backoff: EvaluationBackoff,
+
+    /// Suppresses opportunities repeated across blocks, if enabled
+    deduper: Option<OpportunityDeduper>,
+
+    /// Entry/exit hysteresis per pair, if enabled
+    band: Option<FairValueBand>,
+
+    /// Consecutive-block edge requirement, if enabled
+    persistence: Option<EdgePersistence>,
+
+    /// Cap on executed trades per sliding minute, if enabled
+    throttle: Option<TradeThrottle>,
+
+    /// Live opportunities ranked by net profit across blocks, if enabled
+    opportunity_queue: Option<OpportunityQueue>,
+
+    /// Base token of each pool, read once when profit is converted
+    base_tokens: HashMap<Address, Address>,
+
+    /// FY token the router pulls for carry trades selling FY, read once when
+    /// approvals are managed in carry mode
+    fy_token: Option<Address>,
+
+    /// Carry positions held per pool, released by the executor when their trade fails
+    carry_positions: Arc<CarryPositions>,
+
+    /// Profit totals in the configured profit token, if enabled
+    profit_ledger: Option<ProfitLedger>,
+
+    /// Hashes of submitted trades, filled in by the executor, if cancellation is enabled
+    inflight: Option<Arc<InflightTrades>>,
+
+    /// Base reserved by in-flight trades, released by the executor, if enabled
+    budget: Option<Arc<CapitalBudget>>,
+
+    /// Realized PnL of the current 24h window, fed by the executor, if enabled
+    loss_limit: Option<Arc<DailyLossLimit>>,
+
+    /// Whether trading is halted by the daily loss limit
+    loss_halted: bool,
+
+    /// Last trade emitted per pair, with its calldata and block timestamp
+    pending_trades: HashMap<(Address, Address), (Bytes, u64)>,
+
+    /// Event recording for replay, if enabled
+    recorder: Option<EventRecorder>,
+
+    /// Price source calls made since the last recorded event
+    recorded_calls: Arc<Mutex<Vec<RecordedCall>>>,
+
+    /// On-chain pricing and preview calls made since the last block started
+    rpc_calls: Arc<RpcCallCounter>,
+
+    /// Last fresh price of each pool, reused for pools over the RPC budget
+    cached_prices: Mutex<HashMap<Address, U256>>,
+
+    /// Rotation of the pools priced fresh each block, when bounded
+    scheduler: Option<Mutex<PoolScheduler>>,
+
+    /// Outcome of the most recent block evaluation
+    last_evaluation: Option<EvaluationSnapshot>,
+
+    /// Decision taken in the most recently processed block
+    last_decision: OpportunityDecision,
+
+    /// Recent price moves per pool, for volatility-scaled slippage
+    volatility: PriceVolatility,
+
+    /// Pools past maturity, refreshed once and excluded from trading
+    matured: HashSet<Address>,
+
+    /// Block the pool states were last refreshed at, so ticks and pending swaps
+    /// re-evaluating it do not fetch them again
+    refreshed_block: Option<u64>,
+
+    /// Slow block evaluation alarm, if enabled
+    latency: Option<LatencyAlarm>,
+
+    /// Hot-reloaded config whose safe settings are applied at every block
+    shared_config: Option<SharedConfig>,
+
+    /// Dry run window gating live trading on paper results, if enabled
+    promotion: Option<DryRunPromotion>,
+
+    /// Suppresses ticks right after a block, if enabled
+    debouncer: Option<EventDebouncer>,
+
+    /// Freshness of the rate curve in use
+    curve_health: CurveHealth,
+}
+
+/// Outcome of a block evaluation, cached for read-only queries
+#[derive(Debug, Clone)]
+pub struct EvaluationSnapshot {
+    /// Evaluated block
+    pub block_number: u64,
+
+    /// Timestamp the block was evaluated at
+    pub timestamp: u64,
+
+    /// Pools, prices and skip reason of the evaluation
+    pub record: OpportunityRecord,
+
+    /// Opportunities found, best first
+    pub opportunities: Vec<ArbOpportunity>,
+
+    /// State of every monitored pool, disabled ones included
+    pub pool_states: Vec<PoolState>,
+}
+
+/// A candidate pair that passed the edge checks, waiting to be sized
+#[derive(Debug, Clone, Copy)]
+struct SizingCandidate {
+    cheap_pool: Address,
+    rich_pool: Address,
+    cheap_price: U256,
+    rich_price: U256,
+    ttm_cheap: f64,
+    ttm_rich: f64,
+    target_price: U256,
 }
 
 impl<M: Middleware + Clone + 'static> NumoArb<M> {
-    /// Create a new Numo arbitrage strategy
-    pub fn new(client: Arc<M>, config: Config, sofr_curve: SofrCurve) -> Self {
-        let router = NumoArbRouter::new(config.router_address, client.clone());
+    /// Create a new Numo arbitrage strategy trading through the default router
+    /// policy `config` describes
+    pub fn new(client: Arc<M>, config: Config, curve: RateCurve) -> Self {
+        let policy = DefaultRouterPolicy::from_config(client.clone(), &config);
+        Self::new_with_policy(client, config, curve, Box::new(policy))
+    }
+
+    /// Create a new Numo arbitrage strategy trading through `execution_policy`,
+    /// e.g., a [DefaultRouterPolicy] whose gas components receipt observers share
+    pub fn new_with_policy(
+        client: Arc<M>,
+        config: Config,
+        curve: RateCurve,
+        execution_policy: Box<dyn ExecutionPolicy>,
+    ) -> Self {
+        let recorder = config.record_events_path.clone().map(EventRecorder::new);
+        let recorded_calls = Arc::new(Mutex::new(vec![]));
+        let rpc_calls = Arc::new(RpcCallCounter::new());
+        let mut on_chain =
+            OnChainPriceSource::new(client.clone()).with_call_counter(rpc_calls.clone());
+        if let Some(multicall) = config.multicall_address {
+            on_chain = on_chain.with_multicall(multicall);
+        }
+        let price_source = Self::wrap_price_source(
+            Box::new(on_chain),
+            config.price_probes,
+            recorder.is_some(),
+            &recorded_calls,
+        );
+        let data_logger = config.data_log_path.clone().map(DataLogger::new);
+        let heatmap = config
+            .heatmap_path
+            .clone()
+            .map(|path| HeatmapExporter::new(path, config.heatmap_every_blocks));
+        let kill_switch = KillSwitch::new(config.kill_switch_file.clone());
+        let backoff = EvaluationBackoff::new(config.backoff_after_blocks.unwrap_or(0));
+        let deduper = config.dedup_ttl_blocks.map(OpportunityDeduper::new);
+        let latency = config.max_eval_latency_ms.map(|ms| {
+            LatencyAlarm::new(Duration::from_millis(ms), config.degrade_after_slow_blocks)
+        });
+        let persistence = config.min_edge_persistence_blocks.map(EdgePersistence::new);
+        let throttle = config.max_trades_per_minute.map(TradeThrottle::new);
+        let opportunity_queue = config.opportunity_max_age_blocks.map(OpportunityQueue::new);
+        let promotion = config
+            .dry_run_duration_secs
+            .map(|secs| DryRunPromotion::new(secs, config.promotion_min_net_profit));
+        let band = config
+            .band_thresholds()
+            .map(|(enter, exit)| FairValueBand::new(enter, exit));
+
+        let profit_ledger = config.profit_token.map(|token| {
+            let oracle = config
+                .profit_rates
+                .iter()
+                .fold(FixedRateOracle::new(token), |oracle, (base, rate)| {
+                    oracle.with_rate(*base, *rate)
+                });
+            ProfitLedger::new(token, Box::new(oracle))
+        });
 
         Self {
             client,
-            config,
-            sofr_curve,
-            router,
+            reference_curve: None,
+            execution_policy,
+            price_source,
             pool_states: HashMap::new(),
+            maturities: HashMap::new(),
             last_block: 0,
+            base_fee: None,
+            token_approvals: Vec::new(),
+            unconfirmed_approvals: Vec::new(),
+            data_logger,
+            heatmap,
+            kill_switch,
+            paused: false,
+            backoff,
+            deduper,
+            band,
+            persistence,
+            throttle,
+            opportunity_queue,
+            base_tokens: HashMap::new(),
+            fy_token: None,
+            carry_positions: Arc::new(CarryPositions::new()),
+            profit_ledger,
+            inflight: None,
+            budget: None,
+            loss_limit: None,
+            loss_halted: false,
+            pending_trades: HashMap::new(),
+            recorder,
+            recorded_calls,
+            rpc_calls,
+            cached_prices: Mutex::new(HashMap::new()),
+            scheduler: config
+                .pools_per_block
+                .map(|pools_per_block| Mutex::new(PoolScheduler::new(pools_per_block))),
+            last_evaluation: None,
+            last_decision: OpportunityDecision::default(),
+            volatility: PriceVolatility::new(),
+            matured: HashSet::new(),
+            refreshed_block: None,
+            latency,
+            shared_config: None,
+            promotion,
+            debouncer: config.debounce_window_secs.map(EventDebouncer::new),
+            curve_health: CurveHealth::new(&curve, config.max_curve_age_secs),
+            curve,
+            config,
+        }
+    }
+
+    /// Replace the policy used to turn opportunities into actions
+    pub fn with_execution_policy(mut self, policy: Box<dyn ExecutionPolicy>) -> Self {
+        self.execution_policy = policy;
+        self
+    }
+
+    /// Replace the source of pool prices and trade previews
+    pub fn with_price_source(mut self, source: Box<dyn PriceSource>) -> Self {
+        self.price_source = Self::wrap_price_source(
+            source,
+            self.config.price_probes,
+            self.recorder.is_some(),
+            &self.recorded_calls,
+        );
+        self
+    }
+
+    /// Wrap `source` to log its calls into `calls` when recording, and to price
+    /// pools from the median of `price_probes` probes when more than one
+    ///
+    /// Recording sits below the probes, so a replay with the same configuration
+    /// asks for exactly the recorded previews.
+    fn wrap_price_source(
+        source: Box<dyn PriceSource>,
+        price_probes: usize,
+        recording: bool,
+        calls: &Arc<Mutex<Vec<RecordedCall>>>,
+    ) -> Box<dyn PriceSource> {
+        let source: Box<dyn PriceSource> = if recording {
+            Box::new(RecordingPriceSource::new(source, calls.clone()))
+        } else {
+            source
+        };
+        if price_probes > 1 {
+            Box::new(MedianProbeSource::new(source, price_probes))
+        } else {
+            source
+        }
+    }
+
+    /// Blocks whose evaluation exceeded `max_eval_latency_ms` since startup
+    pub fn slow_blocks(&self) -> u64 {
+        self.latency
+            .as_ref()
+            .map(LatencyAlarm::slow_blocks)
+            .unwrap_or(0)
+    }
+
+    /// Opportunities and prices from the last evaluated block
+    ///
+    /// Served from the cached evaluation without any RPC; `None` until a block has
+    /// been evaluated.
+    pub fn current_opportunities(&self) -> Option<EvaluationSnapshot> {
+        self.last_evaluation.clone()
+    }
+
+    /// Why the most recently processed block traded or did not
+    pub fn last_decision(&self) -> &OpportunityDecision {
+        &self.last_decision
+    }
+
+    /// On-chain pricing and preview calls made so far in the current block
+    pub fn rpc_calls(&self) -> u64 {
+        self.rpc_calls.calls()
+    }
+
+    /// Live opportunities ranked by net profit, if `opportunity_max_age_blocks` is set
+    pub fn live_opportunities(&self) -> Option<&OpportunityQueue> {
+        self.opportunity_queue.as_ref()
+    }
+
+    /// Search the cached pool states for an opportunity at `timestamp` without
+    /// acting on it, e.g., to reproduce a decision from an exported snapshot
+    pub async fn evaluate_offline(
+        &mut self,
+        block_number: u64,
+        timestamp: u64,
+    ) -> Result<(Option<ArbOpportunity>, OpportunityRecord)> {
+        let mut record = OpportunityRecord::new(block_number);
+        let found = self.find_best_opportunity(timestamp, &mut record).await?;
+        Ok((found, record))
+    }
+
+    /// Replace the cached pool states, e.g., with a recorded snapshot
+    pub fn restore_pool_states(&mut self, states: Vec<PoolState>) {
+        self.pool_states.clear();
+        for state in states {
+            self.maturities.insert(state.address, state.maturity);
+            self.pool_states.insert(state.address, state);
+        }
+    }
+
+    /// Snapshot of the cached pool states, in pool list order
+    fn pool_state_snapshot(&self) -> Vec<PoolState> {
+        self.config
+            .pool_addresses
+            .iter()
+            .filter_map(|pool| self.pool_states.get(pool).cloned())
+            .collect()
+    }
+
+    /// Append an event and the price source calls it caused to the recording
+    fn record_event(&self, event: Event, pool_states: Vec<PoolState>) {
+        let Some(recorder) = &self.recorder else {
+            return;
+        };
+        let recorded = RecordedEvent {
+            event,
+            pool_states,
+            curve_fingerprint: curve_fingerprint(&self.curve),
+            calls: std::mem::take(&mut *self.recorded_calls.lock().unwrap()),
+        };
+        if let Err(e) = recorder.record(&recorded) {
+            warn!(path = %recorder.path().display(), error = ?e, "Failed to record event");
+        }
+    }
+
+    /// Cancel in-flight trades that are superseded or older than `cancel_after_secs`,
+    /// looking their hashes up in `inflight`
+    pub fn with_inflight_trades(mut self, inflight: Arc<InflightTrades>) -> Self {
+        self.inflight = Some(inflight);
+        self
+    }
+
+    /// Refuse trades whose `max_base_in` would exceed the capital left in `budget`,
+    /// reserving it for every emitted trade
+    pub fn with_capital_budget(mut self, budget: Arc<CapitalBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Hold carry positions in `positions`, e.g., shared with the executor so failed
+    /// trades free their pool
+    pub fn with_carry_positions(mut self, positions: Arc<CarryPositions>) -> Self {
+        self.carry_positions = positions;
+        self
+    }
+
+    /// Halt trading for the rest of the 24h window once the realized loss tracked by
+    /// `limit` exceeds its maximum
+    pub fn with_loss_limit(mut self, limit: Arc<DailyLossLimit>) -> Self {
+        self.loss_limit = Some(limit);
+        self
+    }
+
+    /// Only trade divergences that `curve` confirms: the rich pool must clear the
+    /// edge against both curves' targets, in the same direction
+    pub fn with_reference_curve(mut self, curve: RateCurve) -> Self {
+        self.reference_curve = Some(curve);
+        self
+    }
+
+    /// Apply the reloadable settings of `config` at the start of every block
+    pub fn with_shared_config(mut self, config: SharedConfig) -> Self {
+        self.shared_config = Some(config);
+        self
+    }
+
+    /// Pick up settings changed by a config reload since the last block
+    fn refresh_config(&mut self) {
+        let Some(shared) = &self.shared_config else {
+            return;
+        };
+        let latest = shared.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let changes = apply_reload(&mut self.config, &latest);
+        for change in &changes {
+            debug!(%change, "Reloaded config setting applied");
+        }
+        if changes.is_empty() {
+            return;
+        }
+        // The band keeps the pairs it entered across threshold changes
+        match (&mut self.band, self.config.band_thresholds()) {
+            (Some(band), Some((enter, exit))) => band.set_thresholds(enter, exit),
+            (band, thresholds) => {
+                *band = thresholds.map(|(enter, exit)| FairValueBand::new(enter, exit))
+            }
+        }
+    }
+
+    /// Swap in a refreshed rate curve, logging the change when enabled
+    pub fn update_curve(&mut self, curve: RateCurve, current_ts: u64) {
+        // Most refreshes re-read an unchanged curve
+        if self.config.log_curve_changes
+            && curve_fingerprint(&curve) != curve_fingerprint(&self.curve)
+        {
+            self.log_curve_change(&curve, current_ts);
+        }
+        self.curve_health.record_success(current_ts, &curve);
+        self.curve = curve;
+    }
+
+    /// Record a failed curve refresh, keeping the current curve
+    pub fn record_curve_failure(&mut self) {
+        self.curve_health.record_failure();
+        warn!(
+            consecutive_failures = self.curve_health.consecutive_failures(),
+            last_success = ?self.curve_health.last_success(),
+            "Rate curve refresh failed, keeping the current curve"
+        );
+    }
+
+    /// Expected gas cost of a trade on the pair in base token wei: the execution
+    /// policy's gas estimate priced at the last block's base fee and converted at
+    /// `native_price_in_base`, or `gas_cost_base` when either is unknown
+    fn gas_cost(&self, cheap_pool: Address, rich_pool: Address, direction: ArbDirection) -> u128 {
+        let (Some(base_fee), Some(native_price)) =
+            (self.base_fee, self.config.native_price_in_base)
+        else {
+            return self.config.gas_cost_base;
+        };
+        let gas = self
+            .execution_policy
+            .expected_gas(cheap_pool, rich_pool, direction)
+            .unwrap_or_else(|| U256::from(FALLBACK_GAS_LIMIT));
+        gas_cost_in_base(gas, base_fee, native_price, self.config.token_decimals.base)
+    }
+
+    /// Curve fetch time, failures and fingerprint, for readiness and metrics
+    pub fn curve_health(&self) -> &CurveHealth {
+        &self.curve_health
+    }
+
+    /// Log the knot diff and the discount factor shift at each pool's maturity
+    fn log_curve_change(&self, curve: &RateCurve, current_ts: u64) {
+        let changes = self.curve.knot_changes(curve);
+        info!(
+            index = curve.index.name(),
+            knots_changed = changes.len(),
+            "Rate curve updated"
+        );
+        for change in changes {
+            info!(
+                t = change.t,
+                old_rate = ?change.old_rate,
+                new_rate = ?change.new_rate,
+                "Curve knot changed"
+            );
+        }
+
+        for (pool, state) in &self.pool_states {
+            let shift = self
+                .curve
+                .discount_factor_shift(curve, current_ts, state.maturity);
+            info!(
+                pool = ?pool,
+                maturity = state.maturity,
+                old_df = shift.old,
+                new_df = shift.new,
+                shift_bps = shift.bps(),
+                "Target discount factor shift"
+            );
         }
     }
 
     /// Find the best arbitrage opportunity between pools
-    async fn find_best_opportunity(&self, current_ts: u64) -> Result<Option<ArbOpportunity>> {
+    /// Details of the evaluation, including why it was skipped, are filled into `record`
+    async fn find_best_opportunity(
+        &self,
+        current_ts: u64,
+        record: &mut OpportunityRecord,
+    ) -> Result<Option<ArbOpportunity>> {
         if self.pool_states.len() < 2 {
+            record.skip(SkipReason::NotEnoughPools);
             return Ok(None);
         }
 
         // Get prices for all pools
         let mut pool_prices: Vec<(Address, U256, f64)> = Vec::new();
+        let mut priced_pools = Vec::with_capacity(self.config.pool_addresses.len());
 
         for pool_addr in &self.config.pool_addresses {
-            let pool = NumoEnginePool::new(*pool_addr, self.client.clone());
+            if self.matured.contains(pool_addr) || self.config.disabled_pools.contains(pool_addr) {
+                continue;
+            }
+            if let (Some(max_ratio), Some(state)) = (
+                self.config.max_reserve_imbalance_ratio,
+                self.pool_states.get(pool_addr),
+            ) {
+                let ratio = reserve_imbalance_ratio(state);
+                if ratio > max_ratio {
+                    warn!(
+                        pool = ?pool_addr,
+                        base_reserves = state.base_reserves,
+                        fy_reserves = state.fy_reserves,
+                        ratio,
+                        max_ratio,
+                        "Pool reserves too imbalanced, skipping"
+                    );
+                    continue;
+                }
+            }
+            priced_pools.push(*pool_addr);
+        }
+
+        // A bounded schedule prices its subset fresh and the other pools from cache
+        let mut cached_pools = vec![];
+        if let Some(scheduler) = &self.scheduler {
+            let selected = scheduler
+                .lock()
+                .unwrap()
+                .select(&priced_pools, record.block);
+            let (fresh, rest): (Vec<_>, Vec<_>) = priced_pools
+                .into_iter()
+                .partition(|pool| selected.contains(pool));
+            priced_pools = fresh;
+            cached_pools.extend(rest);
+        }
+
+        // Over the RPC budget, price what the budget allows and reuse the last known
+        // price of the other pools
+        if let Some(max_calls) = self.config.max_rpc_calls_per_block {
+            let used = self.rpc_calls.calls();
+            let batched = self.config.multicall_address.is_some();
+            let fresh = pools_within_budget(priced_pools.len(), used, max_calls, batched);
+            if fresh < priced_pools.len() {
+                cached_pools.extend(priced_pools.split_off(fresh));
+                warn!(
+                    max_calls,
+                    used,
+                    fresh,
+                    cached = cached_pools.len(),
+                    "RPC budget exceeded, pricing remaining pools from cache"
+                );
+            }
+        }
+
+        // Pools are compared net of their own fees, so a higher fee alone cannot make
+        // a pool look cheap or rich
+        let priced_pools: Vec<(Address, u16)> = priced_pools
+            .into_iter()
+            .map(|pool| {
+                let fee_bps = self.pool_states.get(&pool).map_or(0, |state| state.fee_bps);
+                (pool, fee_bps)
+            })
+            .collect();
 
-            match marginal_price_base_per_fy(&pool).await {
+        // A pool that fails to price is priced from its reserves if enabled, otherwise
+        // skipped; the others are still compared
+        for (pool_addr, price) in self.price_source.marginal_prices_net(&priced_pools).await {
+            let price = match price {
+                Err(e) if self.config.reserve_price_fallback => self
+                    .reserve_price(pool_addr, current_ts)
+                    .inspect(|price| {
+                        warn!(
+                            pool = ?pool_addr,
+                            error = ?e,
+                            price = %price,
+                            "Previews failed, pricing pool from its reserves"
+                        )
+                    })
+                    .ok_or(e),
+                price => price,
+            };
+            match price {
                 Ok(price) => {
-                    if let Some(state) = self.pool_states.get(pool_addr) {
-                        let ttm = self.sofr_curve.time_to_maturity(current_ts, state.maturity);
-                        pool_prices.push((*pool_addr, price, ttm));
+                    self.volatility.observe(pool_addr, price);
+                    self.cached_prices.lock().unwrap().insert(pool_addr, price);
+                    if let Some(state) = self.pool_states.get(&pool_addr) {
+                        let ttm = self.curve.time_to_maturity(current_ts, state.maturity);
+                        if let Some(scheduler) = &self.scheduler {
+                            let target_df = self.target_discount_factor(pool_addr, ttm);
+                            let target = U256::from((target_df * 1e18) as u128);
+                            if price_divergence_bps(price, target) >= self.config.edge_bps {
+                                scheduler.lock().unwrap().mark_hot(pool_addr, record.block);
+                            }
+                        }
+                        if let Some(tolerance_bps) = self.config.max_invariant_deviation_bps {
+                            let deviation_bps = invariant_deviation_bps(state, ttm, price);
+                            if deviation_bps > tolerance_bps {
+                                warn!(
+                                    pool = ?pool_addr,
+                                    base_reserves = state.base_reserves,
+                                    fy_reserves = state.fy_reserves,
+                                    deviation_bps,
+                                    tolerance_bps,
+                                    "Pool reserves break the invariant, skipping"
+                                );
+                                continue;
+                            }
+                        }
+                        pool_prices.push((pool_addr, price, ttm));
                     }
                 }
                 Err(e) => {
@@ -81,43 +735,170 @@ impl<M: Middleware + Clone + 'static> NumoArb<M> {
             }
         }
 
+        for pool_addr in cached_pools {
+            let cached = self.cached_prices.lock().unwrap().get(&pool_addr).copied();
+            match (cached, self.pool_states.get(&pool_addr)) {
+                (Some(price), Some(state)) => {
+                    let ttm = self.curve.time_to_maturity(current_ts, state.maturity);
+                    pool_prices.push((pool_addr, price, ttm));
+                }
+                _ => debug!(pool = ?pool_addr, "No cached price, pool skipped"),
+            }
+        }
+
+        self.export_heatmap(record.block, current_ts, &pool_prices);
+
+        // Observe-only pools are reported above but never picked for a trade
+        pool_prices.retain(|(pool, price, _)| {
+            let observed = self.config.observe_only_pools.contains(pool);
+            if observed {
+                debug!(pool = ?pool, price = %price, "Observe-only pool priced");
+            }
+            !observed
+        });
+
         // Find cheap and rich pools
         // Cheap = lowest price (FY is undervalued)
         // Rich = highest price (FY is overvalued)
-        if pool_prices.is_empty() {
+        let prices: Vec<U256> = pool_prices.iter().map(|(_, price, _)| *price).collect();
+        let Some((cheap_idx, rich_idx)) = cheap_and_rich_indices(&prices) else {
+            record.skip(SkipReason::NoPrices);
             return Ok(None);
-        }
-
-        let cheap_idx = match pool_prices
-            .iter()
-            .enumerate()
-            .min_by_key(|(_, (_, price, _))| *price)
-            .map(|(i, _)| i)
-        {
-            Some(idx) => idx,
-            None => return Ok(None),
         };
 
-        let rich_idx = match pool_prices
-            .iter()
-            .enumerate()
-            .max_by_key(|(_, (_, price, _))| *price)
-            .map(|(i, _)| i)
-        {
-            Some(idx) => idx,
-            None => return Ok(None),
-        };
+        let (cheap_addr, cheap_price, _) = pool_prices[cheap_idx];
+        let (rich_addr, rich_price, _) = pool_prices[rich_idx];
+        record.cheap_pool = Some(cheap_addr);
+        record.rich_pool = Some(rich_addr);
+        record.cheap_price = Some(cheap_price);
+        record.rich_price = Some(rich_price);
 
-        if cheap_idx == rich_idx {
+        // The pools must diverge by at least the edge before anything is sized; a
+        // single pool or identical prices select the same pool on both sides. The
+        // loosest direction's edge admits the pair, each direction's own edge is
+        // checked when it is sized.
+        let edge_bps = self
+            .config
+            .edge_bps_for(ArbDirection::BuyFyThenSellFy)
+            .min(self.config.edge_bps_for(ArbDirection::SellBaseThenSellFy));
+        let candidate_spread =
+            cheap_idx != rich_idx && meets_edge_threshold(rich_price, cheap_price, edge_bps);
+        let degraded = matches!(&self.latency, Some(alarm) if alarm.is_degraded());
+        if !self.backoff.should_evaluate(candidate_spread) || (degraded && !candidate_spread) {
+            debug!(
+                misses = self.backoff.misses(),
+                degraded, "Backing off, spread shows no candidate"
+            );
+            record.skip(SkipReason::Backoff);
+            return Ok(None);
+        }
+        if !candidate_spread {
+            debug!(
+                cheap_price = %cheap_price,
+                rich_price = %rich_price,
+                "Spread between pools below edge"
+            );
+            record.skip(SkipReason::NoSpread);
             return Ok(None);
         }
 
-        let (cheap_addr, cheap_price, _) = pool_prices[cheap_idx];
-        let (rich_addr, rich_price, ttm_rich) = pool_prices[rich_idx];
+        // With maturities apart the widest gap is not necessarily the best trade, since
+        // each pool has its own target. Every ordered pair whose raw gap clears the
+        // edge is gated and sized; the widest pair goes first so its outcome is the
+        // one reported when no pair qualifies.
+        let pair_record = record.clone();
+        let mut candidates = vec![];
+        if let Some(candidate) = self.pair_candidate(
+            pool_prices[cheap_idx],
+            pool_prices[rich_idx],
+            edge_bps,
+            record,
+        ) {
+            candidates.push((candidate, record.clone()));
+        }
+        for (cheap, &cheap_pool) in pool_prices.iter().enumerate() {
+            for (rich, &rich_pool) in pool_prices.iter().enumerate() {
+                let (_, cheap_price, _) = cheap_pool;
+                let (_, rich_price, _) = rich_pool;
+                if (cheap, rich) == (cheap_idx, rich_idx)
+                    || rich_price <= cheap_price
+                    || !meets_edge_threshold(rich_price, cheap_price, edge_bps)
+                {
+                    continue;
+                }
+                let mut candidate_record = pair_record.clone();
+                if let Some(candidate) =
+                    self.pair_candidate(cheap_pool, rich_pool, edge_bps, &mut candidate_record)
+                {
+                    candidates.push((candidate, candidate_record));
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        // Size candidates concurrently, each filling its own copy of the record
+        let sized = size_candidates(
+            candidates,
+            self.config.sizing_concurrency,
+            |(candidate, mut candidate_record)| async move {
+                let sized = self.size_candidate(&candidate, &mut candidate_record).await;
+                (sized, candidate_record)
+            },
+        )
+        .await;
+
+        let (results, records): (Vec<_>, Vec<_>) = sized.into_iter().unzip();
+        let opportunities: Vec<Option<ArbOpportunity>> = results
+            .iter()
+            .map(|result| result.as_ref().ok().cloned().flatten())
+            .collect();
+        match best_opportunity_index(&opportunities) {
+            Some(best) => {
+                *record = records[best].clone();
+                Ok(opportunities[best].clone())
+            }
+            None => {
+                // Nothing sized: report the first candidate's outcome
+                if let Some(first) = records.first() {
+                    *record = first.clone();
+                }
+                match results.into_iter().find_map(Result::err) {
+                    Some(e) => Err(e),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Gate a pair on its rich pool's divergence from target, recording the pair and
+    /// why it was rejected into `record`
+    fn pair_candidate(
+        &self,
+        (cheap_addr, cheap_price, ttm_cheap): (Address, U256, f64),
+        (rich_addr, rich_price, ttm_rich): (Address, U256, f64),
+        edge_bps: u32,
+        record: &mut OpportunityRecord,
+    ) -> Option<SizingCandidate> {
+        record.cheap_pool = Some(cheap_addr);
+        record.rich_pool = Some(rich_addr);
+        record.cheap_price = Some(cheap_price);
+        record.rich_price = Some(rich_price);
 
-        // Calculate target price from SOFR
-        let target_df = self.sofr_curve.discount_factor(ttm_rich);
+        // Calculate target price from SOFR, shifted by the pool's structural spread
+        let target_df = self.target_discount_factor(rich_addr, ttm_rich);
         let target_price = U256::from((target_df * 1e18) as u128);
+        record.target_price = Some(target_price);
+        let divergence_bps = price_divergence_bps(rich_price, target_price);
+        record.divergence_bps = Some(divergence_bps);
+
+        // Selling into the rich pool pays its fee, so the edge must clear it too
+        let rich_fee_bps = self
+            .pool_states
+            .get(&rich_addr)
+            .map(|state| state.fee_bps)
+            .unwrap_or(0);
 
         debug!(
             cheap_pool = ?cheap_addr,
@@ -129,39 +910,289 @@ impl<M: Middleware + Clone + 'static> NumoArb<M> {
         );
 
         // Check if rich pool price is high enough above target
-        if !meets_edge_threshold(rich_price, target_price, self.config.edge_bps) {
-            debug!("Opportunity doesn't meet edge threshold");
+        match &self.band {
+            Some(band) if band.is_entered((cheap_addr, rich_addr)) => {
+                debug!("Pair already entered, waiting for divergence to narrow below exit");
+                record.skip(SkipReason::Hysteresis);
+                return None;
+            }
+            Some(band)
+                if !band.allows_entry(
+                    (cheap_addr, rich_addr),
+                    divergence_bps.saturating_sub(rich_fee_bps as u32),
+                ) =>
+            {
+                debug!("Opportunity doesn't meet entry threshold");
+                record.skip(SkipReason::BelowEdge);
+                return None;
+            }
+            Some(_) => {}
+            None => {
+                if !meets_edge_threshold_after_fee(rich_price, target_price, edge_bps, rich_fee_bps)
+                {
+                    debug!(
+                        rich_fee_bps,
+                        "Opportunity doesn't meet edge threshold net of fee"
+                    );
+                    record.skip(SkipReason::BelowEdge);
+                    return None;
+                }
+            }
+        }
+
+        // A bad curve feed alone must not trigger a trade
+        if let Some(reference) = &self.reference_curve {
+            let reference_df = reference.discount_factor_with_spread(
+                ttm_rich,
+                self.config.pool_config(rich_addr).curve_spread_bps,
+            );
+            let reference_price = U256::from((reference_df * 1e18) as u128);
+            let edge_bps = edge_bps + rich_fee_bps as u32;
+            if !curves_agree(rich_price, target_price, reference_price, edge_bps) {
+                debug!(
+                    target_price = %target_price,
+                    reference_price = %reference_price,
+                    "Reference curve doesn't confirm the divergence"
+                );
+                record.skip(SkipReason::CurveDisagreement);
+                return None;
+            }
+        }
+
+        Some(SizingCandidate {
+            cheap_pool: cheap_addr,
+            rich_pool: rich_addr,
+            cheap_price,
+            rich_price,
+            ttm_cheap,
+            ttm_rich,
+            target_price,
+        })
+    }
+
+    /// Size a candidate pair and build the opportunity, recording why it was skipped
+    async fn size_candidate(
+        &self,
+        candidate: &SizingCandidate,
+        record: &mut OpportunityRecord,
+    ) -> Result<Option<ArbOpportunity>> {
+        let SizingCandidate {
+            cheap_pool: cheap_addr,
+            rich_pool: rich_addr,
+            cheap_price,
+            rich_price,
+            ttm_cheap,
+            ttm_rich,
+            target_price,
+        } = *candidate;
+
+        let buy_allowed = self.direction_allowed(ArbDirection::BuyFyThenSellFy, candidate);
+        let sell_base_allowed = self.direction_allowed(ArbDirection::SellBaseThenSellFy, candidate);
+        if !buy_allowed && !sell_base_allowed {
+            debug!("Divergence below the edge of every direction");
+            record.skip(SkipReason::BelowEdge);
             return Ok(None);
         }
 
-        // Solve for optimal FY amount to trade
-        let rich_pool = NumoEnginePool::new(rich_addr, self.client.clone());
-        let fy_amount =
-            solve_fy_amount_to_target(&rich_pool, target_price, self.config.max_fy_amount).await?;
+        // Solve for the FY amount taking the rich pool to the target, quoting its
+        // post-trade price from its snapshot. Where the closed form applies, the
+        // trade also stops once the two pools' prices meet, whichever comes first
+        let fy_amount = match self.pool_states.get(&rich_addr) {
+            Some(rich) => {
+                let to_target = solve_fy_amount_to_target(
+                    rich,
+                    ttm_rich,
+                    target_price,
+                    self.config.max_fy_amount,
+                )?;
+                let to_convergence = self
+                    .pool_states
+                    .get(&cheap_addr)
+                    .and_then(|cheap| optimal_fy_amount_closed_form(cheap, rich, ttm_rich));
+                match (to_target, to_convergence) {
+                    (Some(target), Some(convergence)) => Some(target.min(convergence)),
+                    (to_target, _) => to_target,
+                }
+            }
+            None => {
+                debug!(rich_pool = ?rich_addr, "No reserves snapshot to size against");
+                None
+            }
+        };
 
         let fy_amount = match fy_amount {
             Some(amt) if amt > 0 => amt,
             _ => {
                 debug!("Could not solve for FY amount");
+                record.skip(SkipReason::NoSolution);
+                return Ok(None);
+            }
+        };
+
+        // Bound the size by the price impact limit on both legs
+        let fy_amount = match (
+            self.config.max_price_impact_bps,
+            self.pool_states.get(&cheap_addr),
+            self.pool_states.get(&rich_addr),
+        ) {
+            (Some(max_impact_bps), Some(cheap_state), Some(rich_state)) => {
+                let cheap_quoter = LocalQuoter::new(cheap_state, ttm_cheap);
+                let rich_quoter = LocalQuoter::new(rich_state, ttm_rich);
+                let capped = cap_fy_amount_by_price_impact(
+                    fy_amount,
+                    &cheap_quoter,
+                    &rich_quoter,
+                    max_impact_bps,
+                );
+                if capped < fy_amount {
+                    debug!(
+                        fy_amount,
+                        capped, max_impact_bps, "FY amount capped by price impact limit"
+                    );
+                }
+                capped
+            }
+            _ => fy_amount,
+        };
+
+        if fy_amount == 0 {
+            debug!("Price impact limit leaves no tradable size");
+            record.skip(SkipReason::PriceImpact);
+            return Ok(None);
+        }
+
+        record.fy_amount = Some(fy_amount);
+
+        // Calculate expected costs and returns; signal-only mode estimates both legs
+        // at the marginal prices instead of previewing them
+        let (max_base_in, min_base_out) = if self.config.log_opportunities_only {
+            (
+                base_for_fy_at_price(fy_amount, cheap_price),
+                base_for_fy_at_price(fy_amount, rich_price),
+            )
+        } else {
+            let max_base_in = self
+                .price_source
+                .preview_buy_fy(cheap_addr, fy_amount)
+                .await?;
+            let min_base_out = self
+                .price_source
+                .preview_sell_fy(rich_addr, fy_amount)
+                .await?;
+            (max_base_in, min_base_out)
+        };
+
+        if self.config.strict_quoter && !self.config.log_opportunities_only {
+            if let Some(drift) = self.quoter_drift(candidate, fy_amount, max_base_in, min_base_out)
+            {
+                if !drift.within(self.config.quoter_tolerance_bps) {
+                    warn!(
+                        cheap_pool = ?cheap_addr,
+                        rich_pool = ?rich_addr,
+                        buy_fy_drift_bps = drift.buy_fy_bps,
+                        sell_fy_drift_bps = drift.sell_fy_bps,
+                        tolerance_bps = self.config.quoter_tolerance_bps,
+                        "On-chain previews drifted from the local quoter, skipping trade"
+                    );
+                    record.skip(SkipReason::QuoterDrift);
+                    return Ok(None);
+                }
+            }
+        }
+
+        // Selling the base the exact buy costs may acquire a different amount of FY;
+        // trade through whichever allowed entrypoint receives more base for it
+        let sell_base = match (sell_base_allowed, self.config.log_opportunities_only) {
+            (false, _) => None,
+            (true, true) => Some((fy_amount, min_base_out)),
+            (true, false) => match self
+                .preview_sell_base_legs(cheap_addr, rich_addr, max_base_in)
+                .await
+            {
+                Ok(legs) => Some(legs),
+                Err(e) => {
+                    debug!(error = ?e, "Sell-base previews failed, buying exact FY only");
+                    None
+                }
+            },
+        };
+        let (direction, fy_amount, min_base_out) = match sell_base {
+            Some((fy_acquired, base_out)) if !buy_allowed || base_out > min_base_out => {
+                (ArbDirection::SellBaseThenSellFy, fy_acquired, base_out)
+            }
+            _ if buy_allowed => (ArbDirection::BuyFyThenSellFy, fy_amount, min_base_out),
+            _ => {
+                debug!("Only the sell-base direction clears its edge and it cannot be priced");
+                record.skip(SkipReason::BelowEdge);
                 return Ok(None);
             }
         };
+        record.fy_amount = Some(fy_amount);
 
-        // Calculate expected costs and returns
-        let cheap_pool = NumoEnginePool::new(cheap_addr, self.client.clone());
-        let max_base_in = cheap_pool.buy_fy_token_preview(fy_amount).call().await?;
-        let min_base_out = rich_pool.sell_fy_token_preview(fy_amount).call().await?;
+        let gas_cost = self.gas_cost(cheap_addr, rich_addr, direction);
+        if let (Some(cheap_state), Some(rich_state)) = (
+            self.pool_states.get(&cheap_addr),
+            self.pool_states.get(&rich_addr),
+        ) {
+            debug!(
+                break_even_edge_bps =
+                    break_even_edge_bps(cheap_state, rich_state, gas_cost, max_base_in),
+                edge_bps = self.config.edge_bps,
+                notional = max_base_in,
+                gas_cost,
+                "Break-even edge for this trade"
+            );
+        }
 
         if max_base_in >= min_base_out {
-            debug!("Trade would be unprofitable before slippage");
+            // Without the cheap pool's fee the buy would have cost less; tell an
+            // edge eaten by the fee apart from no edge at all
+            let cheap_fee_bps = self
+                .pool_states
+                .get(&cheap_addr)
+                .map_or(0, |state| state.fee_bps);
+            let fee = input_fee(max_base_in, cheap_fee_bps);
+            if max_base_in - fee < min_base_out {
+                info!(
+                    cheap_pool = ?cheap_addr,
+                    cheap_fee_bps,
+                    fee,
+                    edge_before_fee = min_base_out - (max_base_in - fee),
+                    "Cheap pool fee eats the whole edge"
+                );
+                record.skip(SkipReason::FeeEatsEdge);
+            } else {
+                debug!("Trade would be unprofitable before slippage");
+                record.skip(SkipReason::Unprofitable);
+            }
             return Ok(None);
         }
 
         let expected_profit = min_base_out.saturating_sub(max_base_in);
+        record.expected_profit = Some(expected_profit);
 
         // Apply slippage protection
-        let max_base_in_slip = apply_slippage(max_base_in, self.config.slippage_bps, true);
-        let min_base_out_slip = apply_slippage(min_base_out, self.config.slippage_bps, false);
+        let (max_base_in_slip, min_base_out_slip) = slippage_bounds(
+            self.config.slippage_model,
+            max_base_in,
+            min_base_out,
+            &self.slippage_inputs(candidate, fy_amount),
+        );
+
+        // Selling base spends exactly the base in, so the cheap leg's buffer lowers
+        // the FY it must acquire instead, and the rich leg's floor with it
+        let (fy_amount, max_base_in_slip, min_base_out_slip) = match direction {
+            ArbDirection::BuyFyThenSellFy => (fy_amount, max_base_in_slip, min_base_out_slip),
+            ArbDirection::SellBaseThenSellFy => {
+                let scale = |amount: u128, numerator: u128, denominator: u128| {
+                    (U256::from(amount) * U256::from(numerator) / U256::from(denominator.max(1)))
+                        .as_u128()
+                };
+                let min_fy_out = scale(fy_amount, max_base_in, max_base_in_slip);
+                let min_base_out = scale(min_base_out_slip, min_fy_out, fy_amount);
+                (min_fy_out, max_base_in, min_base_out)
+            }
+        };
 
         // Check we're not exceeding position limits
         if max_base_in_slip > self.config.max_base_amount {
@@ -170,6 +1201,7 @@ impl<M: Middleware + Clone + 'static> NumoArb<M> {
                 limit = self.config.max_base_amount,
                 "Trade exceeds max base amount"
             );
+            record.skip(SkipReason::ExceedsMaxBase);
             return Ok(None);
         }
 
@@ -183,115 +1215,4056 @@ impl<M: Middleware + Clone + 'static> NumoArb<M> {
             target_price,
             cheap_price,
             rich_price,
+            direction,
         };
 
-        // Return the opportunity (in future: could compare multiple pairs)
+        // Gate on the conservative number; the optimistic one is only reported
+        if !opportunity.is_profitable_worst_case(gas_cost) {
+            debug!(
+                expected_profit,
+                worst_case_profit = opportunity.worst_case_profit(),
+                gas_cost,
+                "Trade would not cover gas at its slippage bounds"
+            );
+            record.skip(SkipReason::WorstCaseUnprofitable);
+            return Ok(None);
+        }
+
+        let net_profit = opportunity.net_profit(gas_cost);
+        if net_profit < 0 || (net_profit as u128) < self.config.min_net_profit {
+            debug!(
+                expected_profit,
+                net_profit,
+                gas_cost,
+                min_net_profit = self.config.min_net_profit,
+                "Trade would not net the minimum profit after gas"
+            );
+            record.skip(SkipReason::BelowMinNetProfit);
+            return Ok(None);
+        }
+
         Ok(Some(opportunity))
     }
 
-    /// Execute an arbitrage opportunity
-    async fn execute_arbitrage(&self, opp: ArbOpportunity) -> Result<Option<Action>> {
-        info!(
-            cheap_pool = ?opp.cheap_pool,
-            rich_pool = ?opp.rich_pool,
+    /// Whether the divergence of `candidate` clears the edge of `direction` net of
+    /// the rich pool's fee; with the fair value band enabled, the band gates entry
+    /// for every direction instead
+    fn direction_allowed(&self, direction: ArbDirection, candidate: &SizingCandidate) -> bool {
+        let rich_fee_bps = self
+            .pool_states
+            .get(&candidate.rich_pool)
+            .map_or(0, |state| state.fee_bps);
+        self.band.is_some()
+            || meets_edge_threshold_after_fee(
+                candidate.rich_price,
+                candidate.target_price,
+                self.config.edge_bps_for(direction),
+                rich_fee_bps,
+            )
+    }
+
+    /// FY acquired selling `base_in` into the cheap pool, and the base that FY
+    /// sells for on the rich pool
+    async fn preview_sell_base_legs(
+        &self,
+        cheap_pool: Address,
+        rich_pool: Address,
+        base_in: u128,
+    ) -> Result<(u128, u128)> {
+        let fy_acquired = self
+            .price_source
+            .preview_sell_base(cheap_pool, base_in)
+            .await?;
+        let base_out = self
+            .price_source
+            .preview_sell_fy(rich_pool, fy_acquired)
+            .await?;
+        Ok((fy_acquired, base_out))
+    }
+
+    /// Inputs of the configured slippage model for trading `fy_amount` of `candidate`
+    fn slippage_inputs(&self, candidate: &SizingCandidate, fy_amount: u128) -> SlippageInputs {
+        let mut inputs = SlippageInputs {
+            slippage_bps: self.config.slippage_bps,
+            ..SlippageInputs::default()
+        };
+        match self.config.slippage_model {
+            SlippageModel::Flat => {}
+            SlippageModel::Quoter => {
+                if let Some(state) = self.pool_states.get(&candidate.cheap_pool) {
+                    inputs.buy_impact_bps =
+                        LocalQuoter::new(state, candidate.ttm_cheap).buy_fy_impact_bps(fy_amount);
+                }
+                if let Some(state) = self.pool_states.get(&candidate.rich_pool) {
+                    inputs.sell_impact_bps =
+                        LocalQuoter::new(state, candidate.ttm_rich).sell_fy_impact_bps(fy_amount);
+                }
+            }
+            SlippageModel::VolScaled { .. } => {
+                let cheap = self.volatility.volatility_bps(candidate.cheap_pool);
+                let rich = self.volatility.volatility_bps(candidate.rich_pool);
+                inputs.volatility_bps = match (cheap, rich) {
+                    (Some(cheap), Some(rich)) => Some(cheap.max(rich)),
+                    (cheap, rich) => cheap.or(rich),
+                };
+            }
+        }
+        inputs
+    }
+
+    /// Execute an arbitrage opportunity
+    async fn execute_arbitrage(&self, opp: ArbOpportunity) -> Result<Option<Action>> {
+        if self.config.dry_run {
+            info!(
+                dry_run = true,
+                cheap_pool = ?opp.cheap_pool,
+                rich_pool = ?opp.rich_pool,
+                direction = ?opp.direction,
+                fy_amount = opp.fy_amount,
+                max_base_in = opp.max_base_in,
+                min_base_out = opp.min_base_out,
+                expected_profit = opp.expected_profit,
+                cheap_price = %opp.cheap_price,
+                rich_price = %opp.rich_price,
+                target_price = %opp.target_price,
+                "Dry run, opportunity not submitted"
+            );
+            return Ok(None);
+        }
+
+        info!(
+            cheap_pool = ?opp.cheap_pool,
+            rich_pool = ?opp.rich_pool,
+            direction = ?opp.direction,
             fy_amount = opp.fy_amount,
             expected_profit = opp.expected_profit,
             "Executing arbitrage"
         );
 
-        // Build transaction to call router
-        let call = self.router.arb_buy_fy_then_sell_fy(
-            opp.cheap_pool,
-            opp.rich_pool,
-            opp.fy_amount,
-            opp.max_base_in,
-            opp.min_base_out,
-            self.client.default_sender().unwrap_or_default(),
+        let action = self.execution_policy.build_action(&opp).await?;
+        if let Some(Action::SubmitTx(submit)) = &action {
+            if let Some(gas_limit) = submit.tx.gas() {
+                let native_price = self
+                    .config
+                    .native_price_in_base
+                    .map(|price| (price, self.config.token_decimals.base));
+                let bid_percentage = submit
+                    .gas_bid_info
+                    .as_ref()
+                    .map_or(100, |info| info.bid_percentage);
+                info!(
+                    gas_limit = %gas_limit,
+                    max_gas_price = %max_profitable_gas_price(&opp, *gas_limit, 100, native_price),
+                    budget_gas_price = %max_profitable_gas_price(
+                        &opp,
+                        *gas_limit,
+                        bid_percentage,
+                        native_price,
+                    ),
+                    bid_gas_price = ?submit.tx.gas_price(),
+                    "Opportunity gas headroom"
+                );
+            }
+        }
+        Ok(action)
+    }
+
+    /// Process a new block event
+    async fn process_new_block(&mut self, block: NewBlockEvent) -> Vec<Action> {
+        self.last_block = block.block_number;
+        self.base_fee = block.base_fee;
+        self.rpc_calls.take();
+        // The startup curve counts as fetched when the first block arrives
+        self.curve_health.seed(block.timestamp);
+
+        if self.kill_switch.is_active() {
+            if !self.paused {
+                warn!("Kill switch active, trading paused");
+                self.paused = true;
+            }
+            debug!(
+                block_number = block.block_number,
+                "Trading paused, skipping block"
+            );
+            self.last_decision = OpportunityDecision::Paused;
+            return vec![];
+        }
+        if self.paused {
+            info!("Kill switch cleared, trading resumed");
+            self.paused = false;
+        }
+        if let Some(limit) = &self.loss_limit {
+            if limit.is_halted(block.timestamp) {
+                if !self.loss_halted {
+                    error!(
+                        net_pnl = limit.net_pnl(block.timestamp),
+                        max_loss = limit.max_loss(),
+                        resumes_at = limit.resumes_at(block.timestamp),
+                        "Daily loss limit exceeded, trading halted until the losses age out"
+                    );
+                    self.loss_halted = true;
+                }
+                debug!(
+                    block_number = block.block_number,
+                    "Daily loss limit hit, skipping block"
+                );
+                self.last_decision = OpportunityDecision::Paused;
+                return vec![];
+            }
+            if self.loss_halted {
+                info!("Daily losses aged out of the window, trading resumed");
+                self.loss_halted = false;
+            }
+        }
+
+        // Trades revert until the router may pull base, so the block goes to the
+        // approvals alone, and trading waits for them to confirm
+        if !self.token_approvals.is_empty() {
+            info!(
+                block_number = block.block_number,
+                approvals = self.token_approvals.len(),
+                "Submitting token approvals before trading"
+            );
+            self.unconfirmed_approvals = self
+                .token_approvals
+                .iter()
+                .filter_map(|action| match action {
+                    Action::Approve(submit) => submit.tx.to_addr().copied(),
+                    _ => None,
+                })
+                .collect();
+            self.last_decision = OpportunityDecision::NotEvaluated;
+            return std::mem::take(&mut self.token_approvals);
+        }
+        if !self.unconfirmed_approvals.is_empty() {
+            self.confirm_approvals().await;
+            if !self.unconfirmed_approvals.is_empty() {
+                info!(
+                    block_number = block.block_number,
+                    tokens = ?self.unconfirmed_approvals,
+                    "Waiting for token approvals to confirm before trading"
+                );
+                self.last_decision = OpportunityDecision::NotEvaluated;
+                return vec![];
+            }
+            info!("Token approvals confirmed, trading");
+        }
+
+        debug!(block_number = block.block_number, "Processing new block");
+
+        let started = Instant::now();
+        self.refresh_config();
+        self.check_promotion(block.timestamp);
+        self.roll_over_matured_pools(block.timestamp).await;
+        self.refresh_pool_states(block.block_number).await;
+
+        let mut record = OpportunityRecord::new(block.block_number);
+        let mut actions = self.cancel_stale_trades(block.timestamp);
+        if self.config.carry_mode {
+            actions.extend(self.evaluate_carry(block.timestamp).await);
+            self.last_decision = OpportunityDecision::NotEvaluated;
+        } else {
+            let evaluated = self.evaluate_block(&block, &mut record).await;
+            self.last_decision = self.block_decision(&record, &evaluated);
+            actions.extend(evaluated);
+        }
+        if let Some(snapshot) = &mut self.last_evaluation {
+            snapshot.record = record.clone();
+        }
+
+        // Repeats of an already reported opportunity stay out of the data log, as
+        // do carry mode blocks, which evaluate no cross-pool opportunity
+        let duplicate = record.skip_reason == Some(SkipReason::Duplicate);
+        if !duplicate && !self.config.carry_mode {
+            self.log_record(&record);
+        }
+        let held_back = matches!(
+            record.skip_reason,
+            Some(SkipReason::NotPersistent)
+                | Some(SkipReason::BudgetExhausted)
+                | Some(SkipReason::RateLimited)
         );
+        let found = record
+            .skip_reason
+            .is_none_or(|reason| reason.found_opportunity());
+        self.backoff.record(duplicate || held_back || found);
 
-        // Estimate gas
-        let gas_estimate = call.estimate_gas().await.unwrap_or(U256::from(500_000));
-        let gas_with_buffer = gas_estimate * U256::from(120) / U256::from(100); // 20% buffer
+        if self.config.order_by_profit {
+            order_actions_by_profit(&mut actions);
+        }
+        self.check_latency(block.block_number, started.elapsed());
+        debug!(
+            block_number = block.block_number,
+            rpc_calls = self.rpc_calls.calls(),
+            "Block processed"
+        );
+        actions
+    }
 
-        // Build transaction
-        let mut tx = call.tx;
-        tx.set_gas(gas_with_buffer);
+    /// Decision for a block whose evaluation filled in `record` and emitted `actions`
+    fn block_decision(
+        &self,
+        record: &OpportunityRecord,
+        actions: &[Action],
+    ) -> OpportunityDecision {
+        if let Some(reason) = record.skip_reason.filter(|r| !r.found_opportunity()) {
+            return OpportunityDecision::Skipped(reason);
+        }
+        let found = self
+            .last_evaluation
+            .as_ref()
+            .and_then(|snapshot| snapshot.opportunities.first().cloned());
+        match found {
+            Some(opp) if actions.iter().any(|a| matches!(a, Action::SubmitTx(_))) => {
+                OpportunityDecision::Executed(opp)
+            }
+            Some(opp) => OpportunityDecision::Found(opp),
+            None => OpportunityDecision::NotEvaluated,
+        }
+    }
 
-        // Create gas bid info
-        let gas_bid_info = Some(GasBidInfo {
-            total_profit: U256::from(opp.expected_profit),
-            bid_percentage: self.config.bid_percentage,
-        });
+    /// Evaluate a block replayed at startup and log what it would have traded
+    ///
+    /// Nothing is emitted and no live state moves: the last block, base fee, caches,
+    /// throttle and cancellation windows all stay with the live stream.
+    async fn process_historical_block(&mut self, block: NewBlockEvent) -> Vec<Action> {
+        match self.evaluate_historical_block(&block).await {
+            Ok((Some(opp), mut record)) => {
+                info!(
+                    block_number = block.block_number,
+                    cheap_pool = ?opp.cheap_pool,
+                    rich_pool = ?opp.rich_pool,
+                    fy_amount = opp.fy_amount,
+                    expected_profit = opp.expected_profit,
+                    "Historical opportunity, not traded"
+                );
+                // Replayed blocks are never traded
+                record.skip(SkipReason::DryRun);
+                self.log_record(&record);
+            }
+            Ok((None, record)) => {
+                debug!(
+                    block_number = block.block_number,
+                    skip_reason = ?record.skip_reason,
+                    "No historical opportunity"
+                );
+                self.log_record(&record);
+            }
+            Err(e) => warn!(
+                block_number = block.block_number,
+                error = ?e,
+                "Historical block could not be evaluated"
+            ),
+        }
+        vec![]
+    }
 
-        let action = Action::SubmitTx(SubmitTxToMempool { tx, gas_bid_info });
+    /// Search `block` for an opportunity against the pools' reserves and previews at
+    /// that block, as a node keeping past state serves them
+    ///
+    /// A separate evaluator runs the search so prices, volatility and pair state
+    /// seen in the past never leak into live trading.
+    async fn evaluate_historical_block(
+        &self,
+        block: &NewBlockEvent,
+    ) -> Result<(Option<ArbOpportunity>, OpportunityRecord)> {
+        let at = BlockId::from(block.block_number);
+        let mut states = Vec::with_capacity(self.config.pool_addresses.len());
+        for (pool_addr, result) in self
+            .fetch_states(&self.config.pool_addresses, Some(at))
+            .await
+        {
+            // Current state would price the past wrongly, so the block is skipped
+            let state = result.with_context(|| format!("Pool {pool_addr:?} state unavailable"))?;
+            states.push(state);
+        }
 
-        Ok(Some(action))
+        let mut config = self.config.clone();
+        config.data_log_path = None;
+        config.heatmap_path = None;
+        config.record_events_path = None;
+        config.snapshot_path = None;
+        let source = OnChainPriceSource::new(self.client.clone()).at_block(at);
+        let mut evaluator = NumoArb::new(self.client.clone(), config, self.curve.clone())
+            .with_price_source(Box::new(source));
+        evaluator.restore_pool_states(states);
+        evaluator
+            .evaluate_offline(block.block_number, block.timestamp)
+            .await
     }
 
-    /// Process a new block event
-    async fn process_new_block(&mut self, block: NewBlockEvent) -> Vec<Action> {
-        self.last_block = block.block_number;
+    /// Swap in the curve a refresh read, or count its failed read
+    fn process_curve_refresh(&mut self, refresh: CurveRefreshEvent) {
+        match refresh.curve {
+            Some(curve) => self.update_curve(curve, refresh.timestamp),
+            None => self.record_curve_failure(),
+        }
+    }
 
-        debug!(block_number = block.block_number, "Processing new block");
+    /// Re-evaluate the last block at the tick's time, once a block has been seen
+    async fn process_tick(&mut self, tick: TickEvent) -> Vec<Action> {
+        if self.last_block == 0 {
+            return vec![];
+        }
+        let block = NewBlockEvent {
+            block_number: self.last_block,
+            timestamp: tick.timestamp,
+            base_fee: self.base_fee,
+        };
+        self.process_new_block(block).await
+    }
 
-        // Find arbitrage opportunity
-        let opportunity = match self.find_best_opportunity(block.timestamp).await {
-            Ok(Some(opp)) => opp,
-            Ok(None) => {
-                debug!("No profitable opportunity found");
-                return vec![];
+    /// Re-evaluate the last block when a pending transaction swaps through a
+    /// monitored pool, reacting before the block that mines it
+    async fn process_pending_tx(&mut self, tx: PendingTxEvent) -> Vec<Action> {
+        if self.last_block == 0 {
+            return vec![];
+        }
+        let Some(pool) = self.pending_tx_pool(&tx) else {
+            return vec![];
+        };
+        debug!(
+            tx_hash = ?tx.tx_hash,
+            pool = ?pool,
+            "Pending transaction touches a monitored pool, re-evaluating"
+        );
+        let block = NewBlockEvent {
+            block_number: self.last_block,
+            timestamp: tx.timestamp,
+            base_fee: self.base_fee,
+        };
+        self.process_new_block(block).await
+    }
+
+    /// Approvals of the router for every pool base token whose allowance would not
+    /// cover a trade of `max_base_amount`, and in carry mode for the FY token whose
+    /// allowance would not cover `max_fy_amount`
+    ///
+    /// Arbitrage buys and sells FY within the router call, so base is the only token
+    /// it pulls from the bot; carry trades selling FY pull the bot's FY.
+    async fn approval_actions(&self) -> Vec<Action> {
+        let router = self.config.router_address;
+        let amount = self.config.approval_amount.map_or(U256::MAX, U256::from);
+        let mut tokens: Vec<Address> = self
+            .config
+            .pool_addresses
+            .iter()
+            .filter_map(|pool| self.base_tokens.get(pool).copied())
+            .chain(self.fy_token)
+            .collect();
+        tokens.sort();
+        tokens.dedup();
+
+        let mut approvals = Vec::new();
+        for token in tokens {
+            let erc20 = ERC20::new(token, self.client.clone());
+            let allowance = match self.router_allowance(token).await {
+                Ok(allowance) => allowance,
+                Err(e) => {
+                    warn!(token = ?token, error = ?e, "Failed to read router allowance");
+                    continue;
+                }
+            };
+            if allowance >= self.required_allowance(token) {
+                debug!(token = ?token, allowance = %allowance, "Router allowance sufficient");
+                continue;
             }
-            Err(e) => {
-                warn!(error = ?e, "Error finding opportunity");
-                return vec![];
+            if self.config.dry_run {
+                info!(token = ?token, allowance = %allowance, "Dry run, approval not submitted");
+                continue;
             }
-        };
+            info!(
+                token = ?token,
+                allowance = %allowance,
+                amount = %amount,
+                "Router allowance too low, approving"
+            );
+            approvals.push(Action::Approve(SubmitTxToMempool {
+                tx: erc20.approve(router, amount).tx,
+                gas_bid_info: None,
+            }));
+        }
+        if !approvals.is_empty() && amount == U256::MAX {
+            warn!("Approving the router for an unlimited amount; set APPROVAL_AMOUNT to bound it");
+        }
+        approvals
+    }
 
-        // Execute if profitable
-        match self.execute_arbitrage(opportunity).await {
-            Ok(Some(action)) => vec![action],
-            Ok(None) => vec![],
-            Err(e) => {
-                warn!(error = ?e, "Error executing arbitrage");
-                vec![]
+    /// Amount of `token` the router may pull from the bot
+    async fn router_allowance(&self, token: Address) -> Result<U256> {
+        let owner = self.client.default_sender().unwrap_or_default();
+        Ok(ERC20::new(token, self.client.clone())
+            .allowance(owner, self.config.router_address)
+            .call()
+            .await?)
+    }
+
+    /// Allowance of `token` a single trade may need: `max_fy_amount` for the FY
+    /// token carry trades sell, `max_base_amount` for base tokens
+    fn required_allowance(&self, token: Address) -> U256 {
+        if self.fy_token == Some(token) {
+            U256::from(self.config.max_fy_amount)
+        } else {
+            U256::from(self.config.max_base_amount)
+        }
+    }
+
+    /// Drop the tokens whose allowance now covers a trade from the unconfirmed
+    /// approvals
+    async fn confirm_approvals(&mut self) {
+        let mut unconfirmed = Vec::new();
+        for token in std::mem::take(&mut self.unconfirmed_approvals) {
+            match self.router_allowance(token).await {
+                Ok(allowance) if allowance >= self.required_allowance(token) => {
+                    debug!(token = ?token, allowance = %allowance, "Token approval confirmed");
+                }
+                Ok(_) => unconfirmed.push(token),
+                Err(e) => {
+                    debug!(token = ?token, error = ?e, "Failed to read router allowance");
+                    unconfirmed.push(token);
+                }
             }
         }
+        self.unconfirmed_approvals = unconfirmed;
     }
-}
 
-#[async_trait]
-impl<M: Middleware + Clone + 'static> Strategy<Event, Action> for NumoArb<M> {
-    async fn sync_state(&mut self) -> Result<()> {
-        info!("Syncing Numo strategy state");
+    /// Monitored pool a pending transaction touches, if any
+    ///
+    /// Our own router transactions are already tracked as in-flight trades.
+    fn pending_tx_pool(&self, tx: &PendingTxEvent) -> Option<Address> {
+        if tx.to == Some(self.config.router_address) {
+            return None;
+        }
+        self.config
+            .pool_addresses
+            .iter()
+            .copied()
+            .find(|pool| tx.touches(*pool))
+    }
 
-        // Fetch initial state for all pools
-        for pool_addr in &self.config.pool_addresses {
-            let pool = NumoEnginePool::new(*pool_addr, self.client.clone());
+    /// Close the dry run window once it elapses, going live or alerting
+    fn check_promotion(&mut self, timestamp: u64) {
+        let Some(promotion) = &mut self.promotion else {
+            return;
+        };
+        let Some(decision) = promotion.observe(timestamp) else {
+            return;
+        };
+        let results = promotion.results();
+        match decision {
+            PromotionDecision::Promote => info!(
+                trades = results.trades,
+                net_profit = results.net_profit(),
+                "Dry run passed, promoting to live trading"
+            ),
+            PromotionDecision::StayInDryRun => error!(
+                trades = results.trades,
+                net_profit = results.net_profit(),
+                min_net_profit = self.config.promotion_min_net_profit,
+                "Dry run failed its promotion criterion, staying in dry run"
+            ),
+        }
+    }
+
+    /// Raise the latency alarm if a block took too long to evaluate
+    fn check_latency(&mut self, block_number: u64, elapsed: Duration) {
+        let Some(alarm) = &mut self.latency else {
+            return;
+        };
+        let was_degraded = alarm.is_degraded();
+        if alarm.observe(elapsed) {
+            warn!(
+                block_number,
+                elapsed_ms = elapsed.as_millis() as u64,
+                max_eval_latency_ms = alarm.max_latency().as_millis() as u64,
+                slow_blocks = alarm.slow_blocks(),
+                "Block evaluation exceeded latency limit"
+            );
+        }
+        match (was_degraded, alarm.is_degraded()) {
+            (false, true) => warn!("Evaluation persistently slow, sizing only candidate spreads"),
+            (true, false) => info!("Evaluation back within latency limit"),
+            _ => {}
+        }
+    }
+
+    /// Refresh the cache of pools that reached maturity at `current_ts` and drop them
+    /// from trading
+    ///
+    /// Reserves cached before maturity no longer match the pool, and its previews
+    /// change meaning once it has matured.
+    async fn roll_over_matured_pools(&mut self, current_ts: u64) {
+        let newly_matured: Vec<(Address, u32)> = self
+            .pool_states
+            .values()
+            .filter(|state| current_ts >= state.maturity as u64)
+            .filter(|state| !self.matured.contains(&state.address))
+            .map(|state| (state.address, state.maturity))
+            .collect();
 
-            match get_pool_state(&pool, *pool_addr).await {
+        for (pool_addr, maturity) in newly_matured {
+            let pool = NumoEnginePool::new(pool_addr, self.client.clone());
+            match get_pool_state(&pool, pool_addr, Some(maturity)).await {
                 Ok(state) => {
                     info!(
                         pool = ?pool_addr,
+                        maturity,
                         base_reserves = state.base_reserves,
                         fy_reserves = state.fy_reserves,
-                        maturity = state.maturity,
-                        "Loaded pool state"
+                        "Pool reached maturity, cache refreshed and pool excluded from trading"
                     );
-                    self.pool_states.insert(*pool_addr, state);
+                    self.pool_states.insert(pool_addr, state);
                 }
                 Err(e) => {
-                    warn!(pool = ?pool_addr, error = ?e, "Failed to load pool state");
+                    warn!(
+                        pool = ?pool_addr,
+                        maturity,
+                        error = ?e,
+                        "Pool reached maturity, cache refresh failed; pool excluded from trading"
+                    );
                 }
             }
+            self.matured.insert(pool_addr);
         }
+    }
 
-        info!(pools_loaded = self.pool_states.len(), "State sync complete");
-        Ok(())
+    /// Re-fetch the reserves of every live pool when `block_number` falls on the
+    /// `state_refresh_blocks` interval and was not refreshed yet, keeping the
+    /// cached state of pools whose fetch fails
+    async fn refresh_pool_states(&mut self, block_number: u64) {
+        let Some(interval) = self.config.state_refresh_blocks else {
+            return;
+        };
+        if !block_number.is_multiple_of(interval) || self.refreshed_block == Some(block_number) {
+            return;
+        }
+        self.refreshed_block = Some(block_number);
+
+        let live: Vec<Address> = self
+            .config
+            .pool_addresses
+            .iter()
+            .filter(|pool| !self.matured.contains(*pool))
+            .copied()
+            .collect();
+        let results = self.fetch_states(&live, None).await;
+
+        let mut refreshed = 0;
+        for (pool_addr, result) in results {
+            match result {
+                Ok(state) => {
+                    self.pool_states.insert(pool_addr, state);
+                    refreshed += 1;
+                }
+                Err(e) => {
+                    warn!(pool = ?pool_addr, error = ?e, "Failed to refresh pool state");
+                }
+            }
+        }
+        debug!(
+            block_number,
+            refreshed,
+            pools = live.len(),
+            "Pool states refreshed"
+        );
     }
 
-    async fn process_event(&mut self, event: Event) -> Vec<Action> {
-        match event {
-            Event::NewBlock(block) => self.process_new_block(block).await,
+    /// Fetch the state of `pools` as of `block`, or the latest block, a bounded
+    /// batch at a time, reusing known maturities
+    async fn fetch_states(
+        &self,
+        pools: &[Address],
+        block: Option<BlockId>,
+    ) -> Vec<(Address, Result<PoolState>)> {
+        let client = self.client.clone();
+        let maturities = &self.maturities;
+        fetch_pool_states(pools, self.config.sync_concurrency, |pool_addr| {
+            let pool = NumoEnginePool::new(pool_addr, client.clone());
+            let maturity = maturities.get(&pool_addr).copied();
+            async move { get_pool_state_at(&pool, pool_addr, maturity, block).await }
+        })
+        .await
+    }
+
+    /// Drift of a candidate's on-chain previews from the local quoters, if both
+    /// pools have a known state and their quoters can fill `fy_amount`
+    fn quoter_drift(
+        &self,
+        candidate: &SizingCandidate,
+        fy_amount: u128,
+        base_in: u128,
+        base_out: u128,
+    ) -> Option<PreviewDrift> {
+        let cheap = self.pool_states.get(&candidate.cheap_pool)?;
+        let rich = self.pool_states.get(&candidate.rich_pool)?;
+        PreviewDrift::of_trade(
+            &LocalQuoter::new(cheap, candidate.ttm_cheap),
+            &LocalQuoter::new(rich, candidate.ttm_rich),
+            fy_amount,
+            base_in,
+            base_out,
+        )
+    }
+
+    /// Open a carry position in every pool whose implied rate is far enough from
+    /// the curve, given its time to maturity
+    ///
+    /// Pools are visited in configured order, skipping matured, disabled and
+    /// observe-only ones. A pool holds one position until it matures, so it is not
+    /// traded again while that position is open.
+    async fn evaluate_carry(&mut self, current_ts: u64) -> Vec<Action> {
+        let mut actions = vec![];
+        for pool in self.config.pool_addresses.clone() {
+            if self.matured.contains(&pool) {
+                self.carry_positions.close(pool);
+                continue;
+            }
+            if self.config.disabled_pools.contains(&pool)
+                || self.config.observe_only_pools.contains(&pool)
+            {
+                continue;
+            }
+            if let Some(direction) = self.carry_positions.position(pool) {
+                debug!(pool = ?pool, direction = ?direction, "Carry position already open, skipping");
+                continue;
+            }
+            let Some(maturity) = self.pool_states.get(&pool).map(|state| state.maturity) else {
+                continue;
+            };
+            let price = match self.price_source.marginal_price(pool).await {
+                Ok(price) => price,
+                Err(e) => {
+                    warn!(pool = ?pool, error = ?e, "Failed to get pool price");
+                    continue;
+                }
+            };
+            let ttm = self.curve.time_to_maturity(current_ts, maturity);
+            let Some(position) = carry_position(
+                pool,
+                price,
+                ttm,
+                self.target_discount_factor(pool, ttm),
+                self.config.edge_bps,
+                self.config.max_fy_amount,
+            ) else {
+                continue;
+            };
+            let trade = CarryTrade::new(position, price, self.config.slippage_bps);
+            info!(
+                pool = ?pool,
+                direction = ?trade.position.direction,
+                fy_amount = trade.position.fy_amount,
+                base_limit = trade.base_limit,
+                expected_profit = trade.expected_profit,
+                pool_rate = trade.position.pool_rate,
+                curve_rate = trade.position.curve_rate,
+                rate_gap_bps = trade.position.rate_gap_bps,
+                price_edge_bps = trade.position.price_edge_bps,
+                ttm,
+                "Carry position"
+            );
+            if let Some(action) = self.execute_carry(trade, current_ts).await {
+                actions.push(action);
+            }
+        }
+        actions
+    }
+
+    /// Execute a carry trade through the gates of [Self::execute_opportunity]:
+    /// signal-only mode, paper trading, dry run, the capital budget, the trade rate
+    /// limit and the execution policy
+    async fn execute_carry(&mut self, trade: CarryTrade, timestamp: u64) -> Option<Action> {
+        let position = &trade.position;
+        if self.config.log_opportunities_only {
+            return None;
+        }
+
+        let gas_cost = self.carry_gas_cost();
+        if let Some(promotion) = self.promotion.as_mut().filter(|p| !p.is_live()) {
+            promotion.record_paper_trade(trade.expected_profit, gas_cost);
+            info!(
+                pool = ?position.pool,
+                fy_amount = position.fy_amount,
+                expected_profit = trade.expected_profit,
+                paper_net_profit = promotion.results().net_profit(),
+                "Paper carry trade"
+            );
+            return None;
+        }
+
+        if self.config.dry_run {
+            info!(
+                dry_run = true,
+                pool = ?position.pool,
+                direction = ?position.direction,
+                fy_amount = position.fy_amount,
+                base_limit = trade.base_limit,
+                expected_profit = trade.expected_profit,
+                "Dry run, carry trade not submitted"
+            );
+            return None;
+        }
+
+        let base_in = trade.base_in();
+        if let Some(budget) = &self.budget {
+            if base_in > budget.available() {
+                debug!(
+                    base_in,
+                    available = budget.available(),
+                    "Capital budget exhausted, skipping"
+                );
+                return None;
+            }
+        }
+
+        if let Some(throttle) = &mut self.throttle {
+            if !throttle.allows(timestamp) {
+                info!(
+                    pool = ?position.pool,
+                    fy_amount = position.fy_amount,
+                    expected_profit = trade.expected_profit,
+                    trades = throttle.count(timestamp),
+                    max_trades_per_minute = throttle.max_trades(),
+                    "Trade rate limit reached, carry trade not executed"
+                );
+                return None;
+            }
+        }
+
+        let action = match self.execution_policy.build_carry_action(&trade).await {
+            Ok(Some(action)) => action,
+            Ok(None) => return None,
+            Err(e) => {
+                warn!(pool = ?position.pool, error = ?e, "Error executing carry trade");
+                return None;
+            }
+        };
+        if !self.reserve_budget(&action, base_in) {
+            return None;
+        }
+        if let Some(throttle) = &mut self.throttle {
+            throttle.record(timestamp);
+        }
+        if let Action::SubmitTx(submit) = &action {
+            if let Some(calldata) = submit.tx.data() {
+                self.carry_positions
+                    .open(position.pool, position.direction, calldata.clone());
+            }
         }
+        Some(action)
+    }
+
+    /// Gas cost of a carry trade in base tokens, priced like [Self::gas_cost] at the
+    /// fallback gas limit since no estimate is kept for single pool trades
+    fn carry_gas_cost(&self) -> u128 {
+        let (Some(base_fee), Some(native_price)) =
+            (self.base_fee, self.config.native_price_in_base)
+        else {
+            return self.config.gas_cost_base;
+        };
+        let gas = U256::from(FALLBACK_GAS_LIMIT);
+        gas_cost_in_base(gas, base_fee, native_price, self.config.token_decimals.base)
+    }
+
+    /// Find and execute the best opportunity for a block, recording the outcome
+    async fn evaluate_block(
+        &mut self,
+        block: &NewBlockEvent,
+        record: &mut OpportunityRecord,
+    ) -> Vec<Action> {
+        // Find arbitrage opportunity
+        let found = self.find_best_opportunity(block.timestamp, record).await;
+        self.last_evaluation = Some(EvaluationSnapshot {
+            block_number: block.block_number,
+            timestamp: block.timestamp,
+            record: record.clone(),
+            opportunities: found.as_ref().ok().cloned().flatten().into_iter().collect(),
+            pool_states: self
+                .config
+                .pool_addresses
+                .iter()
+                .filter_map(|pool| self.pool_states.get(pool).cloned())
+                .collect(),
+        });
+        self.export_snapshot(block);
+
+        // Re-arm the evaluated pair once its divergence has narrowed
+        if let (Some(band), Some(cheap), Some(rich), Some(divergence)) = (
+            &mut self.band,
+            record.cheap_pool,
+            record.rich_pool,
+            record.divergence_bps,
+        ) {
+            band.observe((cheap, rich), divergence);
+        }
+
+        // Refresh the evaluated pair's live opportunity and age out the others
+        let found_gas_cost = match &found {
+            Ok(Some(opp)) => self.gas_cost(opp.cheap_pool, opp.rich_pool, opp.direction),
+            _ => 0,
+        };
+        if let Some(queue) = &mut self.opportunity_queue {
+            match &found {
+                Ok(Some(opp)) => queue.upsert(
+                    opp.clone(),
+                    opp.net_profit(found_gas_cost),
+                    block.block_number,
+                ),
+                Ok(None) => {
+                    if let (Some(cheap), Some(rich)) = (record.cheap_pool, record.rich_pool) {
+                        queue.remove((cheap, rich));
+                    }
+                }
+                Err(_) => {}
+            }
+            let evicted = queue.evict_stale(block.block_number);
+            if let Some(best) = queue.best() {
+                debug!(
+                    live = queue.len(),
+                    evicted,
+                    cheap_pool = ?best.opportunity.cheap_pool,
+                    rich_pool = ?best.opportunity.rich_pool,
+                    net_profit = best.net_profit,
+                    "Best live opportunity"
+                );
+            }
+        }
+
+        // Track the edge streak, resetting it on blocks without an opportunity
+        if let Some(persistence) = &mut self.persistence {
+            let pair = match &found {
+                Ok(Some(opp)) => Some((opp.cheap_pool, opp.rich_pool)),
+                _ => None,
+            };
+            if !persistence.observe(pair, block.block_number) && pair.is_some() {
+                debug!(
+                    block_number = block.block_number,
+                    streak = persistence.streak(),
+                    "Edge has not persisted long enough, skipping"
+                );
+                record.skip(SkipReason::NotPersistent);
+                return vec![];
+            }
+        }
+
+        let opportunity = match found {
+            Ok(Some(opp)) => opp,
+            Ok(None) => {
+                debug!("No profitable opportunity found");
+                return vec![];
+            }
+            Err(e) => {
+                warn!(error = ?e, "Error finding opportunity");
+                record.skip(SkipReason::Error);
+                return vec![];
+            }
+        };
+
+        if let Some(deduper) = &mut self.deduper {
+            if !deduper.check(&opportunity, block.block_number) {
+                debug!(
+                    block_number = block.block_number,
+                    "Opportunity already reported, skipping"
+                );
+                record.skip(SkipReason::Duplicate);
+                return vec![];
+            }
+        }
+
+        self.execute_opportunity(opportunity, block.timestamp, record)
+            .await
+    }
+
+    /// Execute an opportunity, cancelling the pair's in-flight trade it supersedes
+    async fn execute_opportunity(
+        &mut self,
+        opportunity: ArbOpportunity,
+        timestamp: u64,
+        record: &mut OpportunityRecord,
+    ) -> Vec<Action> {
+        if self.config.log_opportunities_only {
+            info!(
+                cheap_pool = ?opportunity.cheap_pool,
+                rich_pool = ?opportunity.rich_pool,
+                cheap_price = %opportunity.cheap_price,
+                rich_price = %opportunity.rich_price,
+                target_price = %opportunity.target_price,
+                fy_amount = opportunity.fy_amount,
+                expected_profit = opportunity.expected_profit,
+                fy = %self.config.token_decimals.fy_amount(opportunity.fy_amount),
+                profit = %self.config.token_decimals.base_amount(opportunity.expected_profit),
+                "Opportunity signal"
+            );
+            record.skip(SkipReason::SignalOnly);
+            return vec![];
+        }
+
+        let gas_cost = self.gas_cost(
+            opportunity.cheap_pool,
+            opportunity.rich_pool,
+            opportunity.direction,
+        );
+        if let Some(promotion) = self.promotion.as_mut().filter(|p| !p.is_live()) {
+            let decimals = self.config.token_decimals;
+            promotion.record_paper_trade(opportunity.expected_profit, gas_cost);
+            info!(
+                cheap_pool = ?opportunity.cheap_pool,
+                rich_pool = ?opportunity.rich_pool,
+                fy_amount = opportunity.fy_amount,
+                expected_profit = opportunity.expected_profit,
+                paper_net_profit = promotion.results().net_profit(),
+                profit = %decimals.base_amount(opportunity.expected_profit),
+                paper_net = %decimals.signed_base_amount(promotion.results().net_profit()),
+                "Paper trade"
+            );
+            record.skip(SkipReason::PaperTrade);
+            return vec![];
+        }
+
+        if let Some(budget) = &self.budget {
+            if opportunity.max_base_in > budget.available() {
+                debug!(
+                    max_base_in = opportunity.max_base_in,
+                    available = budget.available(),
+                    "Capital budget exhausted, skipping"
+                );
+                record.skip(SkipReason::BudgetExhausted);
+                return vec![];
+            }
+        }
+
+        if let Some(throttle) = &mut self.throttle {
+            if !throttle.allows(timestamp) {
+                info!(
+                    cheap_pool = ?opportunity.cheap_pool,
+                    rich_pool = ?opportunity.rich_pool,
+                    fy_amount = opportunity.fy_amount,
+                    expected_profit = opportunity.expected_profit,
+                    trades = throttle.count(timestamp),
+                    max_trades_per_minute = throttle.max_trades(),
+                    "Trade rate limit reached, opportunity not executed"
+                );
+                record.skip(SkipReason::RateLimited);
+                return vec![];
+            }
+        }
+
+        let pair = (opportunity.cheap_pool, opportunity.rich_pool);
+        let expected_profit = opportunity.expected_profit;
+        let max_base_in = opportunity.max_base_in;
+        match self.execute_arbitrage(opportunity).await {
+            Ok(Some(action)) => {
+                if !self.reserve_budget(&action, max_base_in) {
+                    record.skip(SkipReason::BudgetExhausted);
+                    return vec![];
+                }
+                if let Some(throttle) = &mut self.throttle {
+                    throttle.record(timestamp);
+                }
+                if let Some(band) = &mut self.band {
+                    band.enter(pair);
+                }
+                self.record_profit(pair.1, expected_profit);
+                let mut actions: Vec<Action> = self
+                    .supersede(pair, &action, timestamp)
+                    .into_iter()
+                    .collect();
+                actions.push(action);
+                actions
+            }
+            Ok(None) => {
+                record.skip(if self.config.dry_run {
+                    SkipReason::DryRun
+                } else {
+                    SkipReason::PolicyDeclined
+                });
+                vec![]
+            }
+            Err(e) => {
+                warn!(error = ?e, "Error executing arbitrage");
+                record.skip(SkipReason::Error);
+                vec![]
+            }
+        }
+    }
+
+    /// Reserve `max_base_in` of the capital budget for `action`, if enabled
+    fn reserve_budget(&self, action: &Action, max_base_in: u128) -> bool {
+        let (Some(budget), Action::SubmitTx(submit)) = (&self.budget, action) else {
+            return true;
+        };
+        match submit.tx.data() {
+            Some(calldata) => budget.try_reserve(calldata.clone(), max_base_in),
+            None => true,
+        }
+    }
+
+    /// Release the capital budget held by the trade carrying `calldata`, if enabled
+    fn release_budget(&self, calldata: &Bytes) {
+        if let Some(budget) = &self.budget {
+            budget.release(calldata);
+        }
+    }
+
+    /// Track `action` as the pair's latest trade, returning a cancellation for the
+    /// in-flight trade it replaces
+    fn supersede(
+        &mut self,
+        pair: (Address, Address),
+        action: &Action,
+        timestamp: u64,
+    ) -> Option<Action> {
+        let inflight = self.inflight.as_ref()?;
+        let calldata = match action {
+            Action::SubmitTx(submit) => submit.tx.data().cloned()?,
+            Action::Cancel(_) | Action::Approve(_) => return None,
+        };
+
+        let previous = self.pending_trades.insert(pair, (calldata, timestamp));
+        let (calldata, _) = previous?;
+        self.release_budget(&calldata);
+        let tx_hash = inflight.tx_hash(&calldata)?;
+        info!(
+            cheap_pool = ?pair.0,
+            rich_pool = ?pair.1,
+            tx_hash = ?tx_hash,
+            "New trade supersedes in-flight trade, cancelling"
+        );
+        Some(Action::Cancel(tx_hash))
+    }
+
+    /// Cancel tracked trades older than `cancel_after_secs`
+    fn cancel_stale_trades(&mut self, now: u64) -> Vec<Action> {
+        let (Some(inflight), Some(timeout)) = (&self.inflight, self.config.cancel_after_secs)
+        else {
+            return vec![];
+        };
+
+        let mut cancels = vec![];
+        let budget = &self.budget;
+        self.pending_trades.retain(|pair, (calldata, emitted_ts)| {
+            if now.saturating_sub(*emitted_ts) < timeout {
+                return true;
+            }
+            if let Some(budget) = budget {
+                budget.release(calldata);
+            }
+            if let Some(tx_hash) = inflight.tx_hash(calldata) {
+                warn!(
+                    cheap_pool = ?pair.0,
+                    rich_pool = ?pair.1,
+                    tx_hash = ?tx_hash,
+                    age_secs = now.saturating_sub(*emitted_ts),
+                    "In-flight trade is stale, cancelling"
+                );
+                cancels.push(Action::Cancel(tx_hash));
+            }
+            false
+        });
+        cancels
+    }
+
+    /// Record a trade's expected profit in the profit token ledger, if enabled
+    fn record_profit(&mut self, rich_pool: Address, profit: u128) {
+        let Some(ledger) = &mut self.profit_ledger else {
+            return;
+        };
+        let Some(base_token) = self.base_tokens.get(&rich_pool).copied() else {
+            warn!(pool = ?rich_pool, "Base token unknown, profit not recorded");
+            return;
+        };
+        let converted = ledger.record(base_token, profit);
+        info!(
+            base_token = ?base_token,
+            native_profit = profit,
+            native = %self.config.token_decimals.base_amount(profit),
+            profit = ?converted,
+            profit_token = ?ledger.profit_token(),
+            total_profit = %ledger.total(),
+            native_total = ledger.native_total(base_token),
+            trades = ledger.trades(),
+            "Profit recorded"
+        );
+    }
+
+    /// Curve discount factor `ttm` years out, shifted by `pool`'s curve spread
+    fn target_discount_factor(&self, pool: Address, ttm: f64) -> f64 {
+        let spread_bps = self.config.pool_config(pool).curve_spread_bps;
+        self.curve.discount_factor_with_spread(ttm, spread_bps)
+    }
+
+    /// Marginal price `pool`'s cached reserves imply at `timestamp`, if its state is
+    /// known
+    fn reserve_price(&self, pool: Address, timestamp: u64) -> Option<U256> {
+        let state = self.pool_states.get(&pool)?;
+        let ttm = self.curve.time_to_maturity(timestamp, state.maturity);
+        let price = LocalQuoter::new(state, ttm).marginal_price();
+        Some(U256::from((price * 1e18) as u128))
+    }
+
+    /// Export the pairwise spreads of this block's pool prices, if enabled and due
+    fn export_heatmap(&self, block: u64, timestamp: u64, pool_prices: &[(Address, U256, f64)]) {
+        let Some(exporter) = self.heatmap.as_ref().filter(|e| e.is_due(block)) else {
+            return;
+        };
+        let prices: Vec<(Address, U256)> = pool_prices
+            .iter()
+            .map(|(pool, price, _)| (*pool, *price))
+            .collect();
+        let heatmap = SpreadHeatmap::from_prices(block, timestamp, &prices);
+        if let Err(e) = exporter.export(&heatmap) {
+            warn!(path = %exporter.path().display(), error = ?e, "Failed to write heatmap");
+        }
+    }
+
+    /// Write the pool and curve state the block was evaluated against, if enabled
+    fn export_snapshot(&self, block: &NewBlockEvent) {
+        let Some(path) = &self.config.snapshot_path else {
+            return;
+        };
+        let snapshot = StateSnapshot {
+            block_number: block.block_number,
+            timestamp: block.timestamp,
+            curve: self.curve.clone(),
+            pool_states: self.pool_state_snapshot(),
+        };
+        if let Err(e) = snapshot.write(path) {
+            warn!(path = %path.display(), error = ?e, "Failed to write snapshot");
+        }
+    }
+
+    /// Append an evaluated opportunity to the data log, if enabled
+    fn log_record(&self, record: &OpportunityRecord) {
+        if let Some(logger) = &self.data_logger {
+            if let Err(e) = logger.log(record) {
+                warn!(path = %logger.path().display(), error = ?e, "Failed to write data log");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + Clone + 'static> Strategy<Event, Action> for NumoArb<M> {
+    async fn sync_state(&mut self) -> Result<()> {
+        info!("Syncing Numo strategy state");
+
+        if self.config.pool_addresses.len() > self.config.max_pools {
+            anyhow::bail!(
+                "{} pools configured, more than the maximum of {}",
+                self.config.pool_addresses.len(),
+                self.config.max_pools
+            );
+        }
+
+        // Fetch initial state for all pools, a bounded batch at a time
+        let pools = self.config.pool_addresses.clone();
+        let results = self.fetch_states(&pools, None).await;
+
+        for (pool_addr, result) in results {
+            match result {
+                Ok(state) => {
+                    info!(
+                        pool = ?pool_addr,
+                        base_reserves = state.base_reserves,
+                        fy_reserves = state.fy_reserves,
+                        maturity = state.maturity,
+                        "Loaded pool state"
+                    );
+                    self.maturities.insert(pool_addr, state.maturity);
+                    self.pool_states.insert(pool_addr, state);
+                }
+                Err(e) => {
+                    warn!(pool = ?pool_addr, error = ?e, "Failed to load pool state");
+                }
+            }
+        }
+
+        // Base tokens only matter for profit conversion and approvals
+        if self.profit_ledger.is_some() || self.config.auto_approve {
+            for pool_addr in &self.config.pool_addresses {
+                if self.base_tokens.contains_key(pool_addr) {
+                    continue;
+                }
+                let pool = NumoEnginePool::new(*pool_addr, self.client.clone());
+                match pool.base().call().await {
+                    Ok(base_token) => {
+                        self.base_tokens.insert(*pool_addr, base_token);
+                    }
+                    Err(e) => {
+                        warn!(pool = ?pool_addr, error = ?e, "Failed to load pool base token");
+                    }
+                }
+            }
+        }
+
+        // Carry trades selling FY have the router pull it from the bot
+        if self.config.auto_approve && self.config.carry_mode && self.fy_token.is_none() {
+            let router = NumoArbRouter::new(self.config.router_address, self.client.clone());
+            match router.fy_token().call().await {
+                Ok(fy_token) => self.fy_token = Some(fy_token),
+                Err(e) => warn!(error = ?e, "Failed to load router FY token"),
+            }
+        }
+
+        if self.config.auto_approve {
+            self.token_approvals = self.approval_actions().await;
+        }
+
+        info!(pools_loaded = self.pool_states.len(), "State sync complete");
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: Event) -> Vec<Action> {
+        // Most of the mempool is unrelated; drop it before debouncing or recording
+        if let Event::PendingTx(tx) = &event {
+            if self.pending_tx_pool(tx).is_none() {
+                return vec![];
+            }
+        }
+        // Replayed blocks are read-only, so they neither debounce live events nor
+        // belong in a recording of them
+        if let Event::HistoricalBlock(block) = event {
+            return self.process_historical_block(block).await;
+        }
+        // A curve swap carries no chain state to evaluate, and must not be debounced
+        if let Event::CurveRefresh(refresh) = event {
+            self.process_curve_refresh(refresh);
+            return vec![];
+        }
+        if let Some(debouncer) = &mut self.debouncer {
+            if !debouncer.should_process(event_priority(&event), event.timestamp()) {
+                debug!(event = ?event, "Debounced after a higher-priority event");
+                return vec![];
+            }
+        }
+
+        // Capture the inputs before processing so the event can be replayed
+        let recording = self
+            .recorder
+            .is_some()
+            .then(|| (event.clone(), self.pool_state_snapshot()));
+
+        let actions = match event {
+            Event::NewBlock(block) => self.process_new_block(block).await,
+            Event::Tick(tick) => self.process_tick(tick).await,
+            Event::PendingTx(tx) => self.process_pending_tx(tx).await,
+            Event::HistoricalBlock(_) | Event::CurveRefresh(_) => {
+                unreachable!("replayed blocks and curve refreshes are handled above")
+            }
+        };
+
+        if let Some((event, pool_states)) = recording {
+            self.record_event(event, pool_states);
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::RwLock;
+
+    use crate::clock::{Clock, MockClock};
+    use crate::confirmation::{ReceiptObserver, SubmittedTx};
+    use crate::loss_limit::LOSS_WINDOW_SECS;
+    use crate::pnl::PnlEntry;
+    use crate::price_source::QuoterPriceSource;
+    use crate::pricing::apply_slippage;
+    use crate::sofr::{CurveKnot, DayCount};
+    use crate::test_utils::{encode_uints, opportunity, push_pool_prices};
+    use crate::types::{GasEstimation, PoolConfig, SubmitTxToMempool};
+    use ethers::abi::AbiDecode;
+    use numo_bindings::{BuyFYCall, SellFYCall};
+
+    /// Policy that counts calls and returns a fixed transaction, or nothing
+    struct StubPolicy {
+        calls: Arc<AtomicUsize>,
+        emit: bool,
+    }
+
+    #[async_trait]
+    impl ExecutionPolicy for StubPolicy {
+        async fn build_action(&self, opp: &ArbOpportunity) -> Result<Option<Action>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if !self.emit {
+                return Ok(None);
+            }
+            let tx = TransactionRequest::new()
+                .to(opp.rich_pool)
+                .value(opp.fy_amount)
+                .data(opp.fy_amount.to_be_bytes().to_vec());
+            Ok(Some(Action::SubmitTx(SubmitTxToMempool {
+                tx: tx.into(),
+                gas_bid_info: None,
+            })))
+        }
+    }
+
+    fn strategy_with_policy(emit: bool) -> (NumoArb<Provider<MockProvider>>, Arc<AtomicUsize>) {
+        let (provider, _mock) = Provider::mocked();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = StubPolicy {
+            calls: calls.clone(),
+            emit,
+        };
+        let strategy = NumoArb::new(
+            Arc::new(provider),
+            Config::default(),
+            RateCurve::default_usd(),
+        )
+        .with_execution_policy(Box::new(policy));
+        (strategy, calls)
+    }
+
+    #[tokio::test]
+    async fn test_execute_arbitrage_delegates_to_policy() {
+        let (strategy, calls) = strategy_with_policy(true);
+
+        let action = strategy.execute_arbitrage(opportunity()).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        match action {
+            Some(Action::SubmitTx(submit)) => {
+                assert_eq!(submit.tx.to_addr(), Some(&Address::repeat_byte(0x22)));
+                assert_eq!(submit.tx.value(), Some(&U256::from(1_000)));
+            }
+            _ => panic!("expected a SubmitTx action"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_suppresses_actions() {
+        let path = std::env::temp_dir().join(format!("numo-strategy-kill-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let (provider, _mock) = Provider::mocked();
+        let config = Config {
+            kill_switch_file: Some(path.clone()),
+            ..Config::default()
+        };
+        let mut strategy = NumoArb::new(Arc::new(provider), config, RateCurve::default_usd());
+        let block = |block_number| {
+            Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp: 1_700_000_000,
+                base_fee: None,
+            })
+        };
+
+        std::fs::write(&path, b"").unwrap();
+        assert!(strategy.process_event(block(1)).await.is_empty());
+        assert!(strategy.paused);
+        assert!(strategy.process_event(block(2)).await.is_empty());
+        assert!(strategy.paused);
+
+        std::fs::remove_file(&path).unwrap();
+        strategy.process_event(block(3)).await;
+        assert!(!strategy.paused);
+    }
+
+    #[tokio::test]
+    async fn test_daily_loss_limit_halts_trading() {
+        let (provider, _mock) = Provider::mocked();
+        let limit = Arc::new(DailyLossLimit::new(1_000));
+        let mut strategy = NumoArb::new(
+            Arc::new(provider),
+            Config::default(),
+            RateCurve::default_usd(),
+        )
+        .with_loss_limit(limit.clone());
+        let start = 1_700_000_000u64;
+        let block = |block_number, timestamp| {
+            Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp,
+                base_fee: None,
+            })
+        };
+
+        // Losses within the limit keep trading
+        limit.record(start, -600);
+        strategy.process_event(block(1, start + 12)).await;
+        assert!(!strategy.loss_halted);
+
+        // Past the limit, nothing is evaluated for the rest of the window
+        limit.record(start + 12, -500);
+        assert!(strategy
+            .process_event(block(2, start + 24))
+            .await
+            .is_empty());
+        assert!(strategy.loss_halted);
+        let last_minute = start + LOSS_WINDOW_SECS - 60;
+        assert!(strategy
+            .process_event(block(3, last_minute))
+            .await
+            .is_empty());
+        assert!(strategy.loss_halted);
+
+        // Trading resumes once the first loss is a day old
+        strategy
+            .process_event(block(4, start + LOSS_WINDOW_SECS))
+            .await;
+        assert!(!strategy.loss_halted);
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_drives_loss_halt_and_resume() {
+        let (provider, _mock) = Provider::mocked();
+        let clock = Arc::new(MockClock::new(1_700_000_000));
+        let limit = Arc::new(DailyLossLimit::new(1_000).with_clock(clock.clone()));
+        let mut strategy = NumoArb::new(
+            Arc::new(provider),
+            Config::default(),
+            RateCurve::default_usd(),
+        )
+        .with_loss_limit(limit.clone());
+        // Blocks are stamped with the clock, as the block collector does
+        let mut block_number = 0;
+        let mut next_block = || {
+            block_number += 1;
+            Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp: clock.now(),
+                base_fee: None,
+            })
+        };
+        let reverted = PnlEntry {
+            block: 1,
+            tx_hash: TxHash::zero(),
+            cheap_pool: Address::zero(),
+            rich_pool: Address::zero(),
+            expected_profit: 0,
+            gross_profit: 0,
+            gas_spent: 10u128.pow(15),
+            gas_cost_base: Some(700),
+        };
+
+        // Two reverted trades ten minutes apart lose 1_400 and halt trading
+        limit.record_entry(&reverted);
+        clock.advance(600);
+        strategy.process_event(next_block()).await;
+        assert!(!strategy.loss_halted);
+        limit.record_entry(&reverted);
+        strategy.process_event(next_block()).await;
+        assert!(strategy.loss_halted);
+
+        // Every hour until the first loss ages out stays halted
+        for _ in 0..23 {
+            clock.advance(3_600);
+            assert!(strategy.process_event(next_block()).await.is_empty());
+            assert!(strategy.loss_halted);
+        }
+        assert!(matches!(
+            strategy.last_decision(),
+            OpportunityDecision::Paused
+        ));
+
+        // 24h after the first loss it ages out and trading resumes
+        clock.advance(LOSS_WINDOW_SECS - 600 - 23 * 3_600);
+        strategy.process_event(next_block()).await;
+        assert!(!strategy.loss_halted);
+    }
+
+    /// Strategy over `config.pool_addresses`, all matured at `now` so they price at par
+    /// (target 1.0), with mock responses supplied by the caller
+    fn strategy_with_pools(
+        config: Config,
+        fee_bps: u16,
+        now: u64,
+    ) -> (NumoArb<Provider<MockProvider>>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        let pools = config.pool_addresses.clone();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, RateCurve::default_usd());
+        for address in pools {
+            let state = PoolState {
+                address,
+                base_reserves: 1_000_000,
+                fy_reserves: 1_000_000,
+                fee_bps,
+                maturity: now as u32,
+            };
+            strategy.pool_states.insert(address, state);
+        }
+        (strategy, mock)
+    }
+
+    #[tokio::test]
+    async fn test_edge_must_clear_rich_pool_fee() {
+        let now = 1_700_000_000u64;
+        let config = Config {
+            pool_addresses: vec![Address::repeat_byte(0x11), Address::repeat_byte(0x22)],
+            edge_bps: 10,
+            ..Config::default()
+        };
+        let (strategy, mock) = strategy_with_pools(config, 10, now);
+        let one = 10u128.pow(18);
+
+        // The rich pool is 15 bps above target: clears the 10 bps edge, not edge + fee
+        push_pool_prices(&mock, &[one, one + one * 15 / 10_000]);
+        let mut record = OpportunityRecord::new(1);
+        assert!(strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(record.divergence_bps, Some(15));
+        assert_eq!(record.skip_reason, Some(SkipReason::BelowEdge));
+    }
+
+    #[tokio::test]
+    async fn test_equal_and_near_equal_prices_skip_before_sizing() {
+        let now = 1_700_000_000u64;
+        let config = Config {
+            pool_addresses: vec![
+                Address::repeat_byte(0x11),
+                Address::repeat_byte(0x22),
+                Address::repeat_byte(0x33),
+            ],
+            edge_bps: 10,
+            ..Config::default()
+        };
+        let (strategy, mock) = strategy_with_pools(config, 0, now);
+        let one = 10u128.pow(18);
+        let evaluate = |prices: &[u128]| {
+            push_pool_prices(&mock, prices);
+            let mut record = OpportunityRecord::new(1);
+            let strategy = &strategy;
+            async move {
+                let found = strategy.find_best_opportunity(now, &mut record).await;
+                assert!(found.unwrap().is_none());
+                record
+            }
+        };
+
+        // All equal: the first pool is both cheap and rich, and no target is computed
+        let record = evaluate(&[one, one, one]).await;
+        assert_eq!(record.skip_reason, Some(SkipReason::NoSpread));
+        assert_eq!(record.cheap_pool, Some(Address::repeat_byte(0x11)));
+        assert_eq!(record.rich_pool, Some(Address::repeat_byte(0x11)));
+        assert_eq!(record.target_price, None);
+
+        // A 5 bps spread is below the 10 bps edge
+        let record = evaluate(&[one, one + one * 5 / 10_000, one]).await;
+        assert_eq!(record.skip_reason, Some(SkipReason::NoSpread));
+        assert_eq!(record.rich_pool, Some(Address::repeat_byte(0x22)));
+        assert_eq!(record.target_price, None);
+
+        // An 11 bps spread clears the edge and moves on to the target check, where
+        // the rich pool sits at target; equal rich pools resolve to the first
+        let record = evaluate(&[one - one * 11 / 10_000, one, one]).await;
+        assert_eq!(record.skip_reason, Some(SkipReason::BelowEdge));
+        assert_eq!(record.rich_pool, Some(Address::repeat_byte(0x22)));
+        assert!(record.target_price.is_some());
+    }
+
+    /// Quoter-backed source counting trade previews
+    struct CountingSource {
+        inner: QuoterPriceSource,
+        previews: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl PriceSource for CountingSource {
+        async fn marginal_price(&self, pool: Address) -> Result<U256> {
+            self.inner.marginal_price(pool).await
+        }
+
+        async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+            self.previews.fetch_add(1, Ordering::SeqCst);
+            self.inner.preview_buy_fy(pool, fy_out).await
+        }
+
+        async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+            self.previews.fetch_add(1, Ordering::SeqCst);
+            self.inner.preview_sell_fy(pool, fy_in).await
+        }
+
+        async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+            self.previews.fetch_add(1, Ordering::SeqCst);
+            self.inner.preview_sell_base(pool, base_in).await
+        }
+    }
+
+    /// Quoter prices whose FY sale previews pay `drift_bps` less than the local math
+    struct DriftingSource {
+        inner: QuoterPriceSource,
+        drift_bps: u128,
+    }
+
+    #[async_trait]
+    impl PriceSource for DriftingSource {
+        async fn marginal_price(&self, pool: Address) -> Result<U256> {
+            self.inner.marginal_price(pool).await
+        }
+
+        async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+            self.inner.preview_buy_fy(pool, fy_out).await
+        }
+
+        async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+            let base_out = self.inner.preview_sell_fy(pool, fy_in).await?;
+            Ok(base_out * (10_000 - self.drift_bps) / 10_000)
+        }
+
+        async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+            self.inner.preview_sell_base(pool, base_in).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strict_quoter_skips_drifted_previews() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            strict_quoter: true,
+            quoter_tolerance_bps: 10,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+
+        let evaluate = |drift_bps| {
+            let source = DriftingSource {
+                inner: QuoterPriceSource::new()
+                    .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                    .with_pool(rich.address, LocalQuoter::new(&rich, ttm)),
+                drift_bps,
+            };
+            let policy = StubPolicy {
+                calls: Arc::new(AtomicUsize::new(0)),
+                emit: true,
+            };
+            let (provider, _mock) = Provider::mocked();
+            let mut strategy = NumoArb::new(Arc::new(provider), config.clone(), curve.clone())
+                .with_execution_policy(Box::new(policy))
+                .with_price_source(Box::new(source));
+            strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+            async move {
+                let block = NewBlockEvent {
+                    block_number: 1,
+                    timestamp: now,
+                    base_fee: None,
+                };
+                let mut record = OpportunityRecord::new(1);
+                let actions = strategy.evaluate_block(&block, &mut record).await;
+                (actions.len(), record.skip_reason)
+            }
+        };
+
+        // Drift at or below the tolerance still trades
+        assert_eq!(evaluate(0).await, (1, None));
+        assert_eq!(evaluate(5).await, (1, None));
+        assert_eq!(evaluate(9).await, (1, None));
+
+        // Beyond it the trade is skipped
+        assert_eq!(evaluate(50).await, (0, Some(SkipReason::QuoterDrift)));
+    }
+
+    #[tokio::test]
+    async fn test_log_opportunities_only_skips_previews_and_execution() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        // Cheap pool prices FY near 0.96, rich near 0.99, against a target near 0.977
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+
+        for signal_only in [false, true] {
+            let config = Config {
+                pool_addresses: vec![cheap.address, rich.address],
+                max_fy_amount: 1_000 * one,
+                log_opportunities_only: signal_only,
+                ..Config::default()
+            };
+            let curve = RateCurve::default_usd();
+            let ttm = curve.time_to_maturity(now, maturity);
+            let previews = Arc::new(AtomicUsize::new(0));
+            let source = CountingSource {
+                inner: QuoterPriceSource::new()
+                    .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                    .with_pool(rich.address, LocalQuoter::new(&rich, ttm)),
+                previews: previews.clone(),
+            };
+            let calls = Arc::new(AtomicUsize::new(0));
+            let policy = StubPolicy {
+                calls: calls.clone(),
+                emit: true,
+            };
+            let (provider, _mock) = Provider::mocked();
+            let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+                .with_execution_policy(Box::new(policy))
+                .with_price_source(Box::new(source));
+            strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+
+            let block = NewBlockEvent {
+                block_number: 1,
+                timestamp: now,
+                base_fee: None,
+            };
+            let mut record = OpportunityRecord::new(1);
+            let actions = strategy.evaluate_block(&block, &mut record).await;
+
+            // The opportunity is sized and priced either way, and only logged as
+            // a signal in signal-only mode
+            assert!(record.expected_profit.unwrap() > 0);
+            if signal_only {
+                assert_eq!(record.skip_reason, Some(SkipReason::SignalOnly));
+                assert_eq!(record.decision(), "skip");
+                assert!(actions.is_empty());
+                assert_eq!(previews.load(Ordering::SeqCst), 0);
+                assert_eq!(calls.load(Ordering::SeqCst), 0);
+            } else {
+                // Both legs of the exact FY buy, then both legs of selling base
+                assert_eq!(record.skip_reason, None);
+                assert_eq!(actions.len(), 1);
+                assert_eq!(previews.load(Ordering::SeqCst), 4);
+                assert_eq!(calls.load(Ordering::SeqCst), 1);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_previews_but_never_submits() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            dry_run: true,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let previews = Arc::new(AtomicUsize::new(0));
+        let source = CountingSource {
+            inner: QuoterPriceSource::new()
+                .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                .with_pool(rich.address, LocalQuoter::new(&rich, ttm)),
+            previews: previews.clone(),
+        };
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = StubPolicy {
+            calls: calls.clone(),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+
+        let block = NewBlockEvent {
+            block_number: 1,
+            timestamp: now,
+            base_fee: None,
+        };
+        let mut record = OpportunityRecord::new(1);
+        let actions = strategy.evaluate_block(&block, &mut record).await;
+
+        // Sized and previewed like a live trade, but no transaction is built
+        assert_eq!(record.skip_reason, Some(SkipReason::DryRun));
+        assert!(record.expected_profit.unwrap() > 0);
+        assert_eq!(previews.load(Ordering::SeqCst), 4);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert!(actions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_curve_refresh_swaps_curve_and_counts_failures() {
+        let (provider, _mock) = Provider::mocked();
+        let startup = RateCurve::default_usd();
+        let mut strategy = NumoArb::new(Arc::new(provider), Config::default(), startup.clone());
+        let pool = Address::repeat_byte(0x11);
+        let refresh =
+            |timestamp, curve| Event::CurveRefresh(CurveRefreshEvent { timestamp, curve });
+
+        // A failed read keeps the startup curve
+        let before = strategy.target_discount_factor(pool, 0.5);
+        assert!(strategy
+            .process_event(refresh(1_700_000_000, None))
+            .await
+            .is_empty());
+        assert_eq!(strategy.curve_health().consecutive_failures(), 1);
+        assert_eq!(strategy.target_discount_factor(pool, 0.5), before);
+
+        // A read curve is swapped in and moves the targets
+        let mut refreshed = startup.clone();
+        for knot in &mut refreshed.knots {
+            knot.rate += 0.01;
+        }
+        strategy
+            .process_event(refresh(1_700_000_060, Some(refreshed.clone())))
+            .await;
+        let health = strategy.curve_health();
+        assert_eq!(health.consecutive_failures(), 0);
+        assert_eq!(health.last_success(), Some(1_700_000_060));
+        assert_eq!(health.fingerprint(), curve_fingerprint(&refreshed));
+        assert!(strategy.target_discount_factor(pool, 0.5) < before);
+    }
+
+    #[tokio::test]
+    async fn test_readiness_follows_curve_refreshes() {
+        let (provider, _mock) = Provider::mocked();
+        let config = Config {
+            max_curve_age_secs: Some(300),
+            curve_refresh_secs: Some(60),
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve.clone());
+        let refresh =
+            |timestamp, curve| Event::CurveRefresh(CurveRefreshEvent { timestamp, curve });
+        let now = 1_700_000_000;
+
+        // Unready until the curve source has delivered
+        assert!(!strategy.curve_health().is_ready(now));
+        strategy
+            .process_event(refresh(now, Some(curve.clone())))
+            .await;
+        assert!(strategy.curve_health().is_ready(now + 300));
+
+        // Failed refreshes let the curve go stale
+        strategy.process_event(refresh(now + 60, None)).await;
+        strategy.process_event(refresh(now + 120, None)).await;
+        let status = strategy.curve_health().status(now + 301);
+        assert!(!status.ready);
+        assert_eq!(status.consecutive_failures, 2);
+
+        // The next good refresh restores readiness
+        strategy
+            .process_event(refresh(now + 360, Some(curve)))
+            .await;
+        assert!(strategy.curve_health().is_ready(now + 360));
+    }
+
+    #[tokio::test]
+    async fn test_reference_curve_must_confirm_divergence() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        // Cheap pool prices FY near 0.96, rich near 0.99, against a target near 0.977
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let flat = |rate| {
+            let day_count = DayCount::Act360;
+            RateCurve::new(
+                vec![
+                    CurveKnot::from_days(1, rate, day_count),
+                    CurveKnot::from_months(24, rate, day_count),
+                ],
+                day_count,
+            )
+        };
+
+        let evaluate = |reference: RateCurve| {
+            let source = QuoterPriceSource::new()
+                .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+            let policy = StubPolicy {
+                calls: Arc::new(AtomicUsize::new(0)),
+                emit: true,
+            };
+            let (provider, _mock) = Provider::mocked();
+            let mut strategy = NumoArb::new(Arc::new(provider), config.clone(), curve.clone())
+                .with_execution_policy(Box::new(policy))
+                .with_price_source(Box::new(source))
+                .with_reference_curve(reference);
+            strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+            async move {
+                let block = NewBlockEvent {
+                    block_number: 1,
+                    timestamp: now,
+                    base_fee: None,
+                };
+                let mut record = OpportunityRecord::new(1);
+                let actions = strategy.evaluate_block(&block, &mut record).await;
+                (actions.len(), record.skip_reason)
+            }
+        };
+
+        // A 5.5% reference also prices the rich pool well above target
+        assert_eq!(evaluate(flat(0.055)).await, (1, None));
+
+        // A 0.5% reference targets near 0.9975: the rich pool is cheap against it
+        assert_eq!(
+            evaluate(flat(0.005)).await,
+            (0, Some(SkipReason::CurveDisagreement))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disabled_pool_excluded_from_trades_but_kept_in_snapshot() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        // Cheap pool near 0.96 and rich near 0.99; the disabled pool is richer still
+        let (cheap, rich, disabled) = (
+            pool(0x11, 442_000),
+            pool(0x22, 818_000),
+            pool(0x33, 900_000),
+        );
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address, disabled.address],
+            max_fy_amount: 1_000 * one,
+            disabled_pools: vec![disabled.address],
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let source = QuoterPriceSource::new()
+            .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm))
+            .with_pool(disabled.address, LocalQuoter::new(&disabled, ttm));
+        let policy = StubPolicy {
+            calls: Arc::new(AtomicUsize::new(0)),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone(), disabled.clone()]);
+
+        let block = NewBlockEvent {
+            block_number: 1,
+            timestamp: now,
+            base_fee: None,
+        };
+        let mut record = OpportunityRecord::new(1);
+        let actions = strategy.evaluate_block(&block, &mut record).await;
+
+        assert_eq!(actions.len(), 1);
+        assert_eq!(record.rich_pool, Some(rich.address));
+        let snapshot = strategy.current_opportunities().unwrap();
+        assert_eq!(snapshot.opportunities[0].rich_pool, rich.address);
+        let monitored: Vec<_> = snapshot.pool_states.iter().map(|s| s.address).collect();
+        assert_eq!(
+            monitored,
+            vec![cheap.address, rich.address, disabled.address]
+        );
+    }
+
+    /// Quotes pools from the local quoter, with some marginal prices pinned
+    struct PinnedPriceSource {
+        inner: QuoterPriceSource,
+        pinned: HashMap<Address, U256>,
+    }
+
+    #[async_trait]
+    impl PriceSource for PinnedPriceSource {
+        async fn marginal_price(&self, pool: Address) -> Result<U256> {
+            match self.pinned.get(&pool) {
+                Some(price) => Ok(*price),
+                None => self.inner.marginal_price(pool).await,
+            }
+        }
+
+        async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+            self.inner.preview_buy_fy(pool, fy_out).await
+        }
+
+        async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+            self.inner.preview_sell_fy(pool, fy_in).await
+        }
+
+        async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+            self.inner.preview_sell_base(pool, base_in).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_non_extreme_pair_wins_across_maturities() {
+        let now = 1_700_000_000u64;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128, days: u64| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity: (now + days * 24 * 3600) as u32,
+        };
+        // The week-dated pool prices highest, but only because it is near maturity:
+        // it sits exactly at its own target. The half-year pool is the one rich to
+        // its target.
+        let (cheap, rich, near) = (
+            pool(0x11, 442_000, 182),
+            pool(0x22, 818_000, 182),
+            pool(0x33, 818_000, 7),
+        );
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address, near.address],
+            max_fy_amount: 1_000 * one,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = |state: &PoolState| curve.time_to_maturity(now, state.maturity);
+        let inner = QuoterPriceSource::new()
+            .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm(&cheap)))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm(&rich)))
+            .with_pool(near.address, LocalQuoter::new(&near, ttm(&near)));
+        let near_target = U256::from((curve.discount_factor(ttm(&near)) * 1e18) as u128);
+        let source = PinnedPriceSource {
+            inner,
+            pinned: HashMap::from([(near.address, near_target)]),
+        };
+        let policy = StubPolicy {
+            calls: Arc::new(AtomicUsize::new(0)),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve.clone())
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone(), near.clone()]);
+
+        let block = NewBlockEvent {
+            block_number: 1,
+            timestamp: now,
+            base_fee: None,
+        };
+        let mut record = OpportunityRecord::new(1);
+        let actions = strategy.evaluate_block(&block, &mut record).await;
+
+        // The cheapest-to-richest pair is below edge; the inner pair trades
+        assert_eq!(actions.len(), 1);
+        assert_eq!(record.skip_reason, None);
+        assert_eq!(record.cheap_pool, Some(cheap.address));
+        assert_eq!(record.rich_pool, Some(rich.address));
+        assert!(record.rich_price.unwrap() < near_target);
+    }
+
+    #[tokio::test]
+    async fn test_observe_only_pool_in_heatmap_but_never_traded() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        // The observed pool is the richest of the three
+        let (cheap, rich, observed) = (
+            pool(0x11, 442_000),
+            pool(0x22, 818_000),
+            pool(0x33, 900_000),
+        );
+        let path =
+            std::env::temp_dir().join(format!("numo-strategy-observe-{}.json", std::process::id()));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address, observed.address],
+            max_fy_amount: 1_000 * one,
+            observe_only_pools: vec![observed.address],
+            heatmap_path: Some(path.clone()),
+            heatmap_every_blocks: 1,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let source = QuoterPriceSource::new()
+            .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm))
+            .with_pool(observed.address, LocalQuoter::new(&observed, ttm));
+        let policy = StubPolicy {
+            calls: Arc::new(AtomicUsize::new(0)),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone(), observed.clone()]);
+
+        let block = NewBlockEvent {
+            block_number: 1,
+            timestamp: now,
+            base_fee: None,
+        };
+        let mut record = OpportunityRecord::new(1);
+        let actions = strategy.evaluate_block(&block, &mut record).await;
+
+        // The observed pool shows up in the spread matrix, rich against both others
+        let heatmap: SpreadHeatmap =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            heatmap.pools,
+            vec![cheap.address, rich.address, observed.address]
+        );
+        assert!(heatmap.divergence_bps[0][2] > 0);
+        assert!(heatmap.divergence_bps[1][2] > 0);
+
+        // but the trade is against the richest tradable pool
+        assert_eq!(actions.len(), 1);
+        assert_eq!(record.rich_pool, Some(rich.address));
+        let snapshot = strategy.current_opportunities().unwrap();
+        assert!(snapshot
+            .opportunities
+            .iter()
+            .all(|opp| opp.rich_pool != observed.address && opp.cheap_pool != observed.address));
+    }
+
+    #[tokio::test]
+    async fn test_cheap_pool_fee_eating_edge_is_reported() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128, fee_bps: u16| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps,
+            maturity,
+        };
+        // Near 0.96 and 0.99: a 3% edge, but buying on the cheap pool costs 4%
+        let (cheap, rich) = (pool(0x11, 442_000, 400), pool(0x22, 818_000, 0));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let source = QuoterPriceSource::new()
+            .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+        let policy = StubPolicy {
+            calls: Arc::new(AtomicUsize::new(0)),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+
+        let block = NewBlockEvent {
+            block_number: 1,
+            timestamp: now,
+            base_fee: None,
+        };
+        let mut record = OpportunityRecord::new(1);
+        let actions = strategy.evaluate_block(&block, &mut record).await;
+
+        assert!(actions.is_empty());
+        assert_eq!(record.skip_reason, Some(SkipReason::FeeEatsEdge));
+        assert_eq!(SkipReason::FeeEatsEdge.as_str(), "fee_eats_edge");
+
+        // The same pools without the fee trade
+        let free = PoolState {
+            fee_bps: 0,
+            ..cheap
+        };
+        strategy.restore_pool_states(vec![free.clone(), rich.clone()]);
+        let source = QuoterPriceSource::new()
+            .with_pool(free.address, LocalQuoter::new(&free, ttm))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+        strategy = strategy.with_price_source(Box::new(source));
+        let mut record = OpportunityRecord::new(2);
+        let block = NewBlockEvent {
+            block_number: 2,
+            ..block
+        };
+        assert_eq!(strategy.evaluate_block(&block, &mut record).await.len(), 1);
+        assert_eq!(record.skip_reason, None);
+    }
+
+    #[tokio::test]
+    async fn test_block_suppresses_redundant_tick() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            debounce_window_secs: Some(5),
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let source = QuoterPriceSource::new()
+            .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = StubPolicy {
+            calls: calls.clone(),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap, rich]);
+        let tick = |timestamp| Event::Tick(TickEvent { timestamp });
+
+        let block = Event::NewBlock(NewBlockEvent {
+            block_number: 1,
+            timestamp: now,
+            base_fee: Some(U256::from(7)),
+        });
+        assert!(!strategy.process_event(block).await.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // The block just evaluated the same state
+        assert!(strategy.process_event(tick(now + 2)).await.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Past the window the tick re-evaluates, keeping the block's base fee
+        assert!(!strategy.process_event(tick(now + 5)).await.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(strategy.base_fee, Some(U256::from(7)));
+    }
+
+    #[tokio::test]
+    async fn test_pending_swap_through_pool_re_evaluates() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let router = Address::repeat_byte(0x77);
+        let config = Config {
+            router_address: router,
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let source = QuoterPriceSource::new()
+            .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = StubPolicy {
+            calls: calls.clone(),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+        let pending = |to: Address, input: Vec<u8>| {
+            Event::PendingTx(PendingTxEvent {
+                tx_hash: TxHash::repeat_byte(0xaa),
+                to: Some(to),
+                input: input.into(),
+                timestamp: now + 1,
+            })
+        };
+        // A swap through an aggregator passing the pool as an ABI-encoded argument
+        let mut swap = vec![0x12, 0x34, 0x56, 0x78];
+        swap.extend_from_slice(H256::from(cheap.address).as_bytes());
+        let aggregator = Address::repeat_byte(0x99);
+
+        // Nothing to re-evaluate before the first block
+        assert!(strategy
+            .process_event(pending(rich.address, vec![]))
+            .await
+            .is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let block = Event::NewBlock(NewBlockEvent {
+            block_number: 1,
+            timestamp: now,
+            base_fee: None,
+        });
+        assert!(!strategy.process_event(block).await.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Transactions away from our pools, and our own trades, are ignored
+        let unrelated = pending(aggregator, vec![0x12, 0x34, 0x56, 0x78]);
+        assert!(strategy.process_event(unrelated).await.is_empty());
+        assert!(strategy
+            .process_event(pending(router, swap.clone()))
+            .await
+            .is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Swaps calling a pool directly or routed through it re-evaluate
+        assert!(!strategy
+            .process_event(pending(aggregator, swap))
+            .await
+            .is_empty());
+        assert!(!strategy
+            .process_event(pending(rich.address, vec![]))
+            .await
+            .is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_low_allowances_approved_before_trading() {
+        let (provider, mock) = Provider::mocked();
+        let router = Address::repeat_byte(0x77);
+        let pools = [0x11, 0x22, 0x33].map(Address::repeat_byte);
+        let (usd, eur) = (Address::repeat_byte(0xb1), Address::repeat_byte(0xb2));
+        let config = Config {
+            router_address: router,
+            pool_addresses: pools.to_vec(),
+            max_base_amount: 1_000,
+            auto_approve: true,
+            approval_amount: Some(5_000),
+            state_refresh_blocks: None,
+            ..Config::default()
+        };
+        let mut strategy = NumoArb::new(Arc::new(provider), config, RateCurve::default_usd());
+        strategy.base_tokens.insert(pools[0], usd);
+        strategy.base_tokens.insert(pools[1], usd);
+        strategy.base_tokens.insert(pools[2], eur);
+
+        // Each token is read once, in address order; responses are served last-in
+        // first-out, so EUR already covers a full trade and USD does not
+        mock.push::<Bytes, _>(encode_uints(&[1_000])).unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[999])).unwrap();
+        let approvals = strategy.approval_actions().await;
+        let [Action::Approve(submit)] = &approvals[..] else {
+            panic!("expected one approval, got {approvals:?}");
+        };
+        assert_eq!(submit.tx.to_addr(), Some(&usd));
+        let calldata = submit.tx.data().unwrap();
+        assert_eq!(calldata[..4], [0x09, 0x5e, 0xa7, 0xb3]); // approve(address,uint256)
+        assert_eq!(Address::from_slice(&calldata[16..36]), router);
+        assert_eq!(U256::from_big_endian(&calldata[36..68]), U256::from(5_000));
+
+        // The first block carries only the approvals
+        strategy.token_approvals = approvals;
+        let block = |block_number| {
+            Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp: 1_700_000_000,
+                base_fee: None,
+            })
+        };
+        let actions = strategy.process_event(block(1)).await;
+        assert!(matches!(actions[..], [Action::Approve(_)]));
+        assert!(strategy.token_approvals.is_empty());
+        assert_eq!(strategy.unconfirmed_approvals, vec![usd]);
+
+        // Blocks before the approval mines are held
+        mock.push::<Bytes, _>(encode_uints(&[999])).unwrap();
+        assert!(strategy.process_event(block(2)).await.is_empty());
+        assert_eq!(strategy.unconfirmed_approvals, vec![usd]);
+        assert!(matches!(
+            strategy.last_decision,
+            OpportunityDecision::NotEvaluated
+        ));
+
+        // Once the allowance covers a trade, blocks are evaluated again
+        mock.push::<Bytes, _>(encode_uints(&[5_000])).unwrap();
+        strategy.process_event(block(3)).await;
+        assert!(strategy.unconfirmed_approvals.is_empty());
+
+        // Without a bound the router is approved for an unlimited amount
+        strategy.config.approval_amount = None;
+        mock.push::<Bytes, _>(encode_uints(&[0])).unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[0])).unwrap();
+        let approvals = strategy.approval_actions().await;
+        assert_eq!(approvals.len(), 2);
+        let Action::Approve(submit) = &approvals[1] else {
+            panic!("expected an approval");
+        };
+        assert_eq!(submit.tx.to_addr(), Some(&eur));
+        assert_eq!(
+            U256::from_big_endian(&submit.tx.data().unwrap()[36..68]),
+            U256::MAX
+        );
+    }
+
+    #[tokio::test]
+    async fn test_worst_case_profit_gates_trades() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        // About 3% between the pools
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+
+        let evaluate = |slippage_bps| {
+            let config = Config {
+                pool_addresses: vec![cheap.address, rich.address],
+                max_fy_amount: 1_000 * one,
+                slippage_bps,
+                ..Config::default()
+            };
+            let source = QuoterPriceSource::new()
+                .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+            let policy = StubPolicy {
+                calls: Arc::new(AtomicUsize::new(0)),
+                emit: true,
+            };
+            let (provider, _mock) = Provider::mocked();
+            let mut strategy = NumoArb::new(Arc::new(provider), config, curve.clone())
+                .with_execution_policy(Box::new(policy))
+                .with_price_source(Box::new(source));
+            strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+            async move {
+                let block = NewBlockEvent {
+                    block_number: 1,
+                    timestamp: now,
+                    base_fee: None,
+                };
+                let mut record = OpportunityRecord::new(1);
+                let actions = strategy.evaluate_block(&block, &mut record).await;
+                (actions.len(), record)
+            }
+        };
+
+        // 0.5% either side still leaves a profit at the bounds
+        let (trades, record) = evaluate(50).await;
+        assert_eq!((trades, record.skip_reason), (1, None));
+
+        // 3% either side: the previews still show a profit, the bounds a loss
+        let (trades, record) = evaluate(300).await;
+        assert_eq!(trades, 0);
+        assert_eq!(record.skip_reason, Some(SkipReason::WorstCaseUnprofitable));
+        assert!(record.expected_profit.unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_min_net_profit_gates_trades_after_gas() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+
+        let evaluate = |base_fee: Option<U256>, native_price_in_base, min_net_profit| {
+            let config = Config {
+                pool_addresses: vec![cheap.address, rich.address],
+                max_fy_amount: 1_000 * one,
+                slippage_bps: 50,
+                min_net_profit,
+                native_price_in_base,
+                ..Config::default()
+            };
+            let source = QuoterPriceSource::new()
+                .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+            let policy = StubPolicy {
+                calls: Arc::new(AtomicUsize::new(0)),
+                emit: true,
+            };
+            let (provider, _mock) = Provider::mocked();
+            let mut strategy = NumoArb::new(Arc::new(provider), config, curve.clone())
+                .with_execution_policy(Box::new(policy))
+                .with_price_source(Box::new(source));
+            strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+            async move {
+                let block = NewBlockEvent {
+                    block_number: 1,
+                    timestamp: now,
+                    base_fee,
+                };
+                strategy.base_fee = block.base_fee;
+                let mut record = OpportunityRecord::new(1);
+                let actions = strategy.evaluate_block(&block, &mut record).await;
+                (actions.len(), record.skip_reason, record.expected_profit)
+            }
+        };
+
+        let (trades, skip, profit) = evaluate(None, None, 0).await;
+        assert_eq!((trades, skip), (1, None));
+        let profit = profit.unwrap();
+
+        // Without a base fee, gas falls back to gas_cost_base (zero here)
+        assert_eq!(evaluate(None, Some(2.0), profit).await.0, 1);
+        let (trades, skip, _) = evaluate(None, Some(2.0), profit + 1).await;
+        assert_eq!((trades, skip), (0, Some(SkipReason::BelowMinNetProfit)));
+
+        // Native wei cannot be compared to base wei without a price for the gas token
+        assert_eq!(evaluate(Some(U256::one()), None, profit).await.0, 1);
+
+        // A base fee prices the gas estimate, converted into base tokens, into the
+        // net profit
+        let gas_cost = 2 * FALLBACK_GAS_LIMIT as u128;
+        let base_fee = Some(U256::one());
+        assert_eq!(evaluate(base_fee, Some(2.0), profit - gas_cost).await.0, 1);
+        let (trades, skip, _) = evaluate(base_fee, Some(2.0), profit - gas_cost + 1).await;
+        assert_eq!((trades, skip), (0, Some(SkipReason::BelowMinNetProfit)));
+    }
+
+    #[tokio::test]
+    async fn test_live_opportunities_age_out() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            opportunity_max_age_blocks: Some(2),
+            ..Config::default()
+        };
+        let source = QuoterPriceSource::new()
+            .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+            .with_pool(rich.address, LocalQuoter::new(&rich, ttm));
+        let policy = StubPolicy {
+            calls: Arc::new(AtomicUsize::new(0)),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+
+        let block = |block_number| NewBlockEvent {
+            block_number,
+            timestamp: now,
+            base_fee: None,
+        };
+        strategy
+            .evaluate_block(&block(1), &mut OpportunityRecord::new(1))
+            .await;
+        let best = strategy.live_opportunities().unwrap().best().unwrap();
+        assert_eq!(
+            (best.opportunity.cheap_pool, best.opportunity.rich_pool),
+            (cheap.address, rich.address)
+        );
+        assert_eq!(best.updated_block, 1);
+
+        // Without pool states nothing is evaluated, so the pair is not refreshed
+        strategy.restore_pool_states(vec![]);
+        strategy
+            .evaluate_block(&block(3), &mut OpportunityRecord::new(3))
+            .await;
+        assert_eq!(strategy.live_opportunities().unwrap().len(), 1);
+        strategy
+            .evaluate_block(&block(4), &mut OpportunityRecord::new(4))
+            .await;
+        assert!(strategy.live_opportunities().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_edge_must_persist_before_acting() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            min_edge_persistence_blocks: Some(3),
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        // The rich pool either diverges from the cheap one or quotes alongside it
+        let source = |spread: bool| {
+            let rich_state = if spread { &rich } else { &cheap };
+            QuoterPriceSource::new()
+                .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                .with_pool(rich.address, LocalQuoter::new(rich_state, ttm))
+        };
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = StubPolicy {
+            calls: calls.clone(),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy =
+            NumoArb::new(Arc::new(provider), config, curve).with_execution_policy(Box::new(policy));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+
+        let evaluate = |strategy: NumoArb<Provider<MockProvider>>, block_number, spread| {
+            let mut strategy = strategy.with_price_source(Box::new(source(spread)));
+            async move {
+                let block = NewBlockEvent {
+                    block_number,
+                    timestamp: now,
+                    base_fee: None,
+                };
+                let mut record = OpportunityRecord::new(block_number);
+                let actions = strategy.evaluate_block(&block, &mut record).await;
+                (strategy, actions, record.skip_reason)
+            }
+        };
+
+        // A one-block spike is ignored
+        let (strategy, actions, skip) = evaluate(strategy, 1, true).await;
+        assert!(actions.is_empty());
+        assert_eq!(skip, Some(SkipReason::NotPersistent));
+        let (strategy, actions, _) = evaluate(strategy, 2, false).await;
+        assert!(actions.is_empty());
+
+        // An edge persisting for three consecutive blocks is taken on the third
+        let (strategy, _, skip) = evaluate(strategy, 3, true).await;
+        assert_eq!(skip, Some(SkipReason::NotPersistent));
+        let (strategy, _, skip) = evaluate(strategy, 4, true).await;
+        assert_eq!(skip, Some(SkipReason::NotPersistent));
+        let (_, actions, skip) = evaluate(strategy, 5, true).await;
+        assert_eq!(skip, None);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    /// Carry mode strategy over pools maturing half a year after `now`, with the
+    /// price of a pool implying a rate `gap` above the curve's
+    fn carry_strategy(
+        config: Config,
+        now: u64,
+    ) -> (
+        NumoArb<Provider<MockProvider>>,
+        MockProvider,
+        impl Fn(f64) -> u128,
+    ) {
+        let maturity = (now + 365 * 12 * 3600) as u32;
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let curve_rate = (1.0 / curve.discount_factor(ttm) - 1.0) / ttm;
+        let (provider, mock) = Provider::mocked();
+        let pools = config.pool_addresses.clone();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve);
+        strategy.restore_pool_states(
+            pools
+                .into_iter()
+                .map(|address| PoolState {
+                    address,
+                    base_reserves: 1_000_000 * 10u128.pow(18),
+                    fy_reserves: 1_000_000 * 10u128.pow(18),
+                    fee_bps: 0,
+                    maturity,
+                })
+                .collect(),
+        );
+        let price_at = move |gap: f64| (1e18 / (1.0 + (curve_rate + gap) * ttm)) as u128;
+        (strategy, mock, price_at)
+    }
+
+    async fn carry_block(
+        strategy: &mut NumoArb<Provider<MockProvider>>,
+        block_number: u64,
+        timestamp: u64,
+    ) -> Vec<Action> {
+        strategy
+            .process_event(Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp,
+                base_fee: None,
+            }))
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_carry_mode_trades_single_pool_rate_gap() {
+        let now = 1_700_000_000u64;
+        let pool = Address::repeat_byte(0x11);
+        let router = Address::repeat_byte(0x99);
+        let one = 10u128.pow(18);
+        let config = Config {
+            pool_addresses: vec![pool],
+            router_address: router,
+            max_fy_amount: 1_000 * one,
+            carry_mode: true,
+            // The pool's state is restored below
+            state_refresh_blocks: None,
+            ..Config::default()
+        };
+        let slippage_bps = config.slippage_bps;
+        let (mut strategy, mock, price_at) = carry_strategy(config, now);
+
+        // The pool implies a rate 100 bps above the curve: FY is cheap. Its price is
+        // read, then the router call estimated and priced at 10 gwei
+        let price = price_at(0.01);
+        mock.push(U256::from(10_000_000_000u64)).unwrap();
+        mock.push(U256::from(200_000u64)).unwrap();
+        push_pool_prices(&mock, &[price]);
+        let actions = carry_block(&mut strategy, 1, now).await;
+
+        assert_eq!(actions.len(), 1);
+        let Action::SubmitTx(submit) = &actions[0] else {
+            panic!("expected a carry trade");
+        };
+        assert_eq!(submit.tx.to_addr(), Some(&router));
+        assert_eq!(submit.tx.gas(), Some(&U256::from(240_000u64)));
+        assert_eq!(submit.tx.gas_price(), Some(U256::from(11_000_000_000u64)));
+
+        // The router buys the FY for the bot, spending at most the slippage bound
+        // over its value at the probed price
+        let call = BuyFYCall::decode(submit.tx.data().unwrap()).unwrap();
+        assert_eq!(call.pool, pool);
+        assert_eq!(call.fy_out, 1_000 * one);
+        let base = base_for_fy_at_price(call.fy_out, U256::from(price));
+        let bound = call.max_base_in as f64 / base as f64;
+        assert!((bound - (1.0 + slippage_bps as f64 / 10_000.0)).abs() < 1e-9);
+        assert_eq!(call.receiver, Address::zero());
+
+        // The position is held, so the same gap is not traded again
+        assert!(carry_block(&mut strategy, 2, now + 12).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_carry_trades_pools_in_order_and_skips_filtered_ones() {
+        let now = 1_700_000_000u64;
+        let pools: Vec<Address> = [0x44, 0x22, 0x33, 0x11]
+            .into_iter()
+            .map(Address::repeat_byte)
+            .collect();
+        let one = 10u128.pow(18);
+        let config = Config {
+            pool_addresses: pools.clone(),
+            disabled_pools: vec![pools[1]],
+            observe_only_pools: vec![pools[2]],
+            max_fy_amount: 1_000 * one,
+            gas_estimation: GasEstimation::Fixed(200_000),
+            carry_mode: true,
+            state_refresh_blocks: None,
+            ..Config::default()
+        };
+        let (mut strategy, mock, price_at) = carry_strategy(config, now);
+
+        // Only the first and last pools are priced: FY rich in the first, cheap in
+        // the last, each trade priced right after its pool
+        mock.push(U256::from(10_000_000_000u64)).unwrap();
+        push_pool_prices(&mock, &[price_at(0.01)]);
+        mock.push(U256::from(10_000_000_000u64)).unwrap();
+        push_pool_prices(&mock, &[price_at(-0.01)]);
+        let actions = carry_block(&mut strategy, 1, now).await;
+
+        let calldata: Vec<Bytes> = actions
+            .iter()
+            .map(|action| match action {
+                Action::SubmitTx(submit) => submit.tx.data().cloned().unwrap(),
+                _ => panic!("expected carry trades"),
+            })
+            .collect();
+        assert_eq!(calldata.len(), 2);
+        let sell = SellFYCall::decode(&calldata[0]).unwrap();
+        assert_eq!(sell.pool, pools[0]);
+        assert_eq!(sell.fy_in, 1_000 * one);
+        assert!(sell.min_base_out < base_for_fy_at_price(sell.fy_in, U256::from(price_at(-0.01))));
+        assert_eq!(BuyFYCall::decode(&calldata[1]).unwrap().pool, pools[3]);
+
+        // A reverted trade frees its pool, which is traded again on the next block
+        let reverted = SubmittedTx {
+            tx_hash: TxHash::zero(),
+            gas_estimate: U256::from(200_000u64),
+            expected_profit: None,
+            calldata: Some(calldata[1].clone()),
+        };
+        let receipt = TransactionReceipt {
+            status: Some(U64::zero()),
+            ..Default::default()
+        };
+        strategy.carry_positions.on_receipt(&reverted, &receipt);
+        mock.push(U256::from(10_000_000_000u64)).unwrap();
+        push_pool_prices(&mock, &[price_at(0.01)]);
+        let actions = carry_block(&mut strategy, 2, now + 12).await;
+        assert_eq!(actions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_carry_respects_dry_run_signal_only_and_rate_limit() {
+        let now = 1_700_000_000u64;
+        let pools = vec![Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+        let one = 10u128.pow(18);
+        let config = Config {
+            pool_addresses: pools.clone(),
+            max_fy_amount: 1_000 * one,
+            gas_estimation: GasEstimation::Fixed(200_000),
+            carry_mode: true,
+            state_refresh_blocks: None,
+            ..Config::default()
+        };
+
+        // Neither a dry run nor signal-only mode trades, nor holds a position
+        for config in [
+            Config {
+                dry_run: true,
+                ..config.clone()
+            },
+            Config {
+                log_opportunities_only: true,
+                ..config.clone()
+            },
+        ] {
+            let (mut strategy, mock, price_at) = carry_strategy(config, now);
+            push_pool_prices(&mock, &[price_at(0.01), price_at(0.01)]);
+            assert!(carry_block(&mut strategy, 1, now).await.is_empty());
+            assert!(strategy.carry_positions.position(pools[0]).is_none());
+        }
+
+        // One trade a minute: the second pool waits
+        let config = Config {
+            max_trades_per_minute: Some(1),
+            ..config
+        };
+        let (mut strategy, mock, price_at) = carry_strategy(config, now);
+        push_pool_prices(&mock, &[price_at(0.01)]);
+        mock.push(U256::from(10_000_000_000u64)).unwrap();
+        push_pool_prices(&mock, &[price_at(0.01)]);
+        assert_eq!(carry_block(&mut strategy, 1, now).await.len(), 1);
+        assert!(strategy.carry_positions.position(pools[1]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reloaded_edge_applies_on_next_block() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 365 * 24 * 3600) as u32;
+        let pools = [Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+        let config = Config {
+            pool_addresses: pools.to_vec(),
+            edge_bps: 20,
+            // The pools' states are restored below
+            state_refresh_blocks: None,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let target = curve.discount_factor(curve.time_to_maturity(now, maturity));
+        let target = (target * 1e18) as u128;
+        let shared: SharedConfig = Arc::new(RwLock::new(config.clone()));
+        let (provider, mock) = Provider::mocked();
+        let mut strategy =
+            NumoArb::new(Arc::new(provider), config, curve).with_shared_config(shared.clone());
+        strategy.restore_pool_states(
+            pools
+                .iter()
+                .map(|address| PoolState {
+                    address: *address,
+                    base_reserves: 1_000_000,
+                    fy_reserves: 1_000_000,
+                    fee_bps: 10,
+                    maturity,
+                })
+                .collect(),
+        );
+
+        // The rich pool is 15 bps above the cheap one, which sits at target
+        let prices = [target, target + target * 15 / 10_000];
+        let block = |block_number| {
+            Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp: now,
+                base_fee: None,
+            })
+        };
+        let skip_reason = |strategy: &NumoArb<Provider<MockProvider>>| {
+            strategy.current_opportunities().unwrap().record.skip_reason
+        };
+
+        push_pool_prices(&mock, &prices);
+        strategy.process_event(block(1)).await;
+        assert_eq!(skip_reason(&strategy), Some(SkipReason::NoSpread));
+
+        // A 10 bps edge passes the spread check and fails on the rich pool fee
+        shared.write().unwrap().edge_bps = 10;
+        push_pool_prices(&mock, &prices);
+        strategy.process_event(block(2)).await;
+        assert_eq!(skip_reason(&strategy), Some(SkipReason::BelowEdge));
+    }
+
+    #[test]
+    fn test_reload_moves_the_band_thresholds() {
+        let (provider, _mock) = Provider::mocked();
+        let config = Config::default();
+        let shared: SharedConfig = Arc::new(RwLock::new(config.clone()));
+        let mut strategy = NumoArb::new(Arc::new(provider), config, RateCurve::default_usd())
+            .with_shared_config(shared.clone());
+        let pair = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        assert!(strategy.band.is_none());
+
+        // Setting a threshold enables the band
+        shared.write().unwrap().enter_bps = Some(30);
+        strategy.refresh_config();
+        strategy.band.as_mut().unwrap().enter(pair);
+
+        // Moving it keeps the pairs already entered
+        shared.write().unwrap().enter_bps = Some(50);
+        strategy.refresh_config();
+        let band = strategy.band.as_ref().unwrap();
+        assert_eq!(band.enter_bps(), 50);
+        assert!(band.is_entered(pair));
+
+        // Unsetting both disables it
+        shared.write().unwrap().enter_bps = None;
+        strategy.refresh_config();
+        assert!(strategy.band.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_current_opportunities_reflects_last_evaluation() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let source = |rich_state: &PoolState| {
+            QuoterPriceSource::new()
+                .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                .with_pool(rich.address, LocalQuoter::new(rich_state, ttm))
+        };
+        let policy = StubPolicy {
+            calls: Arc::new(AtomicUsize::new(0)),
+            emit: true,
+        };
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy))
+            .with_price_source(Box::new(source(&rich)));
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+        let block = |block_number| {
+            Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp: now,
+                base_fee: None,
+            })
+        };
+        assert!(strategy.current_opportunities().is_none());
+
+        strategy.process_event(block(1)).await;
+        let snapshot = strategy.current_opportunities().unwrap();
+        assert_eq!(snapshot.block_number, 1);
+        assert_eq!(snapshot.opportunities.len(), 1);
+        assert_eq!(snapshot.opportunities[0].rich_pool, rich.address);
+        assert_eq!(
+            snapshot.record.rich_price,
+            Some(snapshot.opportunities[0].rich_price)
+        );
+        assert_eq!(snapshot.record.skip_reason, None);
+
+        // Once the rich pool quotes like the cheap one the snapshot shows the skip
+        let mut strategy = strategy.with_price_source(Box::new(source(&cheap)));
+        strategy.process_event(block(2)).await;
+        let snapshot = strategy.current_opportunities().unwrap();
+        assert_eq!(snapshot.block_number, 2);
+        assert!(snapshot.opportunities.is_empty());
+        assert!(snapshot.record.skip_reason.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_last_decision_explains_each_block() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+        let source = |rich_state: &PoolState| {
+            QuoterPriceSource::new()
+                .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                .with_pool(rich.address, LocalQuoter::new(rich_state, ttm))
+        };
+        let policy = |emit| StubPolicy {
+            calls: Arc::new(AtomicUsize::new(0)),
+            emit,
+        };
+        let limit = Arc::new(DailyLossLimit::new(1_000));
+        let (provider, _mock) = Provider::mocked();
+        let mut strategy = NumoArb::new(Arc::new(provider), config, curve)
+            .with_execution_policy(Box::new(policy(true)))
+            .with_price_source(Box::new(source(&rich)))
+            .with_loss_limit(limit.clone());
+        strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+        let block = |block_number| {
+            Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp: now,
+                base_fee: None,
+            })
+        };
+        assert!(matches!(
+            strategy.last_decision(),
+            OpportunityDecision::NotEvaluated
+        ));
+
+        // A trade is emitted for the opportunity
+        assert!(!strategy.process_event(block(1)).await.is_empty());
+        assert!(matches!(
+            strategy.last_decision(),
+            OpportunityDecision::Executed(opp) if opp.rich_pool == rich.address
+        ));
+
+        // The policy declines it: found, not traded
+        let mut strategy = strategy.with_execution_policy(Box::new(policy(false)));
+        assert!(strategy.process_event(block(2)).await.is_empty());
+        assert!(matches!(
+            strategy.last_decision(),
+            OpportunityDecision::Found(opp) if opp.cheap_pool == cheap.address
+        ));
+
+        // Once the rich pool quotes like the cheap one the skip reason is kept
+        let mut strategy = strategy.with_price_source(Box::new(source(&cheap)));
+        strategy.process_event(block(3)).await;
+        assert!(matches!(
+            strategy.last_decision(),
+            OpportunityDecision::Skipped(SkipReason::NoSpread)
+        ));
+
+        // Halted by the daily loss limit, nothing is evaluated
+        limit.record(now, -2_000);
+        strategy.process_event(block(4)).await;
+        assert!(matches!(
+            strategy.last_decision(),
+            OpportunityDecision::Paused
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pool_states_refreshed_every_interval() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 365 * 86_400) as u32;
+        let pools = vec![Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+        let config = Config {
+            pool_addresses: pools.clone(),
+            state_refresh_blocks: Some(3),
+            ..Config::default()
+        };
+        let (mut strategy, mock) = strategy_with_pools(config, 0, now);
+        for state in strategy.pool_states.values_mut() {
+            state.maturity = maturity;
+            strategy.maturities.insert(state.address, maturity);
+        }
+        let reserves = |strategy: &NumoArb<_>| {
+            pools
+                .iter()
+                .map(|pool| {
+                    let state = &strategy.pool_states[pool];
+                    (state.base_reserves, state.fy_reserves, state.fee_bps)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Off the interval nothing is fetched
+        strategy.refresh_pool_states(4).await;
+        assert_eq!(reserves(&strategy), vec![(1_000_000, 1_000_000, 0); 2]);
+
+        // On it every pool's cache is re-read
+        for _ in &pools {
+            mock.push::<Bytes, _>(encode_uints(&[900_000, 1_100_000, 5]))
+                .unwrap();
+        }
+        strategy.refresh_pool_states(6).await;
+        assert_eq!(reserves(&strategy), vec![(900_000, 1_100_000, 5); 2]);
+        assert!(strategy
+            .pool_states
+            .values()
+            .all(|s| s.maturity == maturity));
+
+        // A failed fetch keeps the cached state
+        strategy.refresh_pool_states(9).await;
+        assert_eq!(reserves(&strategy), vec![(900_000, 1_100_000, 5); 2]);
+
+        // Matured pools are no longer fetched
+        strategy.matured.insert(pools[0]);
+        mock.push::<Bytes, _>(encode_uints(&[800_000, 1_200_000, 5]))
+            .unwrap();
+        strategy.refresh_pool_states(12).await;
+        assert_eq!(
+            reserves(&strategy),
+            vec![(900_000, 1_100_000, 5), (800_000, 1_200_000, 5)]
+        );
+
+        // A tick re-evaluating the same block does not fetch again
+        mock.push::<Bytes, _>(encode_uints(&[700_000, 1_300_000, 5]))
+            .unwrap();
+        strategy.refresh_pool_states(12).await;
+        assert_eq!(
+            reserves(&strategy),
+            vec![(900_000, 1_100_000, 5), (800_000, 1_200_000, 5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_historical_block_evaluated_read_only() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 365 * 86_400) as u32;
+        let pools = vec![Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+        let config = Config {
+            pool_addresses: pools.clone(),
+            ..Config::default()
+        };
+        let (mut strategy, mock) = strategy_with_pools(config, 0, now);
+        for state in strategy.pool_states.values_mut() {
+            state.maturity = maturity;
+            strategy.maturities.insert(state.address, maturity);
+        }
+        let block = NewBlockEvent {
+            block_number: 10,
+            timestamp: now - 600,
+            base_fee: Some(U256::from(25)),
+        };
+        let one = 10u128.pow(18);
+
+        // Reserves and prices are read at the replayed block
+        push_pool_prices(&mock, &[one, one]);
+        for _ in &pools {
+            mock.push::<Bytes, _>(encode_uints(&[900_000, 1_100_000, 0]))
+                .unwrap();
+        }
+        let (found, record) = strategy.evaluate_historical_block(&block).await.unwrap();
+        assert!(found.is_none());
+        assert_eq!(record.block, 10);
+        assert_eq!(record.cheap_price, Some(U256::from(one)));
+        assert_eq!(record.skip_reason, Some(SkipReason::NoSpread));
+
+        // Without past state the block is skipped rather than priced off the present,
+        // and live state never moves
+        assert!(strategy
+            .process_event(Event::HistoricalBlock(block))
+            .await
+            .is_empty());
+        assert_eq!(strategy.last_block, 0);
+        assert_eq!(strategy.base_fee, None);
+        assert!(strategy
+            .pool_states
+            .values()
+            .all(|state| state.base_reserves == 1_000_000));
+    }
+
+    #[tokio::test]
+    async fn test_matured_pools_are_refreshed_and_excluded() {
+        let now = 1_700_000_000u64;
+        let (pool_a, pool_b, pool_c) = (
+            Address::repeat_byte(0x11),
+            Address::repeat_byte(0x22),
+            Address::repeat_byte(0x33),
+        );
+        let config = Config {
+            pool_addresses: vec![pool_a, pool_b, pool_c],
+            ..Config::default()
+        };
+        let (mut strategy, mock) = strategy_with_pools(config, 0, now);
+        let later = (now + 90 * 24 * 3600) as u32;
+        strategy.pool_states.get_mut(&pool_c).unwrap().maturity = later;
+        strategy.maturities.insert(pool_c, later);
+
+        // Before maturity nothing is refreshed
+        strategy.roll_over_matured_pools(now - 1).await;
+        assert!(strategy.matured.is_empty());
+
+        // Crossing it refreshes both matured pools' caches, once
+        mock.push::<Bytes, _>(encode_uints(&[900_000, 1_100_000, 0]))
+            .unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[800_000, 1_200_000, 0]))
+            .unwrap();
+        strategy.roll_over_matured_pools(now).await;
+        let reserves = |pool| {
+            let state = &strategy.pool_states[&pool];
+            (state.base_reserves, state.fy_reserves)
+        };
+        let refreshed = [reserves(pool_a), reserves(pool_b)];
+        assert!(refreshed.contains(&(800_000, 1_200_000)));
+        assert!(refreshed.contains(&(900_000, 1_100_000)));
+        assert_eq!(reserves(pool_c), (1_000_000, 1_000_000));
+        assert_eq!(strategy.matured.len(), 2);
+        strategy.roll_over_matured_pools(now + 12).await;
+        assert_eq!(strategy.matured.len(), 2);
+
+        // Only the live pool is priced, leaving no pair to trade
+        push_pool_prices(&mock, &[10u128.pow(18)]);
+        let mut record = OpportunityRecord::new(1);
+        assert!(strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(record.skip_reason, Some(SkipReason::NoSpread));
+    }
+
+    /// Source pricing every pool at par after a fixed delay
+    struct SlowSource {
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl PriceSource for SlowSource {
+        async fn marginal_price(&self, _pool: Address) -> Result<U256> {
+            tokio::time::sleep(self.delay).await;
+            Ok(U256::exp10(18))
+        }
+
+        async fn preview_buy_fy(&self, _pool: Address, fy_out: u128) -> Result<u128> {
+            Ok(fy_out)
+        }
+
+        async fn preview_sell_fy(&self, _pool: Address, fy_in: u128) -> Result<u128> {
+            Ok(fy_in)
+        }
+
+        async fn preview_sell_base(&self, _pool: Address, base_in: u128) -> Result<u128> {
+            Ok(base_in)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_evaluation_trips_latency_alarm() {
+        let now = 1_700_000_000u64;
+        let config = Config {
+            pool_addresses: vec![Address::repeat_byte(0x11), Address::repeat_byte(0x22)],
+            max_eval_latency_ms: Some(15),
+            degrade_after_slow_blocks: 2,
+            ..Config::default()
+        };
+        let (strategy, _mock) = strategy_with_pools(config, 0, now);
+        let source = |delay_ms| {
+            Box::new(SlowSource {
+                delay: Duration::from_millis(delay_ms),
+            })
+        };
+        let mut strategy = strategy.with_price_source(source(25));
+        let block = |block_number| {
+            Event::NewBlock(NewBlockEvent {
+                block_number,
+                timestamp: now - 1,
+                base_fee: None,
+            })
+        };
+        let skip_reason = |strategy: &NumoArb<Provider<MockProvider>>| {
+            strategy.current_opportunities().unwrap().record.skip_reason
+        };
+
+        // Two slow blocks raise the alarm twice and enter degraded mode
+        strategy.process_event(block(1)).await;
+        assert_eq!(strategy.slow_blocks(), 1);
+        assert_eq!(skip_reason(&strategy), Some(SkipReason::NoSpread));
+        strategy.process_event(block(2)).await;
+        assert_eq!(strategy.slow_blocks(), 2);
+
+        // Degraded, a block without a candidate spread is not sized
+        let mut strategy = strategy.with_price_source(source(0));
+        strategy.process_event(block(3)).await;
+        assert_eq!(skip_reason(&strategy), Some(SkipReason::Backoff));
+
+        // That block was fast, so full evaluation resumes
+        strategy.process_event(block(4)).await;
+        assert_eq!(skip_reason(&strategy), Some(SkipReason::NoSpread));
+        assert_eq!(strategy.slow_blocks(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_imbalanced_pool_is_skipped() {
+        let now = 1_700_000_000u64;
+        let balanced = Address::repeat_byte(0x11);
+        let toxic = Address::repeat_byte(0x22);
+        let config = Config {
+            pool_addresses: vec![balanced, toxic],
+            max_reserve_imbalance_ratio: Some(10.0),
+            ..Config::default()
+        };
+        let (mut strategy, mock) = strategy_with_pools(config, 0, now);
+        let one = 10u128.pow(18);
+
+        // Within the limit both pools are priced
+        strategy.pool_states.get_mut(&toxic).unwrap().fy_reserves = 10_000_000;
+        push_pool_prices(&mock, &[one, one * 101 / 100]);
+        let mut record = OpportunityRecord::new(1);
+        strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap();
+        assert_ne!(record.skip_reason, Some(SkipReason::NoSpread));
+
+        // Beyond it the toxic pool is never priced, leaving no pair to trade
+        strategy.pool_states.get_mut(&toxic).unwrap().fy_reserves = 50_000_000;
+        push_pool_prices(&mock, &[one]);
+        let mut record = OpportunityRecord::new(2);
+        assert!(strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(record.skip_reason, Some(SkipReason::NoSpread));
+    }
+
+    #[tokio::test]
+    async fn test_price_source_can_be_swapped() {
+        let pools = vec![Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+        let now = 1_700_000_000u64;
+        let config = Config {
+            pool_addresses: pools.clone(),
+            ..Config::default()
+        };
+        let (strategy, _mock) = strategy_with_pools(config, 0, now);
+
+        // Without responses the on-chain source cannot price anything
+        let mut record = OpportunityRecord::new(1);
+        assert!(strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(record.skip_reason, Some(SkipReason::NoPrices));
+
+        // The quoter source prices from the cached state without any RPC
+        let mut source = QuoterPriceSource::new();
+        for pool in &pools {
+            let quoter = LocalQuoter::new(&strategy.pool_states[pool], 0.0);
+            source.insert(*pool, quoter);
+        }
+        let strategy = strategy.with_price_source(Box::new(source));
+
+        let mut record = OpportunityRecord::new(2);
+        assert!(strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(record.cheap_price, Some(U256::exp10(18)));
+        assert_eq!(record.skip_reason, Some(SkipReason::NoSpread));
+    }
+
+    #[tokio::test]
+    async fn test_backoff_skips_full_evaluation_until_candidate_spread() {
+        let pool_a = Address::repeat_byte(0x11);
+        let pool_b = Address::repeat_byte(0x22);
+        let now = 1_700_000_000u64;
+
+        let config = Config {
+            pool_addresses: vec![pool_a, pool_b],
+            edge_bps: 10,
+            backoff_after_blocks: Some(2),
+            ..Config::default()
+        };
+        let (mut strategy, mock) = strategy_with_pools(config, 0, now);
+        let one = 10u128.pow(18);
+
+        // Below the threshold, identical prices still go through the spread check
+        for _ in 0..2 {
+            push_pool_prices(&mock, &[one, one]);
+            let mut record = OpportunityRecord::new(1);
+            assert!(strategy
+                .find_best_opportunity(now, &mut record)
+                .await
+                .unwrap()
+                .is_none());
+            assert_ne!(record.skip_reason, Some(SkipReason::Backoff));
+            strategy.backoff.record(false);
+        }
+
+        // Backing off: no spread means no sizing
+        push_pool_prices(&mock, &[one, one]);
+        let mut record = OpportunityRecord::new(3);
+        assert!(strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(record.skip_reason, Some(SkipReason::Backoff));
+        assert!(record.target_price.is_none());
+
+        // A 1% spread between pools is a candidate, so the full evaluation resumes
+        push_pool_prices(&mock, &[one * 99 / 100, one]);
+        let mut record = OpportunityRecord::new(4);
+        assert!(strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(record.skip_reason, Some(SkipReason::BelowEdge));
+        assert_eq!(record.target_price, Some(U256::exp10(18)));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_budget_prices_remaining_pools_from_cache() {
+        let pool_a = Address::repeat_byte(0x11);
+        let pool_b = Address::repeat_byte(0x22);
+        let now = 1_700_000_000u64;
+
+        // Room for one pool's two pricing calls
+        let config = Config {
+            pool_addresses: vec![pool_a, pool_b],
+            edge_bps: 10,
+            max_rpc_calls_per_block: Some(2),
+            ..Config::default()
+        };
+        let (strategy, mock) = strategy_with_pools(config, 0, now);
+        let one = 10u128.pow(18);
+        let cached = U256::from(one * 99 / 100);
+        strategy
+            .cached_prices
+            .lock()
+            .unwrap()
+            .insert(pool_b, cached);
+
+        // Only the first pool is priced on chain
+        push_pool_prices(&mock, &[one]);
+        let mut record = OpportunityRecord::new(1);
+        assert!(strategy
+            .find_best_opportunity(now, &mut record)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(strategy.rpc_calls(), 2);
+        assert_eq!(record.cheap_pool, Some(pool_b));
+        assert_eq!(record.cheap_price, Some(cached));
+        assert_eq!(record.rich_price, Some(U256::from(one)));
+
+        // A new block restarts the count
+        strategy.rpc_calls.take();
+        assert_eq!(strategy.rpc_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_structural_curve_spread_shifts_target() {
+        let (cheap, rich) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let now = 1_700_000_000u64;
+        let maturity = (now + 365 * 86_400) as u32;
+        let config = Config {
+            pool_addresses: vec![cheap, rich],
+            edge_bps: 10,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let target = curve.discount_factor(curve.time_to_maturity(now, maturity));
+
+        // The rich pool trades 15 bps under the pure curve, the cheap pool further
+        // under it, as a pool with a credit premium would
+        let rich_price = (target * (1.0 - 0.0015) * 1e18) as u128;
+        let cheap_price = rich_price * 995 / 1_000;
+        let evaluate = |config: Config| async move {
+            let (mut strategy, mock) = strategy_with_pools(config, 0, now);
+            for state in strategy.pool_states.values_mut() {
+                state.maturity = maturity;
+            }
+            push_pool_prices(&mock, &[cheap_price, rich_price]);
+            let mut record = OpportunityRecord::new(1);
+            let _ = strategy.find_best_opportunity(now, &mut record).await;
+            record
+        };
+
+        // Against the pure curve the rich pool looks mispriced
+        let record = evaluate(config.clone()).await;
+        assert!(record.divergence_bps.unwrap() >= 10);
+        assert_ne!(record.skip_reason, Some(SkipReason::BelowEdge));
+
+        // A +20 bps structural spread lowers its target to within the edge
+        let spread = Config {
+            pool_configs: vec![(
+                rich,
+                PoolConfig {
+                    curve_spread_bps: 20,
+                },
+            )],
+            ..config
+        };
+        let record = evaluate(spread).await;
+        assert!(record.divergence_bps.unwrap() < 10);
+        assert_eq!(record.skip_reason, Some(SkipReason::BelowEdge));
+    }
+
+    /// Quoter prices whose base sale previews acquire `skew_bps` more FY
+    struct SellBaseSkewSource {
+        inner: QuoterPriceSource,
+        skew_bps: i128,
+    }
+
+    #[async_trait]
+    impl PriceSource for SellBaseSkewSource {
+        async fn marginal_price(&self, pool: Address) -> Result<U256> {
+            self.inner.marginal_price(pool).await
+        }
+
+        async fn preview_buy_fy(&self, pool: Address, fy_out: u128) -> Result<u128> {
+            self.inner.preview_buy_fy(pool, fy_out).await
+        }
+
+        async fn preview_sell_fy(&self, pool: Address, fy_in: u128) -> Result<u128> {
+            self.inner.preview_sell_fy(pool, fy_in).await
+        }
+
+        async fn preview_sell_base(&self, pool: Address, base_in: u128) -> Result<u128> {
+            let fy_out = self.inner.preview_sell_base(pool, base_in).await? as i128;
+            Ok((fy_out * (10_000 + self.skew_bps) / 10_000) as u128)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_direction_with_more_base_out_wins() {
+        let now = 1_700_000_000u64;
+        let maturity = (now + 182 * 24 * 3600) as u32;
+        let one = 10u128.pow(18);
+        let pool = |byte: u8, base: u128| PoolState {
+            address: Address::repeat_byte(byte),
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity,
+        };
+        let (cheap, rich) = (pool(0x11, 442_000), pool(0x22, 818_000));
+        let config = Config {
+            pool_addresses: vec![cheap.address, rich.address],
+            max_fy_amount: 1_000 * one,
+            ..Config::default()
+        };
+        let curve = RateCurve::default_usd();
+        let ttm = curve.time_to_maturity(now, maturity);
+
+        let evaluate = |config: Config, skew_bps| {
+            let source = SellBaseSkewSource {
+                inner: QuoterPriceSource::new()
+                    .with_pool(cheap.address, LocalQuoter::new(&cheap, ttm))
+                    .with_pool(rich.address, LocalQuoter::new(&rich, ttm)),
+                skew_bps,
+            };
+            let (provider, _mock) = Provider::mocked();
+            let mut strategy = NumoArb::new(Arc::new(provider), config, curve.clone())
+                .with_price_source(Box::new(source));
+            strategy.restore_pool_states(vec![cheap.clone(), rich.clone()]);
+            async move {
+                let mut record = OpportunityRecord::new(1);
+                let opp = strategy
+                    .find_best_opportunity(now, &mut record)
+                    .await
+                    .unwrap()
+                    .expect("an opportunity");
+                (opp, record)
+            }
+        };
+
+        // Selling base acquires more FY than the exact buy: it sells for more base
+        let (opp, record) = evaluate(config.clone(), 20).await;
+        assert_eq!(opp.direction, ArbDirection::SellBaseThenSellFy);
+        assert!(opp.fy_amount < record.fy_amount.unwrap());
+        assert!(opp.worst_case_profit() > 0);
+
+        // Selling base acquires less: the exact FY buy nets more
+        let buy = evaluate(config.clone(), -20).await.0;
+        assert_eq!(buy.direction, ArbDirection::BuyFyThenSellFy);
+        assert!(buy.expected_profit < opp.expected_profit);
+
+        // Both size the same buy, but selling base spends exactly its cost while
+        // the exact buy allows for slippage on it
+        assert_eq!(
+            buy.max_base_in,
+            apply_slippage(opp.max_base_in, config.slippage_bps, true)
+        );
+
+        // A buy-then-sell edge the trade cannot clear leaves only selling base
+        let sell_base_only = Config {
+            edge_bps_buy_then_sell: Some(5_000),
+            ..config
+        };
+        let (opp, _) = evaluate(sell_base_only, -20).await;
+        assert_eq!(opp.direction, ArbDirection::SellBaseThenSellFy);
+    }
+
+    #[tokio::test]
+    async fn test_direction_edge_governs_buy_then_sell() {
+        let (cheap, rich) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let now = 1_700_000_000u64;
+        let maturity = (now + 365 * 86_400) as u32;
+        let curve = RateCurve::default_usd();
+        let target = curve.discount_factor(curve.time_to_maturity(now, maturity));
+
+        // The rich pool trades 30 bps over the curve, the cheap pool 50 bps under it
+        let rich_price = (target * 1.003 * 1e18) as u128;
+        let cheap_price = rich_price * 995 / 1_000;
+        let evaluate = |config: Config| async move {
+            let (mut strategy, mock) = strategy_with_pools(config, 0, now);
+            for state in strategy.pool_states.values_mut() {
+                state.maturity = maturity;
+            }
+            push_pool_prices(&mock, &[cheap_price, rich_price]);
+            let mut record = OpportunityRecord::new(1);
+            let _ = strategy.find_best_opportunity(now, &mut record).await;
+            record
+        };
+        let config = Config {
+            pool_addresses: vec![cheap, rich],
+            edge_bps: 10,
+            ..Config::default()
+        };
+
+        // The global edge lets the trade through
+        let record = evaluate(config.clone()).await;
+        assert_ne!(record.skip_reason, Some(SkipReason::BelowEdge));
+
+        // Stricter edges in both directions hold it back
+        let strict = Config {
+            edge_bps_buy_then_sell: Some(40),
+            edge_bps_sell_base: Some(40),
+            ..config.clone()
+        };
+        let record = evaluate(strict).await;
+        assert_eq!(record.skip_reason, Some(SkipReason::BelowEdge));
+
+        // A looser sell-base edge still admits the pair for that direction
+        let sell_base = Config {
+            edge_bps_buy_then_sell: Some(40),
+            edge_bps_sell_base: Some(5),
+            ..config.clone()
+        };
+        let record = evaluate(sell_base).await;
+        assert_ne!(record.skip_reason, Some(SkipReason::BelowEdge));
+
+        // A looser buy-then-sell edge lets it through under a strict global edge
+        let loose = Config {
+            edge_bps: 100,
+            edge_bps_buy_then_sell: Some(20),
+            ..config
+        };
+        let record = evaluate(loose).await;
+        assert!(!matches!(
+            record.skip_reason,
+            Some(SkipReason::BelowEdge | SkipReason::NoSpread)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_previews_fall_back_to_reserve_prices() {
+        let (cheap, rich) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let now = 1_700_000_000u64;
+        let one = 10u128.pow(18);
+        let states = [(cheap, 442_000u128), (rich, 818_000u128)].map(|(address, base)| PoolState {
+            address,
+            base_reserves: base * one,
+            fy_reserves: 1_000_000 * one,
+            fee_bps: 0,
+            maturity: (now + 182 * 86_400) as u32,
+        });
+        let config = Config {
+            pool_addresses: vec![cheap, rich],
+            ..Config::default()
+        };
+
+        // No preview answers: without the fallback neither pool is priced
+        let (mut strategy, _mock) = strategy_with_pools(config.clone(), 0, now);
+        strategy.restore_pool_states(states.to_vec());
+        let mut record = OpportunityRecord::new(1);
+        let _ = strategy.find_best_opportunity(now, &mut record).await;
+        assert_eq!(record.skip_reason, Some(SkipReason::NoPrices));
+
+        // With it both are priced from their getCache reserves
+        let fallback = Config {
+            reserve_price_fallback: true,
+            ..config
+        };
+        let (mut strategy, _mock) = strategy_with_pools(fallback, 0, now);
+        strategy.restore_pool_states(states.to_vec());
+        let mut record = OpportunityRecord::new(1);
+        let _ = strategy.find_best_opportunity(now, &mut record).await;
+        assert_eq!(
+            (record.cheap_pool, record.rich_pool),
+            (Some(cheap), Some(rich))
+        );
+        let quoted = |index: usize| {
+            let ttm = strategy.curve.time_to_maturity(now, states[index].maturity);
+            U256::from((LocalQuoter::new(&states[index], ttm).marginal_price() * 1e18) as u128)
+        };
+        assert_eq!(record.cheap_price, Some(quoted(0)));
+        assert_eq!(record.rich_price, Some(quoted(1)));
+    }
+
+    #[tokio::test]
+    async fn test_pools_per_block_rotates_and_keeps_divergent_pool() {
+        let pools: Vec<Address> = [0x11, 0x22, 0x33].map(Address::repeat_byte).to_vec();
+        let now = 1_700_000_000u64;
+        let config = Config {
+            pool_addresses: pools.clone(),
+            edge_bps: 10,
+            pools_per_block: Some(2),
+            ..Config::default()
+        };
+        let (strategy, mock) = strategy_with_pools(config, 0, now);
+        let one = 10u128.pow(18);
+
+        // The first two pools are priced; the first is 2% off its target
+        push_pool_prices(&mock, &[one * 98 / 100, one * 9_995 / 10_000]);
+        let _ = strategy
+            .find_best_opportunity(now, &mut OpportunityRecord::new(1))
+            .await;
+        assert_eq!(strategy.cached_prices.lock().unwrap().len(), 2);
+        let scheduler = strategy.scheduler.as_ref().unwrap();
+        assert!(scheduler.lock().unwrap().is_hot(pools[0], 1));
+        assert!(!scheduler.lock().unwrap().is_hot(pools[1], 1));
+
+        // The divergent pool is priced again alongside the next pool in rotation
+        push_pool_prices(&mock, &[one * 98 / 100, one]);
+        let _ = strategy
+            .find_best_opportunity(now, &mut OpportunityRecord::new(2))
+            .await;
+        let cached = strategy.cached_prices.lock().unwrap().clone();
+        assert_eq!(cached.len(), 3);
+        assert_eq!(cached[&pools[2]], U256::from(one));
+    }
+
+    #[tokio::test]
+    async fn test_trades_per_minute_capped() {
+        let (provider, _mock) = Provider::mocked();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = StubPolicy {
+            calls: calls.clone(),
+            emit: true,
+        };
+        let config = Config {
+            max_trades_per_minute: Some(3),
+            ..Config::default()
+        };
+        let mut strategy = NumoArb::new(Arc::new(provider), config, RateCurve::default_usd())
+            .with_execution_policy(Box::new(policy));
+        let start = 1_700_000_000u64;
+
+        // An opportunity every 5s for a minute: only three are executed, the rest
+        // are logged and skipped
+        let mut executed = 0;
+        let mut limited = 0;
+        for i in 0..12 {
+            let mut record = OpportunityRecord::new(i);
+            let actions = strategy
+                .execute_opportunity(opportunity(), start + i * 5, &mut record)
+                .await;
+            executed += actions.len();
+            if record.skip_reason == Some(SkipReason::RateLimited) {
+                limited += 1;
+            }
+        }
+        assert_eq!(executed, 3);
+        assert_eq!(limited, 9);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // A minute after the first trade its slot frees up
+        let mut record = OpportunityRecord::new(12);
+        let actions = strategy
+            .execute_opportunity(opportunity(), start + 60, &mut record)
+            .await;
+        assert_eq!(actions.len(), 1);
+        assert_eq!(record.skip_reason, None);
+    }
+
+    #[tokio::test]
+    async fn test_superseded_trade_is_cancelled() {
+        let (strategy, _calls) = strategy_with_policy(true);
+        let inflight = Arc::new(InflightTrades::new());
+        let mut strategy = strategy.with_inflight_trades(inflight.clone());
+        let mut record = OpportunityRecord::new(1);
+
+        // First trade on the pair: nothing to cancel
+        let first = strategy
+            .execute_opportunity(opportunity(), 100, &mut record)
+            .await;
+        assert_eq!(first.len(), 1);
+        let calldata = match &first[0] {
+            Action::SubmitTx(submit) => submit.tx.data().cloned().unwrap(),
+            _ => panic!("expected a SubmitTx action"),
+        };
+        let tx_hash = TxHash::repeat_byte(0xaa);
+        inflight.submitted(calldata, tx_hash);
+
+        // A resized opportunity on the same pair replaces the in-flight trade
+        let better = ArbOpportunity {
+            fy_amount: 2_000,
+            expected_profit: 60,
+            ..opportunity()
+        };
+        let second = strategy.execute_opportunity(better, 112, &mut record).await;
+        assert_eq!(second.len(), 2);
+        assert!(matches!(second[0], Action::Cancel(hash) if hash == tx_hash));
+        assert!(matches!(second[1], Action::SubmitTx(_)));
+    }
+
+    #[tokio::test]
+    async fn test_capital_budget_gates_third_trade() {
+        let (strategy, calls) = strategy_with_policy(true);
+        let budget = Arc::new(CapitalBudget::new(2_000));
+        let mut strategy = strategy.with_capital_budget(budget.clone());
+        let trade = |fy_amount| ArbOpportunity {
+            fy_amount,
+            ..opportunity()
+        };
+
+        // Two trades of 950 base each fit in the budget
+        let mut calldata = vec![];
+        for fy_amount in [1_000, 2_000] {
+            let mut record = OpportunityRecord::new(1);
+            let actions = strategy
+                .execute_opportunity(trade(fy_amount), 100, &mut record)
+                .await;
+            match &actions[..] {
+                [Action::SubmitTx(submit)] => calldata.push(submit.tx.data().cloned().unwrap()),
+                _ => panic!("expected a SubmitTx action"),
+            }
+        }
+        assert_eq!(budget.in_use(), 1_900);
+
+        // The third would exceed it while both are in flight
+        let mut record = OpportunityRecord::new(2);
+        let actions = strategy
+            .execute_opportunity(trade(3_000), 112, &mut record)
+            .await;
+        assert!(actions.is_empty());
+        assert_eq!(record.skip_reason, Some(SkipReason::BudgetExhausted));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Once one confirms the budget is released and the trade goes out
+        let submitted = SubmittedTx {
+            tx_hash: TxHash::repeat_byte(0xaa),
+            gas_estimate: U256::from(200_000u64),
+            expected_profit: None,
+            calldata: Some(calldata[0].clone()),
+        };
+        budget.on_receipt(&submitted, &TransactionReceipt::default());
+        let mut record = OpportunityRecord::new(3);
+        let actions = strategy
+            .execute_opportunity(trade(3_000), 124, &mut record)
+            .await;
+        assert_eq!(actions.len(), 1);
+        assert_eq!(budget.in_use(), 1_900);
+    }
+
+    #[tokio::test]
+    async fn test_stale_trade_is_cancelled() {
+        let (provider, _mock) = Provider::mocked();
+        let config = Config {
+            cancel_after_secs: Some(60),
+            ..Config::default()
+        };
+        let inflight = Arc::new(InflightTrades::new());
+        let policy = StubPolicy {
+            calls: Arc::new(AtomicUsize::new(0)),
+            emit: true,
+        };
+        let mut strategy = NumoArb::new(Arc::new(provider), config, RateCurve::default_usd())
+            .with_execution_policy(Box::new(policy))
+            .with_inflight_trades(inflight.clone());
+        let mut record = OpportunityRecord::new(1);
+
+        let actions = strategy
+            .execute_opportunity(opportunity(), 100, &mut record)
+            .await;
+        let calldata = match &actions[0] {
+            Action::SubmitTx(submit) => submit.tx.data().cloned().unwrap(),
+            _ => panic!("expected a SubmitTx action"),
+        };
+        let tx_hash = TxHash::repeat_byte(0xbb);
+        inflight.submitted(calldata, tx_hash);
+
+        assert!(strategy.cancel_stale_trades(159).is_empty());
+        let cancels = strategy.cancel_stale_trades(160);
+        assert!(matches!(cancels[..], [Action::Cancel(hash)] if hash == tx_hash));
+        // The trade is no longer tracked once cancelled
+        assert!(strategy.cancel_stale_trades(200).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_policy_can_skip_opportunity() {
+        let (strategy, calls) = strategy_with_policy(false);
+
+        let action = strategy.execute_arbitrage(opportunity()).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(action.is_none());
     }
 }
@@ -0,0 +1,133 @@
+/// Submission scheduling for the Numo strategy
+/// Reduces self-competition when several actions or instances fire in the same block
+use std::cmp::Reverse;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::prelude::*;
+
+use artemis_core::types::Executor;
+
+use crate::types::Action;
+
+/// Expected profit carried by an action, used as its submission priority
+/// Cancellations and approvals carry no profit but always go first
+pub fn action_profit(action: &Action) -> U256 {
+    match action {
+        Action::SubmitTx(submit) => submit
+            .gas_bid_info
+            .as_ref()
+            .map(|info| info.total_profit)
+            .unwrap_or_default(),
+        Action::Cancel(_) | Action::Approve(_) => U256::MAX,
+    }
+}
+
+/// Order actions produced in the same block by expected profit, highest first.
+/// Ties keep their original order.
+pub fn order_actions_by_profit(actions: &mut [Action]) {
+    actions.sort_by_key(|action| Reverse(action_profit(action)));
+}
+
+/// Deterministic per-instance submission delay in `[0, max_jitter_ms)`
+///
+/// The delay is derived from `seed` (e.g., the bot address) with FNV-1a, so the
+/// same instance always waits the same amount while different instances spread out.
+pub fn submission_jitter(seed: &[u8], max_jitter_ms: u64) -> Duration {
+    if max_jitter_ms == 0 {
+        return Duration::ZERO;
+    }
+    let hash = seed.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    Duration::from_millis(hash % max_jitter_ms)
+}
+
+/// An executor wrapper that waits a fixed delay before every submission
+pub struct JitteredExecutor<A> {
+    executor: Box<dyn Executor<A>>,
+    delay: Duration,
+}
+
+impl<A> JitteredExecutor<A> {
+    pub fn new(executor: Box<dyn Executor<A>>, delay: Duration) -> Self {
+        Self { executor, delay }
+    }
+}
+
+#[async_trait]
+impl<A> Executor<A> for JitteredExecutor<A>
+where
+    A: Send + Sync + 'static,
+{
+    async fn execute(&self, action: A) -> Result<()> {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        self.executor.execute(action).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{GasBidInfo, SubmitTxToMempool};
+
+    fn action(profit: Option<u64>, nonce: u64) -> Action {
+        let tx = TransactionRequest::new().nonce(nonce);
+        Action::SubmitTx(SubmitTxToMempool {
+            tx: tx.into(),
+            gas_bid_info: profit.map(|profit| GasBidInfo {
+                total_profit: U256::from(profit),
+                bid_percentage: 80,
+            }),
+        })
+    }
+
+    fn nonces(actions: &[Action]) -> Vec<u64> {
+        actions
+            .iter()
+            .map(|action| match action {
+                Action::SubmitTx(submit) => submit.tx.nonce().unwrap().as_u64(),
+                Action::Cancel(_) | Action::Approve(_) => u64::MAX,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_order_actions_by_profit() {
+        let mut actions = vec![
+            action(Some(10), 0),
+            action(None, 1),
+            action(Some(30), 2),
+            action(Some(20), 3),
+            action(Some(30), 4),
+        ];
+        order_actions_by_profit(&mut actions);
+
+        // Highest profit first, ties keep their order, missing profit last
+        assert_eq!(nonces(&actions), vec![2, 4, 3, 0, 1]);
+
+        let mut actions = vec![action(Some(10), 0), Action::Cancel(TxHash::zero())];
+        order_actions_by_profit(&mut actions);
+        assert_eq!(nonces(&actions), vec![u64::MAX, 0]);
+    }
+
+    #[test]
+    fn test_submission_jitter_is_deterministic_and_bounded() {
+        let a = Address::repeat_byte(0x11);
+        let b = Address::repeat_byte(0x22);
+
+        assert_eq!(
+            submission_jitter(a.as_bytes(), 50),
+            submission_jitter(a.as_bytes(), 50)
+        );
+        assert_ne!(
+            submission_jitter(a.as_bytes(), 50),
+            submission_jitter(b.as_bytes(), 50)
+        );
+        assert!(submission_jitter(a.as_bytes(), 50) < Duration::from_millis(50));
+        assert_eq!(submission_jitter(a.as_bytes(), 0), Duration::ZERO);
+    }
+}
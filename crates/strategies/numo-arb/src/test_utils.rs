@@ -0,0 +1,48 @@
+/// Shared helpers for unit tests and benches that drive contract calls through a
+/// mock provider; outside the crate's own tests they need the `test-utils` feature
+use ethers::abi::{encode, Token};
+use ethers::prelude::*;
+
+use crate::pricing::PRICE_PROBE_AMOUNT;
+use crate::types::{ArbDirection, ArbOpportunity};
+
+/// ABI-encode a tuple of unsigned integers as an `eth_call` response
+pub fn encode_uints(values: &[u128]) -> Bytes {
+    let tokens: Vec<Token> = values
+        .iter()
+        .map(|value| Token::Uint(U256::from(*value)))
+        .collect();
+    encode(&tokens).into()
+}
+
+/// Push the preview responses that make `marginal_price_base_per_fy` price each pool
+/// at the given 1e18-scaled price, for pools priced in `prices` order
+///
+/// The mock serves responses last-in first-out, so they are pushed in reverse.
+pub fn push_pool_prices(mock: &MockProvider, prices: &[u128]) {
+    let one = 10u128.pow(18);
+    for price in prices.iter().rev() {
+        let fy_out = PRICE_PROBE_AMOUNT * one / price;
+        let base_out = price * PRICE_PROBE_AMOUNT / one;
+        // sellFYTokenPreview is the second probe, sellBasePreview the first
+        mock.push::<Bytes, _>(encode_uints(&[base_out])).unwrap();
+        mock.push::<Bytes, _>(encode_uints(&[fy_out])).unwrap();
+    }
+}
+
+/// A buy-FY opportunity from pool `0x11..` into pool `0x22..`; tests override the
+/// fields they exercise with struct update syntax
+pub fn opportunity() -> ArbOpportunity {
+    ArbOpportunity {
+        cheap_pool: Address::repeat_byte(0x11),
+        rich_pool: Address::repeat_byte(0x22),
+        fy_amount: 1_000,
+        max_base_in: 950,
+        min_base_out: 980,
+        expected_profit: 30,
+        target_price: U256::from(960_000u64),
+        cheap_price: U256::from(950_000u64),
+        rich_price: U256::from(980_000u64),
+        direction: ArbDirection::BuyFyThenSellFy,
+    }
+}
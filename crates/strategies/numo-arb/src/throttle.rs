@@ -0,0 +1,86 @@
+/// Trade rate limit for the Numo strategy
+/// Caps executed trades per sliding minute, so a choppy market that keeps
+/// reopening the same edge cannot burn gas on a burst of trades
+use std::collections::VecDeque;
+
+/// Length of the sliding window trades are counted over
+pub const TRADE_WINDOW_SECS: u64 = 60;
+
+/// Executed trades within the last [TRADE_WINDOW_SECS] seconds
+#[derive(Debug, Clone)]
+pub struct TradeThrottle {
+    /// Trades allowed within a window
+    max_trades: u32,
+
+    /// Times of the trades still within the window, oldest first
+    executed: VecDeque<u64>,
+}
+
+impl TradeThrottle {
+    pub fn new(max_trades: u32) -> Self {
+        Self {
+            max_trades,
+            executed: VecDeque::new(),
+        }
+    }
+
+    /// Whether another trade may execute at `now` (Unix seconds)
+    pub fn allows(&mut self, now: u64) -> bool {
+        self.expire(now);
+        self.executed.len() < self.max_trades as usize
+    }
+
+    /// Record a trade executed at `now`
+    pub fn record(&mut self, now: u64) {
+        self.expire(now);
+        self.executed.push_back(now);
+    }
+
+    /// Trades executed within the window ending at `now`
+    pub fn count(&mut self, now: u64) -> usize {
+        self.expire(now);
+        self.executed.len()
+    }
+
+    pub fn max_trades(&self) -> u32 {
+        self.max_trades
+    }
+
+    fn expire(&mut self, now: u64) {
+        while self
+            .executed
+            .front()
+            .is_some_and(|at| now >= at + TRADE_WINDOW_SECS)
+        {
+            self.executed.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trades_capped_per_sliding_window() {
+        let mut throttle = TradeThrottle::new(3);
+        let start = 1_700_000_000u64;
+
+        // One opportunity a second: only the first three execute
+        let mut executed = 0;
+        for second in 0..30 {
+            if throttle.allows(start + second) {
+                throttle.record(start + second);
+                executed += 1;
+            }
+        }
+        assert_eq!(executed, 3);
+        assert_eq!(throttle.count(start + 29), 3);
+
+        // The window slides: the first trade expires a minute after it executed
+        assert!(!throttle.allows(start + TRADE_WINDOW_SECS - 1));
+        assert!(throttle.allows(start + TRADE_WINDOW_SECS));
+        assert_eq!(throttle.count(start + TRADE_WINDOW_SECS), 2);
+        assert_eq!(throttle.count(start + TRADE_WINDOW_SECS + 2), 0);
+    }
+}
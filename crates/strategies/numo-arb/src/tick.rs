@@ -0,0 +1,90 @@
+/// Periodic re-evaluation ticks for the Numo strategy
+/// Emits a [TickEvent] on a fixed interval so the strategy re-evaluates between
+/// blocks, e.g., to time out stale trades on slow chains
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use artemis_core::types::{Collector, CollectorStream};
+
+use crate::clock::{Clock, SystemClock};
+use crate::types::{Event, TickEvent};
+
+/// A collector emitting a tick every `interval`, stamped with the wall clock
+pub struct TickCollector {
+    interval: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl TickCollector {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Stamp ticks with `clock`'s time rather than the system time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+#[async_trait]
+impl Collector<Event> for TickCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Event>> {
+        let mut interval = tokio::time::interval(self.interval);
+        // The first tick completes immediately; skip it so ticks follow the interval
+        interval.tick().await;
+        let clock = self.clock.clone();
+        let stream = futures::stream::unfold(interval, move |mut interval| {
+            let clock = clock.clone();
+            async move {
+                interval.tick().await;
+                let timestamp = clock.now();
+                Some((Event::Tick(TickEvent { timestamp }), interval))
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_ticks_follow_the_interval() {
+        let collector = TickCollector::new(Duration::from_millis(10));
+        let events: Vec<Event> = collector
+            .get_event_stream()
+            .await
+            .unwrap()
+            .take(2)
+            .collect()
+            .await;
+
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|event| matches!(event, Event::Tick(tick) if tick.timestamp > 0)));
+    }
+
+    #[tokio::test]
+    async fn test_ticks_stamped_with_clock() {
+        let clock = Arc::new(MockClock::new(1_700_000_000));
+        let collector = TickCollector::new(Duration::from_millis(10)).with_clock(clock.clone());
+        let mut stream = collector.get_event_stream().await.unwrap();
+
+        let first = stream.next().await.unwrap();
+        clock.advance(30);
+        let second = stream.next().await.unwrap();
+        assert!(matches!(first, Event::Tick(tick) if tick.timestamp == 1_700_000_000));
+        assert!(matches!(second, Event::Tick(tick) if tick.timestamp == 1_700_000_030));
+    }
+}
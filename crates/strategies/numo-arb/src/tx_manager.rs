@@ -0,0 +1,237 @@
+/// Stuck transaction retries for the Numo strategy
+/// A submission not mined within a number of blocks is resubmitted at the same
+/// nonce with a bumped gas price, up to a number of attempts and a cap on the total
+/// bump; a mined transaction is final, whether it succeeded or reverted
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use tracing::{info, warn};
+
+use crate::cancel::bumped_gas_price;
+
+/// Interval between receipt checks of a pending submission
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When and how far to reprice a transaction that is not being mined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Blocks a submission may stay unmined before it is repriced
+    pub timeout_blocks: u64,
+
+    /// Submissions of one transaction, the first included
+    pub max_attempts: u32,
+
+    /// Gas price bump of each resubmission over the previous one, in bps
+    pub gas_bump_bps: u32,
+
+    /// Cap on the total gas price bump over the first submission, in bps
+    pub max_gas_bump_bps: u32,
+}
+
+impl RetryPolicy {
+    /// Gas price of submission `attempt` (0 for the first) of a transaction first
+    /// sent at `gas_price`, or `None` once the attempts or the bump cap run out
+    pub fn gas_price(&self, gas_price: U256, attempt: u32) -> Option<U256> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let cap = bumped_gas_price(gas_price, self.max_gas_bump_bps);
+        let bumped = (0..attempt).fold(gas_price, |price, _| {
+            bumped_gas_price(price, self.gas_bump_bps)
+        });
+        (bumped <= cap).then_some(bumped)
+    }
+}
+
+/// Watches a submitted transaction and reprices it while it is not mined
+pub struct TxManager<M> {
+    client: Arc<M>,
+    policy: RetryPolicy,
+    poll_interval: Duration,
+}
+
+impl<M> TxManager<M> {
+    pub fn new(client: Arc<M>, policy: RetryPolicy) -> Self {
+        Self {
+            client,
+            policy,
+            poll_interval: RECEIPT_POLL_INTERVAL,
+        }
+    }
+
+    /// Check for receipts every `interval`
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+}
+
+impl<M> TxManager<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    async fn block_number(&self) -> Result<u64> {
+        Ok(self
+            .client
+            .get_block_number()
+            .await
+            .context("Error fetching block number")?
+            .as_u64())
+    }
+
+    /// Wait for `tx`, sent as `tx_hash` with its nonce and gas price set, to be
+    /// mined, resubmitting it as the policy allows
+    ///
+    /// `on_resubmitted` is called with the hash of every resubmission. Every
+    /// submission stays a candidate, so whichever is mined first is returned with
+    /// its receipt; `None` means the last attempt timed out as well.
+    pub async fn confirm(
+        &self,
+        mut tx: TypedTransaction,
+        tx_hash: TxHash,
+        mut on_resubmitted: impl FnMut(TxHash) + Send,
+    ) -> Result<Option<(TxHash, TransactionReceipt)>> {
+        let first_gas_price = tx
+            .gas_price()
+            .context("Transaction to confirm has no gas price")?;
+        let mut hashes = vec![tx_hash];
+        let mut attempt = 0;
+        let mut deadline = self.block_number().await? + self.policy.timeout_blocks;
+        loop {
+            for hash in &hashes {
+                let receipt = self
+                    .client
+                    .get_transaction_receipt(*hash)
+                    .await
+                    .context("Error fetching receipt")?;
+                if let Some(receipt) = receipt {
+                    return Ok(Some((*hash, receipt)));
+                }
+            }
+            let block = self.block_number().await?;
+            if block < deadline {
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            }
+
+            attempt += 1;
+            let last = hashes[hashes.len() - 1];
+            let Some(gas_price) = self.policy.gas_price(first_gas_price, attempt) else {
+                warn!(
+                    tx_hash = ?last,
+                    attempts = attempt,
+                    "Transaction not mined after its last attempt, giving up"
+                );
+                return Ok(None);
+            };
+            tx.set_gas_price(gas_price);
+            match self.client.send_transaction(tx.clone(), None).await {
+                Ok(pending) => {
+                    let resubmitted = *pending;
+                    info!(
+                        replaced = ?last,
+                        tx_hash = ?resubmitted,
+                        attempt,
+                        gas_price = %gas_price,
+                        "Transaction not mined in time, resubmitted with bumped gas"
+                    );
+                    hashes.push(resubmitted);
+                    on_resubmitted(resubmitted);
+                }
+                // An earlier submission may have just been mined, which the next
+                // receipt check picks up
+                Err(e) => warn!(tx_hash = ?last, attempt, error = ?e, "Resubmission failed"),
+            }
+            deadline = block + self.policy.timeout_blocks;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: RetryPolicy = RetryPolicy {
+        timeout_blocks: 2,
+        max_attempts: 3,
+        gas_bump_bps: 1_000,
+        max_gas_bump_bps: 5_000,
+    };
+
+    #[test]
+    fn test_retry_gas_prices_bounded_by_attempts_and_cap() {
+        let price = |policy: &RetryPolicy, attempt| policy.gas_price(U256::from(100), attempt);
+
+        // 10% over the previous submission each time, until the attempts run out
+        assert_eq!(price(&POLICY, 0), Some(U256::from(100)));
+        assert_eq!(price(&POLICY, 1), Some(U256::from(110)));
+        assert_eq!(price(&POLICY, 2), Some(U256::from(121)));
+        assert_eq!(price(&POLICY, 3), None);
+
+        // or until the next bump would exceed the cap (134 over 125)
+        let capped = RetryPolicy {
+            max_attempts: 10,
+            max_gas_bump_bps: 2_500,
+            ..POLICY
+        };
+        assert_eq!(price(&capped, 2), Some(U256::from(121)));
+        assert_eq!(price(&capped, 3), None);
+    }
+
+    #[tokio::test]
+    async fn test_unmined_tx_resubmitted_until_mined() {
+        let (provider, mock) = Provider::mocked();
+        let manager = TxManager::new(Arc::new(provider), POLICY).with_poll_interval(Duration::ZERO);
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(Address::repeat_byte(0x77))
+            .nonce(5)
+            .gas(300_000)
+            .gas_price(100)
+            .into();
+        let (first, second) = (TxHash::repeat_byte(0x01), TxHash::repeat_byte(0x02));
+        let reverted = TransactionReceipt {
+            transaction_hash: second,
+            status: Some(U64::zero()),
+            ..Default::default()
+        };
+
+        // Responses are served last-in first-out: sent at block 10, still unmined at
+        // block 12, resubmitted, then the resubmission is mined and reverts
+        mock.push(reverted).unwrap();
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(second).unwrap();
+        mock.push(U64::from(12)).unwrap();
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(U64::from(11)).unwrap();
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(U64::from(10)).unwrap();
+
+        let mut resubmitted = vec![];
+        let (hash, receipt) = manager
+            .confirm(tx.clone(), first, |hash| resubmitted.push(hash))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resubmitted, vec![second]);
+        // A revert is final rather than retried
+        assert_eq!(hash, second);
+        assert_eq!(receipt.status, Some(U64::zero()));
+
+        // Out of attempts, the manager gives up once the last one times out
+        let single = TxManager::new(
+            manager.client.clone(),
+            RetryPolicy {
+                max_attempts: 1,
+                ..POLICY
+            },
+        );
+        mock.push(U64::from(12)).unwrap();
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        assert!(single.confirm(tx, first, |_| {}).await.unwrap().is_none());
+    }
+}
@@ -1,7 +1,15 @@
 /// Types for the Numo arbitrage strategy
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
 use ethers::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::fmt::TokenDecimals;
+use crate::sofr::RateCurve;
+
 // Re-export types from artemis_core
 pub use artemis_core::executors::mempool_executor::{GasBidInfo, SubmitTxToMempool};
 
@@ -17,6 +25,12 @@ pub struct Config {
     /// Minimum edge in basis points before executing arb (e.g., 10 = 0.10%)
     pub edge_bps: u32,
 
+    /// Edge required of buy-FY-then-sell-FY trades; falls back to `edge_bps`
+    pub edge_bps_buy_then_sell: Option<u32>,
+
+    /// Edge required of sell-base-then-sell-FY trades; falls back to `edge_bps`
+    pub edge_bps_sell_base: Option<u32>,
+
     /// Slippage tolerance in basis points (e.g., 50 = 0.50%)
     pub slippage_bps: u32,
 
@@ -28,6 +42,288 @@ pub struct Config {
 
     /// Percentage of expected profit to bid in gas (0-100)
     pub bid_percentage: u64,
+
+    /// Maximum price impact in basis points any single leg may have on its pool
+    /// (e.g., 50 = never move a pool by more than 0.50%). Disabled when unset.
+    pub max_price_impact_bps: Option<u32>,
+
+    /// CSV file receiving one row per evaluated opportunity. Disabled when unset.
+    pub data_log_path: Option<PathBuf>,
+
+    /// CSV ledger of the realized PnL of every confirmed trade, whose totals carry
+    /// over to the next run. Disabled when unset.
+    pub pnl_ledger_path: Option<PathBuf>,
+
+    /// Order actions produced in the same block by expected profit, highest first
+    pub order_by_profit: bool,
+
+    /// Sentinel file whose existence pauses all trading
+    pub kill_switch_file: Option<PathBuf>,
+
+    /// After this many consecutive blocks without an opportunity, only run the cheap
+    /// spread pre-check until the spread between pools reaches `edge_bps` again.
+    /// Disabled when unset.
+    pub backoff_after_blocks: Option<u32>,
+
+    /// Maximum number of pools the strategy accepts, a sanity cap on the pool list
+    pub max_pools: usize,
+
+    /// Maximum number of pools whose state is fetched concurrently during sync
+    pub sync_concurrency: usize,
+
+    /// Re-fetch the reserves of every live pool once per block whose number is a
+    /// multiple of this (default: 1, every block). Reserves are only fetched at
+    /// sync when unset, e.g., when replaying recorded state.
+    pub state_refresh_blocks: Option<u64>,
+
+    /// Gas limit buffer on top of the estimate in basis points (e.g., 2000 = 20%)
+    pub gas_buffer_bps: u32,
+
+    /// Move the gas buffer toward the p95 of observed gas used / estimate ratios
+    pub auto_adjust_gas_buffer: bool,
+
+    /// Report an unchanged opportunity at most once per this many blocks.
+    /// Disabled when unset.
+    pub dedup_ttl_blocks: Option<u64>,
+
+    /// Reuse gas estimates per pool pair for this many seconds. Disabled when unset.
+    pub gas_cache_ttl_secs: Option<u64>,
+
+    /// Divergence in basis points required to enter a pair. Together with
+    /// `exit_bps` this forms a no-trade band; falls back to `edge_bps`.
+    pub enter_bps: Option<u32>,
+
+    /// Divergence in basis points an entered pair must narrow below before it is
+    /// entered again; falls back to `edge_bps`. The band is disabled when neither
+    /// `enter_bps` nor `exit_bps` is set.
+    pub exit_bps: Option<u32>,
+
+    /// Log knot changes and per-pool discount factor shifts whenever the curve is
+    /// swapped
+    pub log_curve_changes: bool,
+
+    /// Age in seconds since the last curve refresh beyond which the curve counts as
+    /// stale and the strategy reports unready. Needs `curve_refresh_secs`, since a
+    /// curve that is never refreshed would only age. Never stale when unset.
+    pub max_curve_age_secs: Option<u64>,
+
+    /// Re-read the rate curve file every this many seconds, swapping in the new
+    /// curve. The startup curve is kept when unset.
+    pub curve_refresh_secs: Option<u64>,
+
+    /// Floor for the buffered gas limit, guarding against implausibly low estimates
+    pub min_gas_limit: u64,
+
+    /// Priority tip (wei per gas) bid on top of the network gas price, within the
+    /// `bid_percentage` budget
+    pub gas_tip_wei: u64,
+
+    /// Token aggregate profit is reported in, for deployments with several base
+    /// tokens. Disabled when unset.
+    pub profit_token: Option<Address>,
+
+    /// Profit token units per base token unit, keyed by base token
+    pub profit_rates: Vec<(Address, f64)>,
+
+    /// Decimals of the base and FY tokens, for human-readable amounts in logs
+    /// and reports
+    pub token_decimals: TokenDecimals,
+
+    /// Cancel an in-flight trade once it has been pending this many seconds.
+    /// Disabled when unset.
+    pub cancel_after_secs: Option<u64>,
+
+    /// Gas price bump of a cancellation over the cancelled transaction, in bps
+    pub cancel_gas_bump_bps: u32,
+
+    /// Treat a submission unconfirmed for this long as stuck and recover its nonce.
+    /// Disabled when unset.
+    pub nonce_stuck_after_secs: Option<u64>,
+
+    /// How a stuck nonce is recovered
+    pub nonce_recovery: NonceRecoveryMode,
+
+    /// Resubmit a trade not mined within this many blocks at the same nonce, its
+    /// gas price bumped by `cancel_gas_bump_bps`. Disabled when unset.
+    pub tx_timeout_blocks: Option<u64>,
+
+    /// Submissions of one trade under `tx_timeout_blocks`, the first included
+    pub tx_max_attempts: u32,
+
+    /// Cap on the total gas price bump of resubmissions over the first, in bps
+    pub max_gas_bump_bps: u32,
+
+    /// Maximum number of candidate pairs sized concurrently
+    pub sizing_concurrency: usize,
+
+    /// Skip pools whose reserves are more imbalanced than this ratio in either
+    /// direction (e.g., 10 = one side holds at most 10x the other). Disabled when unset.
+    pub max_reserve_imbalance_ratio: Option<f64>,
+
+    /// Skip pools whose quoted price deviates from the price their `getCache`
+    /// reserves imply under the Numo invariant by more than this, in bps.
+    /// Disabled when unset.
+    pub max_invariant_deviation_bps: Option<u32>,
+
+    /// Keep live opportunities ranked by net profit across blocks, dropping pairs
+    /// not refreshed within this many blocks. Disabled when unset.
+    pub opportunity_max_age_blocks: Option<u64>,
+
+    /// Soft budget of pricing and preview RPC calls per block; once exceeded, the
+    /// remaining pools are priced from their last known price. Unlimited when unset.
+    pub max_rpc_calls_per_block: Option<u64>,
+
+    /// Price at most this many pools fresh per block, rotating through the rest
+    /// and pricing them from cache meanwhile; pools recently diverging from their
+    /// target are priced every block. All pools are priced when unset.
+    pub pools_per_block: Option<usize>,
+
+    /// Price a pool whose preview calls fail from its cached `getCache` reserves
+    /// through the local quoter, instead of skipping it
+    pub reserve_price_fallback: bool,
+
+    /// Stop after computing each opportunity at marginal prices and only log it:
+    /// no trade previews, gas estimation or transactions
+    pub log_opportunities_only: bool,
+
+    /// Size and preview every opportunity as for a live trade, but log it instead
+    /// of submitting it
+    pub dry_run: bool,
+
+    /// Approve the router at startup to pull each pool's base token wherever its
+    /// allowance would not cover `max_base_amount`, trading only once the
+    /// approvals confirm. Arbitrage FY never leaves the router call, so it needs no
+    /// allowance; in carry mode the router's FY token is approved to cover
+    /// `max_fy_amount`, which trades selling FY pull from the bot.
+    pub auto_approve: bool,
+
+    /// Allowance granted by `auto_approve`, at least `max_base_amount`. Unlimited
+    /// when unset.
+    pub approval_amount: Option<u128>,
+
+    /// Only act on an opportunity once its pair has shown an edge for this many
+    /// consecutive blocks. Disabled when unset.
+    pub min_edge_persistence_blocks: Option<u64>,
+
+    /// Execute at most this many trades per sliding minute; opportunities past the
+    /// limit are logged but not executed. Disabled when unset.
+    pub max_trades_per_minute: Option<u32>,
+
+    /// Maximum total `max_base_in` across in-flight trades. Disabled when unset.
+    pub max_total_base_at_risk: Option<u128>,
+
+    /// Realized net loss (base token wei) over the last 24h that halts trading until
+    /// enough of it ages out. Gas is priced at `native_price_in_base`, or charged as
+    /// `gas_cost_base` per trade without one. Disabled when unset.
+    pub max_daily_loss: Option<u128>,
+
+    /// Check the receiver's base token balance after every confirmed trade and
+    /// alert when it moved by more than this many bps away from the expected
+    /// profit. Disabled when unset.
+    pub balance_tolerance_bps: Option<u32>,
+
+    /// Chain ID the node must report at startup (e.g., 42220 for Celo). Any chain
+    /// is accepted when unset.
+    pub chain_id: Option<u64>,
+
+    /// How the slippage bounds of a trade are derived from `slippage_bps`
+    pub slippage_model: SlippageModel,
+
+    /// How trade gas limits are chosen before `gas_buffer_bps` is applied
+    pub gas_estimation: GasEstimation,
+
+    /// Expected gas cost of one trade in base token units, used to report the
+    /// break-even edge of each opportunity
+    pub gas_cost_base: u128,
+
+    /// Smallest expected profit after gas (base token wei) a trade must net to be
+    /// submitted. Gas is priced at the block's base fee and converted at
+    /// `native_price_in_base` when both are known, `gas_cost_base` otherwise.
+    pub min_net_profit: u128,
+
+    /// Price of the native gas token in base tokens (e.g., 0.6 for CELO at $0.60
+    /// against a USD base), converting gas priced at the base fee from native wei
+    /// into base token wei. Gas is costed at `gas_cost_base` when unset.
+    pub native_price_in_base: Option<f64>,
+
+    /// Warn when evaluating a block takes longer than this many milliseconds.
+    /// Disabled when unset.
+    pub max_eval_latency_ms: Option<u64>,
+
+    /// Consecutive slow blocks after which only candidate spreads are sized until a
+    /// block is evaluated in time
+    pub degrade_after_slow_blocks: u32,
+
+    /// Number of probe sizes priced per pool, each double the previous; above 1,
+    /// the marginal price is the median of the probe mids
+    pub price_probes: usize,
+
+    /// Instead of cross-pool arbitrage, trade each pool directly when its implied
+    /// rate diverges from the curve, sized by the gap and its time to maturity
+    pub carry_mode: bool,
+
+    /// Check every trade's on-chain previews against the local quoter and skip the
+    /// trade when they drift apart by more than `quoter_tolerance_bps`
+    pub strict_quoter: bool,
+
+    /// Drift allowed between local quoter and on-chain previews under `strict_quoter`
+    pub quoter_tolerance_bps: u32,
+
+    /// JSON file receiving the pool-by-pool spread heatmap. Disabled when unset.
+    pub heatmap_path: Option<PathBuf>,
+
+    /// Export the heatmap on every block divisible by this
+    pub heatmap_every_blocks: u64,
+
+    /// JSON file receiving the pool and curve state of every evaluated block, for
+    /// `--simulate-from-snapshot`. Disabled when unset.
+    pub snapshot_path: Option<PathBuf>,
+
+    /// Simulate trades expecting at least this profit (in base token wei) with
+    /// `eth_call` before submission, skipping those that revert. Disabled when unset.
+    pub min_profit_to_simulate: Option<u128>,
+
+    /// Attach an EIP-2930 access list, created once per pool pair with
+    /// `eth_createAccessList`, to every trade; trades are sent without one when
+    /// the node cannot create it
+    pub use_access_lists: bool,
+
+    /// Storage slot of the pools' packed reserves cache. When set, simulations apply
+    /// the reserve moves of in-flight trades on the same pools as a state override.
+    pub pool_reserves_slot: Option<u64>,
+
+    /// Multicall3 contract pricing all pools in one call, so a reverting pool only
+    /// loses its own price. Pools are priced one by one when unset, with
+    /// `price_probes` above 1 or while recording events.
+    pub multicall_address: Option<Address>,
+
+    /// Paper trade for this long (by block timestamps) before going live, and only
+    /// go live if the paper net profit exceeds `promotion_min_net_profit`.
+    /// Trades live from the start when unset.
+    pub dry_run_duration_secs: Option<u64>,
+
+    /// Paper net profit (base token wei, after `gas_cost_base` per trade) the dry
+    /// run must exceed to be promoted
+    pub promotion_min_net_profit: i128,
+
+    /// Monitored pools excluded from opportunity search, e.g., while suspected
+    /// compromised. Their state is still kept fresh; reloadable on SIGHUP.
+    pub disabled_pools: Vec<Address>,
+
+    /// Pools priced and reported (logs, heatmap) but never traded, e.g., a new
+    /// pool being watched before it is trusted
+    pub observe_only_pools: Vec<Address>,
+
+    /// Per-pool settings; pools not listed use [PoolConfig::default]
+    pub pool_configs: Vec<(Address, PoolConfig)>,
+
+    /// Skip lower-priority events (ticks) arriving within this many seconds of a
+    /// higher-priority one (a block). Every event is processed when unset.
+    pub debounce_window_secs: Option<u64>,
+
+    /// JSONL file receiving every inbound event with the state it was evaluated
+    /// against, for replay. Disabled when unset.
+    pub record_events_path: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -35,34 +331,714 @@ impl Default for Config {
         Self {
             router_address: Address::zero(),
             pool_addresses: vec![],
-            edge_bps: 10,                                 // 0.10% minimum edge
+            edge_bps: 10, // 0.10% minimum edge
+            edge_bps_buy_then_sell: None,
+            edge_bps_sell_base: None,
             slippage_bps: 50,                             // 0.50% slippage tolerance
             max_fy_amount: 100_000u128 * 10u128.pow(18),  // 100k tokens
             max_base_amount: 50_000u128 * 10u128.pow(18), // 50k tokens
             bid_percentage: 80,                           // Bid 80% of profit in gas
+            max_price_impact_bps: None,
+            data_log_path: None,
+            pnl_ledger_path: None,
+            order_by_profit: false,
+            kill_switch_file: None,
+            backoff_after_blocks: None,
+            max_pools: 100,
+            sync_concurrency: 8,
+            state_refresh_blocks: Some(1),
+            gas_buffer_bps: 2_000,
+            auto_adjust_gas_buffer: false,
+            dedup_ttl_blocks: None,
+            gas_cache_ttl_secs: None,
+            enter_bps: None,
+            exit_bps: None,
+            log_curve_changes: true,
+            max_curve_age_secs: None,
+            curve_refresh_secs: None,
+            min_gas_limit: 100_000,
+            gas_tip_wei: 1_000_000_000, // 1 gwei
+            profit_token: None,
+            token_decimals: TokenDecimals::default(),
+            profit_rates: vec![],
+            cancel_after_secs: None,
+            cancel_gas_bump_bps: 1_250,
+            nonce_stuck_after_secs: None,
+            tx_timeout_blocks: None,
+            tx_max_attempts: 3,
+            max_gas_bump_bps: 5_000,
+            nonce_recovery: NonceRecoveryMode::FillGap,
+            sizing_concurrency: 4,
+            record_events_path: None,
+            max_reserve_imbalance_ratio: None,
+            max_invariant_deviation_bps: None,
+            opportunity_max_age_blocks: None,
+            max_rpc_calls_per_block: None,
+            pools_per_block: None,
+            reserve_price_fallback: false,
+            log_opportunities_only: false,
+            dry_run: false,
+            auto_approve: false,
+            approval_amount: None,
+            min_edge_persistence_blocks: None,
+            max_trades_per_minute: None,
+            max_total_base_at_risk: None,
+            max_daily_loss: None,
+            balance_tolerance_bps: None,
+            chain_id: None,
+            slippage_model: SlippageModel::Flat,
+            gas_estimation: GasEstimation::OnChain,
+            gas_cost_base: 0,
+            min_net_profit: 0,
+            native_price_in_base: None,
+            max_eval_latency_ms: None,
+            degrade_after_slow_blocks: 3,
+            price_probes: 1,
+            carry_mode: false,
+            strict_quoter: false,
+            quoter_tolerance_bps: 10,
+            heatmap_path: None,
+            heatmap_every_blocks: 10,
+            snapshot_path: None,
+            min_profit_to_simulate: None,
+            use_access_lists: false,
+            pool_reserves_slot: None,
+            multicall_address: None,
+            dry_run_duration_secs: None,
+            promotion_min_net_profit: 0,
+            disabled_pools: vec![],
+            observe_only_pools: vec![],
+            pool_configs: vec![],
+            debounce_window_secs: None,
+        }
+    }
+}
+
+impl Config {
+    /// Read the configuration from the process environment
+    ///
+    /// Uses the same variable names as the CLI (`ROUTER_ADDRESS`, `POOL_ADDRESSES`,
+    /// `EDGE_BPS`, ...); unset optional variables keep their defaults, those of
+    /// the [Profile] named by `PROFILE` when set.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    /// Build the configuration from a variable lookup, e.g., the process environment
+    pub fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |key: &str| {
+            lookup(key)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let defaults = parse_var::<Profile>("PROFILE", var("PROFILE"))?
+            .map_or_else(Self::default, Profile::config);
+
+        let router_address =
+            var("ROUTER_ADDRESS").ok_or_else(|| anyhow!("ROUTER_ADDRESS is not set"))?;
+        let router_address = parse_address("ROUTER_ADDRESS", &router_address)?;
+
+        let pool_addresses = var("POOL_ADDRESSES")
+            .ok_or_else(|| anyhow!("POOL_ADDRESSES is not set"))?
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| parse_address("POOL_ADDRESSES", s))
+            .collect::<Result<Vec<_>>>()?;
+        if pool_addresses.is_empty() {
+            bail!("At least one pool address must be specified");
+        }
+        let disabled_pools = var("DISABLED_POOLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| parse_address("DISABLED_POOLS", s))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(pool) = disabled_pools.iter().find(|p| !pool_addresses.contains(p)) {
+            bail!("DISABLED_POOLS lists {pool:?}, which is not in POOL_ADDRESSES");
+        }
+        let observe_only_pools = var("OBSERVE_ONLY_POOLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| parse_address("OBSERVE_ONLY_POOLS", s))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(pool) = observe_only_pools
+            .iter()
+            .find(|p| !pool_addresses.contains(p))
+        {
+            bail!("OBSERVE_ONLY_POOLS lists {pool:?}, which is not in POOL_ADDRESSES");
+        }
+        let curve_spreads = var("POOL_CURVE_SPREADS_BPS")
+            .map(|spreads| parse_pool_spreads("POOL_CURVE_SPREADS_BPS", &spreads))
+            .transpose()?
+            .unwrap_or_default();
+        if let Some((pool, _)) = curve_spreads
+            .iter()
+            .find(|(p, _)| !pool_addresses.contains(p))
+        {
+            bail!("POOL_CURVE_SPREADS_BPS lists {pool:?}, which is not in POOL_ADDRESSES");
+        }
+        let pool_configs = curve_spreads
+            .into_iter()
+            .map(|(pool, curve_spread_bps)| (pool, PoolConfig { curve_spread_bps }))
+            .collect();
+        let max_pools = parse_var("MAX_POOLS", var("MAX_POOLS"))?.unwrap_or(defaults.max_pools);
+        if pool_addresses.len() > max_pools {
+            bail!(
+                "POOL_ADDRESSES lists {} pools, more than MAX_POOLS ({max_pools})",
+                pool_addresses.len()
+            );
+        }
+        let sync_concurrency =
+            match parse_var::<usize>("SYNC_CONCURRENCY", var("SYNC_CONCURRENCY"))? {
+                Some(0) => bail!("SYNC_CONCURRENCY must be at least 1"),
+                Some(concurrency) => concurrency,
+                None => defaults.sync_concurrency,
+            };
+
+        let state_refresh_blocks =
+            match parse_var::<u64>("STATE_REFRESH_BLOCKS", var("STATE_REFRESH_BLOCKS"))? {
+                Some(0) => bail!("STATE_REFRESH_BLOCKS must be at least 1"),
+                blocks => blocks.or(defaults.state_refresh_blocks),
+            };
+
+        let pools_per_block = match parse_var::<usize>("POOLS_PER_BLOCK", var("POOLS_PER_BLOCK"))? {
+            Some(0) => bail!("POOLS_PER_BLOCK must be at least 1"),
+            pools_per_block => pools_per_block,
+        };
+
+        let max_curve_age_secs =
+            match parse_var::<u64>("MAX_CURVE_AGE_SECS", var("MAX_CURVE_AGE_SECS"))? {
+                Some(0) => bail!("MAX_CURVE_AGE_SECS must be at least 1"),
+                max_age => max_age,
+            };
+        let curve_refresh_secs =
+            match parse_var::<u64>("CURVE_REFRESH_SECS", var("CURVE_REFRESH_SECS"))? {
+                Some(0) => bail!("CURVE_REFRESH_SECS must be at least 1"),
+                refresh => refresh,
+            };
+        if max_curve_age_secs.is_some() && curve_refresh_secs.is_none() {
+            bail!("MAX_CURVE_AGE_SECS needs CURVE_REFRESH_SECS to refresh the curve");
+        }
+
+        let max_trades_per_minute =
+            match parse_var::<u32>("MAX_TRADES_PER_MINUTE", var("MAX_TRADES_PER_MINUTE"))? {
+                Some(0) => bail!("MAX_TRADES_PER_MINUTE must be at least 1"),
+                max_trades => max_trades,
+            };
+
+        let token_decimals = TokenDecimals {
+            base: parse_var("BASE_DECIMALS", var("BASE_DECIMALS"))?
+                .unwrap_or(defaults.token_decimals.base),
+            fy: parse_var("FY_DECIMALS", var("FY_DECIMALS"))?.unwrap_or(defaults.token_decimals.fy),
+        };
+        if token_decimals.base > MAX_TOKEN_DECIMALS || token_decimals.fy > MAX_TOKEN_DECIMALS {
+            bail!("BASE_DECIMALS and FY_DECIMALS must be at most {MAX_TOKEN_DECIMALS}");
+        }
+
+        let sizing_concurrency =
+            match parse_var::<usize>("SIZING_CONCURRENCY", var("SIZING_CONCURRENCY"))? {
+                Some(0) => bail!("SIZING_CONCURRENCY must be at least 1"),
+                Some(concurrency) => concurrency,
+                None => defaults.sizing_concurrency,
+            };
+
+        let price_probes = match parse_var::<usize>("PRICE_PROBES", var("PRICE_PROBES"))? {
+            Some(0) => bail!("PRICE_PROBES must be at least 1"),
+            Some(probes) => probes,
+            None => defaults.price_probes,
+        };
+
+        let heatmap_every_blocks =
+            match parse_var::<u64>("HEATMAP_EVERY_BLOCKS", var("HEATMAP_EVERY_BLOCKS"))? {
+                Some(0) => bail!("HEATMAP_EVERY_BLOCKS must be at least 1"),
+                Some(blocks) => blocks,
+                None => defaults.heatmap_every_blocks,
+            };
+
+        let edge_bps = parse_bps("EDGE_BPS", var("EDGE_BPS"))?.unwrap_or(defaults.edge_bps);
+        let enter_bps = parse_bps("ENTER_BPS", var("ENTER_BPS"))?;
+        let exit_bps = parse_bps("EXIT_BPS", var("EXIT_BPS"))?;
+        if exit_bps.unwrap_or(edge_bps) > enter_bps.unwrap_or(edge_bps) {
+            bail!("EXIT_BPS must not exceed ENTER_BPS (or EDGE_BPS when unset)");
+        }
+
+        let max_base_amount = parse_var("MAX_BASE_AMOUNT", var("MAX_BASE_AMOUNT"))?
+            .unwrap_or(defaults.max_base_amount);
+        let approval_amount = parse_var::<u128>("APPROVAL_AMOUNT", var("APPROVAL_AMOUNT"))?;
+        if approval_amount.is_some_and(|amount| amount < max_base_amount) {
+            bail!("APPROVAL_AMOUNT must cover MAX_BASE_AMOUNT");
+        }
+
+        Ok(Self {
+            router_address,
+            pool_addresses,
+            edge_bps,
+            edge_bps_buy_then_sell: parse_bps(
+                "EDGE_BPS_BUY_THEN_SELL",
+                var("EDGE_BPS_BUY_THEN_SELL"),
+            )?,
+            edge_bps_sell_base: parse_bps("EDGE_BPS_SELL_BASE", var("EDGE_BPS_SELL_BASE"))?,
+            slippage_bps: parse_bps("SLIPPAGE_BPS", var("SLIPPAGE_BPS"))?
+                .unwrap_or(defaults.slippage_bps),
+            max_fy_amount: parse_var("MAX_FY_AMOUNT", var("MAX_FY_AMOUNT"))?
+                .unwrap_or(defaults.max_fy_amount),
+            max_base_amount,
+            bid_percentage: match parse_var::<u64>("BID_PERCENTAGE", var("BID_PERCENTAGE"))? {
+                Some(pct) if pct > 100 => bail!("BID_PERCENTAGE must be 0-100, got {pct}"),
+                Some(pct) => pct,
+                None => defaults.bid_percentage,
+            },
+            max_price_impact_bps: parse_bps("MAX_PRICE_IMPACT_BPS", var("MAX_PRICE_IMPACT_BPS"))?,
+            data_log_path: var("DATA_LOG_PATH").map(PathBuf::from),
+            pnl_ledger_path: var("PNL_LEDGER_PATH").map(PathBuf::from),
+            order_by_profit: parse_bool("ORDER_BY_PROFIT", var("ORDER_BY_PROFIT"))?
+                .unwrap_or(defaults.order_by_profit),
+            kill_switch_file: var("KILL_SWITCH_FILE").map(PathBuf::from),
+            backoff_after_blocks: parse_var("BACKOFF_AFTER_BLOCKS", var("BACKOFF_AFTER_BLOCKS"))?,
+            max_pools,
+            sync_concurrency,
+            state_refresh_blocks,
+            gas_buffer_bps: parse_bps("GAS_BUFFER_BPS", var("GAS_BUFFER_BPS"))?
+                .unwrap_or(defaults.gas_buffer_bps),
+            auto_adjust_gas_buffer: parse_bool(
+                "AUTO_ADJUST_GAS_BUFFER",
+                var("AUTO_ADJUST_GAS_BUFFER"),
+            )?
+            .unwrap_or(defaults.auto_adjust_gas_buffer),
+            dedup_ttl_blocks: parse_var("DEDUP_TTL_BLOCKS", var("DEDUP_TTL_BLOCKS"))?,
+            gas_cache_ttl_secs: parse_var("GAS_CACHE_TTL_SECS", var("GAS_CACHE_TTL_SECS"))?,
+            enter_bps,
+            exit_bps,
+            log_curve_changes: parse_bool("LOG_CURVE_CHANGES", var("LOG_CURVE_CHANGES"))?
+                .unwrap_or(defaults.log_curve_changes),
+            max_curve_age_secs,
+            curve_refresh_secs,
+            min_gas_limit: parse_var("MIN_GAS_LIMIT", var("MIN_GAS_LIMIT"))?
+                .unwrap_or(defaults.min_gas_limit),
+            gas_tip_wei: parse_var("GAS_TIP_WEI", var("GAS_TIP_WEI"))?
+                .unwrap_or(defaults.gas_tip_wei),
+            profit_token: var("PROFIT_TOKEN")
+                .map(|token| parse_address("PROFIT_TOKEN", &token))
+                .transpose()?,
+            token_decimals,
+            profit_rates: var("PROFIT_RATES")
+                .map(|rates| parse_rates("PROFIT_RATES", &rates))
+                .transpose()?
+                .unwrap_or_default(),
+            cancel_after_secs: parse_var("CANCEL_AFTER_SECS", var("CANCEL_AFTER_SECS"))?,
+            cancel_gas_bump_bps: parse_var("CANCEL_GAS_BUMP_BPS", var("CANCEL_GAS_BUMP_BPS"))?
+                .unwrap_or(defaults.cancel_gas_bump_bps),
+            nonce_stuck_after_secs: match parse_var::<u64>(
+                "NONCE_STUCK_AFTER_SECS",
+                var("NONCE_STUCK_AFTER_SECS"),
+            )? {
+                Some(0) => bail!("NONCE_STUCK_AFTER_SECS must be at least 1"),
+                secs => secs,
+            },
+            nonce_recovery: parse_var("NONCE_RECOVERY", var("NONCE_RECOVERY"))?
+                .unwrap_or(defaults.nonce_recovery),
+            tx_timeout_blocks: match parse_var::<u64>(
+                "TX_TIMEOUT_BLOCKS",
+                var("TX_TIMEOUT_BLOCKS"),
+            )? {
+                Some(0) => bail!("TX_TIMEOUT_BLOCKS must be at least 1"),
+                blocks => blocks,
+            },
+            tx_max_attempts: match parse_var::<u32>("TX_MAX_ATTEMPTS", var("TX_MAX_ATTEMPTS"))? {
+                Some(0) => bail!("TX_MAX_ATTEMPTS must be at least 1"),
+                attempts => attempts.unwrap_or(defaults.tx_max_attempts),
+            },
+            max_gas_bump_bps: parse_var("MAX_GAS_BUMP_BPS", var("MAX_GAS_BUMP_BPS"))?
+                .unwrap_or(defaults.max_gas_bump_bps),
+            sizing_concurrency,
+            record_events_path: var("RECORD_EVENTS_PATH").map(PathBuf::from),
+            log_opportunities_only: parse_bool(
+                "LOG_OPPORTUNITIES_ONLY",
+                var("LOG_OPPORTUNITIES_ONLY"),
+            )?
+            .unwrap_or(defaults.log_opportunities_only),
+            dry_run: parse_bool("DRY_RUN", var("DRY_RUN"))?.unwrap_or(defaults.dry_run),
+            auto_approve: parse_bool("AUTO_APPROVE", var("AUTO_APPROVE"))?
+                .unwrap_or(defaults.auto_approve),
+            approval_amount,
+            max_reserve_imbalance_ratio: match parse_var::<f64>(
+                "MAX_RESERVE_IMBALANCE_RATIO",
+                var("MAX_RESERVE_IMBALANCE_RATIO"),
+            )? {
+                Some(ratio) if ratio.is_nan() || ratio < 1.0 => {
+                    bail!("MAX_RESERVE_IMBALANCE_RATIO must be at least 1, got {ratio}")
+                }
+                ratio => ratio,
+            },
+            max_invariant_deviation_bps: parse_var(
+                "MAX_INVARIANT_DEVIATION_BPS",
+                var("MAX_INVARIANT_DEVIATION_BPS"),
+            )?,
+            opportunity_max_age_blocks: parse_var(
+                "OPPORTUNITY_MAX_AGE_BLOCKS",
+                var("OPPORTUNITY_MAX_AGE_BLOCKS"),
+            )?,
+            max_rpc_calls_per_block: parse_var(
+                "MAX_RPC_CALLS_PER_BLOCK",
+                var("MAX_RPC_CALLS_PER_BLOCK"),
+            )?,
+            pools_per_block,
+            reserve_price_fallback: parse_bool(
+                "RESERVE_PRICE_FALLBACK",
+                var("RESERVE_PRICE_FALLBACK"),
+            )?
+            .unwrap_or(defaults.reserve_price_fallback),
+            min_edge_persistence_blocks: parse_var(
+                "MIN_EDGE_PERSISTENCE_BLOCKS",
+                var("MIN_EDGE_PERSISTENCE_BLOCKS"),
+            )?,
+            max_trades_per_minute,
+            max_total_base_at_risk: parse_var(
+                "MAX_TOTAL_BASE_AT_RISK",
+                var("MAX_TOTAL_BASE_AT_RISK"),
+            )?,
+            max_daily_loss: parse_var("MAX_DAILY_LOSS", var("MAX_DAILY_LOSS"))?,
+            balance_tolerance_bps: parse_var(
+                "BALANCE_TOLERANCE_BPS",
+                var("BALANCE_TOLERANCE_BPS"),
+            )?,
+            chain_id: parse_var("CHAIN_ID", var("CHAIN_ID"))?,
+            slippage_model: parse_var("SLIPPAGE_MODEL", var("SLIPPAGE_MODEL"))?
+                .unwrap_or(defaults.slippage_model),
+            gas_estimation: parse_var("GAS_ESTIMATION", var("GAS_ESTIMATION"))?
+                .unwrap_or(defaults.gas_estimation),
+            gas_cost_base: parse_var("GAS_COST_BASE", var("GAS_COST_BASE"))?
+                .unwrap_or(defaults.gas_cost_base),
+            min_net_profit: parse_var("MIN_NET_PROFIT", var("MIN_NET_PROFIT"))?
+                .unwrap_or(defaults.min_net_profit),
+            native_price_in_base: match parse_var::<f64>(
+                "NATIVE_PRICE_IN_BASE",
+                var("NATIVE_PRICE_IN_BASE"),
+            )? {
+                Some(price) if !price.is_finite() || price <= 0.0 => {
+                    bail!("NATIVE_PRICE_IN_BASE must be positive, got {price}")
+                }
+                price => price,
+            },
+            max_eval_latency_ms: parse_var("MAX_EVAL_LATENCY_MS", var("MAX_EVAL_LATENCY_MS"))?,
+            degrade_after_slow_blocks: parse_var(
+                "DEGRADE_AFTER_SLOW_BLOCKS",
+                var("DEGRADE_AFTER_SLOW_BLOCKS"),
+            )?
+            .unwrap_or(defaults.degrade_after_slow_blocks),
+            price_probes,
+            carry_mode: parse_bool("CARRY_MODE", var("CARRY_MODE"))?.unwrap_or(defaults.carry_mode),
+            strict_quoter: parse_bool("STRICT_QUOTER", var("STRICT_QUOTER"))?
+                .unwrap_or(defaults.strict_quoter),
+            quoter_tolerance_bps: parse_bps("QUOTER_TOLERANCE_BPS", var("QUOTER_TOLERANCE_BPS"))?
+                .unwrap_or(defaults.quoter_tolerance_bps),
+            heatmap_path: var("HEATMAP_PATH").map(PathBuf::from),
+            heatmap_every_blocks,
+            snapshot_path: var("SNAPSHOT_PATH").map(PathBuf::from),
+            min_profit_to_simulate: parse_var(
+                "MIN_PROFIT_TO_SIMULATE",
+                var("MIN_PROFIT_TO_SIMULATE"),
+            )?
+            .or(defaults.min_profit_to_simulate),
+            use_access_lists: parse_bool("USE_ACCESS_LISTS", var("USE_ACCESS_LISTS"))?
+                .unwrap_or(defaults.use_access_lists),
+            pool_reserves_slot: parse_var("POOL_RESERVES_SLOT", var("POOL_RESERVES_SLOT"))?,
+            multicall_address: var("MULTICALL_ADDRESS")
+                .map(|address| parse_address("MULTICALL_ADDRESS", &address))
+                .transpose()?,
+            dry_run_duration_secs: parse_var(
+                "DRY_RUN_DURATION_SECS",
+                var("DRY_RUN_DURATION_SECS"),
+            )?
+            .or(defaults.dry_run_duration_secs),
+            promotion_min_net_profit: parse_var(
+                "PROMOTION_MIN_NET_PROFIT",
+                var("PROMOTION_MIN_NET_PROFIT"),
+            )?
+            .unwrap_or(defaults.promotion_min_net_profit),
+            disabled_pools,
+            observe_only_pools,
+            pool_configs,
+            debounce_window_secs: parse_var("DEBOUNCE_WINDOW_SECS", var("DEBOUNCE_WINDOW_SECS"))?,
+        })
+    }
+
+    /// Base of the `conservative` profile: a wide edge, tight slippage and small
+    /// trades, each simulated, after a day of paper trading
+    pub fn conservative() -> Self {
+        Self {
+            edge_bps: 30,
+            slippage_bps: 20,
+            max_fy_amount: 25_000u128 * 10u128.pow(18),
+            max_base_amount: 12_500u128 * 10u128.pow(18),
+            min_profit_to_simulate: Some(0),
+            dry_run_duration_secs: Some(86_400),
+            ..Self::default()
+        }
+    }
+
+    /// Base of the `aggressive` profile: a thin edge, loose slippage and large
+    /// trades, live from the start without simulation
+    pub fn aggressive() -> Self {
+        Self {
+            edge_bps: 5,
+            slippage_bps: 100,
+            max_fy_amount: 250_000u128 * 10u128.pow(18),
+            max_base_amount: 125_000u128 * 10u128.pow(18),
+            min_profit_to_simulate: None,
+            dry_run_duration_secs: None,
+            ..Self::default()
+        }
+    }
+
+    /// Base of the `monitor_only` profile: opportunities are logged, never traded
+    pub fn monitor_only() -> Self {
+        Self {
+            log_opportunities_only: true,
+            ..Self::default()
+        }
+    }
+
+    /// Edge required of trades in `direction`
+    pub fn edge_bps_for(&self, direction: ArbDirection) -> u32 {
+        match direction {
+            ArbDirection::BuyFyThenSellFy => self.edge_bps_buy_then_sell,
+            ArbDirection::SellBaseThenSellFy => self.edge_bps_sell_base,
+        }
+        .unwrap_or(self.edge_bps)
+    }
+
+    /// Entry and exit thresholds of the fair value band, if either is set
+    pub fn band_thresholds(&self) -> Option<(u32, u32)> {
+        match (self.enter_bps, self.exit_bps) {
+            (None, None) => None,
+            (enter, exit) => Some((
+                enter.unwrap_or(self.edge_bps),
+                exit.unwrap_or(self.edge_bps),
+            )),
         }
     }
+
+    /// Settings of `pool`, the defaults if it has none of its own
+    pub fn pool_config(&self, pool: Address) -> PoolConfig {
+        self.pool_configs
+            .iter()
+            .find(|(address, _)| *address == pool)
+            .map(|(_, config)| *config)
+            .unwrap_or_default()
+    }
+}
+
+/// Settings of a single pool, layered over the global configuration
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolConfig {
+    /// Shift of the pool's target rate in bps, for a structural credit or
+    /// liquidity spread to the curve; positive targets a higher rate (lower price)
+    pub curve_spread_bps: i32,
+}
+
+/// Parse a hex address, naming the variable on failure
+fn parse_address(key: &str, value: &str) -> Result<Address> {
+    Address::from_str(value).with_context(|| format!("Invalid address in {key}: {value}"))
+}
+
+/// Parse an optional variable, naming it on failure
+fn parse_var<T>(key: &str, value: Option<String>) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value
+        .map(|value| {
+            value
+                .parse::<T>()
+                .map_err(|e| anyhow!("Invalid value for {key}: {value} ({e})"))
+        })
+        .transpose()
+}
+
+/// Parse an optional basis-point value, which must not exceed 100%
+fn parse_bps(key: &str, value: Option<String>) -> Result<Option<u32>> {
+    match parse_var::<u32>(key, value)? {
+        Some(bps) if bps > 10_000 => bail!("{key} must be at most 10000 bps, got {bps}"),
+        bps => Ok(bps),
+    }
+}
+
+/// Parse a `token=rate` list, e.g., `0xabc...=1.0,0xdef...=0.9998`
+fn parse_rates(key: &str, value: &str) -> Result<Vec<(Address, f64)>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|entry| {
+            let (token, rate) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid entry in {key}: {entry} (expected token=rate)"))?;
+            let token = parse_address(key, token.trim())?;
+            let rate = rate
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|rate| rate.is_finite() && *rate >= 0.0)
+                .ok_or_else(|| anyhow!("Invalid rate in {key}: {entry}"))?;
+            Ok((token, rate))
+        })
+        .collect()
+}
+
+/// Parse a `pool=bps` list of signed spreads, e.g., `0xabc...=20,0xdef...=-5`
+fn parse_pool_spreads(key: &str, value: &str) -> Result<Vec<(Address, i32)>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|entry| {
+            let (pool, bps) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid entry in {key}: {entry} (expected pool=bps)"))?;
+            let pool = parse_address(key, pool.trim())?;
+            let bps = bps
+                .trim()
+                .parse::<i32>()
+                .ok()
+                .filter(|bps| bps.abs() <= 10_000)
+                .ok_or_else(|| anyhow!("Invalid spread in {key}: {entry}"))?;
+            Ok((pool, bps))
+        })
+        .collect()
+}
+
+/// Parse an optional boolean flag (`true`/`false`/`1`/`0`)
+fn parse_bool(key: &str, value: Option<String>) -> Result<Option<bool>> {
+    value
+        .map(|value| match value.to_lowercase().as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(anyhow!(
+                "Invalid value for {key}: {value} (expected true or false)"
+            )),
+        })
+        .transpose()
 }
 
 /// Events that the Numo strategy processes
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Event {
     /// New block event with timestamp
     NewBlock(NewBlockEvent),
+    /// Past block replayed at startup, evaluated read-only against its own state
+    HistoricalBlock(NewBlockEvent),
+    /// Periodic re-evaluation between blocks
+    Tick(TickEvent),
+    /// Transaction seen in the mempool before it is mined
+    PendingTx(PendingTxEvent),
+    /// Rate curve re-read by the refresh task
+    CurveRefresh(CurveRefreshEvent),
 }
 
-#[derive(Debug, Clone)]
+impl Event {
+    /// Timestamp the event was observed at
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            Event::NewBlock(block) | Event::HistoricalBlock(block) => block.timestamp,
+            Event::Tick(tick) => tick.timestamp,
+            Event::PendingTx(tx) => tx.timestamp,
+            Event::CurveRefresh(refresh) => refresh.timestamp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickEvent {
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveRefreshEvent {
+    pub timestamp: u64,
+
+    /// Curve read by the refresh, None when the read failed
+    pub curve: Option<RateCurve>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTxEvent {
+    pub tx_hash: TxHash,
+
+    /// Recipient of the transaction, None for contract creations
+    pub to: Option<Address>,
+    pub input: Bytes,
+
+    /// Time the transaction was seen (Unix seconds)
+    pub timestamp: u64,
+}
+
+impl PendingTxEvent {
+    /// Build a pending transaction event from a mempool transaction seen at
+    /// `timestamp`
+    pub fn from_transaction(tx: &Transaction, timestamp: u64) -> Self {
+        Self {
+            tx_hash: tx.hash,
+            to: tx.to,
+            input: tx.input.clone(),
+            timestamp,
+        }
+    }
+
+    /// Whether the transaction calls `address` or carries it in its calldata, as
+    /// routers and aggregators do with the pools they swap through
+    pub fn touches(&self, address: Address) -> bool {
+        self.to == Some(address)
+            || self
+                .input
+                .windows(20)
+                .any(|window| window == address.as_bytes())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewBlockEvent {
     pub block_number: u64,
     pub timestamp: u64,
     pub base_fee: Option<U256>,
 }
 
+impl NewBlockEvent {
+    /// Build a block event from a block, if it carries a block number
+    pub fn from_block<TX>(block: &Block<TX>) -> Option<Self> {
+        Some(Self {
+            block_number: block.number?.as_u64(),
+            timestamp: block.timestamp.as_u64(),
+            base_fee: block.base_fee_per_gas,
+        })
+    }
+}
+
 /// Actions that the Numo strategy can emit
 #[derive(Debug, Clone)]
 pub enum Action {
     /// Submit a transaction to the mempool
     SubmitTx(SubmitTxToMempool),
+
+    /// Replace-cancel a pending transaction
+    Cancel(TxHash),
+
+    /// Approve the router to pull a token, submitted ahead of any trade
+    Approve(SubmitTxToMempool),
+}
+
+/// Router entrypoint an arbitrage buys FY through on the cheap pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ArbDirection {
+    /// Buy an exact FY amount on the cheap pool, sell it on the rich pool
+    BuyFyThenSellFy,
+    /// Sell base into the cheap pool for FY, sell the FY on the rich pool
+    SellBaseThenSellFy,
 }
 
 /// Arbitrage opportunity details
@@ -70,13 +1046,21 @@ pub enum Action {
 pub struct ArbOpportunity {
     pub cheap_pool: Address,
     pub rich_pool: Address,
+
+    /// FY bought on the cheap pool; the minimum FY acquired when selling base
     pub fy_amount: u128,
+
+    /// Most base spent on the cheap pool; the exact base sold when selling base
     pub max_base_in: u128,
+
     pub min_base_out: u128,
     pub expected_profit: u128,
     pub target_price: U256,
     pub cheap_price: U256,
     pub rich_price: U256,
+
+    /// Router entrypoint the trade is submitted through
+    pub direction: ArbDirection,
 }
 
 impl ArbOpportunity {
@@ -89,4 +1073,829 @@ impl ArbOpportunity {
     pub fn net_profit(&self, gas_cost: u128) -> i128 {
         (self.expected_profit as i128) - (gas_cost as i128)
     }
+
+    /// Profit if both legs fill at their slippage bounds, the conservative
+    /// counterpart of the preview-based `expected_profit`
+    pub fn worst_case_profit(&self) -> i128 {
+        (self.min_base_out as i128) - (self.max_base_in as i128)
+    }
+
+    /// Check if the opportunity stays profitable after gas at its slippage bounds
+    pub fn is_profitable_worst_case(&self, gas_cost: u128) -> bool {
+        self.worst_case_profit() > gas_cost as i128
+    }
+}
+
+/// Reason an evaluated opportunity was not executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Fewer than two pools have a known state
+    NotEnoughPools,
+    /// No pool could be priced
+    NoPrices,
+    /// The cheapest and richest pool are the same
+    NoSpread,
+    /// The rich pool does not diverge enough from the target price
+    BelowEdge,
+    /// The solver found no size that moves the rich pool toward the target
+    NoSolution,
+    /// The price impact limit leaves no tradable size
+    PriceImpact,
+    /// Buying on the cheap pool costs more than selling on the rich pool returns
+    Unprofitable,
+    /// The trade has an edge before fees, but the cheap pool's fee on the buy
+    /// costs more than it
+    FeeEatsEdge,
+    /// The trade would exceed the maximum base amount
+    ExceedsMaxBase,
+    /// Filled at its slippage bounds, the trade would not cover its gas cost
+    WorstCaseUnprofitable,
+    /// The expected profit after gas falls short of `min_net_profit`
+    BelowMinNetProfit,
+    /// The reference curve does not confirm the divergence from the primary curve
+    CurveDisagreement,
+    /// Backing off after empty blocks and the pool spread shows no candidate
+    Backoff,
+    /// The same opportunity was already reported within the dedup window
+    Duplicate,
+    /// The pair was entered and its divergence has not narrowed below the exit threshold
+    Hysteresis,
+    /// The edge has not yet persisted for the required number of blocks
+    NotPersistent,
+    /// In-flight trades leave too little of the capital budget for the trade
+    BudgetExhausted,
+    /// The trade rate limit was reached within the last minute
+    RateLimited,
+    /// On-chain previews drifted from the local quoter beyond the tolerance
+    QuoterDrift,
+    /// Opportunities are only logged (`log_opportunities_only`)
+    SignalOnly,
+    /// Paper traded while awaiting promotion to live trading
+    PaperTrade,
+    /// Found in dry run mode, not submitted
+    DryRun,
+    /// The execution policy built no trade: awaiting or refused operator approval,
+    /// a reverting simulation or gas fees over the bid budget
+    PolicyDeclined,
+    /// An RPC or other error occurred during evaluation
+    Error,
+}
+
+/// What the strategy decided in the last block it processed
+#[derive(Debug, Clone, Default)]
+pub enum OpportunityDecision {
+    /// No block processed yet, or the block evaluated no cross-pool opportunity
+    /// (carry mode)
+    #[default]
+    NotEvaluated,
+    /// Trading was paused by the kill switch or halted by the daily loss limit
+    Paused,
+    /// The evaluation stopped for the given reason
+    Skipped(SkipReason),
+    /// An opportunity was found but not traded: signal-only mode, a paper trade, or
+    /// declined by the execution policy
+    Found(ArbOpportunity),
+    /// An opportunity was found and a trade emitted for it
+    Executed(ArbOpportunity),
+}
+
+impl SkipReason {
+    /// Stable snake_case name used in logs and reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::NotEnoughPools => "not_enough_pools",
+            SkipReason::NoPrices => "no_prices",
+            SkipReason::NoSpread => "no_spread",
+            SkipReason::BelowEdge => "below_edge",
+            SkipReason::NoSolution => "no_solution",
+            SkipReason::PriceImpact => "price_impact",
+            SkipReason::Unprofitable => "unprofitable",
+            SkipReason::FeeEatsEdge => "fee_eats_edge",
+            SkipReason::ExceedsMaxBase => "exceeds_max_base",
+            SkipReason::WorstCaseUnprofitable => "worst_case_unprofitable",
+            SkipReason::BelowMinNetProfit => "below_min_net_profit",
+            SkipReason::CurveDisagreement => "curve_disagreement",
+            SkipReason::Backoff => "backoff",
+            SkipReason::Duplicate => "duplicate",
+            SkipReason::Hysteresis => "hysteresis",
+            SkipReason::NotPersistent => "not_persistent",
+            SkipReason::BudgetExhausted => "budget_exhausted",
+            SkipReason::RateLimited => "rate_limited",
+            SkipReason::QuoterDrift => "quoter_drift",
+            SkipReason::SignalOnly => "signal_only",
+            SkipReason::PaperTrade => "paper_trade",
+            SkipReason::DryRun => "dry_run",
+            SkipReason::PolicyDeclined => "policy_declined",
+            SkipReason::Error => "error",
+        }
+    }
+
+    /// Whether an opportunity was found and only not traded
+    pub fn found_opportunity(&self) -> bool {
+        matches!(
+            self,
+            SkipReason::SignalOnly
+                | SkipReason::PaperTrade
+                | SkipReason::DryRun
+                | SkipReason::PolicyDeclined
+        )
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Largest supported token decimals; amounts are u128
+pub const MAX_TOKEN_DECIMALS: u8 = 36;
+
+/// Reference volatility of [SlippageModel::VolScaled] when none is given
+pub const DEFAULT_REFERENCE_VOL_BPS: u32 = 10;
+
+/// How a trade's slippage bounds are derived
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SlippageModel {
+    /// `slippage_bps` on both legs
+    #[default]
+    Flat,
+    /// `slippage_bps` plus each leg's own price impact from the local quoter
+    Quoter,
+    /// `slippage_bps` scaled up by recent per-block price volatility once it
+    /// exceeds `reference_vol_bps`
+    VolScaled { reference_vol_bps: u32 },
+}
+
+impl FromStr for SlippageModel {
+    type Err = anyhow::Error;
+
+    /// Parse `flat`, `quoter`, `vol_scaled` or `vol_scaled:<reference bps>`
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "flat" => Ok(SlippageModel::Flat),
+            "quoter" => Ok(SlippageModel::Quoter),
+            "vol_scaled" => Ok(SlippageModel::VolScaled {
+                reference_vol_bps: DEFAULT_REFERENCE_VOL_BPS,
+            }),
+            other => match other.strip_prefix("vol_scaled:").map(str::parse::<u32>) {
+                Some(Ok(reference_vol_bps)) if reference_vol_bps > 0 => {
+                    Ok(SlippageModel::VolScaled { reference_vol_bps })
+                }
+                _ => bail!("expected flat, quoter, vol_scaled or vol_scaled:<bps>"),
+            },
+        }
+    }
+}
+
+/// Named startup preset supplying the defaults explicit settings override
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// See [Config::conservative]
+    Conservative,
+    /// See [Config::aggressive]
+    Aggressive,
+    /// See [Config::monitor_only]
+    MonitorOnly,
+}
+
+impl Profile {
+    /// Base configuration of the profile
+    pub fn config(self) -> Config {
+        match self {
+            Profile::Conservative => Config::conservative(),
+            Profile::Aggressive => Config::aggressive(),
+            Profile::MonitorOnly => Config::monitor_only(),
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = anyhow::Error;
+
+    /// Parse `conservative`, `aggressive` or `monitor_only`
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "conservative" => Ok(Profile::Conservative),
+            "aggressive" => Ok(Profile::Aggressive),
+            "monitor_only" => Ok(Profile::MonitorOnly),
+            _ => bail!("expected conservative, aggressive or monitor_only"),
+        }
+    }
+}
+
+/// How the gas limit of a trade is chosen, before the gas buffer is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GasEstimation {
+    /// `eth_estimateGas` on every attempt
+    #[default]
+    OnChain,
+    /// A fixed gas limit, without an RPC call
+    Fixed(u64),
+    /// The 95th percentile of the gas used by recent confirmed trades, estimating
+    /// on-chain until enough trades have confirmed
+    HistoricalP95,
+}
+
+impl FromStr for GasEstimation {
+    type Err = anyhow::Error;
+
+    /// Parse `on_chain`, `fixed:<gas>` or `historical_p95`
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "on_chain" => Ok(GasEstimation::OnChain),
+            "historical_p95" => Ok(GasEstimation::HistoricalP95),
+            other => match other.strip_prefix("fixed:").map(str::parse::<u64>) {
+                Some(Ok(gas)) if gas > 0 => Ok(GasEstimation::Fixed(gas)),
+                _ => bail!("expected on_chain, fixed:<gas> or historical_p95"),
+            },
+        }
+    }
+}
+
+/// How a stuck nonce is recovered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NonceRecoveryMode {
+    /// Send a 0-value self-transfer at the stalled nonce
+    #[default]
+    FillGap,
+    /// Reset the nonce manager to the on-chain transaction count
+    Reset,
+}
+
+impl FromStr for NonceRecoveryMode {
+    type Err = anyhow::Error;
+
+    /// Parse `fill_gap` or `reset`
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "fill_gap" => Ok(NonceRecoveryMode::FillGap),
+            "reset" => Ok(NonceRecoveryMode::Reset),
+            _ => bail!("expected fill_gap or reset"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worst_case_profit_uses_slippage_bounds() {
+        let opp = ArbOpportunity {
+            cheap_pool: Address::zero(),
+            rich_pool: Address::zero(),
+            fy_amount: 1_000,
+            max_base_in: 965,
+            min_base_out: 975,
+            expected_profit: 30,
+            target_price: U256::zero(),
+            cheap_price: U256::zero(),
+            rich_price: U256::zero(),
+            direction: ArbDirection::BuyFyThenSellFy,
+        };
+
+        assert_eq!(opp.worst_case_profit(), 10);
+        assert!(opp.is_profitable(20));
+        assert!(!opp.is_profitable_worst_case(20));
+        assert!(opp.is_profitable_worst_case(9));
+
+        let underwater = ArbOpportunity {
+            max_base_in: 990,
+            ..opp
+        };
+        assert_eq!(underwater.worst_case_profit(), -15);
+        assert!(!underwater.is_profitable_worst_case(0));
+    }
+    use std::sync::Mutex;
+
+    /// Serializes tests that mutate the process environment
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Every variable read by `Config::from_env`
+    const CONFIG_VARS: &[&str] = &[
+        "PROFILE",
+        "ROUTER_ADDRESS",
+        "POOL_ADDRESSES",
+        "EDGE_BPS",
+        "EDGE_BPS_BUY_THEN_SELL",
+        "EDGE_BPS_SELL_BASE",
+        "SLIPPAGE_BPS",
+        "MAX_FY_AMOUNT",
+        "MAX_BASE_AMOUNT",
+        "BID_PERCENTAGE",
+        "MAX_PRICE_IMPACT_BPS",
+        "DATA_LOG_PATH",
+        "PNL_LEDGER_PATH",
+        "ORDER_BY_PROFIT",
+        "KILL_SWITCH_FILE",
+        "BACKOFF_AFTER_BLOCKS",
+        "MAX_POOLS",
+        "SYNC_CONCURRENCY",
+        "STATE_REFRESH_BLOCKS",
+        "GAS_BUFFER_BPS",
+        "AUTO_ADJUST_GAS_BUFFER",
+        "DEDUP_TTL_BLOCKS",
+        "GAS_CACHE_TTL_SECS",
+        "ENTER_BPS",
+        "EXIT_BPS",
+        "LOG_CURVE_CHANGES",
+        "MAX_CURVE_AGE_SECS",
+        "CURVE_REFRESH_SECS",
+        "MIN_GAS_LIMIT",
+        "GAS_TIP_WEI",
+        "PROFIT_TOKEN",
+        "BASE_DECIMALS",
+        "FY_DECIMALS",
+        "PROFIT_RATES",
+        "CANCEL_AFTER_SECS",
+        "CANCEL_GAS_BUMP_BPS",
+        "NONCE_STUCK_AFTER_SECS",
+        "NONCE_RECOVERY",
+        "TX_TIMEOUT_BLOCKS",
+        "TX_MAX_ATTEMPTS",
+        "MAX_GAS_BUMP_BPS",
+        "SIZING_CONCURRENCY",
+        "RECORD_EVENTS_PATH",
+        "MAX_RESERVE_IMBALANCE_RATIO",
+        "MAX_INVARIANT_DEVIATION_BPS",
+        "OPPORTUNITY_MAX_AGE_BLOCKS",
+        "MAX_RPC_CALLS_PER_BLOCK",
+        "POOLS_PER_BLOCK",
+        "RESERVE_PRICE_FALLBACK",
+        "LOG_OPPORTUNITIES_ONLY",
+        "DRY_RUN",
+        "AUTO_APPROVE",
+        "APPROVAL_AMOUNT",
+        "MIN_EDGE_PERSISTENCE_BLOCKS",
+        "MAX_TRADES_PER_MINUTE",
+        "MAX_TOTAL_BASE_AT_RISK",
+        "MAX_DAILY_LOSS",
+        "BALANCE_TOLERANCE_BPS",
+        "CHAIN_ID",
+        "SLIPPAGE_MODEL",
+        "GAS_ESTIMATION",
+        "GAS_COST_BASE",
+        "MIN_NET_PROFIT",
+        "NATIVE_PRICE_IN_BASE",
+        "MAX_EVAL_LATENCY_MS",
+        "DEGRADE_AFTER_SLOW_BLOCKS",
+        "PRICE_PROBES",
+        "CARRY_MODE",
+        "STRICT_QUOTER",
+        "QUOTER_TOLERANCE_BPS",
+        "HEATMAP_PATH",
+        "HEATMAP_EVERY_BLOCKS",
+        "SNAPSHOT_PATH",
+        "MIN_PROFIT_TO_SIMULATE",
+        "USE_ACCESS_LISTS",
+        "POOL_RESERVES_SLOT",
+        "MULTICALL_ADDRESS",
+        "DRY_RUN_DURATION_SECS",
+        "PROMOTION_MIN_NET_PROFIT",
+        "DISABLED_POOLS",
+        "OBSERVE_ONLY_POOLS",
+        "POOL_CURVE_SPREADS_BPS",
+        "DEBOUNCE_WINDOW_SECS",
+    ];
+
+    /// Run `f` with exactly `vars` set among the config variables, restoring the
+    /// previous environment afterwards
+    fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let saved: Vec<(&str, Option<String>)> = CONFIG_VARS
+            .iter()
+            .map(|key| (*key, std::env::var(key).ok()))
+            .collect();
+
+        for key in CONFIG_VARS {
+            std::env::remove_var(key);
+        }
+        for (key, value) in vars {
+            std::env::set_var(key, value);
+        }
+        let result = f();
+
+        for (key, value) in saved {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+        result
+    }
+
+    const ROUTER: &str = "0x1111111111111111111111111111111111111111";
+    const POOLS: &str =
+        "0x2222222222222222222222222222222222222222, 0x3333333333333333333333333333333333333333";
+
+    #[test]
+    fn test_from_env_defaults() {
+        let config = with_env(
+            &[("ROUTER_ADDRESS", ROUTER), ("POOL_ADDRESSES", POOLS)],
+            || Config::from_env().unwrap(),
+        );
+        let defaults = Config::default();
+
+        assert_eq!(config.router_address, Address::repeat_byte(0x11));
+        assert_eq!(
+            config.pool_addresses,
+            vec![Address::repeat_byte(0x22), Address::repeat_byte(0x33)]
+        );
+        assert_eq!(config.edge_bps, defaults.edge_bps);
+        assert_eq!(config.max_fy_amount, defaults.max_fy_amount);
+        assert_eq!(config.max_price_impact_bps, None);
+        assert!(!config.order_by_profit);
+    }
+
+    #[test]
+    fn test_from_env_overrides() {
+        let config = with_env(
+            &[
+                ("ROUTER_ADDRESS", ROUTER),
+                ("POOL_ADDRESSES", POOLS),
+                ("EDGE_BPS", "25"),
+                ("EDGE_BPS_SELL_BASE", "40"),
+                ("MAX_BASE_AMOUNT", "1000"),
+                ("BID_PERCENTAGE", "0"),
+                ("MAX_PRICE_IMPACT_BPS", "50"),
+                ("ORDER_BY_PROFIT", "true"),
+                ("DATA_LOG_PATH", "/tmp/numo.csv"),
+                ("BACKOFF_AFTER_BLOCKS", "5"),
+                ("PROFIT_TOKEN", ROUTER),
+                (
+                    "PROFIT_RATES",
+                    "0x2222222222222222222222222222222222222222=0.5",
+                ),
+            ],
+            || Config::from_env().unwrap(),
+        );
+
+        assert_eq!(config.edge_bps, 25);
+        assert_eq!(config.edge_bps_for(ArbDirection::BuyFyThenSellFy), 25);
+        assert_eq!(config.edge_bps_for(ArbDirection::SellBaseThenSellFy), 40);
+        assert_eq!(config.max_base_amount, 1000);
+        assert_eq!(config.bid_percentage, 0);
+        assert_eq!(config.max_price_impact_bps, Some(50));
+        assert!(config.order_by_profit);
+        assert_eq!(config.data_log_path, Some(PathBuf::from("/tmp/numo.csv")));
+        assert_eq!(config.backoff_after_blocks, Some(5));
+        assert_eq!(config.profit_token, Some(Address::repeat_byte(0x11)));
+        assert_eq!(config.profit_rates, vec![(Address::repeat_byte(0x22), 0.5)]);
+    }
+
+    #[test]
+    fn test_nonce_recovery_mode_from_str() {
+        assert_eq!(
+            "fill_gap".parse::<NonceRecoveryMode>().unwrap(),
+            NonceRecoveryMode::FillGap
+        );
+        assert_eq!(
+            "Reset".parse::<NonceRecoveryMode>().unwrap(),
+            NonceRecoveryMode::Reset
+        );
+        assert!("resubmit".parse::<NonceRecoveryMode>().is_err());
+    }
+
+    #[test]
+    fn test_slippage_model_from_str() {
+        assert_eq!(
+            "flat".parse::<SlippageModel>().unwrap(),
+            SlippageModel::Flat
+        );
+        assert_eq!(
+            "Quoter".parse::<SlippageModel>().unwrap(),
+            SlippageModel::Quoter
+        );
+        assert_eq!(
+            "vol_scaled".parse::<SlippageModel>().unwrap(),
+            SlippageModel::VolScaled {
+                reference_vol_bps: DEFAULT_REFERENCE_VOL_BPS
+            }
+        );
+        assert_eq!(
+            "vol_scaled:25".parse::<SlippageModel>().unwrap(),
+            SlippageModel::VolScaled {
+                reference_vol_bps: 25
+            }
+        );
+        assert!("vol_scaled:0".parse::<SlippageModel>().is_err());
+        assert!("linear".parse::<SlippageModel>().is_err());
+    }
+
+    #[test]
+    fn test_gas_estimation_from_str() {
+        assert_eq!(
+            "on_chain".parse::<GasEstimation>().unwrap(),
+            GasEstimation::OnChain
+        );
+        assert_eq!(
+            "Fixed:350000".parse::<GasEstimation>().unwrap(),
+            GasEstimation::Fixed(350_000)
+        );
+        assert_eq!(
+            "historical_p95".parse::<GasEstimation>().unwrap(),
+            GasEstimation::HistoricalP95
+        );
+        assert!("fixed:0".parse::<GasEstimation>().is_err());
+        assert!("fixed".parse::<GasEstimation>().is_err());
+    }
+
+    #[test]
+    fn test_profiles_set_documented_defaults() {
+        let profile = |name: &str| {
+            with_env(
+                &[
+                    ("ROUTER_ADDRESS", ROUTER),
+                    ("POOL_ADDRESSES", POOLS),
+                    ("PROFILE", name),
+                ],
+                || Config::from_env().unwrap(),
+            )
+        };
+        let one = 10u128.pow(18);
+
+        let conservative = profile("conservative");
+        assert_eq!(conservative.edge_bps, 30);
+        assert_eq!(conservative.slippage_bps, 20);
+        assert_eq!(conservative.max_fy_amount, 25_000 * one);
+        assert_eq!(conservative.max_base_amount, 12_500 * one);
+        assert_eq!(conservative.min_profit_to_simulate, Some(0));
+        assert_eq!(conservative.dry_run_duration_secs, Some(86_400));
+        assert!(!conservative.log_opportunities_only);
+
+        let aggressive = profile("aggressive");
+        assert_eq!(aggressive.edge_bps, 5);
+        assert_eq!(aggressive.slippage_bps, 100);
+        assert_eq!(aggressive.max_fy_amount, 250_000 * one);
+        assert_eq!(aggressive.max_base_amount, 125_000 * one);
+        assert_eq!(aggressive.min_profit_to_simulate, None);
+        assert_eq!(aggressive.dry_run_duration_secs, None);
+
+        let defaults = Config::default();
+        let monitor = profile("monitor-only");
+        assert!(monitor.log_opportunities_only);
+        assert_eq!(monitor.edge_bps, defaults.edge_bps);
+        assert_eq!(monitor.slippage_bps, defaults.slippage_bps);
+
+        assert!("cautious".parse::<Profile>().is_err());
+    }
+
+    #[test]
+    fn test_explicit_settings_override_profile() {
+        let config = with_env(
+            &[
+                ("ROUTER_ADDRESS", ROUTER),
+                ("POOL_ADDRESSES", POOLS),
+                ("PROFILE", "conservative"),
+                ("EDGE_BPS", "12"),
+                ("MIN_PROFIT_TO_SIMULATE", "1000"),
+                ("DRY_RUN_DURATION_SECS", "3600"),
+            ],
+            || Config::from_env().unwrap(),
+        );
+        assert_eq!(config.edge_bps, 12);
+        assert_eq!(config.min_profit_to_simulate, Some(1000));
+        assert_eq!(config.dry_run_duration_secs, Some(3_600));
+        // Settings left unset keep the profile's values
+        assert_eq!(config.slippage_bps, 20);
+    }
+
+    #[test]
+    fn test_from_env_rejects_invalid_values() {
+        let error =
+            |vars: &[(&str, &str)]| with_env(vars, || Config::from_env().unwrap_err().to_string());
+
+        assert!(error(&[("POOL_ADDRESSES", POOLS)]).contains("ROUTER_ADDRESS is not set"));
+        assert!(error(&[("ROUTER_ADDRESS", ROUTER)]).contains("POOL_ADDRESSES is not set"));
+        assert!(
+            error(&[("ROUTER_ADDRESS", ROUTER), ("POOL_ADDRESSES", " , ")])
+                .contains("At least one pool address")
+        );
+        assert!(
+            error(&[("ROUTER_ADDRESS", "0x1234"), ("POOL_ADDRESSES", POOLS)])
+                .contains("Invalid address in ROUTER_ADDRESS")
+        );
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            (
+                "POOL_ADDRESSES",
+                "0x2222222222222222222222222222222222222222,nope"
+            )
+        ])
+        .contains("Invalid address in POOL_ADDRESSES: nope"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("EDGE_BPS", "-1")
+        ])
+        .contains("Invalid value for EDGE_BPS"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("MIN_NET_PROFIT", "-1")
+        ])
+        .contains("Invalid value for MIN_NET_PROFIT"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("NATIVE_PRICE_IN_BASE", "0")
+        ])
+        .contains("NATIVE_PRICE_IN_BASE must be positive"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("DRY_RUN", "maybe")
+        ])
+        .contains("Invalid value for DRY_RUN"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("MAX_BASE_AMOUNT", "1000"),
+            ("APPROVAL_AMOUNT", "999")
+        ])
+        .contains("APPROVAL_AMOUNT must cover MAX_BASE_AMOUNT"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("SLIPPAGE_BPS", "10001")
+        ])
+        .contains("SLIPPAGE_BPS must be at most 10000 bps"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("BID_PERCENTAGE", "101")
+        ])
+        .contains("BID_PERCENTAGE must be 0-100"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("ORDER_BY_PROFIT", "yes")
+        ])
+        .contains("Invalid value for ORDER_BY_PROFIT"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("MAX_POOLS", "1")
+        ])
+        .contains("more than MAX_POOLS"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("SYNC_CONCURRENCY", "0")
+        ])
+        .contains("SYNC_CONCURRENCY must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("STATE_REFRESH_BLOCKS", "0")
+        ])
+        .contains("STATE_REFRESH_BLOCKS must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("POOLS_PER_BLOCK", "0")
+        ])
+        .contains("POOLS_PER_BLOCK must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("MAX_TRADES_PER_MINUTE", "0")
+        ])
+        .contains("MAX_TRADES_PER_MINUTE must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("MAX_CURVE_AGE_SECS", "0")
+        ])
+        .contains("MAX_CURVE_AGE_SECS must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("CURVE_REFRESH_SECS", "0")
+        ])
+        .contains("CURVE_REFRESH_SECS must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("MAX_CURVE_AGE_SECS", "300")
+        ])
+        .contains("MAX_CURVE_AGE_SECS needs CURVE_REFRESH_SECS"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("SIZING_CONCURRENCY", "0")
+        ])
+        .contains("SIZING_CONCURRENCY must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("PRICE_PROBES", "0")
+        ])
+        .contains("PRICE_PROBES must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("DISABLED_POOLS", ROUTER)
+        ])
+        .contains("not in POOL_ADDRESSES"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("OBSERVE_ONLY_POOLS", ROUTER)
+        ])
+        .contains("OBSERVE_ONLY_POOLS lists"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            (
+                "POOL_CURVE_SPREADS_BPS",
+                "0x1111111111111111111111111111111111111111=20"
+            )
+        ])
+        .contains("POOL_CURVE_SPREADS_BPS lists"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("HEATMAP_EVERY_BLOCKS", "0")
+        ])
+        .contains("HEATMAP_EVERY_BLOCKS must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("ENTER_BPS", "20"),
+            ("EXIT_BPS", "30")
+        ])
+        .contains("EXIT_BPS must not exceed ENTER_BPS"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("MAX_RESERVE_IMBALANCE_RATIO", "0.5")
+        ])
+        .contains("MAX_RESERVE_IMBALANCE_RATIO must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("NONCE_STUCK_AFTER_SECS", "0")
+        ])
+        .contains("NONCE_STUCK_AFTER_SECS must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("TX_TIMEOUT_BLOCKS", "0")
+        ])
+        .contains("TX_TIMEOUT_BLOCKS must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("TX_MAX_ATTEMPTS", "0")
+        ])
+        .contains("TX_MAX_ATTEMPTS must be at least 1"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("BASE_DECIMALS", "40")
+        ])
+        .contains("BASE_DECIMALS and FY_DECIMALS must be at most 36"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            ("PROFIT_RATES", "0x2222222222222222222222222222222222222222")
+        ])
+        .contains("Invalid entry in PROFIT_RATES"));
+        assert!(error(&[
+            ("ROUTER_ADDRESS", ROUTER),
+            ("POOL_ADDRESSES", POOLS),
+            (
+                "POOL_CURVE_SPREADS_BPS",
+                "0x2222222222222222222222222222222222222222=20000"
+            )
+        ])
+        .contains("Invalid spread in POOL_CURVE_SPREADS_BPS"));
+    }
+
+    #[test]
+    fn test_pool_curve_spreads() {
+        let config = with_env(
+            &[
+                ("ROUTER_ADDRESS", ROUTER),
+                ("POOL_ADDRESSES", POOLS),
+                (
+                    "POOL_CURVE_SPREADS_BPS",
+                    "0x2222222222222222222222222222222222222222=20, \
+                     0x3333333333333333333333333333333333333333=-5",
+                ),
+            ],
+            || Config::from_env().unwrap(),
+        );
+        let spread = |byte: u8| {
+            config
+                .pool_config(Address::repeat_byte(byte))
+                .curve_spread_bps
+        };
+        assert_eq!(spread(0x22), 20);
+        assert_eq!(spread(0x33), -5);
+        // Pools without settings of their own use the defaults
+        assert_eq!(spread(0x44), 0);
+    }
 }
@@ -0,0 +1,151 @@
+/// Unsigned transaction output for offline signing
+/// Writes every transaction the bot would submit to a JSON file for review and
+/// signing elsewhere, so a cold wallet never has to be online
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use artemis_core::types::Executor;
+
+use crate::types::SubmitTxToMempool;
+
+/// Contents of one unsigned transaction file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsignedTx {
+    /// The transaction as the bot would have submitted it, unsigned
+    pub tx: TypedTransaction,
+
+    /// Expected profit of the trade, if known
+    pub expected_profit: Option<U256>,
+
+    /// Percentage of the expected profit the bot would have bid in gas
+    pub bid_percentage: Option<u64>,
+}
+
+impl From<&SubmitTxToMempool> for UnsignedTx {
+    fn from(submit: &SubmitTxToMempool) -> Self {
+        Self {
+            tx: submit.tx.clone(),
+            expected_profit: submit.gas_bid_info.as_ref().map(|info| info.total_profit),
+            bid_percentage: submit.gas_bid_info.as_ref().map(|info| info.bid_percentage),
+        }
+    }
+}
+
+/// Executor writing each transaction to `<dir>/<unix ms>-<sequence>.json` instead
+/// of submitting it; needs no signer
+pub struct UnsignedTxExecutor {
+    dir: PathBuf,
+    sequence: AtomicU64,
+}
+
+impl UnsignedTxExecutor {
+    /// Write unsigned transactions into `dir`, creating it if needed
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Cannot create unsigned tx directory {}", dir.display()))?;
+        Ok(Self {
+            dir,
+            sequence: AtomicU64::new(0),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write one transaction, returning the file it was written to
+    ///
+    /// The file is written under a temporary name and renamed into place, so a
+    /// watcher of the directory never sees a partial transaction.
+    pub fn write(&self, submit: &SubmitTxToMempool) -> Result<PathBuf> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let path = self.dir.join(format!("{millis}-{sequence:06}.json"));
+        let partial = path.with_extension("json.partial");
+
+        let json = serde_json::to_string_pretty(&UnsignedTx::from(submit))?;
+        std::fs::write(&partial, json)
+            .with_context(|| format!("Cannot write unsigned tx {}", partial.display()))?;
+        std::fs::rename(&partial, &path)?;
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl Executor<SubmitTxToMempool> for UnsignedTxExecutor {
+    async fn execute(&self, action: SubmitTxToMempool) -> Result<()> {
+        let path = self.write(&action)?;
+        info!(
+            path = %path.display(),
+            to = ?action.tx.to_addr(),
+            "Unsigned transaction written for offline signing"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GasBidInfo;
+
+    #[tokio::test]
+    async fn test_action_written_as_unsigned_tx_file() {
+        let dir = std::env::temp_dir().join(format!("numo-unsigned-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let executor = UnsignedTxExecutor::new(&dir).unwrap();
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(Address::repeat_byte(0x11))
+            .to(Address::repeat_byte(0x22))
+            .data(vec![0xde, 0xad, 0xbe, 0xef])
+            .gas(240_000)
+            .nonce(7)
+            .into();
+        let action = SubmitTxToMempool {
+            tx: tx.clone(),
+            gas_bid_info: Some(GasBidInfo {
+                total_profit: U256::from(1_000),
+                bid_percentage: 80,
+            }),
+        };
+        executor.execute(action.clone()).await.unwrap();
+        executor.execute(action).await.unwrap();
+
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|path| path.extension().unwrap() == "json"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+        assert_eq!(
+            json["tx"]["to"],
+            "0x2222222222222222222222222222222222222222"
+        );
+        assert_eq!(json["tx"]["data"], "0xdeadbeef");
+        assert_eq!(json["tx"]["nonce"], "0x7");
+        assert_eq!(json["bid_percentage"], 80);
+
+        let unsigned: UnsignedTx = serde_json::from_value(json).unwrap();
+        assert_eq!(unsigned.tx, tx);
+        assert_eq!(unsigned.expected_profit, Some(U256::from(1_000)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+/// Per-pool price volatility for the Numo strategy
+/// Tracks an exponentially weighted average of block-to-block marginal price moves,
+/// used to widen slippage bounds when pools are moving fast
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use ethers::prelude::*;
+
+/// Weight of the latest move in the moving average
+const VOLATILITY_ALPHA: f64 = 0.2;
+
+/// Moving average of absolute price moves in basis points, per pool
+#[derive(Debug, Default)]
+pub struct PriceVolatility {
+    /// Last observed price and current average move of each pool
+    pools: Mutex<HashMap<Address, (U256, Option<f64>)>>,
+}
+
+impl PriceVolatility {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the latest marginal price of `pool`
+    pub fn observe(&self, pool: Address, price: U256) {
+        let mut pools = self.pools.lock().unwrap();
+        let Some((last_price, average)) = pools.get_mut(&pool) else {
+            pools.insert(pool, (price, None));
+            return;
+        };
+        if last_price.is_zero() {
+            *last_price = price;
+            return;
+        }
+
+        let diff = if price > *last_price {
+            price - *last_price
+        } else {
+            *last_price - price
+        };
+        // Millionths of a basis point, saturating on absurd moves
+        let scaled = diff * U256::from(10_000_000_000u64) / *last_price;
+        let move_bps = scaled.min(U256::from(u128::MAX)).as_u128() as f64 / 1e6;
+        *average = Some(match *average {
+            Some(average) => average + VOLATILITY_ALPHA * (move_bps - average),
+            None => move_bps,
+        });
+        *last_price = price;
+    }
+
+    /// Average per-block price move of `pool` in basis points, once it has moved at
+    /// least once
+    pub fn volatility_bps(&self, pool: Address) -> Option<f64> {
+        self.pools
+            .lock()
+            .unwrap()
+            .get(&pool)
+            .and_then(|(_, average)| *average)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volatility_tracks_price_moves() {
+        let volatility = PriceVolatility::new();
+        let pool = Address::repeat_byte(0x11);
+        let one = U256::exp10(18);
+
+        volatility.observe(pool, one);
+        assert_eq!(volatility.volatility_bps(pool), None);
+
+        // First move of 1% sets the average outright
+        volatility.observe(pool, one * 101 / 100);
+        let first = volatility.volatility_bps(pool).unwrap();
+        assert!((first - 100.0).abs() < 1e-6);
+
+        // A quiet block pulls it down by the weight of the latest move
+        volatility.observe(pool, one * 101 / 100);
+        let second = volatility.volatility_bps(pool).unwrap();
+        assert!((second - 80.0).abs() < 1e-6);
+
+        assert_eq!(volatility.volatility_bps(Address::repeat_byte(0x22)), None);
+    }
+}